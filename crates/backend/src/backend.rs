@@ -10,19 +10,19 @@ use auth::{
     serve_redirect::{self, ProcessAuthorizationError},
 };
 use bridge::{
-    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{InstanceID, InstanceModSummary, InstanceServerSummary, InstanceWorldSummary, LoaderSpecificModSummary}, message::MessageToFrontend, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{InstanceID, InstanceIconSelection, InstanceModSummary, InstancePackSummary, InstanceServerSummary, InstanceWorldSummary, LoaderSpecificModSummary, ModSummary, PackFolder}, message::MessageToFrontend, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
 use parking_lot::RwLock;
-use reqwest::{StatusCode, redirect::Policy};
+use reqwest::StatusCode;
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthSideRequirement};
+use schema::{content::ContentSource, instance::{InstanceConfiguration, InstanceIcon, InstanceSyncGroup}, loader::Loader, modrinth::{ModrinthLoader, ModrinthSideRequirement, ModrinthVersionFileUpdateResult}};
 use sha1::{Digest, Sha1};
 use tokio::sync::{mpsc::Receiver, OnceCell};
 use ustr::Ustr;
 use uuid::Uuid;
 
 use crate::{
-    account::BackendAccountInfo, config::BackendConfig, directories::LauncherDirectories, id_slab::IdSlab, instance::Instance, launch::Launcher, metadata::{items::MinecraftVersionManifestMetadataItem, manager::MetadataManager}, mod_metadata::ModMetadataManager, persistent::Persistent
+    account::BackendAccountInfo, config::BackendConfig, directories::LauncherDirectories, id_slab::IdSlab, instance::Instance, launch::Launcher, metadata::{items::{MinecraftVersionManifestMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::{MetaLoadError, MetadataManager}}, download::DownloadManager, mod_metadata::{ModMetadataManager, ModUpdateAction}, persistent::Persistent
 };
 
 pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHandle, recv: BackendReceiver) {
@@ -32,24 +32,19 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
         .build()
         .expect("Failed to initialize Tokio runtime");
 
-    let http_client = reqwest::ClientBuilder::new()
-        // .connect_timeout(Duration::from_secs(5))
-        .redirect(Policy::none())
-        .use_rustls_tls()
-        .user_agent("PandoraLauncher/0.1.0 (https://github.com/Moulberry/PandoraLauncher)")
-        .build()
-        .unwrap();
+    let directories = Arc::new(LauncherDirectories::new(launcher_dir));
 
-    let redirecting_http_client = reqwest::ClientBuilder::new()
-        .use_rustls_tls()
-        .user_agent("PandoraLauncher/0.1.0 (https://github.com/Moulberry/PandoraLauncher)")
-        .build()
-        .unwrap();
+    // Load config
+    let mut config: Persistent<BackendConfig> = Persistent::load(directories.config_json.clone());
 
-    let directories = Arc::new(LauncherDirectories::new(launcher_dir));
+    // Skipped rules are logged, the settings page shows them as invalid
+    _ = crate::log_reader::set_redaction_rules(&config.get().redaction);
+
+    let network_configuration = &config.get().network;
+    let downloads = Arc::new(DownloadManager::new(network_configuration));
 
     let meta = Arc::new(MetadataManager::new(
-        http_client.clone(),
+        Arc::clone(&downloads),
         directories.metadata_dir.clone(),
     ));
 
@@ -79,22 +74,18 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
     // Load accounts
     let account_info = Persistent::load(directories.accounts_json.clone());
 
-    // Load config
-    let config = Persistent::load(directories.config_json.clone());
-
     let mut state = BackendState {
         self_handle,
         send: send.clone(),
-        http_client,
-        redirecting_http_client,
         meta: Arc::clone(&meta),
         instance_state: Arc::new(RwLock::new(state_instances)),
         file_watching: Arc::new(RwLock::new(state_file_watching)),
         directories: Arc::clone(&directories),
-        launcher: Launcher::new(meta, directories, send),
+        launcher: Launcher::new(meta, Arc::clone(&downloads), directories, send),
         mod_metadata_manager: Arc::new(mod_metadata_manager),
         account_info: Arc::new(RwLock::new(account_info)),
         config: Arc::new(RwLock::new(config)),
+        downloads,
         secret_storage: Arc::new(OnceCell::new()),
         head_cache: Default::default(),
     };
@@ -120,6 +111,7 @@ pub enum WatchTarget {
     InstanceSavesDir { id: InstanceID },
     ServersDat { id: InstanceID },
     InstanceModsDir { id: InstanceID },
+    InstancePacksDir { id: InstanceID },
 }
 
 pub struct BackendStateInstances {
@@ -138,8 +130,6 @@ pub struct BackendStateFileWatching {
 pub struct BackendState {
     pub self_handle: BackendHandle,
     pub send: FrontendHandle,
-    pub http_client: reqwest::Client,
-    pub redirecting_http_client: reqwest::Client,
     pub meta: Arc<MetadataManager>,
     pub instance_state: Arc<RwLock<BackendStateInstances>>,
     pub file_watching: Arc<RwLock<BackendStateFileWatching>>,
@@ -148,6 +138,7 @@ pub struct BackendState {
     pub mod_metadata_manager: Arc<ModMetadataManager>,
     pub account_info: Arc<RwLock<Persistent<BackendAccountInfo>>>,
     pub config: Arc<RwLock<Persistent<BackendConfig>>>,
+    pub downloads: Arc<DownloadManager>,
     pub secret_storage: Arc<OnceCell<Result<PlatformSecretStorage, SecretStorageError>>>,
     pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>
 }
@@ -288,6 +279,8 @@ impl BackendState {
                 name: instance.name,
                 dot_minecraft_folder: instance.dot_minecraft_path.clone(),
                 configuration: instance.configuration.get().clone(),
+                organization: instance.organization.get().clone(),
+                icon: instance.icon.clone(),
                 worlds_state: Arc::clone(&instance.worlds_state),
                 servers_state: Arc::clone(&instance.servers_state),
                 mods_state: Arc::clone(&instance.mods_state),
@@ -353,7 +346,7 @@ impl BackendState {
         login_tracker: &ProgressTracker,
         modal_action: &ModalAction,
     ) -> Result<(MinecraftProfileResponse, MinecraftAccessToken), LoginError> {
-        let mut authenticator = Authenticator::new(self.http_client.clone());
+        let mut authenticator = Authenticator::new(self.downloads.http_client());
 
         login_tracker.set_total(AUTH_STAGE_COUNT as usize + 1);
         login_tracker.notify();
@@ -537,7 +530,7 @@ impl BackendState {
         let account_info = self.account_info.clone();
         let skin_url = skin.url;
 
-        let http_client = self.http_client.clone();
+        let http_client = self.downloads.http_client();
 
         tokio::task::spawn(async move {
             let Ok(response) = http_client.get(&*skin_url).send().await else {
@@ -599,19 +592,30 @@ impl BackendState {
     }
 
     pub fn prelaunch_apply_syncing(&self, id: InstanceID) {
-        let path = if let Some(instance) = self.instance_state.read().instances.get(id) {
-            instance.dot_minecraft_path.clone()
+        let (path, sync_group) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            (instance.dot_minecraft_path.clone(), instance.configuration.get().sync_group.clone())
         } else {
             return;
         };
 
-        crate::syncing::apply_to_instance(self.config.write().get().sync_targets, &self.directories, path);
+        let group = match &sync_group {
+            InstanceSyncGroup::Default => None,
+            InstanceSyncGroup::Disabled => return,
+            InstanceSyncGroup::Named(name) => Some(&**name),
+        };
+        let Some(sync_targets) = self.sync_targets(group) else {
+            return;
+        };
+
+        let members = self.sync_group_members(group);
+        let sync_dir = crate::syncing::sync_group_dir(&self.directories, group);
+        crate::syncing::apply_to_instance(sync_targets, &sync_dir, &members, path);
     }
 
     pub async fn prelaunch_apply_modpacks(&self, id: InstanceID, modal_action: &ModalAction) -> Vec<PathBuf> {
-        let (loader, minecraft_version, mod_dir) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+        let (loader, minecraft_version, mod_dir, preserved) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
             let configuration = instance.configuration.get();
-            (configuration.loader, configuration.minecraft_version, instance.mods_path.clone())
+            (configuration.loader, configuration.minecraft_version, instance.mods_path.clone(), configuration.preserved_modpack_files.clone())
         } else {
             return Vec::new();
         };
@@ -734,7 +738,11 @@ impl BackendState {
                         let _ = std::fs::hard_link(path, hidden_dest_path);
                     }
                 } else {
+                    let preserved = preserved.iter().any(|preserved| **preserved == *dest_path.as_str());
                     let dest_path = dest_path.to_path(&dot_minecraft_path);
+                    if preserved && dest_path.exists() {
+                        continue;
+                    }
 
                     let _ = std::fs::create_dir_all(dest_path.parent().unwrap());
                     let _ = std::fs::copy(path, dest_path);
@@ -751,6 +759,7 @@ impl BackendState {
                 let tracker = &tracker;
                 let dot_minecraft_path = &dot_minecraft_path;
                 let mod_dir = &mod_dir;
+                let preserved = &preserved;
                 let futures = overrides.iter().map(|(dest_path, file)| async move {
                     let file2 = file.clone();
                     let expected_hash = tokio::task::spawn_blocking(move || {
@@ -775,10 +784,13 @@ impl BackendState {
                             let _ = std::fs::hard_link(path, hidden_dest_path);
                         }
                     } else {
+                        let preserved = preserved.iter().any(|preserved| **preserved == *dest_path.as_str());
                         let dest_path = dest_path.to_path(&dot_minecraft_path);
 
-                        let _ = std::fs::create_dir_all(dest_path.parent().unwrap());
-                        let _ = tokio::fs::copy(path, dest_path).await;
+                        if !preserved || !dest_path.exists() {
+                            let _ = std::fs::create_dir_all(dest_path.parent().unwrap());
+                            let _ = tokio::fs::copy(path, dest_path).await;
+                        }
                     }
                     tracker.add_count(1);
                     tracker.notify();
@@ -861,6 +873,143 @@ impl BackendState {
         result.map(|(mods, _)| mods)
     }
 
+    /// Creates the file that replaces a mod with the update found by the last update check
+    pub fn mod_update_install_file(&self, mod_summary: &InstanceModSummary) -> Result<ContentInstallFile, &'static str> {
+        let Some(update_info) = self.mod_metadata_manager.updates.read().get(&mod_summary.mod_summary.hash).cloned() else {
+            return Err("missing update action");
+        };
+
+        let (filename, download, content_source) = match update_info {
+            ModUpdateAction::ErrorNotFound => return Err("404 not found"),
+            ModUpdateAction::ErrorInvalidHash => return Err("returned invalid hash"),
+            ModUpdateAction::AlreadyUpToDate => return Err("already up-to-date"),
+            ModUpdateAction::ManualInstall => return Err("mod was manually installed"),
+            ModUpdateAction::Pinned => return Err("mod is pinned"),
+            ModUpdateAction::Modrinth(modrinth_file) => {
+                let download = ContentDownload::Url {
+                    url: modrinth_file.url.clone(),
+                    sha1: modrinth_file.hashes.sha1.clone(),
+                    size: modrinth_file.size,
+                };
+                (modrinth_file.filename, download, ContentSource::Modrinth)
+            },
+            ModUpdateAction::CurseForge(curseforge_file) => {
+                let (Some(url), Some(sha1)) = (curseforge_file.download_url.clone(), curseforge_file.sha1().cloned()) else {
+                    return Err("CurseForge doesn't allow downloading this file");
+                };
+                let download = ContentDownload::Url {
+                    url,
+                    sha1,
+                    size: curseforge_file.file_length,
+                };
+                (curseforge_file.file_name, download, ContentSource::CurseForge)
+            },
+        };
+
+        let mut path = mod_summary.path.with_file_name(&*filename);
+        if !mod_summary.enabled {
+            path.add_extension("disabled");
+        }
+        debug_assert!(path.is_absolute());
+
+        Ok(ContentInstallFile {
+            replace_old: Some(mod_summary.path.clone()),
+            path: ContentInstallPath::Raw(path.into()),
+            download,
+            content_source,
+        })
+    }
+
+    /// Looks up the newest version of a Modrinth mod or modpack that supports the given loader and game version
+    pub async fn modrinth_update_version(&self, summary: &ModSummary, loader: ModrinthLoader, minecraft_version: Ustr) -> Result<Option<Arc<ModrinthVersionFileUpdateResult>>, MetaLoadError> {
+        let result = if matches!(summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. }) {
+            self.meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
+                sha1: hex::encode(summary.hash).into(),
+                params: VersionV3UpdateParameters {
+                    loaders: ["mrpack".into()].into(),
+                    loader_fields: VersionV3LoaderFields {
+                        mrpack_loaders: [loader].into(),
+                        game_versions: [minecraft_version].into(),
+                    },
+                },
+            }).await
+        } else {
+            self.meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                sha1: hex::encode(summary.hash).into(),
+                params: VersionUpdateParameters {
+                    loaders: [loader].into(),
+                    game_versions: [minecraft_version].into(),
+                },
+            }).await
+        };
+
+        match result {
+            Ok(result) => Ok(Some(result)),
+            Err(MetaLoadError::NonOK(404)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub async fn modrinth_update_action(&self, summary: &ModSummary, loader: ModrinthLoader, minecraft_version: Ustr) -> Result<ModUpdateAction, MetaLoadError> {
+        let Some(result) = self.modrinth_update_version(summary, loader, minecraft_version).await? else {
+            return Ok(ModUpdateAction::ErrorNotFound);
+        };
+
+        let install_file = result
+            .0
+            .files
+            .iter()
+            .find(|file| file.primary)
+            .unwrap_or(result.0.files.first().unwrap());
+
+        let mut latest_hash = [0u8; 20];
+        let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
+            return Ok(ModUpdateAction::ErrorInvalidHash);
+        };
+
+        if latest_hash == summary.hash {
+            Ok(ModUpdateAction::AlreadyUpToDate)
+        } else {
+            Ok(ModUpdateAction::Modrinth(install_file.clone()))
+        }
+    }
+
+    pub async fn load_instance_packs(self, id: InstanceID, folder: PackFolder) -> Option<Arc<[InstancePackSummary]>> {
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let mut file_watching = self.file_watching.write();
+            if !instance.watching_dot_minecraft {
+                instance.watching_dot_minecraft = true;
+                if file_watching.watcher.watch(&instance.dot_minecraft_path, notify::RecursiveMode::NonRecursive).is_ok() {
+                    file_watching.watching.insert(instance.dot_minecraft_path.clone(), WatchTarget::InstanceDotMinecraftDir {
+                        id: instance.id,
+                    });
+                }
+            }
+            let collection = instance.pack_collection_mut(&folder);
+            if !collection.watching {
+                collection.watching = true;
+                let path = collection.path.clone();
+                if file_watching.watcher.watch(&path, notify::RecursiveMode::NonRecursive).is_ok() {
+                    file_watching.watching.insert(path, WatchTarget::InstancePacksDir { id });
+                }
+            }
+        }
+
+        let result = Instance::load_packs(self.instance_state.clone(), id, folder.clone(), &self.mod_metadata_manager).await;
+
+        // Always sent since the frontend only tracks the pack folders it has asked for
+        if let Some((packs, state, _)) = result.clone() {
+            self.send.send(MessageToFrontend::InstancePacksUpdated {
+                id,
+                folder,
+                state,
+                packs: Arc::clone(&packs),
+            });
+        }
+
+        result.map(|(packs, ..)| packs)
+    }
+
     pub async fn load_instance_worlds(self, id: InstanceID) -> Option<Arc<[InstanceWorldSummary]>> {
         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
             let mut file_watching = self.file_watching.write();
@@ -949,6 +1098,11 @@ impl BackendState {
             memory: None,
             jvm_flags: None,
             jvm_binary: None,
+            resolution: None,
+            environment_variables: None,
+            sync_group: InstanceSyncGroup::Default,
+            preserved_modpack_files: Default::default(),
+            mod_update_preferences: Default::default(),
         };
 
         let info_path = instance_dir.join("info_v1.json");
@@ -982,6 +1136,67 @@ impl BackendState {
     }
 }
 
+impl BackendState {
+    pub async fn set_instance_icon(&self, id: InstanceID, selection: InstanceIconSelection) {
+        let png = match &selection {
+            InstanceIconSelection::Default | InstanceIconSelection::Builtin(_) => None,
+            InstanceIconSelection::File(path) => {
+                let bytes = match tokio::fs::read(path).await {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        self.send.send_error(format!("Unable to read icon: {}", err));
+                        return;
+                    },
+                };
+                if !matches!(image::guess_format(&bytes), Ok(image::ImageFormat::Png)) {
+                    self.send.send_error("Unable to set icon, only png images are supported");
+                    return;
+                }
+                Some(bytes)
+            },
+            InstanceIconSelection::Modpack => {
+                let mods = self.clone().load_instance_mods(id).await.unwrap_or_default();
+                let modpack_icon = mods.iter().find_map(|summary| {
+                    if matches!(summary.mod_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. }) {
+                        summary.mod_summary.png_icon.clone()
+                    } else {
+                        None
+                    }
+                });
+                let Some(modpack_icon) = modpack_icon else {
+                    self.send.send_warning("Unable to set icon, the instance has no modpack with an icon");
+                    return;
+                };
+                Some(modpack_icon.to_vec())
+            },
+        };
+
+        let mut instance_state = self.instance_state.write();
+        let Some(instance) = instance_state.instances.get_mut(id) else {
+            return;
+        };
+
+        let icon_path = instance.icon_path();
+        let icon = if let Some(png) = png {
+            if let Err(err) = crate::write_safe(&icon_path, &png) {
+                self.send.send_error(format!("Unable to save icon: {}", err));
+                return;
+            }
+            InstanceIcon::Custom
+        } else {
+            _ = std::fs::remove_file(&icon_path);
+            match selection {
+                InstanceIconSelection::Builtin(name) => InstanceIcon::Builtin(name),
+                _ => InstanceIcon::Default,
+            }
+        };
+
+        instance.organization.modify(|organization| {
+            organization.icon = icon;
+        });
+    }
+}
+
 impl BackendStateFileWatching {
     pub fn try_watch_filesystem(&mut self, path: &Path, target: WatchTarget) -> bool {
         if self.watcher.watch(path, notify::RecursiveMode::NonRecursive).is_err() {
@@ -225,11 +225,20 @@ impl BackendState {
                 }
                 true
             },
+            WatchTarget::InstancePacksDir { id } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id)
+                    && let Some(collection) = instance.find_pack_collection_mut(path)
+                {
+                    collection.mark_dirty(None);
+                }
+                true
+            },
             WatchTarget::InstanceDotMinecraftDir { id } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.mark_world_dirty(None);
                     instance.mark_servers_dirty();
                     instance.mark_mods_dirty(None);
+                    instance.mark_all_packs_dirty();
                 }
                 true
             },
@@ -267,6 +276,9 @@ impl BackendState {
                     if instance.watching_mods_dir {
                         self.watch_filesystem(&instance.mods_path, WatchTarget::InstanceModsDir { id });
                     }
+                    for collection in instance.packs.values().filter(|collection| collection.watching) {
+                        self.watch_filesystem(&collection.path, WatchTarget::InstancePacksDir { id });
+                    }
                     true
                 } else {
                     false
@@ -291,7 +303,12 @@ impl BackendState {
                 if file_name == "instances" {
                     self.load_all_instances().await;
                 } else if file_name == "config.json" {
-                    self.config.write().mark_changed(&path);
+                    let mut config = self.config.write();
+                    config.mark_changed(&path);
+                    self.downloads.update(&config.get().network);
+                    for error in crate::log_reader::set_redaction_rules(&config.get().redaction) {
+                        self.send.send_warning(format!("Redaction rule skipped. {error}"));
+                    }
                 } else if file_name == "accounts.json" {
                     let mut account_info = self.account_info.write();
                     account_info.mark_changed(&path);
@@ -317,12 +334,23 @@ impl BackendState {
                     } else {
                         self.load_instance_from_path(parent_path, true, true);
                     }
+                } else if file_name == "organization_v1.json" {
+                    if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                        instance.organization.mark_changed(&path);
+                        self.send.send(instance.create_modify_message());
+                    }
+                } else if file_name == "icon.png" {
+                    if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                        instance.reload_icon();
+                        self.send.send(instance.create_modify_message());
+                    }
                 } else if file_name == ".minecraft"
                     && let Some(instance) = self.instance_state.write().instances.get_mut(id)
                 {
                     instance.mark_world_dirty(None);
                     instance.mark_servers_dirty();
                     instance.mark_mods_dirty(None);
+                    instance.mark_all_packs_dirty();
 
                     if instance.watching_dot_minecraft {
                         self.watch_filesystem(path, WatchTarget::InstanceDotMinecraftDir { id });
@@ -336,6 +364,9 @@ impl BackendState {
                     if instance.watching_mods_dir {
                         self.watch_filesystem(&instance.mods_path.clone(), WatchTarget::InstanceModsDir { id });
                     }
+                    for collection in instance.packs.values().filter(|collection| collection.watching) {
+                        self.watch_filesystem(&collection.path, WatchTarget::InstancePacksDir { id });
+                    }
                 }
             },
             WatchTarget::ServersDat { .. } => {},
@@ -357,6 +388,12 @@ impl BackendState {
                             instance.mark_servers_dirty();
                             self.watch_filesystem(path, WatchTarget::ServersDat { id });
                         },
+                        Some("resourcepacks" | "shaderpacks") => {
+                            if let Some(collection) = instance.find_pack_collection_mut(path) && collection.watching {
+                                collection.mark_dirty(None);
+                                self.watch_filesystem(path, WatchTarget::InstancePacksDir { id });
+                            }
+                        },
                         _ => {},
                     }
                 }
@@ -373,6 +410,14 @@ impl BackendState {
                 // If a file inside the world folder is changed (e.g. icon.png), mark the world (parent) as dirty
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.mark_world_dirty(Some(parent_path.into()));
+
+                    if path.file_name().is_some_and(|file_name| file_name == "datapacks")
+                        && let Some(collection) = instance.find_pack_collection_mut(path)
+                        && collection.watching
+                    {
+                        collection.mark_dirty(None);
+                        self.watch_filesystem(path, WatchTarget::InstancePacksDir { id });
+                    }
                 }
             },
             WatchTarget::InstanceSavesDir { id } => {
@@ -390,6 +435,13 @@ impl BackendState {
                     }
                 }
             },
+            WatchTarget::InstancePacksDir { id } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id)
+                    && let Some(collection) = instance.find_pack_collection_mut(parent_path)
+                {
+                    collection.mark_dirty(Some(path.clone()));
+                }
+            },
         }
     }
 
@@ -408,6 +460,11 @@ impl BackendState {
                 if file_name == "info_v1.json" {
                     self.remove_instance(id);
                     self.watch_filesystem(parent_path, WatchTarget::InvalidInstanceDir);
+                } else if file_name == "icon.png"
+                    && let Some(instance) = self.instance_state.write().instances.get_mut(id)
+                {
+                    instance.icon = None;
+                    self.send.send(instance.create_modify_message());
                 }
             },
             WatchTarget::InstanceWorldDir { id } => {
@@ -429,6 +486,13 @@ impl BackendState {
                     }
                 }
             },
+            WatchTarget::InstancePacksDir { id } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id)
+                    && let Some(collection) = instance.find_pack_collection_mut(parent_path)
+                {
+                    collection.mark_dirty(Some(path.clone()));
+                }
+            },
             _ => {},
         }
     }
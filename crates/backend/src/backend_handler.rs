@@ -1,17 +1,17 @@
-use std::{io::{BufRead, Read, Seek, SeekFrom, Write}, path::Path, sync::{atomic::Ordering, Arc}, time::{Duration, SystemTime}};
+use std::{io::{BufRead, Read, Seek, SeekFrom, Write}, path::Path, sync::{atomic::Ordering, Arc}, time::{Duration, Instant, SystemTime}};
 
 use auth::{credentials::AccountCredentials, models::{MinecraftAccessToken, MinecraftProfileResponse}, secret::PlatformSecretStorage};
 use bridge::{
-    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{InstanceStatus, LoaderSpecificModSummary, ModSummary}, message::{LogFiles, MessageToBackend, MessageToFrontend}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
+    game_output::GameOutputEntry, install::{ContentInstall, InstallTarget}, instance::{InstanceID, InstanceStatus, ModSummary}, message::{LogFiles, MessageToBackend, MessageToFrontend}, meta::MetadataResult, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
 };
+use enumset::EnumSet;
 use futures::TryFutureExt;
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{content::ContentSource, modrinth::ModrinthLoader, version::{LaunchArgument, LaunchArgumentValue}};
-use serde::Deserialize;
-use tokio::{io::AsyncBufReadExt, sync::Semaphore};
+use schema::{content::ContentSource, instance::InstanceSyncGroup, modrinth::ModrinthLoader, version::{LaunchArgument, LaunchArgumentValue}};
+use tokio::io::AsyncBufReadExt;
 
 use crate::{
-    account::{BackendAccount, MinecraftLoginInfo}, arcfactory::ArcStrFactory, launch::{ArgumentExpansionKey, LaunchError}, log_reader, metadata::{items::{AssetsIndexMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::ModUpdateAction, BackendState, LoginError
+    account::{BackendAccount, MinecraftLoginInfo}, config::SyncGroup, launch::{ArgumentExpansionKey, LaunchError}, log_parser::{self, LogFileParser}, log_reader, metadata::{items::{AssetsIndexMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem}, manager::MetaLoadError}, mod_metadata::ModUpdateAction, BackendState, LoginError
 };
 
 impl BackendState {
@@ -52,6 +52,113 @@ impl BackendState {
             MessageToBackend::RequestLoadMods { id } => {
                 tokio::task::spawn(self.clone().load_instance_mods(id));
             },
+            MessageToBackend::RequestLoadPacks { id, folder } => {
+                if let Err(err) = crate::packs::check_pack_folder(&folder) {
+                    self.send.send_error(format!("Unable to load packs: {err}"));
+                    return;
+                }
+                tokio::task::spawn(self.clone().load_instance_packs(id, folder));
+            },
+            MessageToBackend::GetWorldDetails { id, world, channel } => {
+                if let Err(err) = self.check_world_path(id, &world, false) {
+                    _ = channel.send(Err(err.into()));
+                    return;
+                }
+
+                tokio::task::spawn_blocking(move || {
+                    let result = crate::world_details::load_world_details(&world);
+                    _ = channel.send(result.map_err(|err| format!("Unable to load world details: {err}").into()));
+                });
+            },
+            MessageToBackend::EditWorld { id, world, edit, channel } => {
+                if let Err(err) = self.check_world_path(id, &world, true) {
+                    _ = channel.send(Err(err.into()));
+                    return;
+                }
+
+                let result = crate::world_details::edit_world(&world, edit)
+                    .and_then(|_| crate::world_details::load_world_details(&world));
+                _ = channel.send(result.map_err(|err| format!("Unable to edit world: {err}").into()));
+
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.mark_world_dirty(Some(world));
+                }
+            },
+            MessageToBackend::PruneWorld { id, world, options, channel } => {
+                if let Err(err) = self.check_world_path(id, &world, !options.dry_run) {
+                    _ = channel.send(Err(err.into()));
+                    return;
+                }
+
+                let instance_state = self.instance_state.clone();
+                tokio::task::spawn(async move {
+                    let dry_run = options.dry_run;
+                    let prune_world = world.clone();
+                    let result = tokio::task::spawn_blocking(move || crate::world_prune::prune_world(&prune_world, &options)).await;
+                    let result = match result {
+                        Ok(result) => result.map_err(|err| format!("Unable to prune world: {err:#}").into()),
+                        Err(_) => Err("Pruning was cancelled".into()),
+                    };
+                    _ = channel.send(result);
+
+                    if !dry_run && let Some(instance) = instance_state.write().instances.get_mut(id) {
+                        instance.mark_world_dirty(Some(world));
+                    }
+                });
+            },
+            MessageToBackend::GetNbtFiles { id, channel } => {
+                let Some(dot_minecraft) = self.instance_state.read().instances.get(id).map(|instance| instance.dot_minecraft_path.clone()) else {
+                    _ = channel.send(Arc::from([]));
+                    return;
+                };
+
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(crate::nbt_file::list_nbt_files(&dot_minecraft));
+                });
+            },
+            MessageToBackend::ReadNbtFile { id, path, channel } => {
+                if let Err(err) = self.check_nbt_file_path(id, &path, false) {
+                    _ = channel.send(Err(err.into()));
+                    return;
+                }
+
+                tokio::task::spawn_blocking(move || {
+                    let result = crate::nbt_file::read_nbt_file(&path);
+                    _ = channel.send(result.map_err(|err| format!("Unable to read {}: {err}", path.display()).into()));
+                });
+            },
+            MessageToBackend::WriteNbtFile { id, path, file, channel } => {
+                if let Err(err) = self.check_nbt_file_path(id, &path, true) {
+                    _ = channel.send(Err(err.into()));
+                    return;
+                }
+
+                let result = crate::nbt_file::write_nbt_file(&path, &file);
+                _ = channel.send(result.map_err(|err| format!("Unable to save {}: {err}", path.display()).into()));
+            },
+            MessageToBackend::EditServerList { id, edit } => {
+                let server_dat_path = if let Some(instance) = self.instance_state.read().instances.get(id) {
+                    instance.server_dat_path.clone()
+                } else {
+                    self.send.send_error("Can't edit servers, unknown id");
+                    return;
+                };
+
+                if let Err(err) = crate::instance::edit_servers_dat(&server_dat_path, edit) {
+                    self.send.send_error(format!("Unable to save servers.dat: {err}"));
+                    return;
+                }
+
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.mark_servers_dirty();
+                }
+            },
+            MessageToBackend::PingServer { address, channel } => {
+                tokio::task::spawn(async move {
+                    let result = crate::server_ping::ping_server(&address).await;
+                    _ = channel.send(result.map_err(|err| err.to_string().into()));
+                });
+            },
             MessageToBackend::CreateInstance { name, version, loader } => {
                 self.create_instance(&name, &version, loader).await;
             },
@@ -66,6 +173,43 @@ impl BackendState {
             MessageToBackend::RenameInstance { id, name } => {
                 self.rename_instance(id, &name).await;
             },
+            MessageToBackend::CloneInstance { id, name, contents, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.clone_instance(id, &name, contents, &modal_action).await {
+                        modal_action.set_error_message(format!("Unable to clone instance: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::CheckInstanceVersionChange { id, target, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    let result = backend.check_instance_version_change(id, target).await;
+                    _ = channel.send(result.map_err(|error| format!("Unable to check version change: {error}").into()));
+                });
+            },
+            MessageToBackend::ChangeInstanceVersion { id, target, disable_incompatible, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.change_instance_version(id, target, disable_incompatible, &modal_action).await {
+                        modal_action.set_error_message(format!("Unable to change version: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GetInstanceVersionRollback { id, channel } => {
+                _ = channel.send(self.get_instance_version_rollback(id));
+            },
+            MessageToBackend::RevertInstanceVersionChange { id, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.revert_instance_version_change(id).await {
+                        modal_action.set_error_message(format!("Unable to revert version change: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                });
+            },
             MessageToBackend::SetInstanceMemory { id, memory } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
@@ -87,6 +231,111 @@ impl BackendState {
                     });
                 }
             },
+            MessageToBackend::GetJvmRecommendation { id, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Some(recommendation) = backend.get_jvm_recommendation(id).await {
+                        _ = channel.send(recommendation);
+                    }
+                });
+            },
+            MessageToBackend::SetInstanceResolution { id, resolution } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.resolution = Some(resolution);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceEnvironmentVariables { id, environment_variables } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.environment_variables = Some(environment_variables);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceGroup { id, group } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.organization.modify(|organization| {
+                        organization.group = group.as_deref().map(str::trim).filter(|group| !group.is_empty()).map(Arc::from);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceTags { id, tags } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.organization.modify(|organization| {
+                        let mut deduplicated: Vec<Arc<str>> = Vec::with_capacity(tags.len());
+                        for tag in tags.iter() {
+                            let tag = tag.trim();
+                            if !tag.is_empty() && !deduplicated.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+                                deduplicated.push(tag.into());
+                            }
+                        }
+                        organization.tags = deduplicated.into();
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceFavourite { id, favourite } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.organization.modify(|organization| {
+                        organization.favourite = favourite;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceIcon { id, icon } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.set_instance_icon(id, icon).await;
+                });
+            },
+            MessageToBackend::GetInstanceNotes { id, channel } => {
+                let notes_path = if let Some(instance) = self.instance_state.read().instances.get(id) {
+                    instance.notes_path()
+                } else {
+                    return;
+                };
+                let notes = std::fs::read_to_string(notes_path).unwrap_or_default();
+                _ = channel.send(notes.into());
+            },
+            MessageToBackend::SetInstanceNotes { id, notes } => {
+                let notes_path = if let Some(instance) = self.instance_state.read().instances.get(id) {
+                    instance.notes_path()
+                } else {
+                    return;
+                };
+                if notes.trim_ascii().is_empty() {
+                    _ = std::fs::remove_file(notes_path);
+                } else if let Err(err) = crate::write_safe(&notes_path, notes.as_bytes()) {
+                    self.send.send_error(format!("Unable to save notes: {}", err));
+                }
+            },
+            MessageToBackend::SetInstanceSyncGroup { id, sync_group } => {
+                let (dot_minecraft, previous) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    let previous = instance.configuration.get().sync_group.clone();
+                    instance.configuration.modify(|configuration| {
+                        configuration.sync_group = sync_group.clone();
+                    });
+                    (instance.dot_minecraft_path.clone(), previous)
+                } else {
+                    return;
+                };
+
+                if previous == sync_group {
+                    return;
+                }
+
+                // Unlink the folders of the previous group so they don't bleed into the new one
+                let previous_group = match &previous {
+                    InstanceSyncGroup::Default => Some(None),
+                    InstanceSyncGroup::Disabled => None,
+                    InstanceSyncGroup::Named(name) => Some(Some(&**name)),
+                };
+                if let Some(previous_group) = previous_group {
+                    let sync_dir = crate::syncing::sync_group_dir(&self.directories, previous_group);
+                    crate::syncing::apply_to_instance(EnumSet::empty(), &sync_dir, &[], dot_minecraft);
+                }
+
+                self.prelaunch_apply_syncing(id);
+            },
             MessageToBackend::KillInstance { id } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     if let Some(mut child) = instance.child.take() {
@@ -159,7 +408,9 @@ impl BackendState {
                 let launch_tracker = ProgressTracker::new(Arc::from("Launching"), self.send.clone());
                 modal_action.trackers.push(launch_tracker.clone());
 
-                let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, configuration, quick_play, login_info, add_mods, &launch_tracker, &modal_action).await;
+                let game_defaults = self.config.write().get().game.clone();
+
+                let result = self.launcher.launch(&self.downloads.redirecting_http_client(), dot_minecraft, configuration, &game_defaults, quick_play, login_info, add_mods, &launch_tracker, &modal_action).await;
 
                 if matches!(result, Err(LaunchError::CancelledByUser)) {
                     self.send.send(MessageToFrontend::CloseModal);
@@ -177,6 +428,9 @@ impl BackendState {
                         }
                         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                             instance.child = Some(child);
+                            instance.organization.modify(|organization| {
+                                organization.last_played = Some(chrono::Utc::now().timestamp_millis());
+                            });
                         }
                     },
                     Err(ref err) => {
@@ -221,6 +475,10 @@ impl BackendState {
                     }
                 }
 
+                if !reload.is_empty() {
+                    crate::batch_update::discard_rollback(&instance.root_path);
+                }
+
                 instance_state.reload_mods_immediately.extend(reload);
             },
             MessageToBackend::SetModChildEnabled { id, mod_id, path, enabled } => {
@@ -243,6 +501,46 @@ impl BackendState {
                     }
                 }
             },
+            MessageToBackend::ResolveModrinthCollection { instance: id, collection, modal_action, channel } => {
+                let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    let configuration = instance.configuration.get();
+                    (configuration.loader, configuration.minecraft_version)
+                } else {
+                    self.send.send_error("Can't install collection, unknown id");
+                    modal_action.set_error_message("Can't install collection, unknown id".into());
+                    modal_action.set_finished();
+                    return;
+                };
+
+                let installed = self.clone().load_instance_mods(id).await.unwrap_or_default();
+
+                match self.resolve_modrinth_collection(collection, loader, version, &installed, &modal_action).await {
+                    Ok(resolution) => {
+                        _ = channel.send(resolution);
+                    },
+                    Err(error) => {
+                        modal_action.set_error_message(format!("Error resolving collection: {}", error).into());
+                    },
+                }
+                modal_action.set_finished();
+            },
+            MessageToBackend::SearchCurseForge { request, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    let result = match backend.curseforge() {
+                        Ok(api) => api.search(&request).await,
+                        Err(error) => Err(error),
+                    };
+                    _ = channel.send(result.map(Arc::new).map_err(|error| format!("{}", error).into()));
+                });
+            },
+            MessageToBackend::ImportCurseForgeModpack { path, modal_action } => {
+                if let Err(error) = self.import_curseforge_modpack(path, &modal_action).await {
+                    modal_action.set_error_message(format!("Error importing modpack: {}", error).into());
+                }
+                modal_action.set_finished();
+                self.send.send(MessageToFrontend::Refresh);
+            },
             MessageToBackend::DownloadAllMetadata => {
                 self.download_all_metadata().await;
             },
@@ -270,12 +568,22 @@ impl BackendState {
                     reload.insert(id);
                 }
 
+                if !reload.is_empty() {
+                    crate::batch_update::discard_rollback(&instance.root_path);
+                }
+
                 instance_state.reload_mods_immediately.extend(reload);
             },
+            MessageToBackend::SetPackEnabled { id, folder, paths, enabled } => {
+                self.set_packs_enabled(id, folder, paths, enabled);
+            },
+            MessageToBackend::DeletePack { id, folder, paths } => {
+                self.delete_packs(id, folder, paths);
+            },
             MessageToBackend::UpdateCheck { instance: id, modal_action } => {
-                let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                let (loader, version, preferences) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     let configuration = instance.configuration.get();
-                    (configuration.loader, configuration.minecraft_version)
+                    (configuration.loader, configuration.minecraft_version, configuration.mod_update_preferences.clone())
                 } else {
                     self.send.send_error("Can't update instance, unknown id");
                     modal_action.set_error_message("Can't update instance, unknown id".into());
@@ -299,23 +607,15 @@ impl BackendState {
                 tracker.set_total(mods.len());
                 modal_action.trackers.push(tracker.clone());
 
-                let semaphore = Semaphore::new(8);
-
-                let params = VersionUpdateParameters {
-                    loaders: [modrinth_loader].into(),
-                    game_versions: [version].into(),
-                };
-
-                let modrinth_modpack_params = VersionV3UpdateParameters {
-                    loaders: ["mrpack".into()].into(),
-                    loader_fields: VersionV3LoaderFields {
-                        mrpack_loaders: [modrinth_loader].into(),
-                        game_versions: [version].into(),
+                // A CurseForge outage or missing api key shouldn't block Modrinth updates, those mods are marked as not found instead
+                let curseforge_actions = match self.curseforge_update_check(&mods, version.as_str(), loader).await {
+                    Ok(actions) => actions,
+                    Err(error) => {
+                        eprintln!("Error checking CurseForge mods for updates: {error}");
+                        FxHashMap::default()
                     },
                 };
 
-                let meta = self.meta.clone();
-
                 let mut futures = Vec::new();
 
                 struct UpdateResult {
@@ -327,11 +627,9 @@ impl BackendState {
                     let sources = self.mod_metadata_manager.read_content_sources();
                     for summary in mods.iter() {
                         let source = sources.get(&summary.mod_summary.hash).copied().unwrap_or(ContentSource::Manual);
-                        let semaphore = &semaphore;
-                        let meta = &meta;
-                        let params = &params;
-                        let modpack_params = &modrinth_modpack_params;
+                        let preference = preferences.get(&summary.mod_summary.id).cloned().unwrap_or_default();
                         let tracker = &tracker;
+                        let curseforge_actions = &curseforge_actions;
                         futures.push(async move {
                             match source {
                                 ContentSource::Manual => {
@@ -339,47 +637,19 @@ impl BackendState {
                                     tracker.notify();
                                     Ok(ModUpdateAction::ManualInstall)
                                 },
+                                ContentSource::CurseForge => {
+                                    tracker.add_count(1);
+                                    tracker.notify();
+                                    let action = curseforge_actions.get(&summary.mod_summary.hash).cloned().unwrap_or(ModUpdateAction::ErrorNotFound);
+                                    Ok(crate::update_preferences::apply_update_preference(action, &preference))
+                                },
                                 ContentSource::Modrinth => {
-                                    let permit = semaphore.acquire().await.unwrap();
-                                    let result = if matches!(summary.mod_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. }) {
-                                        meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
-                                            sha1: hex::encode(summary.mod_summary.hash).into(),
-                                            params: modpack_params.clone()
-                                        }).await
-                                    } else {
-                                        meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                            sha1: hex::encode(summary.mod_summary.hash).into(),
-                                            params: params.clone()
-                                        }).await
-                                    };
-                                    drop(permit);
+                                    let result = self.preferred_modrinth_update_action(&summary.mod_summary, modrinth_loader, version, &preference).await;
 
                                     tracker.add_count(1);
                                     tracker.notify();
 
-                                    if let Err(MetaLoadError::NonOK(404)) = result {
-                                        return Ok(ModUpdateAction::ErrorNotFound);
-                                    }
-
-                                    let result = result?;
-
-                                    let install_file = result
-                                        .0
-                                        .files
-                                        .iter()
-                                        .find(|file| file.primary)
-                                        .unwrap_or(result.0.files.first().unwrap());
-
-                                    let mut latest_hash = [0u8; 20];
-                                    let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
-                                        return Ok(ModUpdateAction::ErrorInvalidHash);
-                                    };
-
-                                    if latest_hash == summary.mod_summary.hash {
-                                        Ok(ModUpdateAction::AlreadyUpToDate)
-                                    } else {
-                                        Ok(ModUpdateAction::Modrinth(install_file.clone()))
-                                    }
+                                    result
                                 },
                             }
                         }.map_ok(|action| UpdateResult {
@@ -411,8 +681,16 @@ impl BackendState {
                 tracker.set_finished(ProgressTrackerFinishType::Normal);
                 modal_action.set_finished();
             },
+            MessageToBackend::InstallMissingDependencies { instance, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.install_missing_dependencies(instance, &modal_action).await;
+                    modal_action.set_finished();
+                    backend.send.send(MessageToFrontend::Refresh);
+                });
+            },
             MessageToBackend::UpdateMod { instance: id, mod_id, modal_action } => {
-                let content_install = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                let (root_path, content_install) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     let configuration = instance.configuration.get();
                     let (loader, minecraft_version) = (configuration.loader, configuration.minecraft_version);
                     let Some(mod_summary) = instance.try_get_mod(mod_id) else {
@@ -421,83 +699,111 @@ impl BackendState {
                         return;
                     };
 
-                    let Some(update_info) = self.mod_metadata_manager.updates.read().get(&mod_summary.mod_summary.hash).cloned() else {
-                        self.send.send_error("Can't update mod in instance, missing update action");
-                        modal_action.set_finished();
-                        return;
-                    };
-
-                    match update_info {
-                        ModUpdateAction::ErrorNotFound => {
-                            self.send.send_error("Can't update mod in instance, 404 not found");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::ErrorInvalidHash => {
-                            self.send.send_error("Can't update mod in instance, returned invalid hash");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::AlreadyUpToDate => {
-                            self.send.send_error("Can't update mod in instance, already up-to-date");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::ManualInstall => {
-                            self.send.send_error("Can't update mod in instance, mod was manually installed");
+                    let install_file = match self.mod_update_install_file(mod_summary) {
+                        Ok(install_file) => install_file,
+                        Err(error) => {
+                            self.send.send_error(format!("Can't update mod in instance, {error}"));
                             modal_action.set_finished();
                             return;
                         },
-                        ModUpdateAction::Modrinth(modrinth_file) => {
-                            let mut path = mod_summary.path.with_file_name(&*modrinth_file.filename);
-                            if !mod_summary.enabled {
-                                path.add_extension("disabled");
-                            }
-                            debug_assert!(path.is_absolute());
-                            ContentInstall {
-                                target: InstallTarget::Instance(id),
-                                loader_hint: loader,
-                                version_hint: Some(minecraft_version.into()),
-                                files: [ContentInstallFile {
-                                    replace_old: Some(mod_summary.path.clone()),
-                                    path: bridge::install::ContentInstallPath::Raw(path.into()),
-                                    download: ContentDownload::Url {
-                                        url: modrinth_file.url.clone(),
-                                        sha1: modrinth_file.hashes.sha1.clone(),
-                                        size: modrinth_file.size,
-                                    },
-                                    content_source: ContentSource::Modrinth,
-                                }].into(),
-                            }
-                        },
-                    }
+                    };
+
+                    (instance.root_path.clone(), ContentInstall {
+                        target: InstallTarget::Instance(id),
+                        loader_hint: loader,
+                        version_hint: Some(minecraft_version.into()),
+                        files: [install_file].into(),
+                    })
                 } else {
                     self.send.send_error("Can't update mod in instance, unknown instance id");
                     modal_action.set_finished();
                     return;
                 };
 
-                self.install_content(content_install, modal_action.clone()).await;
+                if self.install_content(content_install, modal_action.clone()).await.is_some() {
+                    crate::batch_update::discard_rollback(&root_path);
+                }
                 modal_action.set_finished();
                 self.send.send(MessageToFrontend::Refresh);
             },
+            MessageToBackend::GetPendingModUpdates { instance, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    let result = backend.get_pending_mod_updates(instance).await;
+                    _ = channel.send(result.map_err(|error| format!("Unable to list updates: {error}").into()));
+                });
+            },
+            MessageToBackend::UpdateAllMods { instance, mod_ids, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.update_mods(instance, &mod_ids, &modal_action).await {
+                        modal_action.set_error_message(format!("Unable to update mods: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                    backend.send.send(MessageToFrontend::Refresh);
+                });
+            },
+            MessageToBackend::GetModUpdateRollback { instance, channel } => {
+                _ = channel.send(self.get_mod_update_rollback(instance));
+            },
+            MessageToBackend::RevertModUpdates { instance, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.revert_mod_updates(instance).await {
+                        modal_action.set_error_message(format!("Unable to revert mod updates: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::SetModUpdatePreference { instance, mod_summary_id, preference } => {
+                // Re-evaluating the update can hit the network, so it shouldn't block other messages
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.set_mod_update_preference(instance, mod_summary_id, preference).await;
+                });
+            },
+            MessageToBackend::SkipModUpdate { instance, mod_id } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.skip_mod_update(instance, mod_id).await;
+                });
+            },
+            MessageToBackend::GetModpackUpdatePreview { instance, mod_id, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    let result = backend.get_modpack_update_preview(instance, mod_id).await;
+                    _ = channel.send(result.map_err(|error| format!("Unable to preview modpack update: {error}").into()));
+                });
+            },
+            MessageToBackend::UpdateModpack { instance, mod_id, keep_files, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.update_modpack(instance, mod_id, &keep_files, &modal_action).await {
+                        modal_action.set_error_message(format!("Unable to update modpack: {}", error).into());
+                    }
+                    modal_action.set_finished();
+                    backend.send.send(MessageToFrontend::Refresh);
+                });
+            },
+            MessageToBackend::PackUpdateCheck { instance, folder, modal_action } => {
+                self.pack_update_check(instance, folder, modal_action).await;
+            },
+            MessageToBackend::UpdatePack { instance, folder, path, modal_action } => {
+                self.update_pack(instance, folder, path, modal_action).await;
+            },
             MessageToBackend::Sleep5s => {
                 tokio::time::sleep(Duration::from_secs(5)).await;
             },
             MessageToBackend::ReadLog { path, send } => {
                 let frontend = self.send.clone();
                 let serial = AtomicOptionSerial::default();
+                let mut parser = LogFileParser::for_path(&path);
 
                 let file = match std::fs::File::open(path) {
                     Ok(file) => file,
                     Err(e) => {
                         let error = format!("Unable to read file: {e}");
-                        for line in error.split('\n') {
-                            let replaced = log_reader::replace(line.trim_ascii_end());
-                            if send.send(replaced.into()).await.is_err() {
-                                return;
-                            }
-                        }
+                        _ = send.send(log_parser::error_entry(&log_reader::replace(&error))).await;
                         frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                         return;
                     },
@@ -512,31 +818,33 @@ impl BackendState {
                     let mut buf_reader = std::io::BufReader::new(gz_decoder);
                     tokio::task::spawn_blocking(move || {
                         let mut line = String::new();
-                        let mut factory = ArcStrFactory::default();
                         loop {
                             match buf_reader.read_line(&mut line) {
-                                Ok(0) => return,
+                                Ok(0) => break,
                                 Ok(_) => {
-                                    let replaced = log_reader::replace(line.trim_ascii_end());
-                                    if send.blocking_send(factory.create(&replaced)).is_err() {
-                                        return;
+                                    if let Some(entry) = parser.push_line(&log_reader::replace(&line)) {
+                                        if send.blocking_send(entry).is_err() {
+                                            return;
+                                        }
+                                        frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                                     }
                                     line.clear();
-                                    frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                                 },
                                 Err(e) => {
-                                    let error = format!("Error while reading file: {e}");
-                                    for line in error.split('\n') {
-                                        let replaced = log_reader::replace(line.trim_ascii_end());
-                                        if send.blocking_send(factory.create(&replaced)).is_err() {
-                                            return;
-                                        }
+                                    if let Some(entry) = parser.flush() && send.blocking_send(entry).is_err() {
+                                        return;
                                     }
+                                    let error = format!("Error while reading file: {e}");
+                                    _ = send.blocking_send(log_parser::error_entry(&log_reader::replace(&error)));
                                     frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                                     return;
                                 },
                             }
                         }
+                        if let Some(entry) = parser.flush() {
+                            _ = send.blocking_send(entry);
+                        }
+                        frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                     });
                     return;
                 }
@@ -546,8 +854,7 @@ impl BackendState {
                 let mut reader = tokio::io::BufReader::new(tokio::fs::File::from_std(file));
 
                 tokio::task::spawn(async move {
-                    let mut first = true;
-                    let mut factory = ArcStrFactory::default();
+                    let mut idle_since = None;
                     loop {
                         tokio::select! {
                             _ = send.closed() => {
@@ -556,48 +863,50 @@ impl BackendState {
                             read = reader.read_until('\n' as u8, &mut line) => match read {
                                 Ok(0) => {
                                     // EOF reached. If this file is being actively written to (e.g. latest.log),
-                                    // then there could be more data
+                                    // then there could be more data, so the last entry is only finished once
+                                    // nothing has been written for a while. Otherwise a stack trace written across
+                                    // two polls would be split up
+                                    let idle_since = idle_since.get_or_insert_with(Instant::now);
+                                    if idle_since.elapsed() >= LOG_IDLE_FLUSH {
+                                        let mut sent = !line.is_empty();
+                                        if !push_log_data(&mut parser, &line, &send).await {
+                                            return;
+                                        }
+                                        line.clear();
+
+                                        if let Some(entry) = parser.flush() {
+                                            if send.send(entry).await.is_err() {
+                                                return;
+                                            }
+                                            sent = true;
+                                        }
+                                        if sent {
+                                            frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
+                                        }
+                                    }
+
                                     tokio::time::sleep(Duration::from_millis(250)).await;
                                 },
                                 Ok(_) => {
-                                    match str::from_utf8(&*line) {
-                                        Ok(utf8) => {
-                                            if first {
-                                                first = false;
-                                                for line in utf8.split('\n') {
-                                                    let replaced = log_reader::replace(line.trim_ascii_end());
-                                                    if send.send(factory.create(&replaced)).await.is_err() {
-                                                        return;
-                                                    }
-                                                }
-                                            } else {
-                                                let replaced = log_reader::replace(utf8.trim_ascii_end());
-                                                if send.send(factory.create(&replaced)).await.is_err() {
-                                                    return;
-                                                }
-                                            }
-                                        },
-                                        Err(e) => {
-                                            let error = format!("Invalid UTF8: {e}");
-                                            for line in error.split('\n') {
-                                                let replaced = log_reader::replace(line.trim_ascii_end());
-                                                if send.blocking_send(factory.create(&replaced)).is_err() {
-                                                    return;
-                                                }
-                                            }
-                                        },
+                                    idle_since = None;
+
+                                    // Wait for the rest of a partially written line
+                                    if !line.ends_with(b"\n") {
+                                        continue;
+                                    }
+
+                                    if !push_log_data(&mut parser, &line, &send).await {
+                                        return;
                                     }
                                     frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                                     line.clear();
                                 },
                                 Err(e) => {
-                                    let error = format!("Error while reading file: {e}");
-                                    for line in error.split('\n') {
-                                        let replaced = log_reader::replace(line.trim_ascii_end());
-                                        if send.blocking_send(factory.create(&replaced)).is_err() {
-                                            return;
-                                        }
+                                    if let Some(entry) = parser.flush() && send.send(entry).await.is_err() {
+                                        return;
                                     }
+                                    let error = format!("Error while reading file: {e}");
+                                    _ = send.send(log_parser::error_entry(&log_reader::replace(&error))).await;
                                     frontend.send_with_serial(MessageToFrontend::Refresh, &serial);
                                     return;
                                 },
@@ -608,11 +917,13 @@ impl BackendState {
             },
             MessageToBackend::GetLogFiles { instance: id, channel } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    let logs = instance.dot_minecraft_path.join("logs");
+                    let mut paths_with_time = Vec::new();
+                    let mut total_gzipped_size = 0;
 
-                    if let Ok(read_dir) = std::fs::read_dir(logs) {
-                        let mut paths_with_time = Vec::new();
-                        let mut total_gzipped_size = 0;
+                    for (folder, extension) in [("logs", ".log"), ("crash-reports", ".txt")] {
+                        let Ok(read_dir) = std::fs::read_dir(instance.dot_minecraft_path.join(folder)) else {
+                            continue;
+                        };
 
                         for file in read_dir {
                             let Ok(entry) = file else {
@@ -628,7 +939,7 @@ impl BackendState {
 
                             if filename.ends_with(".log.gz") {
                                 total_gzipped_size += metadata.len();
-                            } else if !filename.ends_with(".log") {
+                            } else if !filename.ends_with(extension) {
                                 continue;
                             }
 
@@ -637,33 +948,39 @@ impl BackendState {
 
                             paths_with_time.push((Arc::from(entry.path()), created.max(modified)));
                         }
+                    }
 
-                        paths_with_time.sort_by_key(|(_, t)| *t);
-                        let paths = paths_with_time.into_iter().map(|(p, _)| p).rev().collect();
+                    paths_with_time.sort_by_key(|(_, t)| *t);
+                    let paths = paths_with_time.into_iter().map(|(p, _)| p).rev().collect();
 
-                        let _ = channel.send(LogFiles { paths, total_gzipped_size: total_gzipped_size.min(usize::MAX as u64) as usize });
-                    }
+                    let _ = channel.send(LogFiles { paths, total_gzipped_size: total_gzipped_size.min(usize::MAX as u64) as usize });
                 }
             },
-            MessageToBackend::GetSyncState { channel } => {
-                let result = crate::syncing::get_sync_state(self.config.write().get().sync_targets, &self.directories);
+            MessageToBackend::GetSyncState { group, channel } => {
+                let Some(want_sync) = self.sync_targets(group.as_deref()) else {
+                    return;
+                };
 
-                match result {
-                    Ok(state) => {
-                        _ = channel.send(state);
-                    },
-                    Err(error) => {
-                        self.send.send_error(format!("Error while getting sync state: {error}"));
-                    },
-                }
+                let groups = self.config.write().get().sync_groups.iter().map(|sync_group| sync_group.name.clone()).collect();
+                let members = self.sync_group_members(group.as_deref());
+                let sync_dir = crate::syncing::sync_group_dir(&self.directories, group.as_deref());
+                _ = channel.send(crate::syncing::get_sync_state(want_sync, &sync_dir, &members, groups));
             },
-            MessageToBackend::SetSyncing { target, value } => {
+            MessageToBackend::SetSyncing { group, target, value } => {
+                if self.sync_targets(group.as_deref()).is_none() {
+                    self.send.send_error("Unknown sync group");
+                    return;
+                }
+
+                let members = self.sync_group_members(group.as_deref());
+                let sync_dir = crate::syncing::sync_group_dir(&self.directories, group.as_deref());
+
                 let mut write = self.config.write();
 
                 let result = if value {
-                    crate::syncing::enable_all(target, &self.directories)
+                    crate::syncing::enable_all(target, &sync_dir, &members)
                 } else {
-                    crate::syncing::disable_all(target, &self.directories).map(|_| true)
+                    crate::syncing::disable_all(target, &sync_dir, &members).map(|_| true)
                 };
 
                 match result {
@@ -679,15 +996,116 @@ impl BackendState {
                     },
                 }
 
-                if value {
-                    write.modify(|config| {
-                        config.sync_targets.insert(target);
-                    });
-                } else {
-                    write.modify(|config| {
-                        config.sync_targets.remove(target);
+                write.modify(|config| {
+                    let sync_targets = match &group {
+                        None => &mut config.sync_targets,
+                        Some(group) => match config.sync_groups.iter_mut().find(|sync_group| sync_group.name == *group) {
+                            Some(sync_group) => &mut sync_group.sync_targets,
+                            None => return,
+                        },
+                    };
+                    if value {
+                        sync_targets.insert(target);
+                    } else {
+                        sync_targets.remove(target);
+                    }
+                });
+            },
+            MessageToBackend::GetSyncGroups { channel } => {
+                _ = channel.send(self.config.write().get().sync_groups.iter().map(|sync_group| sync_group.name.clone()).collect());
+            },
+            MessageToBackend::CreateSyncGroup { name } => {
+                if !crate::syncing::is_valid_sync_group_name(&name) {
+                    self.send.send_error("Invalid sync group name");
+                    return;
+                }
+
+                let mut write = self.config.write();
+                if write.get().sync_groups.iter().any(|sync_group| sync_group.name == name) {
+                    self.send.send_error("A sync group with that name already exists");
+                    return;
+                }
+
+                if let Err(error) = std::fs::create_dir_all(crate::syncing::sync_group_dir(&self.directories, Some(&*name))) {
+                    self.send.send_error(format!("Unable to create sync group folder: {error}"));
+                    return;
+                }
+
+                write.modify(|config| {
+                    config.sync_groups.push(SyncGroup {
+                        name,
+                        sync_targets: EnumSet::empty(),
                     });
+                });
+            },
+            MessageToBackend::DeleteSyncGroup { name } => {
+                // Members are moved out of the group, the synced folder itself is kept
+                let sync_dir = crate::syncing::sync_group_dir(&self.directories, Some(&*name));
+                for dot_minecraft in self.sync_group_members(Some(&*name)) {
+                    crate::syncing::apply_to_instance(EnumSet::empty(), &sync_dir, &[], dot_minecraft);
+                }
+
+                for instance in self.instance_state.write().instances.iter_mut() {
+                    if instance.configuration.get().sync_group.is_member_of(Some(&*name)) {
+                        instance.configuration.modify(|configuration| {
+                            configuration.sync_group = InstanceSyncGroup::Disabled;
+                        });
+                    }
+                }
+
+                self.config.write().modify(|config| {
+                    config.sync_groups.retain(|sync_group| sync_group.name != name);
+                });
+            },
+            MessageToBackend::GetNetworkConfiguration { channel } => {
+                _ = channel.send(self.config.write().get().network.clone());
+            },
+            MessageToBackend::SetNetworkConfiguration { configuration } => {
+                self.downloads.update(&configuration);
+                self.config.write().modify(|config| {
+                    config.network = configuration;
+                });
+            },
+            MessageToBackend::GetCurseForgeConfiguration { channel } => {
+                _ = channel.send(self.config.write().get().curseforge.clone());
+            },
+            MessageToBackend::SetCurseForgeConfiguration { configuration } => {
+                self.config.write().modify(|config| {
+                    config.curseforge = configuration;
+                });
+            },
+            MessageToBackend::GetPasteConfiguration { channel } => {
+                _ = channel.send(self.config.write().get().paste.clone());
+            },
+            MessageToBackend::SetPasteConfiguration { configuration } => {
+                self.config.write().modify(|config| {
+                    config.paste = configuration;
+                });
+            },
+            MessageToBackend::GetRedactionConfiguration { channel } => {
+                _ = channel.send(self.config.write().get().redaction.clone());
+            },
+            MessageToBackend::SetRedactionConfiguration { configuration } => {
+                for error in log_reader::set_redaction_rules(&configuration) {
+                    self.send.send_warning(format!("Redaction rule skipped. {error}"));
                 }
+                self.config.write().modify(|config| {
+                    config.redaction = configuration;
+                });
+            },
+            MessageToBackend::GetGameConfiguration { channel } => {
+                _ = channel.send(self.config.write().get().game.clone());
+            },
+            MessageToBackend::SetGameConfiguration { configuration } => {
+                self.config.write().modify(|config| {
+                    config.game = configuration;
+                });
+            },
+            MessageToBackend::TestRedactionRules { rules, sample, channel } => {
+                let result = log_reader::test_redaction_rules(&rules, &sample)
+                    .map(Arc::from)
+                    .map_err(|e| Arc::from(e.to_string()));
+                _ = channel.send(result);
             },
             MessageToBackend::CleanupOldLogFiles { instance: id } => {
                 let mut deleted = 0;
@@ -717,142 +1135,23 @@ impl BackendState {
 
                 self.send.send_success(format!("Deleted {} files", deleted));
             },
-            MessageToBackend::UploadLogFile { path, modal_action } => {
-                let file = match std::fs::File::open(path) {
-                    Ok(file) => file,
-                    Err(e) => {
-                        let error = format!("Unable to read file: {e}");
-                        modal_action.set_error_message(log_reader::replace(&error).into());
-                        modal_action.set_finished();
-                        return;
-                    },
-                };
-
-                let tracker = ProgressTracker::new("Reading log file".into(), self.send.clone());
-                tracker.set_total(4);
-                tracker.notify();
-                modal_action.trackers.push(tracker.clone());
-
-                let mut reader = std::io::BufReader::new(file);
-                let Ok(buffer) = reader.fill_buf() else {
-                    tracker.set_finished(ProgressTrackerFinishType::Error);
-                    tracker.notify();
-                    return;
-                };
-
-                let mut content = String::new();
-
-                if buffer.len() >= 2 && buffer[0] == 0x1F && buffer[1] == 0x8B {
-                    let mut gz_decoder = flate2::bufread::GzDecoder::new(reader);
-                    if let Err(e) = gz_decoder.read_to_string(&mut content) {
-                        let error = format!("Error while reading file: {e}");
-                        modal_action.set_error_message(log_reader::replace(&error).into());
-                        modal_action.set_finished();
-                        return;
-                    }
-                } else {
-                    if let Err(e) = reader.read_to_string(&mut content) {
-                        let error = format!("Error while reading file: {e}");
-                        modal_action.set_error_message(log_reader::replace(&error).into());
-                        modal_action.set_finished();
-                        return;
-                    }
-                }
-
-                tracker.set_title("Redacting sensitive information".into());
-                tracker.set_count(1);
-                tracker.notify();
-
-                // Truncate to 11mb, mclo.gs limit as of right now is ~10.5mb
-                if content.len() > 11000000 {
-                    for i in 0..4 {
-                        if content.is_char_boundary(11000000 - i) {
-                            content.truncate(11000000 - i);
-                            break;
-                        }
-                    }
-                }
-
-                let replaced = log_reader::replace(&*content);
-
-                tracker.set_title("Uploading to mclo.gs".into());
-                tracker.set_count(2);
-                tracker.notify();
-
-                if replaced.trim_ascii().is_empty() {
-                    modal_action.set_error_message("Log file was empty, didn't upload".into());
-                    modal_action.set_finished();
-                    return;
-                }
-
-                let result = self.http_client.post("https://api.mclo.gs/1/log").form(&[("content", &*replaced)]).send().await;
-
-                let resp = match result {
-                    Ok(resp) => resp,
-                    Err(e) => {
-                        let error = format!("Error while uploading log: {e:?}");
-                        modal_action.set_error_message(error.into());
-                        modal_action.set_finished();
-                        return;
-                    },
-                };
-
-                tracker.set_count(3);
-                tracker.notify();
-
-                let bytes = match resp.bytes().await {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        let error = format!("Error while reading mclo.gs response: {e:?}");
-                        modal_action.set_error_message(error.into());
-                        modal_action.set_finished();
-                        return;
-                    },
-                };
-
-                #[derive(Deserialize)]
-                struct McLogsResponse {
-                    success: bool,
-                    url: Option<String>,
-                    error: Option<String>,
+            MessageToBackend::ExportLog { path, lines } => {
+                let mut text = String::new();
+                for line in lines.iter() {
+                    text.push_str(&log_reader::replace(line));
+                    text.push('\n');
                 }
 
-                let response: McLogsResponse = match serde_json::from_slice(&bytes) {
-                    Ok(response) => response,
-                    Err(e) => {
-                        let error = format!("Error while deserializing mclo.gs response: {e:?}");
-                        modal_action.set_error_message(error.into());
-                        modal_action.set_finished();
-                        return;
-                    },
-                };
-
-                if response.success {
-                    if let Some(url) = response.url {
-                        modal_action.set_visit_url(ModalActionVisitUrl {
-                            message: format!("Open {}", url).into(),
-                            url: url.into(),
-                            prevent_auto_finish: true,
-                        });
-                        modal_action.set_finished();
-                    } else {
-                        modal_action.set_error_message("Success returned, but missing url".into());
-                        modal_action.set_finished();
-                    }
-                } else {
-                    if let Some(e) = response.error {
-                        let error = format!("mclo.gs rejected upload: {e}");
-                        modal_action.set_error_message(error.into());
-                        modal_action.set_finished();
-                    } else {
-                        modal_action.set_error_message("Failure returned, but missing error".into());
-                        modal_action.set_finished();
-                    }
+                match crate::write_safe(&path, text.as_bytes()) {
+                    Ok(()) => self.send.send_success(format!("Exported log to {}", path.display())),
+                    Err(err) => self.send.send_error(format!("Unable to export log: {err}")),
                 }
-
-                tracker.set_count(4);
-                tracker.set_finished(ProgressTrackerFinishType::Normal);
-                tracker.notify();
+            },
+            MessageToBackend::UploadLogFile { path, modal_action } => {
+                self.upload_log_file(&path, modal_action).await;
+            },
+            MessageToBackend::UploadLogBundle { instance, modal_action } => {
+                self.upload_log_bundle(instance, modal_action).await;
             },
             MessageToBackend::AddNewAccount { modal_action } => {
                 self.login_flow(&modal_action, None).await;
@@ -1067,6 +1366,57 @@ impl BackendState {
 
         println!("Done downloading all metadata");
     }
+
+    fn check_nbt_file_path(&self, id: InstanceID, path: &Path, editing: bool) -> Result<(), &'static str> {
+        let instance_state = self.instance_state.read();
+        let Some(instance) = instance_state.instances.get(id) else {
+            return Err("Unknown instance id");
+        };
+        let inside_instance = path.starts_with(&instance.dot_minecraft_path)
+            && path.components().all(|component| !matches!(component, std::path::Component::ParentDir));
+        if !inside_instance {
+            return Err("File isn't inside the instance folder");
+        }
+        if editing && instance.child.is_some() {
+            return Err("Can't save files while the instance is running");
+        }
+        Ok(())
+    }
+
+    fn check_world_path(&self, id: InstanceID, world: &Path, editing: bool) -> Result<(), &'static str> {
+        let instance_state = self.instance_state.read();
+        let Some(instance) = instance_state.instances.get(id) else {
+            return Err("Unknown instance id");
+        };
+        if world.parent() != Some(&*instance.saves_path) {
+            return Err("World isn't inside the instance saves folder");
+        }
+        if editing && instance.child.is_some() {
+            return Err("Can't edit a world while the instance is running");
+        }
+        Ok(())
+    }
+}
+
+const LOG_IDLE_FLUSH: Duration = Duration::from_secs(1);
+
+/// Returns false if the receiver was closed
+async fn push_log_data(parser: &mut LogFileParser, data: &[u8], send: &tokio::sync::mpsc::Sender<GameOutputEntry>) -> bool {
+    match str::from_utf8(data) {
+        Ok(utf8) => {
+            // The first read also contains the initially buffered data, which can span multiple lines
+            for line in utf8.split('\n') {
+                if let Some(entry) = parser.push_line(&log_reader::replace(line)) && send.send(entry).await.is_err() {
+                    return false;
+                }
+            }
+            true
+        },
+        Err(e) => {
+            let error = format!("Invalid UTF8: {e}");
+            send.send(log_parser::error_entry(&log_reader::replace(&error))).await.is_ok()
+        },
+    }
 }
 
 fn set_mod_child_enabled(child_state_path: &Path, child: &str, enabled: bool) -> std::io::Result<()> {
@@ -0,0 +1,321 @@
+use std::{collections::BTreeMap, ffi::OsStr, path::{Path, PathBuf}, sync::{atomic::Ordering, Arc}};
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallPath, InstallTarget},
+    instance::{InstanceID, InstanceModID, InstanceModSummary, LoaderSpecificModSummary, ModSummary, PendingModUpdate},
+    modal_action::ModalAction,
+};
+use schema::{
+    instance::ModUpdatePreference,
+    modrinth::{ModrinthFile, ModrinthLoader, ModrinthProjectVersion, ModrinthProjectVersionsRequest},
+};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use ustr::Ustr;
+
+use crate::{metadata::items::ModrinthProjectVersionsMetadataItem, mod_metadata::ModUpdateAction, BackendState};
+
+const ROLLBACK_JSON: &str = "update_rollback.json";
+
+#[derive(Error, Debug)]
+pub enum BatchUpdateError {
+    #[error("Unknown instance")]
+    UnknownInstance,
+    #[error("None of the selected mods have an update")]
+    NothingToUpdate,
+    #[error("There is no update to revert")]
+    NoRollback,
+    #[error("{0} was changed after the update, it can no longer be reverted")]
+    ModifiedSinceUpdate(Arc<str>),
+    #[error("An I/O error occured while updating mods:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("A serialization error occured while saving the rollback:\n{0}")]
+    SerdeError(#[from] serde_json::Error),
+}
+
+/// The mods replaced by the most recent batch update, the old jars are kept in the content library
+#[derive(Serialize, Deserialize)]
+struct UpdateRollback {
+    mods: Vec<UpdateRollbackEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UpdateRollbackEntry {
+    name: Arc<str>,
+    old_path: PathBuf,
+    library_path: PathBuf,
+    new_path: PathBuf,
+    /// Sha1 of the update, so a jar that was replaced since isn't deleted
+    new_sha1: Arc<str>,
+}
+
+impl BackendState {
+    pub async fn get_pending_mod_updates(&self, id: InstanceID) -> Result<Arc<[PendingModUpdate]>, BatchUpdateError> {
+        let Some(mods) = self.clone().load_instance_mods(id).await else {
+            return Err(BatchUpdateError::UnknownInstance);
+        };
+
+        let (loader, minecraft_version, preferences) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            (configuration.loader, configuration.minecraft_version, configuration.mod_update_preferences.clone())
+        } else {
+            return Err(BatchUpdateError::UnknownInstance);
+        };
+
+        let futures = mods.iter().filter(|summary| can_batch_update(summary, &preferences)).map(|summary| async move {
+            let action = self.mod_metadata_manager.updates.read().get(&summary.mod_summary.hash).cloned();
+            let (new_version, changelog) = match action? {
+                ModUpdateAction::Modrinth(file) => {
+                    let version = self.modrinth_version_for_file(&summary.mod_summary, loader.as_modrinth_loader(), minecraft_version, &file).await;
+                    let new_version = version.as_ref().and_then(|version| version.version_number.clone()).unwrap_or(file.filename);
+                    (new_version, version.and_then(|version| version.changelog))
+                },
+                ModUpdateAction::CurseForge(file) => (file.display_name, None),
+                _ => return None,
+            };
+            Some(PendingModUpdate {
+                mod_id: summary.id,
+                name: summary.mod_summary.name.clone(),
+                old_version: summary.mod_summary.version_str.clone(),
+                new_version,
+                changelog,
+            })
+        });
+
+        Ok(futures::future::join_all(futures).await.into_iter().flatten().collect())
+    }
+
+    pub async fn update_mods(&self, id: InstanceID, mod_ids: &[InstanceModID], modal_action: &ModalAction) -> Result<(), BatchUpdateError> {
+        let (root_path, loader, minecraft_version, updates) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(BatchUpdateError::UnknownInstance);
+            };
+            let configuration = instance.configuration.get();
+            let (loader, minecraft_version, preferences) = (configuration.loader, configuration.minecraft_version, configuration.mod_update_preferences.clone());
+
+            let updates: Vec<_> = mod_ids.iter()
+                .filter_map(|mod_id| instance.try_get_mod(*mod_id))
+                .filter(|summary| can_batch_update(summary, &preferences))
+                .filter_map(|summary| Some((summary.clone(), self.mod_update_install_file(summary).ok()?)))
+                .collect();
+            (instance.root_path.clone(), loader, minecraft_version, updates)
+        };
+
+        if updates.is_empty() {
+            return Err(BatchUpdateError::NothingToUpdate);
+        }
+
+        let mut rollback = UpdateRollback { mods: Vec::new() };
+        for (summary, install_file) in &updates {
+            let (ContentInstallPath::Raw(new_path), ContentDownload::Url { sha1, .. }) = (&install_file.path, &install_file.download) else {
+                continue;
+            };
+            let extension = if summary.path.extension() == Some(OsStr::new("disabled")) {
+                summary.path.with_extension("").extension().map(OsStr::to_owned)
+            } else {
+                summary.path.extension().map(OsStr::to_owned)
+            };
+            rollback.mods.push(UpdateRollbackEntry {
+                name: summary.mod_summary.name.clone(),
+                old_path: summary.path.to_path_buf(),
+                library_path: crate::create_content_library_path(&self.directories.content_library_dir, summary.mod_summary.hash,
+                    extension.as_ref().and_then(|extension| extension.to_str())),
+                new_path: new_path.to_path_buf(),
+                new_sha1: sha1.clone(),
+            });
+        }
+
+        // Manually installed jars aren't in the content library yet, they need to be kept before they're replaced
+        let rollback = tokio::task::spawn_blocking(move || -> std::io::Result<UpdateRollback> {
+            for entry in &rollback.mods {
+                if entry.library_path.exists() {
+                    continue;
+                }
+                if let Some(parent) = entry.library_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                if std::fs::hard_link(&entry.old_path, &entry.library_path).is_err() {
+                    std::fs::copy(&entry.old_path, &entry.library_path)?;
+                }
+            }
+            Ok(rollback)
+        }).await.unwrap()?;
+
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(id),
+            loader_hint: loader,
+            version_hint: Some(minecraft_version.into()),
+            files: updates.into_iter().map(|(_, install_file)| install_file).collect(),
+        };
+
+        // install_content reports its own errors to the modal
+        if self.install_content(content_install, modal_action.clone()).await.is_none() {
+            return Ok(());
+        }
+
+        crate::write_safe(&root_path.join(ROLLBACK_JSON), &serde_json::to_vec(&rollback)?)?;
+
+        Ok(())
+    }
+
+    pub fn get_mod_update_rollback(&self, id: InstanceID) -> Option<Arc<[Arc<str>]>> {
+        let root_path = self.instance_state.read().instances.get(id)?.root_path.clone();
+        let data = std::fs::read(root_path.join(ROLLBACK_JSON)).ok()?;
+        let rollback: UpdateRollback = serde_json::from_slice(&data).ok()?;
+        Some(rollback.mods.into_iter().map(|entry| entry.name).collect())
+    }
+
+    pub async fn revert_mod_updates(&self, id: InstanceID) -> Result<(), BatchUpdateError> {
+        let Some(root_path) = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone()) else {
+            return Err(BatchUpdateError::UnknownInstance);
+        };
+
+        tokio::task::spawn_blocking(move || revert_rollback(&root_path)).await.unwrap()?;
+
+        self.instance_state.write().reload_mods_immediately.insert(id);
+
+        Ok(())
+    }
+
+    /// Finds the Modrinth version an update file belongs to, which may not be the newest if the update preference skipped it
+    async fn modrinth_version_for_file(&self, summary: &ModSummary, loader: ModrinthLoader, minecraft_version: Ustr, file: &ModrinthFile) -> Option<ModrinthProjectVersion> {
+        let contains_file = |version: &ModrinthProjectVersion| version.files.iter().any(|version_file| version_file.hashes.sha1 == file.hashes.sha1);
+
+        let latest = self.modrinth_update_version(summary, loader, minecraft_version).await.ok()??;
+        if contains_file(&latest.0) {
+            return Some(latest.0.clone());
+        }
+
+        let versions = self.meta.fetch(&ModrinthProjectVersionsMetadataItem(&ModrinthProjectVersionsRequest {
+            project_id: latest.0.project_id.clone(),
+            game_versions: Some([minecraft_version.as_str().into()].into()),
+            loaders: Some([loader].into()),
+        })).await.ok()?;
+        versions.0.iter().find(|version| contains_file(version)).cloned()
+    }
+}
+
+/// Forgets the most recent batch update, called when the instance's mods are changed in any other way
+pub(crate) fn discard_rollback(root_path: &Path) {
+    _ = std::fs::remove_file(root_path.join(ROLLBACK_JSON));
+}
+
+fn revert_rollback(root_path: &Path) -> Result<(), BatchUpdateError> {
+    let rollback_path = root_path.join(ROLLBACK_JSON);
+    let data = std::fs::read(&rollback_path).map_err(|_| BatchUpdateError::NoRollback)?;
+    let rollback: UpdateRollback = serde_json::from_slice(&data)?;
+
+    // Check every jar before touching any, so the instance is never left half reverted
+    for entry in &rollback.mods {
+        let Ok(data) = std::fs::read(&entry.new_path) else {
+            return Err(BatchUpdateError::ModifiedSinceUpdate(entry.name.clone()));
+        };
+        let mut hasher = Sha1::new();
+        hasher.update(&data);
+        if !hex::encode(hasher.finalize()).eq_ignore_ascii_case(&entry.new_sha1) {
+            return Err(BatchUpdateError::ModifiedSinceUpdate(entry.name.clone()));
+        }
+    }
+
+    for entry in &rollback.mods {
+        std::fs::remove_file(&entry.new_path)?;
+        if !entry.old_path.exists() && std::fs::hard_link(&entry.library_path, &entry.old_path).is_err() {
+            std::fs::copy(&entry.library_path, &entry.old_path)?;
+        }
+    }
+
+    std::fs::remove_file(rollback_path)?;
+    Ok(())
+}
+
+fn can_batch_update(summary: &InstanceModSummary, preferences: &BTreeMap<Arc<str>, ModUpdatePreference>) -> bool {
+    summary.mod_summary.update_status.load(Ordering::Relaxed).can_update()
+        && !preferences.get(&summary.mod_summary.id).is_some_and(|preference| preference.pinned)
+        // Modpacks can change files outside of mods, so they are only updated through their preview
+        && !matches!(summary.mod_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha1(data: &[u8]) -> Arc<str> {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        hex::encode(hasher.finalize()).into()
+    }
+
+    /// Sets up an instance where `example-1.0.jar` was updated to `example-2.0.jar`
+    fn updated_instance(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("batch-update-{name}-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("mods")).unwrap();
+        std::fs::create_dir_all(root.join("library")).unwrap();
+
+        std::fs::write(root.join("library").join("old"), b"old jar").unwrap();
+        std::fs::write(root.join("mods").join("example-2.0.jar"), b"new jar").unwrap();
+
+        let rollback = UpdateRollback {
+            mods: vec![UpdateRollbackEntry {
+                name: "Example".into(),
+                old_path: root.join("mods").join("example-1.0.jar"),
+                library_path: root.join("library").join("old"),
+                new_path: root.join("mods").join("example-2.0.jar"),
+                new_sha1: sha1(b"new jar").to_ascii_uppercase().into(),
+            }],
+        };
+        std::fs::write(root.join(ROLLBACK_JSON), serde_json::to_vec(&rollback).unwrap()).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_revert() {
+        let root = updated_instance("revert");
+        revert_rollback(&root).unwrap();
+
+        assert!(!root.join("mods").join("example-2.0.jar").exists());
+        assert_eq!(std::fs::read(root.join("mods").join("example-1.0.jar")).unwrap(), b"old jar");
+        assert!(!root.join(ROLLBACK_JSON).exists());
+        assert!(matches!(revert_rollback(&root), Err(BatchUpdateError::NoRollback)));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_refuse_replaced_jar() {
+        let root = updated_instance("replaced");
+        std::fs::write(root.join("mods").join("example-2.0.jar"), b"another jar").unwrap();
+
+        assert!(matches!(revert_rollback(&root), Err(BatchUpdateError::ModifiedSinceUpdate(name)) if &*name == "Example"));
+        assert_eq!(std::fs::read(root.join("mods").join("example-2.0.jar")).unwrap(), b"another jar");
+        assert!(!root.join("mods").join("example-1.0.jar").exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_refuse_missing_jar() {
+        let root = updated_instance("missing");
+        std::fs::remove_file(root.join("mods").join("example-2.0.jar")).unwrap();
+
+        assert!(matches!(revert_rollback(&root), Err(BatchUpdateError::ModifiedSinceUpdate(_))));
+        assert!(!root.join("mods").join("example-1.0.jar").exists());
+        assert!(root.join(ROLLBACK_JSON).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_discard_rollback() {
+        let root = updated_instance("discard");
+        discard_rollback(&root);
+        assert!(!root.join(ROLLBACK_JSON).exists());
+        assert!(matches!(revert_rollback(&root), Err(BatchUpdateError::NoRollback)));
+        // Discarding without a rollback is a no-op
+        discard_rollback(&root);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
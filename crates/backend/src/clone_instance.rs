@@ -0,0 +1,156 @@
+use std::{ffi::OsStr, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    instance::{CloneInstanceContent, InstanceID},
+    modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType},
+};
+use enumset::EnumSet;
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+use crate::{BackendState, WatchTarget};
+
+#[derive(Error, Debug)]
+pub enum CloneInstanceError {
+    #[error("Unknown instance")]
+    UnknownInstance,
+    #[error("Name must not be a path: {0}")]
+    NameIsPath(Arc<str>),
+    #[error("Name is invalid: {0}")]
+    InvalidName(Arc<str>),
+    #[error("Name is already used")]
+    NameAlreadyUsed,
+    #[error("Cancelled by user")]
+    CancelledByUser,
+    #[error("An I/O error occured while copying the instance:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("A serialization error occured while copying the instance:\n{0}")]
+    SerdeError(#[from] serde_json::Error),
+}
+
+impl BackendState {
+    pub async fn clone_instance(&self, id: InstanceID, name: &str, contents: EnumSet<CloneInstanceContent>, modal_action: &ModalAction) -> Result<(), CloneInstanceError> {
+        if !crate::is_single_component_path(name) {
+            return Err(CloneInstanceError::NameIsPath(name.into()));
+        }
+        if !sanitize_filename::is_sanitized_with_options(name, sanitize_filename::OptionsForCheck { windows: true, ..Default::default() }) {
+            return Err(CloneInstanceError::InvalidName(name.into()));
+        }
+        if self.instance_state.read().instances.iter().any(|i| i.name == name) {
+            return Err(CloneInstanceError::NameAlreadyUsed);
+        }
+
+        let (root_path, dot_minecraft_path, configuration, mut organization) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(CloneInstanceError::UnknownInstance);
+            };
+            (instance.root_path.clone(), instance.dot_minecraft_path.clone(), instance.configuration.get().clone(), instance.organization.get().clone())
+        };
+
+        // Mod jars are immutable, so the clone can share them with the content library instead of duplicating them
+        let mut library_links = FxHashMap::default();
+        if contents.contains(CloneInstanceContent::Mods) && let Some(mods) = self.clone().load_instance_mods(id).await {
+            for summary in mods.iter() {
+                let extension = if summary.path.extension() == Some(OsStr::new("disabled")) {
+                    summary.path.with_extension("").extension().map(OsStr::to_owned)
+                } else {
+                    summary.path.extension().map(OsStr::to_owned)
+                };
+                let library_path = crate::create_content_library_path(&self.directories.content_library_dir, summary.mod_summary.hash, extension.as_ref().and_then(|extension| extension.to_str()));
+                if library_path.is_file() {
+                    library_links.insert(summary.path.to_path_buf(), library_path);
+                }
+            }
+        }
+
+        let new_root_path = self.directories.instances_dir.join(name);
+        if new_root_path.exists() {
+            return Err(CloneInstanceError::NameAlreadyUsed);
+        }
+
+        self.watch_filesystem(&self.directories.instances_dir.clone(), WatchTarget::InstancesDir);
+
+        let tracker = ProgressTracker::new("Copying files".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+
+        let result = tokio::task::spawn_blocking({
+            let tracker = tracker.clone();
+            let modal_action = modal_action.clone();
+            let new_root_path = new_root_path.clone();
+            move || -> Result<(), CloneInstanceError> {
+                let new_dot_minecraft_path = new_root_path.join(".minecraft");
+                std::fs::create_dir_all(&new_dot_minecraft_path)?;
+
+                for content in contents {
+                    for relative in content.paths() {
+                        let from = dot_minecraft_path.join(relative);
+                        if from.exists() {
+                            copy_recursive(&from, &new_dot_minecraft_path.join(relative), &library_links, &tracker, &modal_action)?;
+                        }
+                    }
+                }
+
+                for file in ["icon.png", "notes.md"] {
+                    let from = root_path.join(file);
+                    if from.is_file() {
+                        std::fs::copy(from, new_root_path.join(file))?;
+                    }
+                }
+
+                organization.favourite = false;
+                organization.last_played = None;
+                crate::write_safe(&new_root_path.join("organization_v1.json"), &serde_json::to_vec(&organization)?)?;
+
+                // Written last, the instance is registered by the file watcher once its configuration exists
+                crate::write_safe(&new_root_path.join("info_v1.json"), &serde_json::to_vec(&configuration)?)?;
+
+                Ok(())
+            }
+        }).await.unwrap();
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        if result.is_err() {
+            _ = std::fs::remove_dir_all(&new_root_path);
+        }
+
+        result
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path, library_links: &FxHashMap<PathBuf, PathBuf>, tracker: &ProgressTracker, modal_action: &ModalAction) -> Result<(), CloneInstanceError> {
+    if modal_action.has_requested_cancel() {
+        return Err(CloneInstanceError::CancelledByUser);
+    }
+
+    let metadata = std::fs::symlink_metadata(from)?;
+
+    // Symlinks are folders shared by a sync group, they are recreated when the clone is launched
+    if metadata.is_symlink() {
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()), library_links, tracker, modal_action)?;
+        }
+        return Ok(());
+    }
+
+    tracker.add_total(1);
+    tracker.notify();
+
+    let linked = library_links.get(from).is_some_and(|library_path| std::fs::hard_link(library_path, to).is_ok());
+    if !linked {
+        std::fs::copy(from, to)?;
+    }
+
+    tracker.add_count(1);
+    tracker.notify();
+
+    Ok(())
+}
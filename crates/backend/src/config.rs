@@ -1,8 +1,30 @@
+use std::sync::Arc;
+
 use bridge::message::SyncTarget;
 use enumset::EnumSet;
+use schema::{curseforge::CurseForgeConfiguration, instance::GameConfiguration, network::NetworkConfiguration, paste::PasteConfiguration, redaction::RedactionConfiguration};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct BackendConfig {
     pub sync_targets: EnumSet<SyncTarget>,
+    #[serde(default, deserialize_with = "schema::try_deserialize", skip_serializing_if = "Vec::is_empty")]
+    pub sync_groups: Vec<SyncGroup>,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub network: NetworkConfiguration,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub curseforge: CurseForgeConfiguration,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub paste: PasteConfiguration,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub redaction: RedactionConfiguration,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub game: GameConfiguration,
+}
+
+/// A named set of sync targets with its own synced folder, shared by the instances assigned to it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SyncGroup {
+    pub name: Arc<str>,
+    pub sync_targets: EnumSet<SyncTarget>,
 }
@@ -0,0 +1,767 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::InstanceModSummary, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+};
+use rc_zip_sync::ReadZip;
+use reqwest::{header::ACCEPT, StatusCode};
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{
+    content::ContentSource, curseforge::{
+        CurseForgeFile, CurseForgeFileDependency, CurseForgeFingerprintsResult, CurseForgeManifest, CurseForgeMod, CurseForgeModLoaderType, CurseForgeResponse, CurseForgeSearchRequest, CurseForgeSearchResult, CURSEFORGE_CLASS_RESOURCEPACKS, CURSEFORGE_CLASS_SHADERS, CURSEFORGE_MINECRAFT_GAME_ID
+    }, loader::Loader
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{download::DownloadManager, mod_metadata::ModUpdateAction, BackendState};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CurseForgeError {
+    #[error("No CurseForge API key configured, add one in the network settings")]
+    MissingApiKey,
+    #[error("Failed to request from CurseForge:\n{0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("CurseForge returned non-200 status code: {0}")]
+    NotOK(StatusCode),
+    #[error("Failed to parse CurseForge response:\n{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Unable to find a compatible file for CurseForge project {0}")]
+    NoCompatibleFile(u32),
+    #[error("{0} doesn't allow third-party downloads, download it manually from CurseForge")]
+    DownloadsDisabled(Arc<str>),
+    #[error("CurseForge file {0} is missing a sha1 hash")]
+    MissingHash(Arc<str>),
+    #[error("Invalid filename: {0}")]
+    InvalidFilename(Arc<str>),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to read modpack archive:\n{0}")]
+    ZipError(#[from] rc_zip_sync::rc_zip::Error),
+    #[error("Modpack is missing manifest.json")]
+    MissingManifest,
+}
+
+pub struct CurseForgeApi<'a> {
+    http_client: reqwest::Client,
+    downloads: &'a DownloadManager,
+    api_url: Arc<str>,
+    api_key: Arc<str>,
+}
+
+pub struct ResolvedCurseForgeFile {
+    pub file: CurseForgeFile,
+    pub name: Arc<str>,
+    pub class_id: Option<u32>,
+}
+
+impl BackendState {
+    pub fn curseforge(&self) -> Result<CurseForgeApi<'_>, CurseForgeError> {
+        let configuration = self.config.write().get().curseforge.clone();
+
+        let Some(api_key) = configuration.api_key.clone().filter(|key| !key.trim_ascii().is_empty()) else {
+            return Err(CurseForgeError::MissingApiKey);
+        };
+
+        Ok(CurseForgeApi::new(self.downloads.redirecting_http_client(), &self.downloads, configuration.api_url().into(), api_key))
+    }
+
+    /// Replaces CurseForge downloads with concrete url downloads, adding any required dependencies of unpinned projects
+    pub async fn resolve_curseforge_content(&self, content: &ContentInstall) -> Result<Arc<[ContentInstallFile]>, CurseForgeError> {
+        let mut requested = Vec::new();
+        let mut requested_files = FxHashMap::default();
+        let mut files = Vec::with_capacity(content.files.len());
+
+        for content_file in content.files.iter() {
+            if let ContentDownload::CurseForge { project_id, file_id } = content_file.download {
+                requested.push((project_id, file_id));
+                requested_files.insert(project_id, content_file);
+            } else {
+                files.push(content_file.clone());
+            }
+        }
+
+        if requested.is_empty() {
+            return Ok(content.files.clone());
+        }
+
+        let mut minecraft_version = content.version_hint.clone();
+        let mut loader = content.loader_hint;
+        if let InstallTarget::Instance(id) = content.target && let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            minecraft_version = minecraft_version.or_else(|| Some(configuration.minecraft_version.as_str().into()));
+            if loader == Loader::Unknown {
+                loader = configuration.loader;
+            }
+        }
+
+        let include_dependencies = requested.iter().any(|(_, file_id)| file_id.is_none());
+
+        let api = self.curseforge()?;
+        let resolved = api.resolve(&requested, minecraft_version.as_deref(), loader, include_dependencies).await?;
+
+        for resolved in resolved {
+            let original = requested_files.get(&resolved.file.mod_id);
+            let path = original.map(|file| file.path.clone()).filter(|path| !matches!(path, ContentInstallPath::Automatic));
+
+            let mut install_file = resolved.to_content_install_file(path)?;
+            install_file.replace_old = original.and_then(|file| file.replace_old.clone());
+            files.push(install_file);
+        }
+
+        Ok(files.into())
+    }
+
+    /// Checks CurseForge mods for updates by fingerprint, also identifying manually installed mods when an api key is configured
+    pub async fn curseforge_update_check(
+        &self,
+        mods: &[InstanceModSummary],
+        minecraft_version: &str,
+        loader: Loader,
+    ) -> Result<FxHashMap<[u8; 20], ModUpdateAction>, CurseForgeError> {
+        let mut candidates = Vec::new();
+        let mut has_curseforge_mods = false;
+        {
+            let sources = self.mod_metadata_manager.read_content_sources();
+            for summary in mods {
+                match sources.get(&summary.mod_summary.hash).copied().unwrap_or(ContentSource::Manual) {
+                    ContentSource::CurseForge => {
+                        has_curseforge_mods = true;
+                        candidates.push(summary);
+                    },
+                    ContentSource::Manual => candidates.push(summary),
+                    _ => {},
+                }
+            }
+        }
+
+        let api = match self.curseforge() {
+            Ok(api) => api,
+            Err(CurseForgeError::MissingApiKey) if !has_curseforge_mods => return Ok(FxHashMap::default()),
+            Err(error) => return Err(error),
+        };
+
+        let paths: Vec<_> = candidates.iter().map(|summary| (summary.path.clone(), summary.mod_summary.hash)).collect();
+        let fingerprints = tokio::task::spawn_blocking(move || {
+            paths.into_iter().filter_map(|(path, hash)| {
+                Some((fingerprint(&std::fs::read(path).ok()?), hash))
+            }).collect::<FxHashMap<u32, [u8; 20]>>()
+        }).await.unwrap();
+
+        if fingerprints.is_empty() {
+            return Ok(FxHashMap::default());
+        }
+
+        let actions = api.fingerprint_update_actions(&fingerprints, minecraft_version, loader).await?;
+
+        self.mod_metadata_manager.set_content_sources(actions.keys().map(|hash| (*hash, ContentSource::CurseForge)));
+
+        Ok(actions)
+    }
+
+    pub async fn import_curseforge_modpack(&self, path: Arc<Path>, modal_action: &ModalAction) -> Result<(), CurseForgeError> {
+        let (manifest, overrides) = tokio::task::spawn_blocking(move || read_modpack(&path)).await.unwrap()?;
+
+        let content_install = modpack_content_install(&manifest);
+
+        // Errors are reported through the modal action by install_content
+        let Some(dot_minecraft_path) = self.install_content(content_install, modal_action.clone()).await else {
+            return Ok(());
+        };
+
+        if !overrides.is_empty() {
+            let tracker = ProgressTracker::new("Copying overrides".into(), self.send.clone());
+            modal_action.trackers.push(tracker.clone());
+
+            tracker.set_total(overrides.len());
+            tracker.notify();
+
+            let result = tokio::task::spawn_blocking({
+                let tracker = tracker.clone();
+                move || {
+                    for (path, data) in overrides {
+                        let dest_path = path.to_path(&dot_minecraft_path);
+                        if let Some(parent) = dest_path.parent() {
+                            std::fs::create_dir_all(parent)?;
+                        }
+                        std::fs::write(dest_path, data)?;
+
+                        tracker.add_count(1);
+                        tracker.notify();
+                    }
+                    std::io::Result::Ok(())
+                }
+            }).await.unwrap();
+
+            tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+            tracker.notify();
+
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+fn modpack_content_install(manifest: &CurseForgeManifest) -> ContentInstall {
+    ContentInstall {
+        target: InstallTarget::NewInstance { name: manifest.name.clone() },
+        loader_hint: manifest.loader(),
+        version_hint: Some(manifest.minecraft.version.clone()),
+        files: manifest.files.iter().filter(|file| file.required).map(|file| ContentInstallFile {
+            replace_old: None,
+            path: ContentInstallPath::Automatic,
+            download: ContentDownload::CurseForge {
+                project_id: file.project_id,
+                file_id: Some(file.file_id),
+            },
+            content_source: ContentSource::CurseForge,
+        }).collect(),
+    }
+}
+
+fn read_modpack(path: &Path) -> Result<(CurseForgeManifest, Vec<(SafePath, Vec<u8>)>), CurseForgeError> {
+    let file = std::fs::File::open(path)?;
+    let archive = file.read_zip()?;
+
+    let Some(manifest_file) = archive.by_name("manifest.json") else {
+        return Err(CurseForgeError::MissingManifest);
+    };
+    let manifest: CurseForgeManifest = serde_json::from_slice(&manifest_file.bytes()?)?;
+
+    let overrides_dir = manifest.overrides.as_deref().unwrap_or("overrides");
+
+    let mut overrides = Vec::new();
+    for entry in archive.entries() {
+        if entry.kind() != rc_zip_sync::rc_zip::EntryKind::File {
+            continue;
+        }
+        let Some(path) = SafePath::new(&entry.name).and_then(|path| path.strip_prefix(overrides_dir)) else {
+            continue;
+        };
+        overrides.push((path, entry.bytes()?));
+    }
+
+    Ok((manifest, overrides))
+}
+
+impl<'a> CurseForgeApi<'a> {
+    pub fn new(http_client: reqwest::Client, downloads: &'a DownloadManager, api_url: Arc<str>, api_key: Arc<str>) -> Self {
+        Self {
+            http_client,
+            downloads,
+            api_url,
+            api_key,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T, CurseForgeError> {
+        let request = request
+            .header("x-api-key", self.api_key.trim_ascii())
+            .header(ACCEPT, "application/json")
+            .build()?;
+
+        let response = self.downloads.send(&self.http_client, request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(CurseForgeError::NotOK(response.status()));
+        }
+
+        let bytes = response.bytes().await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn search(&self, request: &CurseForgeSearchRequest) -> Result<CurseForgeSearchResult, CurseForgeError> {
+        let mut query = vec![
+            ("gameId", CURSEFORGE_MINECRAFT_GAME_ID.to_string()),
+            ("classId", request.class_id.to_string()),
+            ("sortField", "2".to_string()),
+            ("sortOrder", "desc".to_string()),
+            ("index", request.index.to_string()),
+            ("pageSize", request.page_size.to_string()),
+        ];
+        if let Some(search) = &request.query {
+            query.push(("searchFilter", search.to_string()));
+        }
+        if let Some(game_version) = &request.game_version {
+            query.push(("gameVersion", game_version.to_string()));
+        }
+        let loader_type = CurseForgeModLoaderType::from_loader(request.loader);
+        if loader_type != CurseForgeModLoaderType::Any && request.class_id != CURSEFORGE_CLASS_RESOURCEPACKS && request.class_id != CURSEFORGE_CLASS_SHADERS {
+            query.push(("modLoaderType", loader_type.id().to_string()));
+        }
+
+        let url = format!("{}/v1/mods/search", self.api_url);
+        self.send(self.http_client.get(url).query(&query)).await
+    }
+
+    pub async fn get_mods(&self, mod_ids: &[u32]) -> Result<Vec<CurseForgeMod>, CurseForgeError> {
+        let url = format!("{}/v1/mods", self.api_url);
+        let response: CurseForgeResponse<Vec<CurseForgeMod>> = self.send(self.http_client.post(url).json(&json!({
+            "modIds": mod_ids,
+        }))).await?;
+        Ok(response.data)
+    }
+
+    pub async fn get_files(&self, file_ids: &[u32]) -> Result<Vec<CurseForgeFile>, CurseForgeError> {
+        let url = format!("{}/v1/mods/files", self.api_url);
+        let response: CurseForgeResponse<Vec<CurseForgeFile>> = self.send(self.http_client.post(url).json(&json!({
+            "fileIds": file_ids,
+        }))).await?;
+        Ok(response.data)
+    }
+
+    pub async fn get_mod_files(&self, mod_id: u32, minecraft_version: &str, loader: CurseForgeModLoaderType) -> Result<Vec<CurseForgeFile>, CurseForgeError> {
+        let url = format!("{}/v1/mods/{}/files", self.api_url, mod_id);
+        let mut query = vec![
+            ("gameVersion", minecraft_version.to_string()),
+            ("pageSize", "50".to_string()),
+        ];
+        if loader != CurseForgeModLoaderType::Any {
+            query.push(("modLoaderType", loader.id().to_string()));
+        }
+
+        let response: CurseForgeResponse<Vec<CurseForgeFile>> = self.send(self.http_client.get(url).query(&query)).await?;
+        Ok(response.data)
+    }
+
+    pub async fn fingerprints(&self, fingerprints: &[u32]) -> Result<CurseForgeFingerprintsResult, CurseForgeError> {
+        let url = format!("{}/v1/fingerprints/{}", self.api_url, CURSEFORGE_MINECRAFT_GAME_ID);
+        let response: CurseForgeResponse<CurseForgeFingerprintsResult> = self.send(self.http_client.post(url).json(&json!({
+            "fingerprints": fingerprints,
+        }))).await?;
+        Ok(response.data)
+    }
+
+    /// Looks up files by fingerprint, returning the update action for the hash of each matched file
+    pub async fn fingerprint_update_actions(
+        &self,
+        fingerprints: &FxHashMap<u32, [u8; 20]>,
+        minecraft_version: &str,
+        loader: Loader,
+    ) -> Result<FxHashMap<[u8; 20], ModUpdateAction>, CurseForgeError> {
+        let fingerprint_list: Vec<u32> = fingerprints.keys().copied().collect();
+        let matches = self.fingerprints(&fingerprint_list).await?.exact_matches;
+
+        let mut futures = Vec::with_capacity(matches.len());
+        for fingerprint_match in matches {
+            let Some(hash) = fingerprints.get(&fingerprint_match.file.file_fingerprint).copied() else {
+                continue;
+            };
+            futures.push(async move {
+                let latest = self.latest_file(fingerprint_match.id, minecraft_version, loader).await?;
+                let action = match latest {
+                    Some(latest) if latest.id != fingerprint_match.file.id && latest.file_fingerprint != fingerprint_match.file.file_fingerprint => {
+                        ModUpdateAction::CurseForge(latest)
+                    },
+                    Some(_) => ModUpdateAction::AlreadyUpToDate,
+                    None => ModUpdateAction::ErrorNotFound,
+                };
+                Ok::<_, CurseForgeError>((hash, action))
+            });
+        }
+
+        Ok(futures::future::try_join_all(futures).await?.into_iter().collect())
+    }
+
+    pub async fn latest_file(&self, mod_id: u32, minecraft_version: &str, loader: Loader) -> Result<Option<CurseForgeFile>, CurseForgeError> {
+        let loader = CurseForgeModLoaderType::from_loader(loader);
+        let files = self.get_mod_files(mod_id, minecraft_version, loader).await?;
+        Ok(pick_file(&files, minecraft_version, loader).cloned())
+    }
+
+    /// Resolves projects (with an optional pinned file) to concrete files, pulling in required dependencies
+    pub async fn resolve(
+        &self,
+        requested: &[(u32, Option<u32>)],
+        minecraft_version: Option<&str>,
+        loader: Loader,
+        include_dependencies: bool,
+    ) -> Result<Vec<ResolvedCurseForgeFile>, CurseForgeError> {
+        let mut files: FxHashMap<u32, CurseForgeFile> = FxHashMap::default();
+
+        let file_ids: Vec<u32> = requested.iter().filter_map(|(_, file_id)| *file_id).collect();
+        if !file_ids.is_empty() {
+            for file in self.get_files(&file_ids).await? {
+                files.insert(file.mod_id, file);
+            }
+        }
+        if let Some((mod_id, _)) = requested.iter().find(|(mod_id, file_id)| file_id.is_some() && !files.contains_key(mod_id)) {
+            return Err(CurseForgeError::NoCompatibleFile(*mod_id));
+        }
+
+        let mut pending: Vec<u32> = requested.iter().filter(|(_, file_id)| file_id.is_none()).map(|(mod_id, _)| *mod_id).collect();
+        let mut visited = FxHashSet::default();
+
+        while !pending.is_empty() {
+            let mut futures = Vec::new();
+            for mod_id in pending.drain(..) {
+                if files.contains_key(&mod_id) || !visited.insert(mod_id) {
+                    continue;
+                }
+                let Some(minecraft_version) = minecraft_version else {
+                    return Err(CurseForgeError::NoCompatibleFile(mod_id));
+                };
+                futures.push(async move {
+                    let file = self.latest_file(mod_id, minecraft_version, loader).await?;
+                    file.ok_or(CurseForgeError::NoCompatibleFile(mod_id))
+                });
+            }
+
+            for file in futures::future::try_join_all(futures).await? {
+                files.insert(file.mod_id, file);
+            }
+
+            if include_dependencies {
+                for file in files.values() {
+                    for dependency in &file.dependencies {
+                        if dependency.relation_type == CurseForgeFileDependency::REQUIRED && !files.contains_key(&dependency.mod_id) {
+                            pending.push(dependency.mod_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mod_ids: Vec<u32> = files.keys().copied().collect();
+        let mods: FxHashMap<u32, CurseForgeMod> = if mod_ids.is_empty() {
+            FxHashMap::default()
+        } else {
+            self.get_mods(&mod_ids).await?.into_iter().map(|project| (project.id, project)).collect()
+        };
+
+        Ok(files.into_values().map(|file| {
+            let project = mods.get(&file.mod_id);
+            ResolvedCurseForgeFile {
+                name: project.map(|project| project.name.clone()).unwrap_or_else(|| file.display_name.clone()),
+                class_id: project.and_then(|project| project.class_id),
+                file,
+            }
+        }).collect())
+    }
+}
+
+impl ResolvedCurseForgeFile {
+    pub fn to_content_install_file(&self, path: Option<ContentInstallPath>) -> Result<ContentInstallFile, CurseForgeError> {
+        let Some(url) = self.file.download_url.clone() else {
+            return Err(CurseForgeError::DownloadsDisabled(self.name.clone()));
+        };
+        let Some(sha1) = self.file.sha1().cloned() else {
+            return Err(CurseForgeError::MissingHash(self.file.file_name.clone()));
+        };
+
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let path = format!("{}/{}", class_directory(self.class_id), self.file.file_name);
+                let Some(path) = SafePath::new(&path) else {
+                    return Err(CurseForgeError::InvalidFilename(self.file.file_name.clone()));
+                };
+                ContentInstallPath::Safe(path)
+            },
+        };
+
+        Ok(ContentInstallFile {
+            replace_old: None,
+            path,
+            download: ContentDownload::Url {
+                url,
+                sha1,
+                size: self.file.file_length,
+            },
+            content_source: ContentSource::CurseForge,
+        })
+    }
+}
+
+pub fn class_directory(class_id: Option<u32>) -> &'static str {
+    match class_id {
+        Some(CURSEFORGE_CLASS_RESOURCEPACKS) => "resourcepacks",
+        Some(CURSEFORGE_CLASS_SHADERS) => "shaderpacks",
+        _ => "mods",
+    }
+}
+
+/// Prefers the newest release, falling back to the newest beta/alpha
+pub fn pick_file<'a>(files: &'a [CurseForgeFile], minecraft_version: &str, loader: CurseForgeModLoaderType) -> Option<&'a CurseForgeFile> {
+    let compatible = || files.iter().filter(|file| file.supports(minecraft_version, loader));
+
+    compatible().filter(|file| file.release_type == CurseForgeFile::RELEASE).max_by_key(|file| file.id)
+        .or_else(|| compatible().max_by_key(|file| file.id))
+}
+
+/// CurseForge's file fingerprint: 32-bit murmur2 with seed 1, ignoring whitespace bytes
+pub fn fingerprint(bytes: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let data: Vec<u8> = bytes.iter().copied().filter(|byte| !matches!(byte, 9 | 10 | 13 | 32)).collect();
+
+    let mut hash = 1 ^ data.len() as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if remainder.len() >= 3 {
+        hash ^= (remainder[2] as u32) << 16;
+    }
+    if remainder.len() >= 2 {
+        hash ^= (remainder[1] as u32) << 8;
+    }
+    if !remainder.is_empty() {
+        hash ^= remainder[0] as u32;
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bridge::install::{ContentDownload, ContentInstallPath, InstallTarget};
+    use rustc_hash::FxHashMap;
+    use schema::{curseforge::{CurseForgeSearchRequest, CURSEFORGE_CLASS_MODS}, loader::Loader, network::NetworkConfiguration};
+    use serde_json::json;
+
+    use crate::{
+        download::DownloadManager, mod_metadata::ModUpdateAction, test_server::{create_zip, TestRequest, TestResponse, TestServer}
+    };
+
+    use super::{fingerprint, modpack_content_install, read_modpack, CurseForgeApi};
+
+    fn file_json(id: u32, mod_id: u32, fingerprint: u32, game_versions: &[&str]) -> serde_json::Value {
+        json!({
+            "id": id,
+            "modId": mod_id,
+            "displayName": format!("File {id}"),
+            "fileName": format!("file-{id}.jar"),
+            "releaseType": 1,
+            "hashes": [{ "value": format!("{id:040}"), "algo": 1 }],
+            "fileLength": 1000 + id,
+            "downloadUrl": format!("https://edge.forgecdn.net/files/{id}/file-{id}.jar"),
+            "gameVersions": game_versions,
+            "fileFingerprint": fingerprint,
+        })
+    }
+
+    fn respond(data: serde_json::Value) -> TestResponse {
+        TestResponse::ok(serde_json::to_vec(&json!({ "data": data })).unwrap())
+    }
+
+    async fn test_api(handler: impl Fn(&TestRequest) -> TestResponse + Send + Sync + 'static) -> (TestServer, reqwest::Client, DownloadManager) {
+        let server = TestServer::start(handler).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let downloads = DownloadManager::new(&NetworkConfiguration::default());
+        (server, client, downloads)
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(b""), 1540447798);
+        assert_eq!(fingerprint(b"a"), 626045324);
+        assert_eq!(fingerprint(b"ab"), 1692487918);
+        assert_eq!(fingerprint(b"abc"), 1621425345);
+        assert_eq!(fingerprint(b"abcd"), 3376380438);
+        assert_eq!(fingerprint(b"helloworld"), 2824650221);
+        assert_eq!(fingerprint(b"Thequickbrownfoxjumpsoverthelazydog"), 3751777527);
+    }
+
+    #[test]
+    fn test_fingerprint_whitespace() {
+        // Tabs, newlines, carriage returns and spaces are stripped before hashing
+        assert_eq!(fingerprint(b"hello world"), 2824650221);
+        assert_eq!(fingerprint(b"The quick brown fox jumps over the lazy dog"), 3751777527);
+        assert_eq!(fingerprint(b" a\tb\r\nc d\n"), fingerprint(b"abcd"));
+        assert_eq!(fingerprint(b" \t\r\n"), fingerprint(b""));
+
+        // Other control characters are kept
+        assert_ne!(fingerprint(b"ab\x0bcd"), fingerprint(b"abcd"));
+        assert_ne!(fingerprint(b"ab\x00cd"), fingerprint(b"abcd"));
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let (server, client, downloads) = test_api(|request| {
+            assert_eq!(request.method, "GET");
+            assert!(request.path.starts_with("/v1/mods/search?"));
+            TestResponse::ok(serde_json::to_vec(&json!({
+                "data": [
+                    { "id": 238222, "name": "Just Enough Items", "slug": "jei", "classId": 6, "downloadCount": 1.5e8, "authors": [{ "name": "mezz" }] },
+                ],
+                "pagination": { "index": 0, "pageSize": 20, "resultCount": 1, "totalCount": 1 },
+            })).unwrap())
+        }).await;
+        let api = CurseForgeApi::new(client, &downloads, server.url.as_str().into(), " key ".into());
+
+        let result = api.search(&CurseForgeSearchRequest {
+            query: Some("jei".into()),
+            class_id: CURSEFORGE_CLASS_MODS,
+            game_version: Some("1.20.1".into()),
+            loader: Loader::Fabric,
+            index: 0,
+            page_size: 20,
+        }).await.unwrap();
+
+        assert_eq!(result.data.len(), 1);
+        assert_eq!(result.data[0].id, 238222);
+        assert_eq!(&*result.data[0].name, "Just Enough Items");
+        assert_eq!(&*result.data[0].authors[0].name, "mezz");
+        assert_eq!(result.pagination.total_count, 1);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("x-api-key"), Some("key"));
+        for param in ["gameId=432", "classId=6", "searchFilter=jei", "gameVersion=1.20.1", "modLoaderType=4", "pageSize=20"] {
+            assert!(requests[0].path.contains(param), "{} is missing {param}", requests[0].path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_error_status() {
+        let (server, client, downloads) = test_api(|_| TestResponse::status(403)).await;
+        let api = CurseForgeApi::new(client, &downloads, server.url.as_str().into(), "key".into());
+
+        let result = api.search(&CurseForgeSearchRequest {
+            query: None,
+            class_id: CURSEFORGE_CLASS_MODS,
+            game_version: None,
+            loader: Loader::Vanilla,
+            index: 0,
+            page_size: 20,
+        }).await;
+
+        assert!(matches!(result, Err(super::CurseForgeError::NotOK(status)) if status.as_u16() == 403));
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_update_actions() {
+        let (server, client, downloads) = test_api(|request| {
+            match (request.method.as_str(), request.path.split('?').next().unwrap()) {
+                ("POST", "/v1/fingerprints/432") => {
+                    let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    let mut fingerprints: Vec<u64> = body["fingerprints"].as_array().unwrap().iter().map(|value| value.as_u64().unwrap()).collect();
+                    fingerprints.sort();
+                    assert_eq!(fingerprints, [111, 222, 333]);
+
+                    respond(json!({
+                        "exactMatches": [
+                            { "id": 100, "file": file_json(10, 100, 111, &["1.20.1", "Fabric"]) },
+                            { "id": 200, "file": file_json(20, 200, 222, &["1.20.1", "Fabric"]) },
+                        ],
+                    }))
+                },
+                ("GET", "/v1/mods/100/files") => respond(json!([
+                    file_json(10, 100, 111, &["1.20.1", "Fabric"]),
+                    file_json(11, 100, 112, &["1.20.1", "Fabric"]),
+                    file_json(12, 100, 113, &["1.20.1", "Forge"]),
+                ])),
+                ("GET", "/v1/mods/200/files") => respond(json!([
+                    file_json(20, 200, 222, &["1.20.1", "Fabric"]),
+                ])),
+                _ => TestResponse::status(404),
+            }
+        }).await;
+        let api = CurseForgeApi::new(client, &downloads, server.url.as_str().into(), "key".into());
+
+        let fingerprints: FxHashMap<u32, [u8; 20]> = [(111, [1; 20]), (222, [2; 20]), (333, [3; 20])].into_iter().collect();
+        let actions = api.fingerprint_update_actions(&fingerprints, "1.20.1", Loader::Fabric).await.unwrap();
+
+        assert_eq!(actions.len(), 2);
+        assert!(matches!(&actions[&[1; 20]], ModUpdateAction::CurseForge(file) if file.id == 11));
+        assert!(matches!(&actions[&[2; 20]], ModUpdateAction::AlreadyUpToDate));
+        assert!(!actions.contains_key(&[3; 20]));
+
+        let files_request = server.requests().into_iter().find(|request| request.path.starts_with("/v1/mods/100/files")).unwrap();
+        assert!(files_request.path.contains("gameVersion=1.20.1"));
+        assert!(files_request.path.contains("modLoaderType=4"));
+    }
+
+    #[tokio::test]
+    async fn test_import_manifest() {
+        let manifest = json!({
+            "minecraft": {
+                "version": "1.20.1",
+                "modLoaders": [{ "id": "forge-47.2.0", "primary": true }],
+            },
+            "manifestType": "minecraftModpack",
+            "name": "Test Pack",
+            "version": "1.0.0",
+            "files": [
+                { "projectID": 100, "fileID": 11, "required": true },
+                { "projectID": 300, "fileID": 31, "required": false },
+            ],
+            "overrides": "overrides",
+        });
+        let manifest = serde_json::to_vec(&manifest).unwrap();
+        let archive = create_zip(&[
+            ("manifest.json", &manifest),
+            ("overrides/config/test.toml", b"enabled = true"),
+            ("overrides/options.txt", b"fov:1.0"),
+            ("modlist.html", b"<ul></ul>"),
+        ]);
+
+        let path = std::env::temp_dir().join(format!("curseforge-import-test-{}.zip", std::process::id()));
+        std::fs::write(&path, archive).unwrap();
+        let result = read_modpack(&path);
+        _ = std::fs::remove_file(&path);
+        let (manifest, mut overrides) = result.unwrap();
+
+        overrides.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        let overrides: Vec<(&str, &[u8])> = overrides.iter().map(|(path, data)| (path.as_str(), data.as_slice())).collect();
+        assert_eq!(overrides, [("config/test.toml", &b"enabled = true"[..]), ("options.txt", &b"fov:1.0"[..])]);
+
+        let content = modpack_content_install(&manifest);
+        assert!(matches!(&content.target, InstallTarget::NewInstance { name } if &**name == "Test Pack"));
+        assert_eq!(content.loader_hint, Loader::Forge);
+        assert_eq!(content.version_hint.as_deref(), Some("1.20.1"));
+        assert_eq!(content.files.len(), 1, "optional files aren't installed");
+
+        let requested: Vec<(u32, Option<u32>)> = content.files.iter().filter_map(|file| match file.download {
+            ContentDownload::CurseForge { project_id, file_id } => Some((project_id, file_id)),
+            _ => None,
+        }).collect();
+        assert_eq!(requested, [(100, Some(11))]);
+
+        let (server, client, downloads) = test_api(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/v1/mods/files") => {
+                    assert_eq!(body["fileIds"], json!([11]));
+                    respond(json!([file_json(11, 100, 112, &["1.20.1", "Forge"])]))
+                },
+                ("POST", "/v1/mods") => {
+                    assert_eq!(body["modIds"], json!([100]));
+                    respond(json!([{ "id": 100, "name": "Test Mod", "classId": 6 }]))
+                },
+                _ => TestResponse::status(404),
+            }
+        }).await;
+        let api = CurseForgeApi::new(client, &downloads, server.url.as_str().into(), "key".into());
+
+        let resolved = api.resolve(&requested, content.version_hint.as_deref(), content.loader_hint, false).await.unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(&*resolved[0].name, "Test Mod");
+
+        let install_file = resolved[0].to_content_install_file(None).unwrap();
+        assert!(matches!(&install_file.path, ContentInstallPath::Safe(path) if path.as_str() == "mods/file-11.jar"));
+        let ContentDownload::Url { url, sha1, size } = &install_file.download else {
+            panic!("expected a url download, got {:?}", install_file.download);
+        };
+        assert_eq!(&**url, "https://edge.forgecdn.net/files/11/file-11.jar");
+        assert_eq!(sha1, &Arc::<str>::from(format!("{:040}", 11)));
+        assert_eq!(*size, 1011);
+    }
+}
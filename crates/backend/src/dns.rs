@@ -0,0 +1,336 @@
+use std::{net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr}, time::Duration};
+
+use tokio::net::UdpSocket;
+
+const DNS_PORT: u16 = 53;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+const MAX_NAME_LENGTH: usize = 253;
+/// Limits compression pointer chains, which could otherwise loop forever
+const MAX_POINTERS: usize = 16;
+
+/// Looks up the `_minecraft._tcp` SRV record of a host like the game does for addresses without a port,
+/// returning the target host and port
+pub async fn lookup_minecraft_srv(host: &str) -> Option<(String, u16)> {
+    if host.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+
+    let name = format!("_minecraft._tcp.{}", host.trim_end_matches('.'));
+    let id = rand::random::<u16>();
+    let query = build_srv_query(id, &name)?;
+
+    for nameserver in system_nameservers() {
+        match query_nameserver(nameserver, &query).await {
+            Ok(response) => return parse_srv_response(id, &response),
+            Err(error) => {
+                eprintln!("Unable to look up {name} using {nameserver}: {error}");
+            },
+        }
+    }
+    None
+}
+
+async fn query_nameserver(nameserver: SocketAddr, query: &[u8]) -> std::io::Result<Vec<u8>> {
+    let bind: SocketAddr = if nameserver.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect(nameserver).await?;
+    socket.send(query).await?;
+
+    let mut response = vec![0_u8; 4096];
+    let length = tokio::time::timeout(QUERY_TIMEOUT, socket.recv(&mut response)).await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+    response.truncate(length);
+    Ok(response)
+}
+
+fn build_srv_query(id: u16, name: &str) -> Option<Vec<u8>> {
+    if name.len() > MAX_NAME_LENGTH {
+        return None;
+    }
+
+    let mut query = Vec::with_capacity(18 + name.len());
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question
+    query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return None;
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&TYPE_SRV.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Some(query)
+}
+
+/// Picks the record with the lowest priority, preferring higher weights. A target of `.` means there is no service
+fn parse_srv_response(id: u16, response: &[u8]) -> Option<(String, u16)> {
+    let header = response.get(..12)?;
+    let is_response = header[2] & 0x80 != 0;
+    let response_code = header[3] & 0x0F;
+    if u16::from_be_bytes([header[0], header[1]]) != id || !is_response || response_code != 0 {
+        return None;
+    }
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+
+    let mut offset = 12;
+    for _ in 0..questions {
+        offset = skip_name(response, offset)? + 4;
+    }
+
+    let mut best: Option<(u16, u16, String, u16)> = None;
+    for _ in 0..answers {
+        offset = skip_name(response, offset)?;
+        let record = response.get(offset..offset + 10)?;
+        let record_type = u16::from_be_bytes([record[0], record[1]]);
+        let data_length = u16::from_be_bytes([record[8], record[9]]) as usize;
+        offset += 10;
+        let data_end = offset + data_length;
+        if data_end > response.len() {
+            return None;
+        }
+
+        if record_type == TYPE_SRV && data_length > 6 {
+            let data = &response[offset..offset + 6];
+            let priority = u16::from_be_bytes([data[0], data[1]]);
+            let weight = u16::from_be_bytes([data[2], data[3]]);
+            let port = u16::from_be_bytes([data[4], data[5]]);
+            let target = read_name(response, offset + 6)?;
+
+            let better = best.as_ref().is_none_or(|(best_priority, best_weight, ..)| {
+                priority < *best_priority || (priority == *best_priority && weight > *best_weight)
+            });
+            if better {
+                best = Some((priority, weight, target, port));
+            }
+        }
+        offset = data_end;
+    }
+
+    let (_, _, target, port) = best?;
+    if target.is_empty() {
+        None
+    } else {
+        Some((target, port))
+    }
+}
+
+/// Returns the offset after the name at `offset`
+fn skip_name(message: &[u8], mut offset: usize) -> Option<usize> {
+    loop {
+        let length = *message.get(offset)?;
+        if length == 0 {
+            return Some(offset + 1);
+        } else if length & 0xC0 == 0xC0 {
+            return Some(offset + 2);
+        }
+        offset += 1 + length as usize;
+    }
+}
+
+fn read_name(message: &[u8], mut offset: usize) -> Option<String> {
+    let mut name = String::new();
+    let mut pointers = 0;
+    loop {
+        let length = *message.get(offset)?;
+        if length == 0 {
+            return Some(name);
+        } else if length & 0xC0 == 0xC0 {
+            pointers += 1;
+            if pointers > MAX_POINTERS {
+                return None;
+            }
+            offset = (((length & 0x3F) as usize) << 8) | *message.get(offset + 1)? as usize;
+            continue;
+        }
+
+        let label = message.get(offset + 1..offset + 1 + length as usize)?;
+        if !name.is_empty() {
+            name.push('.');
+        }
+        name.push_str(std::str::from_utf8(label).ok()?);
+        if name.len() > MAX_NAME_LENGTH {
+            return None;
+        }
+        offset += 1 + length as usize;
+    }
+}
+
+fn system_nameservers() -> Vec<SocketAddr> {
+    let mut nameservers: Vec<SocketAddr> = Vec::new();
+
+    #[cfg(unix)]
+    if let Ok(resolv_conf) = std::fs::read_to_string("/etc/resolv.conf") {
+        nameservers.extend(parse_resolv_conf(&resolv_conf));
+    }
+
+    #[cfg(windows)]
+    nameservers.extend(windows_nameservers());
+
+    nameservers.dedup();
+    nameservers
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_resolv_conf(resolv_conf: &str) -> impl Iterator<Item = SocketAddr> + '_ {
+    resolv_conf.lines().filter_map(|line| {
+        let mut words = line.split_ascii_whitespace();
+        if words.next()? != "nameserver" {
+            return None;
+        }
+        // Link-local addresses may have a zone, e.g. fe80::1%eth0, which can't be used without the interface index
+        let address: IpAddr = words.next()?.parse().ok()?;
+        Some(SocketAddr::new(address, DNS_PORT))
+    })
+}
+
+/// Windows keeps the nameservers of each interface in the registry, either configured manually or through DHCP
+#[cfg(windows)]
+fn windows_nameservers() -> Vec<SocketAddr> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("reg")
+        .args(["query", r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip\Parameters\Interfaces", "/s"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    parse_registry_nameservers(&output).collect()
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_registry_nameservers(output: &str) -> impl Iterator<Item = SocketAddr> + '_ {
+    output.lines().filter_map(|line| {
+        let mut parts = line.split_ascii_whitespace();
+        let name = parts.next()?;
+        if name != "NameServer" && name != "DhcpNameServer" || parts.next()? != "REG_SZ" {
+            return None;
+        }
+        Some(parts.flat_map(|addresses| addresses.split(',')).filter_map(|address| address.parse::<IpAddr>().ok()).collect::<Vec<_>>())
+    }).flatten().map(|address| SocketAddr::new(address, DNS_PORT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for label in name.split('.').filter(|label| !label.is_empty()) {
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    /// A response to the query with the given `(priority, weight, port, target)` records,
+    /// the targets pointing back into the question name through compression when they are `None`
+    fn response(id: u16, response_code: u8, records: &[(u16, u16, u16, Option<&str>)]) -> Vec<u8> {
+        let mut response = build_srv_query(id, "_minecraft._tcp.example.com").unwrap();
+        response[2] |= 0x80;
+        response[3] |= response_code;
+        response[7] = records.len() as u8;
+
+        for (priority, weight, port, target) in records {
+            // Pointer to the question name
+            response.extend_from_slice(&[0xC0, 12]);
+            response.extend_from_slice(&TYPE_SRV.to_be_bytes());
+            response.extend_from_slice(&CLASS_IN.to_be_bytes());
+            response.extend_from_slice(&300_u32.to_be_bytes());
+
+            let mut data = Vec::new();
+            data.extend_from_slice(&priority.to_be_bytes());
+            data.extend_from_slice(&weight.to_be_bytes());
+            data.extend_from_slice(&port.to_be_bytes());
+            match target {
+                Some(target) => data.extend_from_slice(&name(target)),
+                // "mc" followed by a pointer to example.com in the question
+                None => data.extend_from_slice(&[2, b'm', b'c', 0xC0, 12 + 16]),
+            }
+            response.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            response.extend_from_slice(&data);
+        }
+        response
+    }
+
+    #[test]
+    fn test_build_query() {
+        let query = build_srv_query(0x1234, "_minecraft._tcp.example.com").unwrap();
+        assert_eq!(&query[..12], &[0x12, 0x34, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(&query[12..query.len() - 4], &name("_minecraft._tcp.example.com")[..]);
+        assert_eq!(&query[query.len() - 4..], &[0x00, 0x21, 0x00, 0x01]);
+
+        assert!(build_srv_query(1, "_minecraft._tcp.example..com").is_none());
+        assert!(build_srv_query(1, &format!("_minecraft._tcp.{}.com", "a".repeat(64))).is_none());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let records = [(10, 5, 25566, Some("backup.example.com")), (5, 1, 25570, Some("low.example.com")), (5, 10, 25580, None)];
+        assert_eq!(parse_srv_response(7, &response(7, 0, &records)), Some(("mc.example.com".into(), 25580)));
+        assert_eq!(parse_srv_response(7, &response(7, 0, &records[..2])), Some(("low.example.com".into(), 25570)));
+    }
+
+    #[test]
+    fn test_parse_no_service() {
+        assert_eq!(parse_srv_response(7, &response(7, 0, &[])), None);
+        assert_eq!(parse_srv_response(7, &response(7, 0, &[(0, 0, 25565, Some("."))])), None);
+        // NXDOMAIN
+        assert_eq!(parse_srv_response(7, &response(7, 3, &[(0, 0, 25565, Some("mc.example.com"))])), None);
+        // Answers to another query
+        assert_eq!(parse_srv_response(8, &response(7, 0, &[(0, 0, 25565, Some("mc.example.com"))])), None);
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let valid = response(7, 0, &[(0, 0, 25565, Some("mc.example.com"))]);
+        for length in 0..valid.len() {
+            assert_eq!(parse_srv_response(7, &valid[..length]), None, "truncated to {length}");
+        }
+
+        // A pointer to itself
+        let mut looping = response(7, 0, &[(0, 0, 25565, None)]);
+        let length = looping.len();
+        looping[length - 2] = 0xC0;
+        looping[length - 1] = (length - 2) as u8;
+        assert_eq!(parse_srv_response(7, &looping), None);
+    }
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let resolv_conf = "# Generated\nsearch lan\nnameserver 192.168.1.1\nnameserver  2001:4860:4860::8888\nnameserver fe80::1%eth0\noptions edns0\n";
+        let nameservers: Vec<_> = parse_resolv_conf(resolv_conf).collect();
+        assert_eq!(nameservers, [
+            SocketAddr::new(Ipv4Addr::new(192, 168, 1, 1).into(), DNS_PORT),
+            SocketAddr::new("2001:4860:4860::8888".parse().unwrap(), DNS_PORT),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_registry_nameservers() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Services\\Tcpip\\Parameters\\Interfaces\\{A}\r\n    NameServer    REG_SZ    1.1.1.1,8.8.8.8\r\n    DhcpNameServer    REG_SZ    192.168.1.1 192.168.1.2\r\n    EnableDHCP    REG_DWORD    0x1\r\n    NameServer    REG_SZ    \r\n";
+        let nameservers: Vec<_> = parse_registry_nameservers(output).map(|address| address.ip().to_string()).collect();
+        assert_eq!(nameservers, ["1.1.1.1", "8.8.8.8", "192.168.1.1", "192.168.1.2"]);
+    }
+
+    #[tokio::test]
+    async fn test_ip_addresses_skip_lookup() {
+        assert_eq!(lookup_minecraft_srv("127.0.0.1").await, None);
+        assert_eq!(lookup_minecraft_srv("::1").await, None);
+    }
+}
@@ -0,0 +1,388 @@
+use std::{path::{Path, PathBuf}, sync::Arc, time::{Duration, Instant}};
+
+use bridge::modal_action::ProgressTracker;
+use bytes::{Bytes, BytesMut};
+use parking_lot::{Mutex, RwLock};
+use reqwest::{header::{RANGE, RETRY_AFTER}, Request, Response, StatusCode, Url};
+use rustc_hash::FxHashMap;
+use schema::network::{DownloadConfiguration, NetworkConfiguration};
+use sha1::{Digest, Sha1};
+use tokio::{io::AsyncWriteExt, sync::{OwnedSemaphorePermit, Semaphore}};
+
+use crate::network::NetworkSettings;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(16);
+
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    #[error("Failed to load remote content")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Remote server returned non-200 status code: {0}")]
+    NotOK(StatusCode),
+    #[error("Downloaded file had the wrong size")]
+    WrongFilesize,
+    #[error("Downloaded file had the wrong hash")]
+    WrongHash,
+}
+
+impl DownloadError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            DownloadError::Reqwest(error) => is_retryable_error(error),
+            DownloadError::IoError(_) => false,
+            DownloadError::NotOK(status) => is_retryable_status(*status),
+            DownloadError::WrongFilesize => true,
+            DownloadError::WrongHash => true,
+        }
+    }
+}
+
+pub struct DownloadPermit {
+    _global: OwnedSemaphorePermit,
+    _host: OwnedSemaphorePermit,
+}
+
+pub struct DownloadResponse {
+    pub response: Response,
+    _permit: DownloadPermit,
+}
+
+struct DownloadLimits {
+    configuration: DownloadConfiguration,
+    global: Arc<Semaphore>,
+    hosts: FxHashMap<Arc<str>, Arc<Semaphore>>,
+}
+
+/// A file download that may be resumed from its `.part` file across retries
+struct FileDownload<'a> {
+    url: &'a str,
+    path: &'a Path,
+    part_path: PathBuf,
+    expected_hash: [u8; 20],
+    size: usize,
+    tracker: &'a ProgressTracker,
+    /// Bytes of this file already reported to the (possibly shared) tracker
+    counted: usize,
+}
+
+/// Shared scheduler for all downloads made by the backend, enforcing concurrency and bandwidth limits
+pub struct DownloadManager {
+    network: NetworkSettings,
+    limits: RwLock<DownloadLimits>,
+    throttle_next: Mutex<Instant>,
+}
+
+impl DownloadManager {
+    pub fn new(configuration: &NetworkConfiguration) -> Self {
+        let downloads = configuration.downloads.clone();
+        Self {
+            network: NetworkSettings::new(configuration),
+            limits: RwLock::new(DownloadLimits {
+                global: Arc::new(Semaphore::new(downloads.max_concurrent.max(1))),
+                hosts: FxHashMap::default(),
+                configuration: downloads,
+            }),
+            throttle_next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn update(&self, configuration: &NetworkConfiguration) {
+        self.network.update(configuration);
+
+        let mut limits = self.limits.write();
+        if limits.configuration != configuration.downloads {
+            // Permits held by in-flight downloads belong to the old semaphores and are simply dropped later
+            limits.global = Arc::new(Semaphore::new(configuration.downloads.max_concurrent.max(1)));
+            limits.hosts.clear();
+            limits.configuration = configuration.downloads.clone();
+        }
+    }
+
+    pub fn http_client(&self) -> reqwest::Client {
+        self.network.http_client()
+    }
+
+    pub fn redirecting_http_client(&self) -> reqwest::Client {
+        self.network.redirecting_http_client()
+    }
+
+    fn max_retries(&self) -> u32 {
+        self.limits.read().configuration.max_retries
+    }
+
+    pub async fn acquire(&self, url: &Url) -> DownloadPermit {
+        let (global, host) = {
+            let mut limits = self.limits.write();
+            let max_per_host = limits.configuration.max_per_host.max(1);
+            let host: Arc<str> = url.host_str().unwrap_or("").into();
+            let host = limits.hosts.entry(host).or_insert_with(|| Arc::new(Semaphore::new(max_per_host))).clone();
+            (limits.global.clone(), host)
+        };
+
+        // Acquire the host permit first so one busy host doesn't hold global permits while waiting
+        let host = host.acquire_owned().await.unwrap();
+        let global = global.acquire_owned().await.unwrap();
+
+        DownloadPermit {
+            _global: global,
+            _host: host,
+        }
+    }
+
+    async fn throttle(&self, bytes: usize) {
+        let Some(limit_kib) = self.limits.read().configuration.bandwidth_limit_kib else {
+            return;
+        };
+        if limit_kib == 0 {
+            return;
+        }
+
+        let bytes_per_second = limit_kib as f64 * 1024.0;
+        let delay = {
+            let mut next = self.throttle_next.lock();
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + Duration::from_secs_f64(bytes as f64 / bytes_per_second);
+            start.saturating_duration_since(now)
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn send_once(&self, client: &reqwest::Client, request: Request) -> reqwest::Result<DownloadResponse> {
+        let permit = self.acquire(request.url()).await;
+        let response = self.network.execute(client, request).await?;
+        Ok(DownloadResponse {
+            response,
+            _permit: permit,
+        })
+    }
+
+    /// Sends the request, retrying with exponential backoff on connection errors and transient status codes
+    pub async fn send(&self, client: &reqwest::Client, request: Request) -> reqwest::Result<DownloadResponse> {
+        let max_retries = self.max_retries();
+
+        let mut attempt = 0;
+        let mut request = request;
+        loop {
+            let retry_request = if attempt < max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let Some(retry_request) = retry_request else {
+                return self.send_once(client, request).await;
+            };
+
+            match self.send_once(client, request).await {
+                Ok(response) if is_retryable_status(response.response.status()) => {
+                    let retry_after = response.response.headers().get(RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .map(|seconds| Duration::from_secs(seconds).min(MAX_BACKOFF));
+                    eprintln!("Request to {} returned {}, retrying", response.response.url(), response.response.status());
+                    drop(response);
+                    tokio::time::sleep(retry_after.unwrap_or_else(|| backoff(attempt))).await;
+                },
+                Err(error) if is_retryable_error(&error) => {
+                    eprintln!("Request failed, retrying: {error}");
+                    tokio::time::sleep(backoff(attempt)).await;
+                },
+                result => return result,
+            }
+
+            attempt += 1;
+            request = retry_request;
+        }
+    }
+
+    pub async fn get(&self, client: &reqwest::Client, url: &str) -> reqwest::Result<DownloadResponse> {
+        let request = client.get(url).build()?;
+        self.send(client, request).await
+    }
+
+    /// Downloads the full body into memory, retrying if the connection drops part way through
+    pub async fn get_bytes(&self, client: &reqwest::Client, url: &str) -> reqwest::Result<Bytes> {
+        let max_retries = self.max_retries();
+
+        let mut attempt = 0;
+        loop {
+            let result: reqwest::Result<Bytes> = async {
+                // Single attempt, this loop already retries the whole download
+                let mut response = self.send_once(client, client.get(url).build()?).await?.error_for_status()?;
+
+                let mut bytes = BytesMut::new();
+                while let Some(chunk) = response.response.chunk().await? {
+                    self.throttle(chunk.len()).await;
+                    bytes.extend_from_slice(&chunk);
+                }
+                Ok(bytes.freeze())
+            }.await;
+
+            match result {
+                Err(error) if attempt < max_retries && is_retryable_error(&error) => {
+                    eprintln!("Download of {url} failed, retrying: {error}");
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Downloads into `path`, keeping a `.part` file around so interrupted downloads can be resumed
+    pub async fn download_file(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        path: &Path,
+        expected_hash: [u8; 20],
+        size: usize,
+        tracker: &ProgressTracker,
+    ) -> Result<(), DownloadError> {
+        let mut download = FileDownload {
+            url,
+            path,
+            part_path: path.with_added_extension("part"),
+            expected_hash,
+            size,
+            tracker,
+            counted: 0,
+        };
+        let part_path = download.part_path.clone();
+        let max_retries = self.max_retries();
+
+        let mut attempt = 0;
+        loop {
+            let result = self.try_download_file(client, &mut download).await;
+
+            match result {
+                Err(error) if attempt < max_retries && error.is_retryable() => {
+                    eprintln!("Download of {url} failed, retrying: {error}");
+                    if matches!(error, DownloadError::WrongFilesize | DownloadError::WrongHash) {
+                        let _ = tokio::fs::remove_file(&part_path).await;
+                    }
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                },
+                Err(error) => {
+                    if matches!(error, DownloadError::WrongFilesize | DownloadError::WrongHash) {
+                        let _ = tokio::fs::remove_file(&part_path).await;
+                    }
+                    return Err(error);
+                },
+                Ok(()) => return Ok(()),
+            }
+        }
+    }
+
+    async fn try_download_file(&self, client: &reqwest::Client, download: &mut FileDownload<'_>) -> Result<(), DownloadError> {
+        let FileDownload { url, path, ref part_path, expected_hash, size, tracker, ref mut counted } = *download;
+        let existing = tokio::fs::metadata(part_path).await.map(|metadata| metadata.len() as usize).unwrap_or(0);
+
+        let ranged = existing > 0 && existing < size;
+
+        let mut request = client.get(url);
+        if ranged {
+            request = request.header(RANGE, format!("bytes={existing}-"));
+        }
+
+        let mut response = self.send_once(client, request.build()?).await?;
+
+        let status = response.response.status();
+        let resume = match status {
+            StatusCode::PARTIAL_CONTENT if ranged => true,
+            // Some servers answer with 206 for the full body even when no range was requested
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => false,
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                let _ = tokio::fs::remove_file(part_path).await;
+                return Err(DownloadError::WrongFilesize);
+            },
+            status => return Err(DownloadError::NotOK(status)),
+        };
+
+        let (mut file, mut hasher, mut total_bytes) = if resume {
+            let hash_path = part_path.to_path_buf();
+            let hasher = tokio::task::spawn_blocking(move || {
+                let mut hasher = Sha1::new();
+                std::io::copy(&mut std::fs::File::open(hash_path)?, &mut hasher)?;
+                std::io::Result::Ok(hasher)
+            }).await.unwrap()?;
+
+            let file = tokio::fs::OpenOptions::new().append(true).open(part_path).await?;
+            (file, hasher, existing)
+        } else {
+            (tokio::fs::File::create(part_path).await?, Sha1::new(), 0)
+        };
+
+        let mut add_progress = |total_bytes: usize| {
+            if total_bytes > *counted {
+                tracker.add_count(total_bytes - *counted);
+                tracker.notify();
+                *counted = total_bytes;
+            }
+        };
+        add_progress(total_bytes);
+
+        while let Some(chunk) = response.response.chunk().await? {
+            self.throttle(chunk.len()).await;
+
+            total_bytes += chunk.len();
+            if total_bytes > size {
+                return Err(DownloadError::WrongFilesize);
+            }
+
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+            add_progress(total_bytes);
+        }
+
+        file.flush().await?;
+        drop(file);
+
+        if total_bytes != size {
+            return Err(DownloadError::WrongFilesize);
+        }
+        if *hasher.finalize() != expected_hash {
+            return Err(DownloadError::WrongHash);
+        }
+
+        tokio::fs::rename(part_path, path).await?;
+        Ok(())
+    }
+}
+
+impl DownloadResponse {
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    pub fn error_for_status(mut self) -> reqwest::Result<Self> {
+        self.response = self.response.error_for_status()?;
+        Ok(self)
+    }
+
+    pub async fn bytes(self) -> reqwest::Result<Bytes> {
+        self.response.bytes().await
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF.saturating_mul(1 << attempt.min(8)).min(MAX_BACKOFF)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT || status.is_server_error()
+}
+
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    if let Some(status) = error.status() {
+        return is_retryable_status(status);
+    }
+    error.is_connect() || error.is_timeout() || error.is_request() || error.is_body()
+}
@@ -1,14 +1,12 @@
-use std::{ffi::{OsStr, OsString}, io::Write, path::{Path, PathBuf}, sync::Arc};
+use std::{ffi::{OsStr, OsString}, path::{Path, PathBuf}, sync::Arc};
 
 use bridge::{
     install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{LoaderSpecificModSummary, ModSummary}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
-use reqwest::StatusCode;
 use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthLoader, ModrinthProjectVersionsRequest}};
 use sha1::{Digest, Sha1};
-use tokio::io::AsyncWriteExt;
 
-use crate::{lockfile::Lockfile, metadata::{items::{MinecraftVersionManifestMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthVersionMetadataItem}, manager::MetaLoadError}, BackendState};
+use crate::{download::DownloadError, lockfile::Lockfile, metadata::{items::{MinecraftVersionManifestMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthVersionMetadataItem}, manager::MetaLoadError}, BackendState};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ContentInstallError {
@@ -18,14 +16,8 @@ pub enum ContentInstallError {
     UnableToDetermineContentType(Arc<str>),
     #[error("Invalid filename: {0}")]
     InvalidFilename(Arc<str>),
-    #[error("Failed to download remote content")]
-    Reqwest(#[from] reqwest::Error),
-    #[error("Remote server returned non-200 status code: {0}")]
-    NotOK(StatusCode),
-    #[error("Downloaded file had the wrong size")]
-    WrongFilesize,
-    #[error("Downloaded file had the wrong hash")]
-    WrongHash,
+    #[error("Failed to download remote content:\n{0}")]
+    DownloadError(#[from] DownloadError),
     #[error("Hash isn't a valid sha1 hash:\n{0}")]
     InvalidHash(Arc<str>),
     #[error("Failed to perform I/O operation:\n{0}")]
@@ -46,15 +38,13 @@ struct InstallFromContentLibrary {
 }
 
 #[derive(Clone)]
-struct FilenameAndExtension {
-    filename: Option<OsString>,
+pub(crate) struct FilenameAndExtension {
     extension: Option<OsString>,
 }
 
 impl From<&SafePath> for FilenameAndExtension {
     fn from(value: &SafePath) -> Self {
         FilenameAndExtension {
-            filename: value.file_name().map(OsString::from),
             extension: value.extension().map(OsString::from),
         }
     }
@@ -63,15 +53,25 @@ impl From<&SafePath> for FilenameAndExtension {
 impl From<&Path> for FilenameAndExtension {
     fn from(value: &Path) -> Self {
         FilenameAndExtension {
-            filename: value.file_name().map(OsString::from),
             extension: value.extension().map(OsString::from),
         }
     }
 }
 
 impl BackendState {
-    pub async fn install_content(&self, content: ContentInstall, modal_action: ModalAction) {
-        let semaphore = tokio::sync::Semaphore::new(8);
+    /// Returns the .minecraft directory the content was installed into, if any
+    pub async fn install_content(&self, mut content: ContentInstall, modal_action: ModalAction) -> Option<PathBuf> {
+        match self.resolve_curseforge_content(&content).await {
+            Ok(files) => content.files = files,
+            Err(error) => {
+                modal_action.set_error_message(Arc::from(format!("{}", error).as_str()));
+                return None;
+            },
+        }
+
+        let download_tracker = ProgressTracker::new("Downloading content".into(), self.send.clone());
+        modal_action.trackers.push(download_tracker.clone());
+        download_tracker.notify();
 
         let mut tasks = Vec::new();
 
@@ -128,8 +128,8 @@ impl BackendState {
                                 return Err(ContentInstallError::InvalidFilename(install_file.filename.clone()));
                             };
 
-                            let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                                (&safe_filename).into(), url, sha1, size, &semaphore).await?;
+                            let (path, hash, mod_summary) = self.download_file_into_library(
+                                (&safe_filename).into(), url, sha1, size, &download_tracker).await?;
 
                             let install_path = match &content_file.path {
                                 ContentInstallPath::Raw(path) => path.clone(),
@@ -181,8 +181,8 @@ impl BackendState {
                             ContentInstallPath::Automatic => unimplemented!(),
                         };
 
-                        let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                            name, url, sha1, size, &semaphore).await?;
+                        let (path, hash, mod_summary) = self.download_file_into_library(
+                            name, url, sha1, size, &download_tracker).await?;
 
                         let install_path = match &content_file.path {
                             ContentInstallPath::Raw(path) => path.clone(),
@@ -199,6 +199,9 @@ impl BackendState {
                             mod_summary
                         });
                     },
+                    bridge::install::ContentDownload::CurseForge { .. } => {
+                        unreachable!("CurseForge downloads are resolved before installing")
+                    },
                     bridge::install::ContentDownload::File { path: ref copy_path } => {
                         let title = format!("Copying {}", copy_path.file_name().unwrap().to_string_lossy());
                         let tracker = ProgressTracker::new(title.into(), self.send.clone());
@@ -273,6 +276,10 @@ impl BackendState {
         }
 
         let result: Result<Vec<InstallFromContentLibrary>, ContentInstallError> = futures::future::try_join_all(tasks).await;
+
+        download_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        download_tracker.notify();
+
         match result {
             Ok(files) => {
                 let mut instance_dir = None;
@@ -315,7 +322,7 @@ impl BackendState {
                     });
                 self.mod_metadata_manager.set_content_sources(sources);
 
-                if let Some(instance_dir) = instance_dir {
+                if let Some(instance_dir) = &instance_dir {
                     for install in files {
                         let target_path = instance_dir.join(&install.install_path);
 
@@ -327,15 +334,18 @@ impl BackendState {
                         let _ = std::fs::hard_link(install.from, target_path);
                     }
                 }
+
+                instance_dir.map(|dir| dir.to_path_buf())
             },
             Err(error) => {
                 modal_action.set_error_message(Arc::from(format!("{}", error).as_str()));
+                None
             },
         }
     }
 
-    async fn download_file_into_library(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ModSummary>>), ContentInstallError> {
-        let mut result = self.download_file_into_library_inner(modal_action, name, url, sha1, size, semaphore).await?;
+    pub(crate) async fn download_file_into_library(&self, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, tracker: &ProgressTracker) -> Result<(PathBuf, [u8; 20], Option<Arc<ModSummary>>), ContentInstallError> {
+        let mut result = self.download_file_into_library_inner(name, url, sha1, size, tracker).await?;
 
         if let Some(summary) = &result.2 {
             if let LoaderSpecificModSummary::ModrinthModpack { downloads, .. } = &summary.extra {
@@ -347,12 +357,11 @@ impl BackendState {
                     };
 
                     let name = FilenameAndExtension {
-                        filename: path.file_name().map(OsString::from),
                         extension: path.extension().map(OsString::from),
                     };
 
-                    tasks.push(self.download_file_into_library_inner(modal_action, name,
-                        &download.downloads[0], &download.hashes.sha1, download.file_size, semaphore));
+                    tasks.push(self.download_file_into_library_inner(name,
+                        &download.downloads[0], &download.hashes.sha1, download.file_size, tracker));
                 }
 
                 _ = futures::future::try_join_all(tasks).await;
@@ -363,7 +372,7 @@ impl BackendState {
         Ok(result)
     }
 
-    async fn download_file_into_library_inner(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ModSummary>>), ContentInstallError> {
+    async fn download_file_into_library_inner(&self, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, tracker: &ProgressTracker) -> Result<(PathBuf, [u8; 20], Option<Arc<ModSummary>>), ContentInstallError> {
 
         let mut expected_hash = [0u8; 20];
         let Ok(_) = hex::decode_to_slice(&**sha1, &mut expected_hash) else {
@@ -384,15 +393,7 @@ impl BackendState {
 
         let lockfile = Lockfile::create(path.with_added_extension("lock").into()).await;
 
-        let _permit = semaphore.acquire().await.unwrap();
-
-        let file_name = name.filename.clone();
-
-        let title = format!("Downloading {}", file_name.as_deref().map(|s| s.to_string_lossy()).unwrap_or(std::borrow::Cow::Borrowed("???")));
-        let tracker = ProgressTracker::new(title.into(), self.send.clone());
-        modal_action.trackers.push(tracker.clone());
-
-        tracker.set_total(size);
+        tracker.add_total(size);
         tracker.notify();
 
         let valid_hash_on_disk = {
@@ -403,62 +404,13 @@ impl BackendState {
         };
 
         if valid_hash_on_disk {
-            tracker.set_count(size);
-            tracker.set_finished(ProgressTrackerFinishType::Fast);
+            tracker.add_count(size);
             tracker.notify();
             let summary = self.mod_metadata_manager.get_path(&path);
             return Ok((path, expected_hash, summary));
         }
 
-        let response = self.redirecting_http_client.get(&**url).send().await?;
-
-        if response.status() != StatusCode::OK {
-            return Err(ContentInstallError::NotOK(response.status()));
-        }
-
-        // Tokio doesn't have lock, so we use std temporarily to lock it
-        let file = std::fs::File::create(&path)?;
-        _ = file.lock();
-
-        let mut file = tokio::fs::File::from_std(file);
-
-        use futures::StreamExt;
-        let mut stream = response.bytes_stream();
-
-        let mut total_bytes = 0;
-
-        let mut hasher = Sha1::new();
-        while let Some(item) = stream.next().await {
-            let item = item?;
-
-            total_bytes += item.len();
-            tracker.add_count(item.len());
-            tracker.notify();
-
-            hasher.write_all(&item)?;
-            file.write_all(&item).await?;
-        }
-
-        tracker.set_finished(ProgressTrackerFinishType::Fast);
-
-        let actual_hash = hasher.finalize();
-
-        let wrong_hash = *actual_hash != expected_hash;
-        let wrong_size = total_bytes != size;
-
-        if wrong_hash || wrong_size {
-            let _ = file.set_len(0).await;
-            drop(file);
-            let _ = tokio::fs::remove_file(&path).await;
-
-            if wrong_hash {
-                return Err(ContentInstallError::WrongHash);
-            } else if wrong_size {
-                return Err(ContentInstallError::WrongFilesize);
-            } else {
-                unreachable!();
-            }
-        }
+        self.downloads.download_file(&self.downloads.redirecting_http_client(), url, &path, expected_hash, size, tracker).await?;
 
         drop(lockfile);
 
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet, ffi::OsStr, hash::{DefaultHasher, Hash, Hasher}, io::Read, path::Path, process::Child, sync::{
+    collections::{HashMap, HashSet}, ffi::OsStr, hash::{DefaultHasher, Hash, Hasher}, io::Read, path::{Path, PathBuf}, process::Child, sync::{
         atomic::Ordering, Arc
     }
 };
@@ -8,11 +8,11 @@ use anyhow::Context;
 use base64::Engine;
 use bridge::{
     instance::{
-        InstanceID, InstanceModID, InstanceModSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary,
+        InstanceID, InstanceModID, InstanceModSummary, InstancePackSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, PackFolder, ServerListEdit,
     }, message::{AtomicBridgeDataLoadState, BridgeDataLoadState, MessageToFrontend}, notify_signal::{KeepAliveNotifySignal, KeepAliveNotifySignalHandle}
 };
 use parking_lot::RwLock;
-use schema::instance::InstanceConfiguration;
+use schema::instance::{InstanceConfiguration, InstanceOrganization};
 use thiserror::Error;
 
 use ustr::Ustr;
@@ -29,6 +29,8 @@ pub struct Instance {
     pub mods_path: Arc<Path>,
     pub name: Ustr,
     pub configuration: Persistent<InstanceConfiguration>,
+    pub organization: Persistent<InstanceOrganization>,
+    pub icon: Option<Arc<[u8]>>,
 
     pub child: Option<Child>,
 
@@ -54,6 +56,53 @@ pub struct Instance {
     mods_generation: usize,
     pending_mods_load: Option<KeepAliveNotifySignalHandle>,
     pub mods: Option<Arc<[InstanceModSummary]>>,
+
+    pub packs: HashMap<PackFolder, PackCollection>,
+}
+
+#[derive(Debug)]
+pub struct PackCollection {
+    pub path: Arc<Path>,
+    pub watching: bool,
+    pub state: Arc<AtomicBridgeDataLoadState>,
+    dirty: HashSet<Arc<Path>>,
+    all_dirty: bool,
+    pending_load: Option<KeepAliveNotifySignalHandle>,
+    pub packs: Option<Arc<[InstancePackSummary]>>,
+}
+
+impl PackCollection {
+    fn new(path: Arc<Path>) -> Self {
+        Self {
+            path,
+            watching: false,
+            state: Arc::new(AtomicBridgeDataLoadState::new(BridgeDataLoadState::Unloaded)),
+            dirty: HashSet::new(),
+            all_dirty: true,
+            pending_load: None,
+            packs: None,
+        }
+    }
+
+    pub fn mark_dirty(&mut self, path: Option<Arc<Path>>) {
+        if self.all_dirty {
+            return;
+        }
+
+        if let Some(path) = path {
+            if !self.dirty.insert(path) {
+                return;
+            }
+        } else {
+            self.all_dirty = true;
+        }
+
+        cas_update(&self.state, |state| match state {
+            BridgeDataLoadState::Loading => BridgeDataLoadState::LoadingDirty,
+            BridgeDataLoadState::Loaded => BridgeDataLoadState::LoadedDirty,
+            _ => state,
+        });
+    }
 }
 
 impl Id for InstanceID {
@@ -93,6 +142,8 @@ impl Instance {
     pub fn on_root_renamed(&mut self, path: &Path) {
         self.name = path.file_name().unwrap().to_string_lossy().into_owned().into();
         self.root_path = path.into();
+        self.configuration.set_path(path.join("info_v1.json").into());
+        self.organization.set_path(path.join("organization_v1.json").into());
 
         let mut dot_minecraft_path = path.to_owned();
         dot_minecraft_path.push(".minecraft");
@@ -105,6 +156,53 @@ impl Instance {
         self.server_dat_path = server_dat_path.into();
         self.saves_path = saves_path.into();
         self.mods_path = mods_path.into();
+
+        let folders: Vec<PackFolder> = self.packs.keys().cloned().collect();
+        for folder in folders {
+            let path = self.pack_folder_path(&folder).into();
+            if let Some(collection) = self.packs.get_mut(&folder) {
+                collection.path = path;
+                collection.mark_dirty(None);
+            }
+        }
+    }
+
+    pub fn icon_path(&self) -> PathBuf {
+        self.root_path.join("icon.png")
+    }
+
+    pub fn notes_path(&self) -> PathBuf {
+        self.root_path.join("notes.md")
+    }
+
+    pub fn reload_icon(&mut self) {
+        self.icon = std::fs::read(self.icon_path()).ok().map(Arc::from);
+    }
+
+    pub fn pack_folder_path(&self, folder: &PackFolder) -> PathBuf {
+        match folder {
+            PackFolder::ResourcePacks => self.dot_minecraft_path.join("resourcepacks"),
+            PackFolder::ShaderPacks => self.dot_minecraft_path.join("shaderpacks"),
+            PackFolder::DataPacks { world } => self.saves_path.join(&**world).join("datapacks"),
+        }
+    }
+
+    pub fn pack_collection_mut(&mut self, folder: &PackFolder) -> &mut PackCollection {
+        if !self.packs.contains_key(folder) {
+            let path = self.pack_folder_path(folder).into();
+            self.packs.insert(folder.clone(), PackCollection::new(path));
+        }
+        self.packs.get_mut(folder).unwrap()
+    }
+
+    pub fn find_pack_collection_mut(&mut self, folder_path: &Path) -> Option<&mut PackCollection> {
+        self.packs.values_mut().find(|collection| &*collection.path == folder_path)
+    }
+
+    pub fn mark_all_packs_dirty(&mut self) {
+        for collection in self.packs.values_mut() {
+            collection.mark_dirty(None);
+        }
     }
 
     pub fn try_get_mod(&self, id: InstanceModID) -> Option<&InstanceModSummary> {
@@ -536,6 +634,148 @@ impl Instance {
         summaries
     }
 
+    pub async fn load_packs(
+        instances: Arc<RwLock<BackendStateInstances>>,
+        id: InstanceID,
+        folder: PackFolder,
+        mod_metadata_manager: &Arc<ModMetadataManager>,
+    ) -> Option<(Arc<[InstancePackSummary]>, Arc<AtomicBridgeDataLoadState>, bool)> {
+        let mut await_pending: Option<KeepAliveNotifySignalHandle> = None;
+
+        let (future, keep_alive) = loop {
+            if let Some(pending) = await_pending {
+                pending.await_notification().await;
+            }
+
+            let mut guard = instances.write();
+            let this = guard.instances.get_mut(id)?;
+            let collection = this.pack_collection_mut(&folder);
+
+            if let Some(pending) = &collection.pending_load && !pending.is_notified() {
+                await_pending = Some(pending.clone());
+                continue;
+            }
+
+            if cfg!(debug_assertions) && !collection.watching {
+                panic!("Must be watching {:?}", collection.path);
+            }
+
+            let future = if let Some(last) = &collection.packs && !collection.all_dirty {
+                if !collection.dirty.is_empty() {
+                    let dirty = std::mem::take(&mut collection.dirty);
+                    let folder = folder.clone();
+                    let mod_metadata_manager = mod_metadata_manager.clone();
+                    let last = last.clone();
+                    tokio::task::spawn_blocking(move || {
+                        Self::load_packs_dirty(dirty, &folder, &mod_metadata_manager, last)
+                    })
+                } else {
+                    return Some((last.clone(), collection.state.clone(), false));
+                }
+            } else {
+                let path = collection.path.clone();
+                let folder = folder.clone();
+                let mod_metadata_manager = mod_metadata_manager.clone();
+                tokio::task::spawn_blocking(move || {
+                    Self::load_packs_all(&path, &folder, &mod_metadata_manager)
+                })
+            };
+
+            let keep_alive = KeepAliveNotifySignal::new();
+            collection.pending_load = Some(keep_alive.create_handle());
+
+            collection.state.store(BridgeDataLoadState::Loading, Ordering::Release);
+            collection.all_dirty = false;
+            collection.dirty.clear();
+
+            break (future, keep_alive);
+        };
+
+        let result = future.await.unwrap();
+
+        let mut guard = instances.write();
+        let this = guard.instances.get_mut(id)?;
+        let collection = this.packs.get_mut(&folder)?;
+
+        cas_update(&collection.state, |old_state| match old_state {
+            BridgeDataLoadState::LoadingDirty => BridgeDataLoadState::LoadedDirty,
+            BridgeDataLoadState::Loading => BridgeDataLoadState::Loaded,
+            _ => unreachable!(),
+        });
+
+        collection.packs = Some(result.clone());
+        collection.pending_load = None;
+        keep_alive.notify();
+        Some((result, collection.state.clone(), true))
+    }
+
+    fn load_packs_all(path: &Path, folder: &PackFolder, mod_metadata_manager: &ModMetadataManager) -> Arc<[InstancePackSummary]> {
+        let Ok(directory) = std::fs::read_dir(&path) else {
+            return [].into();
+        };
+
+        let mut summaries = Vec::with_capacity(32);
+
+        for entry in directory {
+            let Ok(entry) = entry else {
+                eprintln!("Error reading file in pack folder: {:?}", entry.unwrap_err());
+                continue;
+            };
+
+            if let Some(summary) = crate::packs::create_instance_pack_summary(&entry.path(), folder, mod_metadata_manager) {
+                summaries.push(summary);
+            }
+        }
+
+        summaries.sort_by(|a, b| lexical_sort::natural_lexical_cmp(&a.lowercase_filename, &b.lowercase_filename));
+
+        summaries.into()
+    }
+
+    fn load_packs_dirty(
+        dirty: HashSet<Arc<Path>>,
+        folder: &PackFolder,
+        mod_metadata_manager: &ModMetadataManager,
+        last: Arc<[InstancePackSummary]>,
+    ) -> Arc<[InstancePackSummary]> {
+        let mut summaries = Vec::with_capacity(last.len() + 4);
+
+        let mut alternative_dirty = HashSet::new();
+
+        for path in dirty.iter() {
+            // Enabling or disabling a pack renames it, so the other name has to be checked too
+            let mut alternate_path = path.to_path_buf();
+            if let Some(extension) = path.extension() && extension == "disabled" {
+                alternate_path.set_extension("");
+            } else {
+                alternate_path.add_extension("disabled");
+            };
+
+            let check_alternative = !dirty.contains(&*alternate_path);
+
+            if let Some(summary) = crate::packs::create_instance_pack_summary(path, folder, mod_metadata_manager) {
+                summaries.push(summary);
+            } else if check_alternative {
+                if let Some(summary) = crate::packs::create_instance_pack_summary(&alternate_path, folder, mod_metadata_manager) {
+                    summaries.push(summary);
+                }
+            }
+            if check_alternative {
+                alternative_dirty.insert(alternate_path);
+            }
+        }
+
+        for old_summary in &*last {
+            if !dirty.contains(&old_summary.path) && !alternative_dirty.contains(&*old_summary.path) && old_summary.path.exists() {
+                summaries.push(old_summary.clone());
+            }
+        }
+
+        summaries.sort_by(|a, b| lexical_sort::natural_lexical_cmp(&a.lowercase_filename, &b.lowercase_filename));
+
+        summaries.into()
+    }
+
     pub fn load_from_folder(path: impl AsRef<Path>) -> Result<Self, InstanceLoadError> {
         let path = path.as_ref();
         if !path.is_dir() {
@@ -545,6 +785,7 @@ impl Instance {
         let info_path: Arc<Path> = path.join("info_v1.json").into();
 
         let instance_info: Persistent<InstanceConfiguration> = Persistent::try_load(info_path.clone())?;
+        let organization: Persistent<InstanceOrganization> = Persistent::load(path.join("organization_v1.json").into());
 
         let mut dot_minecraft_path = path.to_owned();
         dot_minecraft_path.push(".minecraft");
@@ -562,6 +803,8 @@ impl Instance {
             mods_path: mods_path.into(),
             name: path.file_name().unwrap().to_string_lossy().into_owned().into(),
             configuration: instance_info,
+            organization,
+            icon: std::fs::read(path.join("icon.png")).ok().map(Arc::from),
 
             child: None,
 
@@ -587,6 +830,8 @@ impl Instance {
             mods_generation: 0,
             pending_mods_load: None,
             mods: None,
+
+            packs: HashMap::new(),
         })
     }
 
@@ -685,6 +930,8 @@ impl Instance {
             name: self.name,
             dot_minecraft_folder: self.dot_minecraft_path.clone(),
             configuration: self.configuration.get().clone(),
+            organization: self.organization.get().clone(),
+            icon: self.icon.clone(),
             status,
         }
     }
@@ -766,15 +1013,7 @@ fn load_world_summary(path: &Path) -> anyhow::Result<InstanceWorldSummary> {
         anyhow::bail!("level.dat doesn't exist");
     }
 
-    let compressed = std::fs::read(&level_dat_path)?;
-
-    let mut decoder = flate2::bufread::GzDecoder::new(compressed.as_slice());
-
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)?;
-
-    let mut nbt_data = decompressed.as_slice();
-    let result = nbt::decode::read_named(&mut nbt_data)?;
+    let result = crate::world_details::read_gzip_nbt(&level_dat_path)?;
 
     let root = result.as_compound().context("Unable to get root compound")?;
     let data = root.find_compound("Data").context("Unable to get Data")?;
@@ -823,18 +1062,13 @@ fn load_servers_summary(server_dat_path: &Path) -> anyhow::Result<Vec<InstanceSe
 
     let mut summaries = Vec::with_capacity(servers.len());
 
-    for server in servers.iter() {
+    for (index, server) in servers.iter().enumerate() {
         let server = server.as_compound().unwrap();
 
-        if let Some(hidden) = server.find_byte("hidden")
-            && *hidden != 0
-        {
+        if !is_listed_server(server) {
             continue;
         }
-
-        let Some(ip) = server.find_string("ip") else {
-            continue;
-        };
+        let ip = server.find_string("ip").unwrap();
 
         let name: Arc<str> = server
             .find_string("name")
@@ -846,6 +1080,7 @@ fn load_servers_summary(server_dat_path: &Path) -> anyhow::Result<Vec<InstanceSe
             .and_then(|v| base64::engine::general_purpose::STANDARD.decode(v).map(Arc::from).ok());
 
         summaries.push(InstanceServerSummary {
+            index,
             name,
             ip: Arc::from(ip.as_str()),
             png_icon: icon,
@@ -855,6 +1090,74 @@ fn load_servers_summary(server_dat_path: &Path) -> anyhow::Result<Vec<InstanceSe
     Ok(summaries)
 }
 
+/// Hidden entries and entries without an address are skipped in the summaries, but keep their
+/// position in servers.dat so the indices of the remaining servers refer to the raw list
+fn is_listed_server(server: nbt::CompoundRef) -> bool {
+    let hidden = server.find_byte("hidden").is_some_and(|hidden| *hidden != 0);
+    !hidden && server.find_string("ip").is_some()
+}
+
+pub(crate) fn edit_servers_dat(server_dat_path: &Path, edit: ServerListEdit) -> anyhow::Result<()> {
+    let mut result = if server_dat_path.is_file() {
+        let raw = std::fs::read(server_dat_path)?;
+        let mut nbt_data = raw.as_slice();
+        nbt::decode::read_named(&mut nbt_data)?
+    } else {
+        nbt::NBT::new()
+    };
+
+    let mut root = result.as_compound_mut().context("Unable to get root compound")?;
+    if root.find_list("servers", nbt::TAG_COMPOUND_ID).is_none() {
+        if root.contains_key("servers") {
+            anyhow::bail!("servers.dat contains an invalid servers list");
+        }
+        root.create_list("servers", nbt::TAG_COMPOUND_ID);
+    }
+    let mut servers = root.find_list_mut("servers", nbt::TAG_COMPOUND_ID).unwrap();
+
+    // Indices come from load_servers_summary, if one points at an entry that isn't listed the
+    // file changed since then and the edit would hit the wrong server
+    let listed = |servers: &nbt::ListRefMut, index: usize| {
+        servers.get(index).and_then(|server| server.as_compound()).is_some_and(is_listed_server)
+    };
+    let indices = match &edit {
+        ServerListEdit::Add { .. } => [None, None],
+        ServerListEdit::Edit { index, .. } | ServerListEdit::Remove { index } => [Some(*index), None],
+        ServerListEdit::Swap { first, second } => [Some(*first), Some(*second)],
+    };
+    if !indices.into_iter().flatten().all(|index| listed(&servers, index)) {
+        anyhow::bail!("Unknown server");
+    }
+
+    match edit {
+        ServerListEdit::Add { name, ip } => {
+            let mut server = servers.create_compound();
+            server.insert_string("name", name.to_string());
+            server.insert_string("ip", ip.to_string());
+        },
+        ServerListEdit::Edit { index, name, ip } => {
+            let mut server = servers.get_compound_mut(index).unwrap();
+            let ip_changed = server.find_string("ip").is_none_or(|old_ip| old_ip.as_str() != &*ip);
+            server.insert_string("name", name.to_string());
+            server.insert_string("ip", ip.to_string());
+            if ip_changed {
+                // The icon belongs to the old address, Minecraft will fetch the new one
+                server.remove("icon");
+            }
+        },
+        ServerListEdit::Remove { index } => {
+            servers.remove(index);
+        },
+        ServerListEdit::Swap { first, second } => {
+            servers.swap(first, second);
+        },
+    }
+
+    let bytes = nbt::encode::write_named(&result);
+    crate::write_safe(server_dat_path, &bytes)?;
+    Ok(())
+}
+
 fn cas_update(state: &Arc<AtomicBridgeDataLoadState>, func: impl Fn(BridgeDataLoadState) -> BridgeDataLoadState) {
     let mut old_state = state.load(Ordering::Acquire);
     loop {
@@ -870,3 +1173,52 @@ fn cas_update(state: &Arc<AtomicBridgeDataLoadState>, func: impl Fn(BridgeDataLo
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_servers(path: &Path) {
+        let mut nbt = nbt::NBT::new();
+        let mut root = nbt.as_compound_mut().unwrap();
+        let mut servers = root.create_list("servers", nbt::TAG_COMPOUND_ID);
+        for (name, ip, hidden) in [("First", "first.example", false), ("Realm", "realm.example", true), ("Second", "second.example", false)] {
+            let mut server = servers.create_compound();
+            server.insert_string("name", name.to_string());
+            server.insert_string("ip", ip.to_string());
+            if hidden {
+                server.insert_byte("hidden", 1);
+            }
+        }
+        std::fs::write(path, nbt::encode::write_named(&nbt)).unwrap();
+    }
+
+    fn ips(path: &Path) -> Vec<(usize, String)> {
+        load_servers_summary(path).unwrap().iter().map(|server| (server.index, server.ip.to_string())).collect()
+    }
+
+    #[test]
+    fn test_server_indices_include_hidden() {
+        let dir = std::env::temp_dir().join(format!("pandora-servers-dat-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("servers.dat");
+        write_servers(&path);
+
+        assert_eq!(ips(&path), [(0, "first.example".into()), (2, "second.example".into())]);
+
+        // Indices that point at the hidden entry are rejected instead of editing it
+        assert!(edit_servers_dat(&path, ServerListEdit::Remove { index: 1 }).is_err());
+        assert!(edit_servers_dat(&path, ServerListEdit::Swap { first: 0, second: 1 }).is_err());
+        assert!(edit_servers_dat(&path, ServerListEdit::Remove { index: 3 }).is_err());
+
+        edit_servers_dat(&path, ServerListEdit::Swap { first: 0, second: 2 }).unwrap();
+        assert_eq!(ips(&path), [(0, "second.example".into()), (2, "first.example".into())]);
+
+        edit_servers_dat(&path, ServerListEdit::Edit { index: 2, name: "Renamed".into(), ip: "renamed.example".into() }).unwrap();
+        edit_servers_dat(&path, ServerListEdit::Remove { index: 0 }).unwrap();
+        // The hidden entry moved up but is still kept
+        assert_eq!(ips(&path), [(1, "renamed.example".into())]);
+
+        _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{instance::{InstanceID, InstanceModSummary, LoaderSpecificModSummary}, jvm::JvmRecommendation};
+use schema::loader::Loader;
+use ustr::Ustr;
+
+use crate::{launch::Launcher, metadata::items::{MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem}, BackendState};
+
+const MIN_RECOMMENDED_MEMORY: u32 = 1024;
+/// Larger heaps mostly increase garbage collection pauses
+const MAX_RECOMMENDED_MEMORY: u32 = 16384;
+/// Memory left for the operating system and the launcher when the instance is large
+const RESERVED_SYSTEM_MEMORY: u32 = 2048;
+
+impl BackendState {
+    pub async fn get_jvm_recommendation(&self, id: InstanceID) -> Option<JvmRecommendation> {
+        let configuration = self.instance_state.write().instances.get_mut(id)?.configuration.get().clone();
+        let game_configuration = configuration.resolve_game_configuration(&self.config.write().get().game);
+
+        let enabled_mods = if configuration.loader == Loader::Vanilla {
+            0
+        } else {
+            self.clone().load_instance_mods(id).await.map(|mods| count_enabled_mods(&mods)).unwrap_or(0)
+        };
+
+        let java_major_version = if game_configuration.jvm_binary.enabled && let Some(path) = &game_configuration.jvm_binary.value.path {
+            read_java_release_version(path)
+        } else {
+            self.mojang_java_major_version(configuration.minecraft_version).await
+        };
+
+        let (total_system_memory, available_system_memory) = system_memory();
+        let (memory_min, memory_max, reasons) = recommend_memory(configuration.loader, enabled_mods, java_major_version, total_system_memory);
+
+        Some(JvmRecommendation {
+            memory_min,
+            memory_max,
+            reasons,
+            enabled_mods,
+            total_system_memory,
+            available_system_memory,
+            java_major_version,
+        })
+    }
+
+    async fn mojang_java_major_version(&self, minecraft_version: Ustr) -> Option<u32> {
+        let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await.ok()?;
+        let link = versions.versions.iter().find(|version| version.id == minecraft_version)?;
+        let version_info = self.meta.fetch(&MinecraftVersionMetadataItem(link)).await.ok()?;
+        Some(version_info.java_version.as_ref().map(|java_version| java_version.major_version).unwrap_or(8))
+    }
+}
+
+/// Returns the recommended min and max memory in MiB along with the reasons for the max
+fn recommend_memory(loader: Loader, enabled_mods: usize, java_major_version: Option<u32>, total_system_memory: Option<u32>) -> (u32, u32, Arc<[Arc<str>]>) {
+    let mut reasons: Vec<Arc<str>> = Vec::new();
+
+    let (base, per_mod) = match loader {
+        Loader::Vanilla | Loader::Unknown => (2048, 0),
+        Loader::Fabric => (2048, 24),
+        Loader::Forge | Loader::NeoForge => (3072, 40),
+    };
+    reasons.push(format!("{base} MiB for {}", loader.name()).into());
+
+    let mut memory_max = base;
+    if enabled_mods > 0 {
+        let for_mods = per_mod * enabled_mods as u32;
+        memory_max += for_mods;
+        reasons.push(format!("{for_mods} MiB for {enabled_mods} enabled mods").into());
+    }
+
+    if java_major_version.is_some_and(|java| java <= 8) {
+        memory_max = memory_max.saturating_sub(512);
+        reasons.push("512 MiB less since older versions use less memory".into());
+    }
+
+    memory_max = memory_max.div_ceil(512) * 512;
+    if memory_max > MAX_RECOMMENDED_MEMORY {
+        memory_max = MAX_RECOMMENDED_MEMORY;
+        reasons.push("Limited to 16 GiB, larger heaps increase garbage collection pauses".into());
+    }
+    memory_max = memory_max.max(MIN_RECOMMENDED_MEMORY);
+
+    if let Some(total) = total_system_memory {
+        let limit = (total.saturating_sub(RESERVED_SYSTEM_MEMORY) / 512 * 512).max(MIN_RECOMMENDED_MEMORY);
+        if memory_max > limit {
+            memory_max = limit;
+            reasons.push(format!("Limited to {limit} MiB to leave memory for the system").into());
+        }
+    }
+
+    let memory_min = (memory_max / 2 / 256 * 256).max(512).min(memory_max);
+
+    (memory_min, memory_max, reasons.into())
+}
+
+fn count_enabled_mods(mods: &[InstanceModSummary]) -> usize {
+    mods.iter().filter(|summary| summary.enabled).map(|summary| {
+        match &summary.mod_summary.extra {
+            LoaderSpecificModSummary::ModrinthModpack { downloads, .. } => downloads.len().saturating_sub(summary.disabled_children.len()),
+            _ => 1,
+        }
+    }).sum()
+}
+
+/// Total and available memory in MiB
+fn system_memory() -> (Option<u32>, Option<u32>) {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+
+    let to_mib = |bytes: u64| {
+        if bytes == 0 {
+            None
+        } else {
+            u32::try_from(bytes / 1024 / 1024).ok()
+        }
+    };
+    (to_mib(system.total_memory()), to_mib(system.available_memory()))
+}
+
+/// Reads the major version from the `release` file that JDK and JRE distributions place in the java home
+fn read_java_release_version(path: &Path) -> Option<u32> {
+    let binary = Launcher::search_for_java_binary(path)?;
+    let release = binary.ancestors().skip(2).take(2).find_map(|home| std::fs::read_to_string(home.join("release")).ok())?;
+
+    let version = release.lines().find_map(|line| line.strip_prefix("JAVA_VERSION="))?.trim().trim_matches('"');
+    parse_java_major_version(version)
+}
+
+fn parse_java_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    let first = parts.next()?.parse::<u32>().ok()?;
+    if first == 1 {
+        parts.next()?.parse::<u32>().ok()
+    } else {
+        Some(first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_vanilla() {
+        let (min, max, reasons) = recommend_memory(Loader::Vanilla, 0, Some(21), None);
+        assert_eq!((min, max), (1024, 2048));
+        assert_eq!(reasons.len(), 1);
+    }
+
+    #[test]
+    fn test_recommend_per_mod() {
+        // 2048 + 100 * 24 rounded up to a multiple of 512
+        let (min, max, reasons) = recommend_memory(Loader::Fabric, 100, Some(21), Some(32768));
+        assert_eq!((min, max), (2304, 4608));
+        assert_eq!(&*reasons[1], "2400 MiB for 100 enabled mods");
+
+        let (_, max, _) = recommend_memory(Loader::Fabric, 1, Some(21), None);
+        assert_eq!(max, 2560);
+        let (_, max, _) = recommend_memory(Loader::NeoForge, 1, Some(21), None);
+        assert_eq!(max, 3584);
+    }
+
+    #[test]
+    fn test_recommend_old_java() {
+        let (min, max, _) = recommend_memory(Loader::Vanilla, 0, Some(8), None);
+        assert_eq!((min, max), (768, 1536));
+        // Unknown java versions aren't assumed to be old
+        let (_, max, _) = recommend_memory(Loader::Vanilla, 0, None, None);
+        assert_eq!(max, 2048);
+    }
+
+    #[test]
+    fn test_recommend_limits() {
+        let (min, max, reasons) = recommend_memory(Loader::Forge, 400, Some(17), Some(65536));
+        assert_eq!((min, max), (8192, MAX_RECOMMENDED_MEMORY));
+        assert!(reasons.last().unwrap().starts_with("Limited to 16 GiB"));
+
+        let (min, max, reasons) = recommend_memory(Loader::Forge, 50, Some(17), Some(4096));
+        assert_eq!((min, max), (1024, 2048));
+        assert_eq!(&**reasons.last().unwrap(), "Limited to 2048 MiB to leave memory for the system");
+
+        // Never below the minimum, even if that doesn't leave the reserved memory
+        let (min, max, _) = recommend_memory(Loader::Forge, 50, Some(17), Some(2048));
+        assert_eq!((min, max), (512, MIN_RECOMMENDED_MEMORY));
+    }
+
+    #[test]
+    fn test_parse_java_major_version() {
+        let cases = [
+            ("1.8.0_402", Some(8)),
+            ("1.8", Some(8)),
+            ("17.0.2", Some(17)),
+            ("21", Some(21)),
+            ("21-ea", Some(21)),
+            ("11.0.22+7", Some(11)),
+            ("1", None),
+            ("", None),
+            ("openjdk", None),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(parse_java_major_version(version), expected, "{version}");
+        }
+    }
+}
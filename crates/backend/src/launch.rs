@@ -11,7 +11,7 @@ use rc_zip_sync::ReadZip;
 use regex::Regex;
 use rustc_hash::FxHashMap;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeSide}, instance::InstanceConfiguration, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::MavenMetadataXml, version::{
+    assets_index::AssetsIndex, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeSide}, instance::{GameConfiguration, InstanceConfiguration}, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::MavenMetadataXml, version::{
         GameLibrary, GameLibraryArtifact, GameLibraryDownloads, GameLibraryExtractOptions, GameLogging, LaunchArgument, LaunchArgumentValue, MinecraftVersion, OsArch, OsName, PartialMinecraftVersion, Rule, RuleAction
     }, version_manifest::MinecraftVersionManifest
 };
@@ -22,12 +22,13 @@ use ustr::Ustr;
 use crate::{
     account::MinecraftLoginInfo, directories::LauncherDirectories, launch_wrapper, metadata::{items::{AssetsIndexMetadataItem, FabricLaunchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem}, manager::{
         MetaLoadError, MetadataManager,
-    }}
+    }}, download::DownloadManager
 };
 
 #[derive(Clone)]
 pub struct Launcher {
     meta: Arc<MetadataManager>,
+    downloads: Arc<DownloadManager>,
     directories: Arc<LauncherDirectories>,
     sender: FrontendHandle,
 }
@@ -67,9 +68,10 @@ pub enum AddVanillaJar {
 }
 
 impl Launcher {
-    pub fn new(meta: Arc<MetadataManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle) -> Self {
+    pub fn new(meta: Arc<MetadataManager>, downloads: Arc<DownloadManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle) -> Self {
         Self {
             meta,
+            downloads,
             directories,
             sender,
         }
@@ -80,6 +82,7 @@ impl Launcher {
         http_client: &reqwest::Client,
         dot_minecraft_path: Arc<Path>,
         instance_info: InstanceConfiguration,
+        game_defaults: &GameConfiguration,
         quick_play: Option<QuickPlayLaunch>,
         login_info: MinecraftLoginInfo,
         add_mods: Vec<PathBuf>,
@@ -88,8 +91,10 @@ impl Launcher {
     ) -> Result<Child, LaunchError> {
         launch_tracker.set_total(6);
 
+        let game_configuration = instance_info.resolve_game_configuration(game_defaults);
+
         let (version_info, add_vanilla_jar) = tokio::select! {
-            result = self.create_launch_version(http_client, &modal_action.trackers, launch_tracker, &instance_info) => result?,
+            result = self.create_launch_version(http_client, &modal_action.trackers, launch_tracker, &instance_info, &game_configuration) => result?,
             _ = modal_action.request_cancel.cancelled() => {
                 self.sender.send(MessageToFrontend::CloseModal);
                 return Err(LaunchError::CancelledByUser);
@@ -103,7 +108,11 @@ impl Launcher {
 
         let launch_rule_context = LaunchRuleContext {
             is_demo_user: false,
-            custom_resolution: None,
+            custom_resolution: if game_configuration.resolution.enabled {
+                Some((game_configuration.resolution.value.width.max(1), game_configuration.resolution.value.height.max(1)))
+            } else {
+                None
+            },
             quick_play,
         };
 
@@ -128,7 +137,7 @@ impl Launcher {
         let mojang_java_binary_future = self.load_mojang_java_binary(
             &self.meta,
             http_client,
-            &instance_info,
+            &game_configuration,
             &version_info,
             &modal_action.trackers,
             launch_tracker,
@@ -209,6 +218,7 @@ impl Launcher {
             libraries_dir: self.directories.libraries_dir.clone(),
             game_dir: dot_minecraft_path,
             configuration: instance_info,
+            game_configuration,
             assets_root: self.directories.assets_root_dir.clone(),
             temp_dir: self.directories.temp_dir.clone(),
             assets_index_name,
@@ -237,6 +247,7 @@ impl Launcher {
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
+        game_configuration: &GameConfiguration,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         match instance_info.loader {
             Loader::Vanilla => {
@@ -384,7 +395,7 @@ impl Launcher {
                     self.meta.fetch(&ForgeInstallerMavenMetadataItem)
                 ).await?;
 
-                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info,
+                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info, game_configuration,
                     minecraft_versions,
                     loader_versions,
                     "https://maven.minecraftforge.net/net/minecraftforge/forge/{0}/forge-{0}-installer.jar.sha1",
@@ -404,7 +415,7 @@ impl Launcher {
                     self.meta.fetch(&NeoforgeInstallerMavenMetadataItem)
                 ).await?;
 
-                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info,
+                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info, game_configuration,
                     minecraft_versions,
                     loader_versions,
                     "https://maven.neoforged.net/releases/net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar.sha1",
@@ -424,6 +435,7 @@ impl Launcher {
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
+        game_configuration: &GameConfiguration,
         minecraft_versions: Arc<MinecraftVersionManifest>,
         loader_versions: Arc<MavenMetadataXml>,
         installer_hash_url: &'static str,
@@ -477,7 +489,7 @@ impl Launcher {
         let installer_hash_url = installer_hash_url.replace("{0}", &latest_loader_version);
         let (base_version, installer_sha1) = futures::future::join(
             self.meta.fetch(&MinecraftVersionMetadataItem(version_link)),
-            Self::download_sha1(http_client, &installer_hash_url)
+            Self::download_sha1(http_client, &self.downloads, &installer_hash_url)
         ).await;
         let base_version = base_version?;
 
@@ -503,7 +515,7 @@ impl Launcher {
         let mojang_java_binary_future = self.load_mojang_java_binary(
             &self.meta,
             http_client,
-            instance_info,
+            game_configuration,
             &base_version,
             progress_trackers,
             launch_tracker,
@@ -540,7 +552,7 @@ impl Launcher {
 
         // Download mirror list
         let mirror = if check_mirrors {
-            Self::download_random_mirror(http_client, &install_profile.mirror_list).await
+            Self::download_random_mirror(http_client, &self.downloads, &install_profile.mirror_list).await
         } else {
             None
         };
@@ -726,12 +738,8 @@ impl Launcher {
         Ok((Arc::new(version.apply_to(&base_version)), AddVanillaJar::No))
     }
 
-    async fn download_sha1(http_client: &reqwest::Client, url: &str) -> Option<Ustr> {
-        let response = http_client
-            .get(url)
-            .send().await.ok()?;
-
-        let bytes = response.bytes().await.ok()?;
+    async fn download_sha1(http_client: &reqwest::Client, downloads: &DownloadManager, url: &str) -> Option<Ustr> {
+        let bytes = downloads.get_bytes(http_client, url).await.ok()?;
 
         if bytes.len() != 40 {
             return None;
@@ -740,12 +748,8 @@ impl Launcher {
         Some(str::from_utf8(&bytes).ok()?.into())
     }
 
-    async fn download_random_mirror(http_client: &reqwest::Client, url: &str) -> Option<Arc<str>> {
-        let response = http_client
-            .get(url)
-            .send().await.ok()?;
-
-        let bytes = response.bytes().await.ok()?;
+    async fn download_random_mirror(http_client: &reqwest::Client, downloads: &DownloadManager, url: &str) -> Option<Arc<str>> {
+        let bytes = downloads.get_bytes(http_client, url).await.ok()?;
 
         #[derive(Deserialize)]
         struct Mirror {
@@ -763,16 +767,15 @@ impl Launcher {
         &self,
         meta: &MetadataManager,
         http_client: &reqwest::Client,
-        configuration: &InstanceConfiguration,
+        game_configuration: &GameConfiguration,
         version_info: &MinecraftVersion,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
     ) -> Result<PathBuf, LoadJavaRuntimeError> {
-        if let Some(jvm_binary) = &configuration.jvm_binary {
-            if jvm_binary.enabled && let Some(path) = &jvm_binary.path {
-                if let Some(binary) = Self::search_for_java_binary(&path) {
-                    return Ok(binary);
-                }
+        let jvm_binary = &game_configuration.jvm_binary;
+        if jvm_binary.enabled && let Some(path) = &jvm_binary.value.path {
+            if let Some(binary) = Self::search_for_java_binary(&path) {
+                return Ok(binary);
             }
         }
 
@@ -834,7 +837,7 @@ impl Launcher {
         progress_trackers.push(java_runtime_tracker.clone());
         java_runtime_tracker.notify();
 
-        let result = do_java_runtime_load(http_client, runtime_component_dir, fresh_install, runtime, &java_runtime_tracker).await;
+        let result = do_java_runtime_load(http_client, &self.downloads, runtime_component_dir, fresh_install, runtime, &java_runtime_tracker).await;
 
         java_runtime_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         java_runtime_tracker.notify();
@@ -875,7 +878,7 @@ impl Launcher {
             self.directories.assets_objects_dir.clone()
         };
 
-        let result = do_asset_objects_load(http_client, assets_index, assets_dir, &assets_tracker).await;
+        let result = do_asset_objects_load(http_client, &self.downloads, assets_index, assets_dir, &assets_tracker).await;
 
         assets_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         assets_tracker.notify();
@@ -901,7 +904,7 @@ impl Launcher {
         libraries_tracker.notify();
 
         let result =
-            do_libraries_load(http_client, artifacts, self.directories.libraries_dir.clone(), &libraries_tracker).await;
+            do_libraries_load(http_client, &self.downloads, artifacts, self.directories.libraries_dir.clone(), &libraries_tracker).await;
 
         libraries_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         libraries_tracker.notify();
@@ -949,11 +952,7 @@ impl Launcher {
             return Some(expand_logging_argument(client.argument.as_str(), &path));
         }
 
-        let Ok(response) = http_client.get(client.file.url.as_str()).send().await else {
-            eprintln!("Failed to make request to download log configuration");
-            return None;
-        };
-        let Ok(bytes) = response.bytes().await else {
+        let Ok(bytes) = self.downloads.get_bytes(http_client, client.file.url.as_str()).await else {
             eprintln!("Failed to download log configuration");
             return None;
         };
@@ -1104,7 +1103,7 @@ impl Launcher {
         }
     }
 
-    fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
+    pub(crate) fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
         if path.is_file() {
             return Some(path.to_path_buf());
         }
@@ -1314,6 +1313,7 @@ pub enum LoadJavaRuntimeError {
 
 async fn do_java_runtime_load(
     http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
     runtime_component_dir: PathBuf,
     fresh_install: bool,
     runtime: Arc<JavaRuntimeComponentManifest>,
@@ -1321,8 +1321,6 @@ async fn do_java_runtime_load(
 ) -> Result<PathBuf, LoadJavaRuntimeError> {
     let mut links = HashMap::new();
 
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(fresh_install);
 
@@ -1350,7 +1348,6 @@ async fn do_java_runtime_load(
                 total_size += downloads.raw.size;
 
                 let started_downloading = &started_downloading;
-                let download_semaphore = &download_semaphore;
                 let disk_semaphore = &disk_semaphore;
 
                 let task = async move {
@@ -1381,10 +1378,7 @@ async fn do_java_runtime_load(
                         (false, downloads.raw.size as usize, &downloads.raw)
                     };
 
-                    let permit = download_semaphore.acquire().await.unwrap();
-                    let response = http_client.get(download.url.as_str()).send().await?;
-                    let bytes = response.bytes().await?;
-                    drop(permit);
+                    let bytes = download_manager.get_bytes(http_client, download.url.as_str()).await?;
 
                     if bytes.len() != size {
                         return Err(LoadJavaRuntimeError::WrongResponseSize(size, bytes.len()));
@@ -1520,12 +1514,11 @@ pub enum LoadAssetObjectsError {
 
 async fn do_asset_objects_load(
     http_client: &reqwest::Client,
+    downloads: &DownloadManager,
     assets_index: Arc<AssetsIndex>,
     assets_objects_dir: Arc<Path>,
     assets_tracker: &ProgressTracker,
 ) -> Result<(), LoadAssetObjectsError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
 
@@ -1548,7 +1541,6 @@ async fn do_asset_objects_load(
         total_size += asset.size;
 
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
         let disk_semaphore = &disk_semaphore;
 
         let url = format!("https://resources.download.minecraft.net/{}/{}", &asset.hash[..2], &asset.hash);
@@ -1575,10 +1567,7 @@ async fn do_asset_objects_load(
                 assets_tracker.set_title(Arc::from("Downloading game assets"));
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(&url).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            let bytes = Arc::new(downloads.get_bytes(http_client, &url).await?);
 
             if bytes.len() != asset.size as usize {
                 return Err(LoadAssetObjectsError::WrongResponseSize(asset.size as usize, bytes.len()));
@@ -1634,12 +1623,11 @@ pub enum LoadLibrariesError {
 
 async fn do_libraries_load(
     http_client: &reqwest::Client,
+    downloads: &DownloadManager,
     artifacts: &[GameLibraryArtifact],
     libraries_dir: Arc<Path>,
     libraries_tracker: &ProgressTracker,
 ) -> Result<Vec<(Ustr, PathBuf)>, LoadLibrariesError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
 
@@ -1674,7 +1662,6 @@ async fn do_libraries_load(
         total_size += tracker_size;
 
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
         let disk_semaphore = &disk_semaphore;
 
         let task = async move {
@@ -1701,10 +1688,7 @@ async fn do_libraries_load(
                 libraries_tracker.set_title(Arc::from("Downloading game libraries"));
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(artifact.url.as_str()).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            let bytes = Arc::new(downloads.get_bytes(http_client, artifact.url.as_str()).await?);
 
             if let Some(artifact_size) = artifact.size && bytes.len() != artifact_size as usize {
                 return Err(LoadLibrariesError::WrongResponseSize(artifact_size as usize, bytes.len()));
@@ -1966,6 +1950,8 @@ pub struct LaunchContext {
     pub libraries_dir: Arc<Path>,
     pub game_dir: Arc<Path>,
     pub configuration: InstanceConfiguration,
+    /// The instance's settings combined with the launcher-wide defaults
+    pub game_configuration: GameConfiguration,
     pub assets_root: Arc<Path>,
     pub temp_dir: Arc<Path>,
     pub assets_index_name: String,
@@ -2005,18 +1991,26 @@ impl LaunchContext {
             command.arg(log_configuration);
         }
 
-        if let Some(memory) = &self.configuration.memory && memory.enabled {
+        let memory = &self.game_configuration.memory;
+        if memory.enabled {
+            let memory = &memory.value;
             command.arg(format!("-Xms{}m", memory.min));
             command.arg(format!("-Xmx{}m", memory.max.max(memory.min).max(128)));
         }
-        if let Some(jvm_flags) = &self.configuration.jvm_flags && jvm_flags.enabled {
-            if let Ok(split) = shell_words::split(&jvm_flags.flags) {
+        let jvm_flags = &self.game_configuration.jvm_flags;
+        if jvm_flags.enabled {
+            if let Ok(split) = shell_words::split(&jvm_flags.value.flags) {
                 command.args(split);
             } else {
-                command.args(jvm_flags.flags.split_whitespace());
+                command.args(jvm_flags.value.flags.split_whitespace());
             }
         }
 
+        let environment_variables = &self.game_configuration.environment_variables;
+        if environment_variables.enabled {
+            command.envs(environment_variables.value.parse());
+        }
+
         command.arg("com.moulberry.pandora.LaunchWrapper");
 
         let mut child = command.spawn()?;
@@ -1,10 +1,11 @@
 #![deny(unused_must_use)]
 
 mod backend;
-use std::{ffi::OsString, io::Write, path::{Path, PathBuf}};
+use std::{ffi::OsString, io::Write, path::{Path, PathBuf}, sync::Arc};
 
 pub use backend::*;
 use rand::RngCore;
+use rustc_hash::FxHashSet;
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 
@@ -12,21 +13,41 @@ mod backend_filesystem;
 mod backend_handler;
 
 mod account;
-mod arcfactory;
+mod batch_update;
+mod clone_instance;
 mod config;
+mod curseforge;
 mod directories;
+mod dns;
+mod download;
 mod install_content;
 mod instance;
 mod java_manifest;
+mod jvm_recommendation;
 mod launch;
 mod launch_wrapper;
 mod lockfile;
+mod log_parser;
 mod log_reader;
 mod metadata;
+mod missing_dependencies;
 mod mod_metadata;
+mod modpack_update;
+mod modrinth_collection;
+mod nbt_file;
+mod network;
+mod packs;
+mod paste;
 mod id_slab;
 mod persistent;
+mod server_ping;
 mod syncing;
+#[cfg(test)]
+mod test_server;
+mod update_preferences;
+mod version_change;
+mod world_details;
+mod world_prune;
 
 pub(crate) fn is_single_component_path(path: &str) -> bool {
     let path = std::path::Path::new(path);
@@ -87,6 +108,16 @@ pub(crate) fn write_safe(path: &Path, content: &[u8]) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Returns the shared copy of `value`, so that repeated strings like logger and thread names are only allocated once
+pub(crate) fn intern(interned: &mut FxHashSet<Arc<str>>, value: &str) -> Arc<str> {
+    if let Some(existing) = interned.get(value) {
+        return existing.clone();
+    }
+    let value: Arc<str> = value.into();
+    interned.insert(value.clone());
+    value
+}
+
 pub(crate) fn child_state_path(path: &Path) -> Option<PathBuf> {
     let mut new_path = path.to_path_buf();
 
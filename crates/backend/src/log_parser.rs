@@ -0,0 +1,262 @@
+use std::{path::Path, sync::Arc, time::SystemTime};
+
+use bridge::game_output::{GameOutputEntry, GameOutputLogLevel};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use rustc_hash::FxHashSet;
+
+/// Parses log4j pattern lines from log files and crash reports, folding continuation
+/// lines such as stack traces into the entry they belong to
+pub struct LogFileParser {
+    date: NaiveDate,
+    last_time: i64,
+    pending: Option<PendingEntry>,
+    interned: FxHashSet<Arc<str>>,
+}
+
+struct PendingEntry {
+    time: i64,
+    level: GameOutputLogLevel,
+    logger: Option<Arc<str>>,
+    thread: Option<Arc<str>>,
+    lines: Vec<Arc<str>>,
+    parsed: bool,
+}
+
+struct ParsedLine<'a> {
+    time: i64,
+    thread: &'a str,
+    level: GameOutputLogLevel,
+    logger: Option<&'a str>,
+    message: &'a str,
+}
+
+impl LogFileParser {
+    pub fn for_path(path: &Path) -> Self {
+        // Rotated logs and crash reports contain their date in the file name, e.g. 2024-01-12-1.log.gz
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let file_name = file_name.strip_prefix("crash-").unwrap_or(file_name);
+        let date = file_name.get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .or_else(|| {
+                let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+                Some(DateTime::<Local>::from(modified).date_naive())
+            })
+            .unwrap_or_else(|| DateTime::<Local>::from(SystemTime::now()).date_naive());
+
+        let last_time = local_millis(date, NaiveTime::MIN).unwrap_or_default();
+
+        Self {
+            date,
+            last_time,
+            pending: None,
+            interned: FxHashSet::default(),
+        }
+    }
+
+    /// Adds a line, returning the previous entry if this line started a new one
+    pub fn push_line(&mut self, line: &str) -> Option<GameOutputEntry> {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return None;
+        }
+
+        if let Some(parsed) = self.parse_line(line) {
+            let finished = self.flush();
+            self.last_time = parsed.time;
+            self.pending = Some(PendingEntry {
+                time: parsed.time,
+                level: parsed.level,
+                logger: parsed.logger.map(|logger| crate::intern(&mut self.interned, logger)),
+                thread: Some(crate::intern(&mut self.interned, parsed.thread)),
+                lines: vec![parsed.message.into()],
+                parsed: true,
+            });
+            return finished;
+        }
+
+        let continuation = line.starts_with([' ', '\t']) || line.starts_with("Caused by: ") || line.starts_with("Suppressed: ");
+        if let Some(pending) = &mut self.pending && (pending.parsed || continuation) {
+            pending.lines.push(line.into());
+            return None;
+        }
+
+        let finished = self.flush();
+        self.pending = Some(PendingEntry {
+            time: self.last_time,
+            level: GameOutputLogLevel::Other,
+            logger: None,
+            thread: None,
+            lines: vec![line.into()],
+            parsed: false,
+        });
+        finished
+    }
+
+    pub fn flush(&mut self) -> Option<GameOutputEntry> {
+        let pending = self.pending.take()?;
+        Some(GameOutputEntry {
+            time: pending.time,
+            level: pending.level,
+            logger: pending.logger,
+            thread: pending.thread,
+            text: pending.lines.into(),
+        })
+    }
+
+    fn parse_line<'a>(&self, line: &'a str) -> Option<ParsedLine<'a>> {
+        let rest = line.strip_prefix('[')?;
+        let (time, rest) = rest.split_once("] [")?;
+        let time = self.parse_time(time)?;
+
+        let (thread_and_level, rest) = rest.split_once(']')?;
+        let (thread, level) = thread_and_level.rsplit_once('/')?;
+        let level = GameOutputLogLevel::from_name(level)?;
+
+        // Forge puts the logger in brackets, Fabric in parentheses
+        let (logger, rest) = if let Some(rest) = rest.strip_prefix(" [") {
+            let (logger, rest) = rest.split_once(']')?;
+            (Some(logger), rest)
+        } else if let Some(rest) = rest.strip_prefix(" (") {
+            let (logger, rest) = rest.split_once(')')?;
+            (Some(logger), rest)
+        } else {
+            (None, rest)
+        };
+
+        let message = rest.strip_prefix(':').unwrap_or(rest);
+        let message = message.strip_prefix(' ').unwrap_or(message);
+
+        Some(ParsedLine { time, thread, level, logger, message })
+    }
+
+    fn parse_time(&self, time: &str) -> Option<i64> {
+        // Forge includes the date, e.g. 12Jan2024 12:34:56.789
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(time, "%d%b%Y %H:%M:%S%.f") {
+            return local_millis(date_time.date(), date_time.time());
+        }
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S%.f").ok()?;
+        local_millis(self.date, time)
+    }
+}
+
+pub fn error_entry(message: &str) -> GameOutputEntry {
+    GameOutputEntry {
+        time: Utc::now().timestamp_millis(),
+        level: GameOutputLogLevel::Fatal,
+        logger: None,
+        thread: None,
+        text: message.split('\n').map(|line| Arc::from(line.trim_ascii_end())).collect(),
+    }
+}
+
+fn local_millis(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    Local.from_local_datetime(&date.and_time(time)).earliest().map(|date_time| date_time.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> LogFileParser {
+        LogFileParser::for_path(Path::new("2024-01-12-1.log.gz"))
+    }
+
+    fn text(entry: &GameOutputEntry) -> Vec<&str> {
+        entry.text.iter().map(|line| &**line).collect()
+    }
+
+    #[test]
+    fn test_vanilla_header() {
+        let mut parser = parser();
+        assert!(parser.push_line("[12:34:56] [Render thread/INFO]: Setting user: Player\n").is_none());
+        let entry = parser.flush().unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Info);
+        assert_eq!(entry.thread.as_deref(), Some("Render thread"));
+        assert_eq!(entry.logger, None);
+        assert_eq!(text(&entry), ["Setting user: Player"]);
+        assert_eq!(entry.time, local_millis(NaiveDate::from_ymd_opt(2024, 1, 12).unwrap(), NaiveTime::from_hms_opt(12, 34, 56).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_fabric_header() {
+        let mut parser = parser();
+        parser.push_line("[12:34:56] [main/INFO] (FabricLoader/GameProvider) Loading Minecraft 1.21.1 with Fabric Loader 0.16.5");
+        let entry = parser.flush().unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Info);
+        assert_eq!(entry.logger.as_deref(), Some("FabricLoader/GameProvider"));
+        assert_eq!(text(&entry), ["Loading Minecraft 1.21.1 with Fabric Loader 0.16.5"]);
+
+        parser.push_line("[12:34:56.789] [main/WARN] (FabricLoader/Mixin): Reference map not found");
+        let entry = parser.flush().unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Warn);
+        assert_eq!(text(&entry), ["Reference map not found"]);
+    }
+
+    #[test]
+    fn test_forge_header() {
+        let mut parser = parser();
+        parser.push_line("[03Feb2024 08:15:30.250] [main/ERROR] [net.minecraftforge.fml.loading.ModSorter/LOADING]: Missing mods");
+        let entry = parser.flush().unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Error);
+        assert_eq!(entry.thread.as_deref(), Some("main"));
+        assert_eq!(entry.logger.as_deref(), Some("net.minecraftforge.fml.loading.ModSorter/LOADING"));
+        assert_eq!(text(&entry), ["Missing mods"]);
+        // The date comes from the line instead of the file name
+        let expected = local_millis(NaiveDate::from_ymd_opt(2024, 2, 3).unwrap(), NaiveTime::from_hms_milli_opt(8, 15, 30, 250).unwrap());
+        assert_eq!(entry.time, expected.unwrap());
+    }
+
+    #[test]
+    fn test_multi_line_entry() {
+        let mut parser = parser();
+        assert!(parser.push_line("[12:00:00] [Server thread/ERROR]: Encountered an unexpected exception").is_none());
+        assert!(parser.push_line("java.lang.NullPointerException: null").is_none());
+        assert!(parser.push_line("\tat net.minecraft.server.Main.run(Main.java:12)").is_none());
+        assert!(parser.push_line("Caused by: java.io.IOException").is_none());
+        assert!(parser.push_line("").is_none());
+
+        let entry = parser.push_line("[12:00:01] [Server thread/INFO]: Stopping server").unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Error);
+        assert_eq!(text(&entry), [
+            "Encountered an unexpected exception",
+            "java.lang.NullPointerException: null",
+            "\tat net.minecraft.server.Main.run(Main.java:12)",
+            "Caused by: java.io.IOException",
+        ]);
+
+        let entry = parser.flush().unwrap();
+        assert_eq!(text(&entry), ["Stopping server"]);
+        assert!(parser.flush().is_none());
+    }
+
+    #[test]
+    fn test_unparsed_lines() {
+        let mut parser = parser();
+        parser.push_line("[12:00:00] [main/INFO]: Started");
+        let started = parser.flush().unwrap();
+
+        // Lines without a header, like crash report sections, start their own entry unless they are a continuation
+        assert!(parser.push_line("---- Minecraft Crash Report ----").is_none());
+        assert!(parser.push_line("\tTime: 2024-01-12").is_none());
+        let entry = parser.push_line("Description: Ticking entity").unwrap();
+        assert_eq!(entry.level, GameOutputLogLevel::Other);
+        assert_eq!(entry.time, started.time);
+        assert_eq!(text(&entry), ["---- Minecraft Crash Report ----", "\tTime: 2024-01-12"]);
+        assert_eq!(text(&parser.flush().unwrap()), ["Description: Ticking entity"]);
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        let parser = parser();
+        assert!(parser.parse_line("[12:00:00] [main/VERBOSE]: Unknown level").is_none());
+        assert!(parser.parse_line("[not a time] [main/INFO]: Message").is_none());
+        assert!(parser.parse_line("[12:00:00] main/INFO: Message").is_none());
+        assert!(parser.parse_line("12:00:00 [main/INFO]: Message").is_none());
+    }
+
+    #[test]
+    fn test_file_date() {
+        let parser = LogFileParser::for_path(Path::new("crash-2023-12-31_23.59.59-client.txt"));
+        assert_eq!(parser.date, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+    }
+}
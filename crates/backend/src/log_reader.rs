@@ -11,7 +11,10 @@ use bridge::{
 use chrono::Utc;
 use memchr::memchr;
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use regex::Regex;
+use rustc_hash::FxHashSet;
+use schema::redaction::{RedactionConfiguration, RedactionRule};
 use thiserror::Error;
 
 static GAME_OUTPUT_ID: AtomicUsize = AtomicUsize::new(0);
@@ -29,16 +32,73 @@ static REPLACEMENTS: Lazy<[(Regex, &'static str); 7]> = Lazy::new(|| {
     ]
 });
 
+static USER_REPLACEMENTS: Lazy<RwLock<Arc<[(Regex, Arc<str>)]>>> = Lazy::new(|| RwLock::new(Arc::from([])));
+
+#[derive(Error, Debug)]
+#[error("Invalid pattern in rule {index}:\n{error}")]
+pub struct RedactionRuleError {
+    index: usize,
+    error: regex::Error,
+}
+
 pub fn replace(string: &str) -> Cow<'_, str> {
+    let user_replacements = USER_REPLACEMENTS.read().clone();
+    replace_with(string, &user_replacements)
+}
+
+fn replace_with<'a>(string: &'a str, user_replacements: &[(Regex, Arc<str>)]) -> Cow<'a, str> {
     let mut replaced = Cow::Borrowed(string);
     for (regex, replacement) in &*REPLACEMENTS {
         if let Cow::Owned(new) = regex.replace_all(&replaced, *replacement) {
             replaced = Cow::Owned(new);
         }
     }
+    for (regex, replacement) in user_replacements {
+        if let Cow::Owned(new) = regex.replace_all(&replaced, &**replacement) {
+            replaced = Cow::Owned(new);
+        }
+    }
     replaced
 }
 
+fn compile_redaction_rule(index: usize, rule: &RedactionRule) -> Result<(Regex, Arc<str>), RedactionRuleError> {
+    match Regex::new(&rule.pattern) {
+        Ok(regex) => Ok((regex, rule.replacement.clone())),
+        Err(error) => Err(RedactionRuleError { index: index + 1, error }),
+    }
+}
+
+fn compile_redaction_rules(rules: &[RedactionRule]) -> Result<Vec<(Regex, Arc<str>)>, RedactionRuleError> {
+    rules.iter().enumerate()
+        .filter(|(_, rule)| rule.enabled)
+        .map(|(index, rule)| compile_redaction_rule(index, rule))
+        .collect()
+}
+
+/// Applies the rules to all logs from now on. Invalid rules are skipped so the valid ones still apply,
+/// the errors for the skipped rules are returned so they can be reported
+pub fn set_redaction_rules(configuration: &RedactionConfiguration) -> Vec<RedactionRuleError> {
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for (index, rule) in configuration.rules.iter().enumerate().filter(|(_, rule)| rule.enabled) {
+        match compile_redaction_rule(index, rule) {
+            Ok(replacement) => compiled.push(replacement),
+            Err(error) => {
+                eprintln!("Skipping redaction rule: {error}");
+                errors.push(error);
+            },
+        }
+    }
+    *USER_REPLACEMENTS.write() = compiled.into();
+    errors
+}
+
+/// Applies the built-in replacements and the given rules to the sample, without saving the rules
+pub fn test_redaction_rules(rules: &[RedactionRule], sample: &str) -> Result<String, RedactionRuleError> {
+    let compiled = compile_redaction_rules(rules)?;
+    Ok(replace_with(sample, &compiled).into_owned())
+}
+
 pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sender: FrontendHandle) {
     let id = GAME_OUTPUT_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     let keep_alive = KeepAlive::new();
@@ -65,6 +125,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
                             id,
                             time: Utc::now().timestamp_millis(),
                             level: GameOutputLogLevel::Error,
+                            logger: None,
+                            thread: None,
                             text: Arc::new([replaced.trim_end().into()]),
                         });
                         raw_text.clear();
@@ -80,7 +142,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
             stack: Vec::new(),
             id,
             sender: sender.clone(),
-            empty_message: "<empty>".into()
+            empty_message: "<empty>".into(),
+            interned: FxHashSet::default(),
         };
         let mut log_input = LogInput {
             buffer: Vec::new(),
@@ -107,6 +170,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
                         id,
                         time: Utc::now().timestamp_millis(),
                         level: GameOutputLogLevel::Fatal,
+                        logger: None,
+                        thread: None,
                         text: Arc::new([format!("(Pandora) There was an error while reading the log: {panic_error_str}").into()]),
                     });
                     return;
@@ -121,6 +186,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
                 id,
                 time: Utc::now().timestamp_millis(),
                 level: GameOutputLogLevel::Fatal,
+                logger: None,
+                thread: None,
                 text: Arc::new([format!("(Pandora) There was an error while reading the log: {error}").into()]),
             });
         }
@@ -148,6 +215,7 @@ struct LogReader {
     id: usize,
     sender: FrontendHandle,
     empty_message: Arc<str>,
+    interned: FxHashSet<Arc<str>>,
 }
 
 struct LogInput {
@@ -160,6 +228,8 @@ enum LogOutputState {
     Event {
         timestamp: Option<i64>,
         level: Option<GameOutputLogLevel>,
+        logger: Option<Arc<str>>,
+        thread: Option<Arc<str>>,
         text: Option<Arc<str>>,
         throwable: Option<Arc<str>>,
     },
@@ -809,6 +879,8 @@ impl LogReader {
                     self.stack.push(LogOutputState::Event {
                         timestamp: None,
                         level: None,
+                        logger: None,
+                        thread: None,
                         text: None,
                         throwable: None
                     });
@@ -848,7 +920,7 @@ impl LogReader {
                     return Err(HandleOutputError::UnmatchedElement(str::from_utf8(name)?.into()));
                 }
 
-                let Some(LogOutputState::Event { timestamp, level, mut text, mut throwable }) = self.stack.pop() else {
+                let Some(LogOutputState::Event { timestamp, level, logger, thread, mut text, mut throwable }) = self.stack.pop() else {
                     unreachable!()
                 };
                 let mut lines = Vec::new();
@@ -908,6 +980,8 @@ impl LogReader {
                     id: self.id,
                     time: timestamp.unwrap_or(Utc::now().timestamp_millis()),
                     level: level.unwrap_or(GameOutputLogLevel::Other),
+                    logger,
+                    thread,
                     text: final_lines,
                 });
             },
@@ -953,10 +1027,10 @@ impl LogReader {
 
     fn apply_attribute_key_value(&mut self, key: NamedAttributeKey, value: &[u8]) {
         match self.stack.last_mut() {
-            Some(LogOutputState::Event { timestamp, level, .. }) => {
+            Some(LogOutputState::Event { timestamp, level, logger, thread, .. }) => {
                 match key {
                     NamedAttributeKey::Logger => {
-                        // Ignore
+                        *logger = Some(crate::intern(&mut self.interned, &String::from_utf8_lossy(value)));
                     },
                     NamedAttributeKey::Timestamp => {
                         let Ok(value) = str::from_utf8(&value) else {
@@ -978,7 +1052,7 @@ impl LogReader {
                         });
                     },
                     NamedAttributeKey::Thread => {
-                        // Ignore
+                        *thread = Some(crate::intern(&mut self.interned, &String::from_utf8_lossy(value)));
                     }
                     _ => {
                         if cfg!(debug_assertions) {
@@ -1057,7 +1131,9 @@ impl LogReader {
             id: self.id,
             time: Utc::now().timestamp_millis(),
             level: GameOutputLogLevel::Info,
-            text: Arc::new([line.into()]),
+            logger: None,
+            thread: None,
+            text: Arc::new([replace(line).into()]),
         });
 
         Ok(())
@@ -1067,3 +1143,74 @@ impl LogReader {
 fn is_xml_whitespace(byte: u8) -> bool {
     matches!(byte, b'\r' | b'\n' | b'\t' | b' ')
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replacement: &str) -> RedactionRule {
+        RedactionRule {
+            enabled: true,
+            pattern: pattern.into(),
+            replacement: replacement.into(),
+        }
+    }
+
+    #[test]
+    fn test_valid_rules() {
+        let rules = [
+            rule(r"\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}", "*.*.*.*"),
+            rule(r"server=(\w+)", "server=$1-hidden"),
+        ];
+        let result = test_redaction_rules(&rules, "Connecting to 192.168.0.12, server=lobby").unwrap();
+        assert_eq!(result, "Connecting to *.*.*.*, server=lobby-hidden");
+
+        // Built-in replacements apply before the rules
+        let result = test_redaction_rules(&rules, "Loading /home/alice/.minecraft with --accessToken, abc123").unwrap();
+        assert_eq!(result, "Loading /home/*****/.minecraft with --accessToken, *****");
+    }
+
+    #[test]
+    fn test_disabled_rule() {
+        let mut disabled = rule("(", "");
+        disabled.enabled = false;
+        let rules = [disabled, rule("secret", "*****")];
+        assert_eq!(test_redaction_rules(&rules, "a secret").unwrap(), "a *****");
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let rules = [rule("valid", "ok"), rule("[unclosed", "")];
+        let error = test_redaction_rules(&rules, "valid").unwrap_err();
+        assert_eq!(error.index, 2);
+        assert!(compile_redaction_rules(&rules).is_err());
+    }
+
+    #[test]
+    fn test_overlapping_rules() {
+        // Rules apply in order, later rules see the output of earlier ones
+        let rules = [rule(r"token-\d+", "token-***"), rule("token", "[hidden]")];
+        assert_eq!(test_redaction_rules(&rules, "got token-1234").unwrap(), "got [hidden]-***");
+
+        let rules = [rule("token", "[hidden]"), rule(r"token-\d+", "token-***")];
+        assert_eq!(test_redaction_rules(&rules, "got token-1234").unwrap(), "got [hidden]-1234");
+
+        // Matches of a single rule don't overlap each other
+        let rules = [rule("aa", "b")];
+        assert_eq!(test_redaction_rules(&rules, "aaa").unwrap(), "ba");
+    }
+
+    #[test]
+    fn test_set_rules_skips_invalid() {
+        let configuration = RedactionConfiguration {
+            rules: vec![rule("[unclosed", ""), rule("hunter2", "*******")],
+        };
+        let errors = set_redaction_rules(&configuration);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].index, 1);
+        assert_eq!(replace("password is hunter2"), "password is *******");
+
+        set_redaction_rules(&RedactionConfiguration::default());
+        assert_eq!(replace("password is hunter2"), "password is hunter2");
+    }
+}
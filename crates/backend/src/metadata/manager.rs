@@ -5,14 +5,14 @@ use std::{
 use bridge::keep_alive::{KeepAlive, KeepAliveHandle};
 use reqwest::StatusCode;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::FabricLoaderManifest, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::JavaRuntimes, maven::MavenMetadataXml, modrinth::{ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, version::MinecraftVersion, version_manifest::MinecraftVersionManifest
+    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::FabricLoaderManifest, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::JavaRuntimes, maven::MavenMetadataXml, modrinth::{ModrinthCollection, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthProjectsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, version::MinecraftVersion, version_manifest::MinecraftVersionManifest
 };
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 use tokio::task::JoinHandle;
 use ustr::Ustr;
 
-use crate::metadata::items::MetadataItem;
+use crate::{download::DownloadManager, metadata::items::MetadataItem};
 
 const DATA_TTL: Duration = Duration::from_secs(5 * 60);
 
@@ -33,6 +33,8 @@ pub struct MetadataManagerStates {
     pub(super) modrinth_project_versions: HashMap<ModrinthProjectVersionsRequest, MetaLoadStateWrapper<ModrinthProjectVersionsResult>>,
     pub(super) modrinth_versions: HashMap<Arc<str>, MetaLoadStateWrapper<ModrinthProjectVersion>>,
     pub(super) modrinth_version_updates: HashMap<Arc<str>, MetaLoadStateWrapper<ModrinthVersionFileUpdateResult>>,
+    pub(super) modrinth_collections: HashMap<Arc<str>, MetaLoadStateWrapper<ModrinthCollection>>,
+    pub(super) modrinth_projects: HashMap<Arc<[Arc<str>]>, MetaLoadStateWrapper<ModrinthProjectsResult>>,
 }
 
 pub struct MetadataManager {
@@ -47,7 +49,7 @@ pub struct MetadataManager {
 
     expiring: tokio::sync::Mutex<VecDeque<(Instant, KeepAlive)>>,
 
-    http_client: reqwest::Client,
+    downloads: Arc<DownloadManager>,
 }
 
 #[derive(thiserror::Error, Clone, Debug)]
@@ -145,7 +147,7 @@ pub enum MetaLoadState<T> {
 }
 
 impl MetadataManager {
-    pub fn new(http_client: reqwest::Client, directory: Arc<Path>) -> Self {
+    pub fn new(downloads: Arc<DownloadManager>, directory: Arc<Path>) -> Self {
         Self {
             states: tokio::sync::Mutex::new(MetadataManagerStates::default()),
 
@@ -158,7 +160,7 @@ impl MetadataManager {
 
             expiring: Default::default(),
 
-            http_client,
+            downloads,
         }
     }
 
@@ -193,7 +195,8 @@ impl MetadataManager {
                 &mut wrapper.1,
                 item,
                 cache_file,
-                &self.http_client,
+                &self.downloads.http_client(),
+                &self.downloads,
             );
         }
     }
@@ -220,7 +223,8 @@ impl MetadataManager {
                 &mut wrapper.1,
                 item,
                 cache_file,
-                &self.http_client,
+                &self.downloads.http_client(),
+                &self.downloads,
             );
         }
 
@@ -255,8 +259,10 @@ impl MetadataManager {
         item: &I,
         cache_file: Option<impl AsRef<Path> + Send + Sync + 'static>,
         http_client: &reqwest::Client,
+        downloads: &Arc<DownloadManager>,
     ) {
-        let request = item.request(http_client);
+        let (client, request) = item.request(http_client).timeout(std::time::Duration::from_secs(5)).build_split();
+        let downloads = Arc::clone(downloads);
         let expected_hash = item.data_hash().and_then(|sha1| {
             let mut expected_hash = [0u8; 20];
             hex::decode_to_slice(sha1.as_str(), &mut expected_hash).ok()?;
@@ -308,7 +314,7 @@ impl MetadataManager {
             }
 
             let mut result: Result<Arc<I::T>, MetaLoadError> = async move {
-                let response = request.timeout(std::time::Duration::from_secs(5)).send().await?;
+                let response = downloads.send(&client, request?).await?;
 
                 let status = response.status();
                 if status != StatusCode::OK {
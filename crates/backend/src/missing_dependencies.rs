@@ -0,0 +1,101 @@
+use bridge::{
+    dependencies::{check_dependencies, DependencyIssueKind},
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget},
+    instance::InstanceID,
+    modal_action::ModalAction,
+};
+use rustc_hash::FxHashSet;
+use schema::{content::ContentSource, modrinth::{ModrinthDependencyType, ModrinthLoader}};
+
+use crate::BackendState;
+
+impl BackendState {
+    /// Installs the Modrinth projects required by mods with missing dependencies. Loader mod ids don't necessarily
+    /// match Modrinth project ids, so the projects are taken from the Modrinth versions of the installed jars instead
+    pub async fn install_missing_dependencies(&self, id: InstanceID, modal_action: &ModalAction) {
+        let Some(mods) = self.clone().load_instance_mods(id).await else {
+            modal_action.set_error_message("Unable to install dependencies, unknown instance".into());
+            return;
+        };
+
+        let (loader, minecraft_version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            (configuration.loader, configuration.minecraft_version)
+        } else {
+            modal_action.set_error_message("Unable to install dependencies, unknown instance".into());
+            return;
+        };
+
+        let modrinth_loader = loader.as_modrinth_loader();
+        if modrinth_loader == ModrinthLoader::Unknown {
+            modal_action.set_error_message("Unable to install dependencies, unsupported loader".into());
+            return;
+        }
+
+        let issues = check_dependencies(&mods, minecraft_version.as_str());
+        let missing_for: FxHashSet<&str> = issues.iter()
+            .filter(|issue| matches!(issue.kind, DependencyIssueKind::Missing { .. }))
+            .map(|issue| &*issue.mod_name)
+            .collect();
+        if missing_for.is_empty() {
+            return;
+        }
+
+        let lookups = futures::future::join_all(mods.iter().filter(|summary| summary.enabled).map(|summary| async move {
+            (summary, self.modrinth_update_version(&summary.mod_summary, modrinth_loader, minecraft_version).await)
+        })).await;
+
+        let mut installed_projects = FxHashSet::default();
+        let mut required = Vec::new();
+        for (summary, lookup) in &lookups {
+            let name = &*summary.mod_summary.name;
+            match lookup {
+                Ok(Some(version)) => {
+                    installed_projects.insert(version.0.project_id.clone());
+                    if !missing_for.contains(name) {
+                        continue;
+                    }
+                    for dependency in version.0.dependencies.iter().flatten() {
+                        if dependency.dependency_type == ModrinthDependencyType::Required && let Some(project_id) = &dependency.project_id
+                            && !required.contains(project_id)
+                        {
+                            required.push(project_id.clone());
+                        }
+                    }
+                },
+                // Each failure is reported on its own so the dependencies of other mods can still be installed
+                Ok(None) if missing_for.contains(name) => {
+                    self.send.send_warning(format!("Unable to find the dependencies of {name}, it isn't available on Modrinth for this version"));
+                },
+                Err(error) if missing_for.contains(name) => {
+                    self.send.send_warning(format!("Unable to find the dependencies of {name}: {error}"));
+                },
+                _ => {},
+            }
+        }
+        required.retain(|project_id| !installed_projects.contains(project_id));
+
+        if required.is_empty() {
+            modal_action.set_error_message("None of the missing dependencies could be found on Modrinth".into());
+            return;
+        }
+
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(id),
+            loader_hint: loader,
+            version_hint: Some(minecraft_version.into()),
+            files: required.into_iter().map(|project_id| ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Automatic,
+                download: ContentDownload::Modrinth {
+                    project_id,
+                    version_id: None,
+                },
+                content_source: ContentSource::Modrinth,
+            }).collect(),
+        };
+
+        // install_content reports its own errors to the modal
+        self.install_content(content_install, modal_action.clone()).await;
+    }
+}
@@ -2,14 +2,14 @@ use std::{
     collections::HashMap, io::{Cursor, Write}, path::{Path, PathBuf}, sync::Arc
 };
 
-use bridge::{instance::{AtomicContentUpdateStatus, ContentUpdateStatus, LoaderSpecificModSummary, ModSummary}, safe_path::SafePath};
+use bridge::{instance::{AtomicContentUpdateStatus, ContentUpdateStatus, LoaderSpecificModSummary, ModDependency, ModDependencyKind, ModSummary, ModVersionRequirement, ProvidedMod}, safe_path::SafePath};
 use image::imageops::FilterType;
 use indexmap::IndexMap;
 use parking_lot::{RwLock, RwLockReadGuard};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use rc_zip_sync::EntryHandle;
+use rc_zip_sync::{EntryHandle, ReadZip};
 use rustc_hash::FxHashMap;
-use schema::{content::ContentSource, fabric_mod::{FabricModJson, Icon, Person}, forge_mod::{JarJarMetadata, ModsToml}, modification::ModrinthModpackFileDownload, modrinth::{ModrinthFile, ModrinthSideRequirement}, mrpack::ModrinthIndexJson};
+use schema::{content::ContentSource, curseforge::CurseForgeFile, fabric_mod::{FabricModJson, Icon, Person}, forge_mod::{JarJarMetadata, ModsToml}, modification::ModrinthModpackFileDownload, modrinth::{ModrinthFile, ModrinthSideRequirement}, mrpack::ModrinthIndexJson};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeAs, SerializeAs};
 use sha1::{Digest, Sha1};
@@ -20,7 +20,9 @@ pub enum ModUpdateAction {
     ErrorInvalidHash,
     AlreadyUpToDate,
     ManualInstall,
+    Pinned,
     Modrinth(ModrinthFile),
+    CurseForge(CurseForgeFile),
 }
 
 impl ModUpdateAction {
@@ -30,7 +32,9 @@ impl ModUpdateAction {
             ModUpdateAction::ErrorInvalidHash => ContentUpdateStatus::ErrorInvalidHash,
             ModUpdateAction::AlreadyUpToDate => ContentUpdateStatus::AlreadyUpToDate,
             ModUpdateAction::ManualInstall => ContentUpdateStatus::ManualInstall,
+            ModUpdateAction::Pinned => ContentUpdateStatus::Pinned,
             ModUpdateAction::Modrinth(_) => ContentUpdateStatus::Modrinth,
+            ModUpdateAction::CurseForge(_) => ContentUpdateStatus::CurseForge,
         }
     }
 }
@@ -201,6 +205,27 @@ impl ModMetadataManager {
         lowercase_search_key.push_str("$$");
         lowercase_search_key.push_str(&name.to_lowercase());
 
+        let mut provides = Vec::new();
+        add_fabric_provides(&mut provides, &fabric_mod_json.id, &fabric_mod_json.version, &fabric_mod_json.provides);
+        for jar in &fabric_mod_json.jars {
+            add_nested_fabric_provides(&mut provides, archive, &jar.file, 0);
+        }
+
+        let mut dependencies = Vec::new();
+        for (kind, map) in [
+            (ModDependencyKind::Required, fabric_mod_json.depends),
+            (ModDependencyKind::Optional, fabric_mod_json.recommends),
+            (ModDependencyKind::Incompatible, fabric_mod_json.breaks),
+        ] {
+            for (id, versions) in map {
+                dependencies.push(ModDependency {
+                    id,
+                    kind,
+                    requirement: ModVersionRequirement::Fabric(versions.into_vec().into()),
+                });
+            }
+        }
+
         Some(Arc::new(ModSummary {
             id: fabric_mod_json.id,
             hash,
@@ -210,6 +235,8 @@ impl ModMetadataManager {
             version_str: format!("v{}", fabric_mod_json.version).into(),
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: provides.into(),
+            dependencies: dependencies.into(),
             extra: LoaderSpecificModSummary::Fabric
         }))
     }
@@ -244,6 +271,7 @@ impl ModMetadataManager {
         lowercase_search_key.push_str("$$");
         lowercase_search_key.push_str(&name.to_lowercase());
 
+        let mut jar_version = None;
         let mut version = format!("v{}", first.version.as_deref().unwrap_or("1"));
         if version.contains("${file.jarVersion}") {
             if let Some(manifest) = archive.by_name("META-INF/MANIFEST.MF") {
@@ -252,12 +280,51 @@ impl ModMetadataManager {
                         let manifest_map = crate::java_manifest::parse_java_manifest(manifest_str);
                         if let Some(impl_version) = manifest_map.get("Implementation-Version") {
                             version = version.replace("${file.jarVersion}", impl_version);
+                            jar_version = Some(Arc::<str>::from(impl_version.as_str()));
                         }
                     }
                 }
             }
         }
 
+        let mut provides = Vec::new();
+        let mut dependencies = Vec::new();
+        for forge_mod in &mods_toml.mods {
+            let version = match &forge_mod.version {
+                Some(version) if version.contains("${file.jarVersion}") => jar_version.clone(),
+                Some(version) if version.contains("${") => None,
+                version => version.clone(),
+            };
+            provides.push(ProvidedMod {
+                id: forge_mod.mod_id.clone(),
+                version,
+            });
+
+            for dependency in mods_toml.dependencies.get(&forge_mod.mod_id).into_iter().flatten() {
+                if dependency.side.as_deref().is_some_and(|side| side.eq_ignore_ascii_case("server")) {
+                    continue;
+                }
+
+                let kind = match dependency.dependency_type.as_deref().map(str::to_ascii_lowercase).as_deref() {
+                    Some("required") => ModDependencyKind::Required,
+                    Some("optional") => ModDependencyKind::Optional,
+                    Some("incompatible") => ModDependencyKind::Incompatible,
+                    Some(_) => continue,
+                    None if dependency.mandatory == Some(true) => ModDependencyKind::Required,
+                    None => ModDependencyKind::Optional,
+                };
+
+                dependencies.push(ModDependency {
+                    id: dependency.mod_id.clone(),
+                    kind,
+                    requirement: match &dependency.version_range {
+                        Some(range) if !range.trim_ascii().is_empty() => ModVersionRequirement::Maven(range.clone()),
+                        _ => ModVersionRequirement::Any,
+                    },
+                });
+            }
+        }
+
         Some(Arc::new(ModSummary {
             id: first.mod_id.clone(),
             hash,
@@ -267,6 +334,8 @@ impl ModMetadataManager {
             version_str: version.into(),
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: provides.into(),
+            dependencies: dependencies.into(),
             extra: LoaderSpecificModSummary::Forge
         }))
     }
@@ -369,6 +438,8 @@ impl ModMetadataManager {
             version_str: format!("v{}", modrinth_index_json.version_id).into(),
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: Arc::from([]),
+            dependencies: Arc::from([]),
             extra: LoaderSpecificModSummary::ModrinthModpack {
                 downloads: modrinth_index_json.files,
                 summaries: summaries.into(),
@@ -443,16 +514,70 @@ impl ModMetadataManager {
             version_str: version.unwrap_or_default(),
             png_icon: None,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: Arc::from([]),
+            dependencies: Arc::from([]),
             extra: LoaderSpecificModSummary::JavaModule
         }))
     }
 }
 
+fn add_fabric_provides(provides: &mut Vec<ProvidedMod>, id: &Arc<str>, version: &Arc<str>, aliases: &[Arc<str>]) {
+    for id in std::iter::once(id).chain(aliases) {
+        provides.push(ProvidedMod {
+            id: id.clone(),
+            version: Some(version.clone()),
+        });
+    }
+}
+
+// Jar-in-jar mods (e.g. the modules of Fabric API) satisfy dependencies too
+fn add_nested_fabric_provides<R: rc_zip_sync::HasCursor>(provides: &mut Vec<ProvidedMod>, archive: &rc_zip_sync::ArchiveHandle<R>, path: &str, depth: usize) {
+    const MAX_DEPTH: usize = 4;
+    if depth >= MAX_DEPTH {
+        return;
+    }
+
+    let Some(nested) = archive.by_name(path) else {
+        return;
+    };
+    let Ok(nested_bytes) = nested.bytes() else {
+        return;
+    };
+    let nested_bytes: &[u8] = &nested_bytes;
+    let Ok(nested_archive) = nested_bytes.read_zip() else {
+        return;
+    };
+    let Some(file) = nested_archive.by_name("fabric.mod.json") else {
+        return;
+    };
+    let Ok(mut bytes) = file.bytes() else {
+        return;
+    };
+    for byte in bytes.iter_mut() {
+        if *byte == '\n' as u8 {
+            *byte = ' ' as u8;
+        }
+    }
+    let Ok(fabric_mod_json) = serde_json::from_slice::<FabricModJson>(&bytes) else {
+        return;
+    };
+
+    add_fabric_provides(provides, &fabric_mod_json.id, &fabric_mod_json.version, &fabric_mod_json.provides);
+    for jar in &fabric_mod_json.jars {
+        add_nested_fabric_provides(provides, &nested_archive, &jar.file, depth + 1);
+    }
+}
+
 fn load_icon<R: rc_zip_sync::HasCursor>(icon_file: rc_zip_sync::EntryHandle<R>) -> Option<Arc<[u8]>> {
-    let Ok(mut icon_bytes) = icon_file.bytes() else {
+    let Ok(icon_bytes) = icon_file.bytes() else {
         return None;
     };
 
+    resize_icon(icon_bytes)
+}
+
+/// Scales the png to 64x64 so that icons render consistently
+pub(crate) fn resize_icon(mut icon_bytes: Vec<u8>) -> Option<Arc<[u8]>> {
     let Ok(image) = image::load_from_memory(&icon_bytes) else {
         return None;
     };
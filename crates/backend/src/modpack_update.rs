@@ -0,0 +1,392 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use bridge::{
+    install::{ContentInstall, InstallTarget},
+    instance::{InstanceID, InstanceModID, InstanceModSummary, LoaderSpecificModSummary, ModSummary, ModpackFileChange, ModpackFileChangeKind, ModpackUpdatePreview},
+    modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType},
+    safe_path::SafePath,
+};
+use schema::{loader::Loader, modrinth::ModrinthSideRequirement};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use ustr::Ustr;
+
+use crate::{install_content::ContentInstallError, mod_metadata::ModUpdateAction, BackendState};
+
+#[derive(Error, Debug)]
+pub enum ModpackUpdateError {
+    #[error("Unknown instance")]
+    UnknownInstance,
+    #[error("Unknown mod")]
+    UnknownMod,
+    #[error("Not a Modrinth modpack")]
+    NotAModpack,
+    #[error("No update is available, try checking for updates again")]
+    NoUpdate,
+    #[error("The new version is not a valid modpack")]
+    InvalidUpdate,
+    #[error("Update is unavailable, {0}")]
+    UpdateUnavailable(&'static str),
+    #[error("Error downloading modpack:\n{0}")]
+    ContentInstallError(#[from] ContentInstallError),
+    #[error("An I/O error occured while updating the modpack:\n{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// The file a modpack places into the .minecraft folder
+#[derive(Clone)]
+struct ModpackFile {
+    sha1: [u8; 20],
+    is_override: bool,
+}
+
+struct ModpackDiff {
+    summary: InstanceModSummary,
+    new_summary: Arc<ModSummary>,
+    loader: Loader,
+    minecraft_version: Ustr,
+    changes: Vec<ModpackFileChange>,
+}
+
+impl BackendState {
+    pub async fn get_modpack_update_preview(&self, id: InstanceID, mod_id: InstanceModID) -> Result<ModpackUpdatePreview, ModpackUpdateError> {
+        let tracker = ProgressTracker::new("Downloading modpack".into(), self.send.clone());
+        let diff = self.modpack_update_diff(id, mod_id, &tracker).await?;
+
+        let changelog = self.modrinth_update_version(&diff.summary.mod_summary, diff.loader.as_modrinth_loader(), diff.minecraft_version).await
+            .ok()
+            .flatten()
+            .and_then(|version| version.0.changelog.clone());
+
+        Ok(ModpackUpdatePreview {
+            name: diff.summary.mod_summary.name.clone(),
+            old_version: diff.summary.mod_summary.version_str.clone(),
+            new_version: diff.new_summary.version_str.clone(),
+            changelog,
+            files: diff.changes.into(),
+        })
+    }
+
+    pub async fn update_modpack(&self, id: InstanceID, mod_id: InstanceModID, keep_files: &[Arc<str>], modal_action: &ModalAction) -> Result<(), ModpackUpdateError> {
+        let tracker = ProgressTracker::new("Downloading modpack".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let diff = self.modpack_update_diff(id, mod_id, &tracker).await;
+        tracker.set_finished(ProgressTrackerFinishType::from_err(diff.is_err()));
+        tracker.notify();
+        let diff = diff?;
+
+        let install_file = self.mod_update_install_file(&diff.summary).map_err(ModpackUpdateError::UpdateUnavailable)?;
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(id),
+            loader_hint: diff.loader,
+            version_hint: Some(diff.minecraft_version.into()),
+            files: [install_file].into(),
+        };
+
+        // install_content reports its own errors to the modal
+        if self.install_content(content_install, modal_action.clone()).await.is_none() {
+            return Ok(());
+        }
+
+        let keep = |change: &ModpackFileChange| change.user_modified && keep_files.contains(&change.path);
+
+        let dot_minecraft_path = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(ModpackUpdateError::UnknownInstance);
+            };
+            instance.configuration.modify(|configuration| {
+                let mut preserved: Vec<Arc<str>> = configuration.preserved_modpack_files.iter()
+                    .filter(|path| !diff.changes.iter().any(|change| change.path == **path))
+                    .cloned()
+                    .collect();
+                preserved.extend(diff.changes.iter().filter(|change| keep(change)).map(|change| change.path.clone()));
+                configuration.preserved_modpack_files = preserved.into();
+            });
+            instance.dot_minecraft_path.clone()
+        };
+
+        // Added and changed files are written by the next launch, files the new version no longer has need to go now
+        let removed: Vec<Arc<str>> = diff.changes.iter()
+            .filter(|change| change.kind == ModpackFileChangeKind::Removed && !is_mod_jar(&change.path) && !keep(change))
+            .map(|change| change.path.clone())
+            .collect();
+        tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            for path in removed {
+                let Some(path) = SafePath::new(&path) else {
+                    continue;
+                };
+                if let Err(error) = std::fs::remove_file(path.to_path(&dot_minecraft_path)) && error.kind() != std::io::ErrorKind::NotFound {
+                    return Err(error);
+                }
+            }
+            Ok(())
+        }).await.unwrap()?;
+
+        Ok(())
+    }
+
+    async fn modpack_update_diff(&self, id: InstanceID, mod_id: InstanceModID, tracker: &ProgressTracker) -> Result<ModpackDiff, ModpackUpdateError> {
+        let (summary, loader, minecraft_version, dot_minecraft_path) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(ModpackUpdateError::UnknownInstance);
+            };
+            let Some(summary) = instance.try_get_mod(mod_id).cloned() else {
+                return Err(ModpackUpdateError::UnknownMod);
+            };
+            let configuration = instance.configuration.get();
+            (summary, configuration.loader, configuration.minecraft_version, instance.dot_minecraft_path.clone())
+        };
+
+        if !matches!(summary.mod_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. }) {
+            return Err(ModpackUpdateError::NotAModpack);
+        }
+
+        let update = self.mod_metadata_manager.updates.read().get(&summary.mod_summary.hash).cloned();
+        let Some(ModUpdateAction::Modrinth(file)) = update else {
+            return Err(ModpackUpdateError::NoUpdate);
+        };
+
+        let (_, _, new_summary) = self.download_file_into_library(Path::new(&*file.filename).into(),
+            &file.url, &file.hashes.sha1, file.size, tracker).await?;
+        let Some(new_summary) = new_summary else {
+            return Err(ModpackUpdateError::InvalidUpdate);
+        };
+        if !matches!(new_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. }) {
+            return Err(ModpackUpdateError::InvalidUpdate);
+        }
+
+        let changes = tokio::task::spawn_blocking({
+            let old_summary = summary.mod_summary.clone();
+            let new_summary = new_summary.clone();
+            move || diff_modpack_files(&old_summary, &new_summary, &dot_minecraft_path)
+        }).await.unwrap();
+
+        Ok(ModpackDiff {
+            summary,
+            new_summary,
+            loader,
+            minecraft_version,
+            changes,
+        })
+    }
+}
+
+fn diff_modpack_files(old: &ModSummary, new: &ModSummary, dot_minecraft_path: &Path) -> Vec<ModpackFileChange> {
+    let old_files = modpack_files(old);
+    let new_files = modpack_files(new);
+
+    let mut changes = Vec::new();
+    for (path, old_file) in &old_files {
+        let kind = match new_files.get(path) {
+            Some(new_file) if new_file.sha1 == old_file.sha1 => continue,
+            Some(_) => ModpackFileChangeKind::Changed,
+            None => ModpackFileChangeKind::Removed,
+        };
+        let user_modified = is_user_modified(path, old_file, dot_minecraft_path);
+        changes.push(ModpackFileChange {
+            path: path.clone(),
+            kind,
+            is_override: new_files.get(path).unwrap_or(old_file).is_override,
+            user_modified,
+        });
+    }
+    for (path, new_file) in &new_files {
+        if old_files.contains_key(path) {
+            continue;
+        }
+        changes.push(ModpackFileChange {
+            path: path.clone(),
+            kind: ModpackFileChangeKind::Added,
+            is_override: new_file.is_override,
+            user_modified: is_user_modified(path, new_file, dot_minecraft_path),
+        });
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// All files placed by a modpack on the client, keyed by their normalized path
+fn modpack_files(summary: &ModSummary) -> BTreeMap<Arc<str>, ModpackFile> {
+    let mut files = BTreeMap::new();
+    let LoaderSpecificModSummary::ModrinthModpack { downloads, overrides, .. } = &summary.extra else {
+        return files;
+    };
+
+    for download in downloads.iter() {
+        if download.env.is_some_and(|env| env.client == ModrinthSideRequirement::Unsupported) {
+            continue;
+        }
+        let Some(path) = SafePath::new(&download.path) else {
+            continue;
+        };
+        let mut sha1 = [0u8; 20];
+        let Ok(_) = hex::decode_to_slice(&*download.hashes.sha1, &mut sha1) else {
+            continue;
+        };
+        files.insert(path.as_str().into(), ModpackFile { sha1, is_override: false });
+    }
+
+    // Overrides are copied after the downloads, so they win if both have the same path
+    for (path, data) in overrides.iter() {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        files.insert(path.as_str().into(), ModpackFile { sha1: hasher.finalize().into(), is_override: true });
+    }
+
+    files
+}
+
+/// Mod jars are linked in hidden or passed to the loader directly, so the user can never have changed them
+fn is_mod_jar(path: &str) -> bool {
+    path.starts_with("mods/") && path.ends_with(".jar")
+}
+
+fn is_user_modified(path: &str, expected: &ModpackFile, dot_minecraft_path: &Path) -> bool {
+    if is_mod_jar(path) {
+        return false;
+    }
+    let Some(path) = SafePath::new(path) else {
+        return false;
+    };
+    let Ok(data) = std::fs::read(path.to_path(dot_minecraft_path)) else {
+        return false;
+    };
+    let mut hasher = Sha1::new();
+    hasher.update(&data);
+    let sha1: [u8; 20] = hasher.finalize().into();
+    sha1 != expected.sha1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use bridge::instance::{AtomicContentUpdateStatus, ContentUpdateStatus};
+
+    use super::*;
+
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+
+    /// A modpack version with downloads of `(path, contents, client support)` and overrides of `(path, contents)`
+    fn modpack(downloads: &[(&str, &[u8], &str)], overrides: &[(&str, &[u8])]) -> ModSummary {
+        let downloads = downloads.iter().map(|(path, data, client)| serde_json::from_value(serde_json::json!({
+            "path": path,
+            "hashes": { "sha1": hex::encode(sha1(data)) },
+            "env": { "client": client, "server": "required" },
+            "downloads": [],
+            "fileSize": data.len(),
+        })).unwrap()).collect();
+
+        ModSummary {
+            id: "pack".into(),
+            hash: [0; 20],
+            name: "Pack".into(),
+            lowercase_search_key: "pack".into(),
+            version_str: "1.0".into(),
+            authors: "".into(),
+            png_icon: None,
+            update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: [].into(),
+            dependencies: [].into(),
+            extra: LoaderSpecificModSummary::ModrinthModpack {
+                downloads,
+                summaries: [].into(),
+                overrides: overrides.iter().map(|(path, data)| (SafePath::new(path).unwrap(), Arc::from(*data))).collect(),
+            },
+        }
+    }
+
+    fn dot_minecraft(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("modpack-update-{name}-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&path);
+        for (file, data) in files {
+            let file = path.join(file);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, data).unwrap();
+        }
+        path
+    }
+
+    fn changes(changes: &[ModpackFileChange]) -> Vec<(&str, ModpackFileChangeKind, bool, bool)> {
+        changes.iter().map(|change| (&*change.path, change.kind, change.is_override, change.user_modified)).collect()
+    }
+
+    #[test]
+    fn test_diff_kinds() {
+        let old = modpack(&[("mods/kept.jar", b"kept", "required"), ("mods/changed.jar", b"v1", "required"), ("mods/removed.jar", b"removed", "required")], &[]);
+        let new = modpack(&[("mods/kept.jar", b"kept", "required"), ("mods/changed.jar", b"v2", "required"), ("mods/added.jar", b"added", "optional")], &[]);
+        let path = dot_minecraft("kinds", &[]);
+
+        assert_eq!(changes(&diff_modpack_files(&old, &new, &path)), [
+            ("mods/added.jar", ModpackFileChangeKind::Added, false, false),
+            ("mods/changed.jar", ModpackFileChangeKind::Changed, false, false),
+            ("mods/removed.jar", ModpackFileChangeKind::Removed, false, false),
+        ]);
+    }
+
+    #[test]
+    fn test_diff_skips_server_files() {
+        let old = modpack(&[], &[]);
+        let new = modpack(&[("mods/server-only.jar", b"server", "unsupported"), ("../escape.jar", b"escape", "required")], &[]);
+        let path = dot_minecraft("server", &[]);
+
+        assert!(diff_modpack_files(&old, &new, &path).is_empty());
+    }
+
+    #[test]
+    fn test_diff_overrides() {
+        // Overrides replace downloads with the same path
+        let old = modpack(&[("config/a.toml", b"download", "required")], &[("config/b.toml", b"b = 1")]);
+        let new = modpack(&[("config/a.toml", b"download", "required")], &[("config/a.toml", b"override"), ("config/b.toml", b"b = 1")]);
+        let path = dot_minecraft("overrides", &[("config/a.toml", b"download"), ("config/b.toml", b"b = 1")]);
+
+        assert_eq!(changes(&diff_modpack_files(&old, &new, &path)), [
+            ("config/a.toml", ModpackFileChangeKind::Changed, true, false),
+        ]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_diff_user_modified() {
+        let old = modpack(&[], &[("config/options.toml", b"fov = 70"), ("config/removed.toml", b"old")]);
+        let new = modpack(&[], &[("config/options.toml", b"fov = 90"), ("config/added.toml", b"new")]);
+        let path = dot_minecraft("user-modified", &[
+            ("config/options.toml", b"fov = 110"),
+            ("config/removed.toml", b"old"),
+            ("config/added.toml", b"mine"),
+        ]);
+
+        assert_eq!(changes(&diff_modpack_files(&old, &new, &path)), [
+            ("config/added.toml", ModpackFileChangeKind::Added, true, true),
+            ("config/options.toml", ModpackFileChangeKind::Changed, true, true),
+            ("config/removed.toml", ModpackFileChangeKind::Removed, true, false),
+        ]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_is_user_modified() {
+        let path = dot_minecraft("is-modified", &[("config/a.toml", b"a"), ("mods/a.jar", b"changed")]);
+        let file = |data: &[u8]| ModpackFile { sha1: sha1(data), is_override: true };
+
+        assert!(!is_user_modified("config/a.toml", &file(b"a"), &path));
+        assert!(is_user_modified("config/a.toml", &file(b"b"), &path));
+        // Missing files weren't modified, they're just placed again
+        assert!(!is_user_modified("config/missing.toml", &file(b"a"), &path));
+        assert!(!is_user_modified("mods/a.jar", &file(b"original"), &path));
+        assert!(!is_user_modified("../outside.toml", &file(b"a"), &path));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
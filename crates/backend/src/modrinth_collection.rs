@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use bridge::{
+    install::{ModrinthCollectionProject, ModrinthCollectionProjectStatus, ModrinthCollectionResolution}, instance::InstanceModSummary, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{loader::Loader, modrinth::{ModrinthDependencyType, ModrinthLoader, ModrinthProject, ModrinthProjectType, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthVersionStatus, ModrinthVersionType}};
+use ustr::Ustr;
+
+use crate::{metadata::{items::{ModrinthCollectionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthProjectsMetadataItem}, manager::MetaLoadError}, BackendState};
+
+impl BackendState {
+    pub async fn resolve_modrinth_collection(
+        &self,
+        collection_id: Arc<str>,
+        loader: Loader,
+        minecraft_version: Ustr,
+        installed: &[InstanceModSummary],
+        modal_action: &ModalAction,
+    ) -> Result<ModrinthCollectionResolution, MetaLoadError> {
+        let tracker = ProgressTracker::new("Resolving collection".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let result = self.resolve_modrinth_collection_inner(collection_id, loader, minecraft_version, installed, &tracker).await;
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        result
+    }
+
+    async fn resolve_modrinth_collection_inner(
+        &self,
+        collection_id: Arc<str>,
+        loader: Loader,
+        minecraft_version: Ustr,
+        installed: &[InstanceModSummary],
+        tracker: &ProgressTracker,
+    ) -> Result<ModrinthCollectionResolution, MetaLoadError> {
+        let collection = self.meta.fetch(&ModrinthCollectionMetadataItem(collection_id)).await?;
+
+        let projects: Arc<[ModrinthProject]> = if collection.projects.is_empty() {
+            Arc::from([])
+        } else {
+            self.meta.fetch(&ModrinthProjectsMetadataItem(collection.projects.clone())).await?.0.clone()
+        };
+
+        tracker.set_total(projects.len());
+        tracker.notify();
+
+        let installed_hashes: FxHashSet<[u8; 20]> = installed.iter().map(|summary| summary.mod_summary.hash).collect();
+        let modrinth_loader = loader.as_modrinth_loader();
+        let game_versions: Arc<[Arc<str>]> = [Arc::from(minecraft_version.as_str())].into();
+
+        let mut futures = Vec::with_capacity(projects.len());
+        for project in projects.iter() {
+            let game_versions = &game_versions;
+            let installed_hashes = &installed_hashes;
+            futures.push(async move {
+                let loaders = match project.project_type {
+                    ModrinthProjectType::Mod if modrinth_loader != ModrinthLoader::Unknown => Some([modrinth_loader].into()),
+                    ModrinthProjectType::Resourcepack | ModrinthProjectType::Shader => None,
+                    _ => {
+                        tracker.add_count(1);
+                        tracker.notify();
+                        return Ok(ModrinthCollectionProjectStatus::Unsupported);
+                    },
+                };
+
+                let versions = self.meta.fetch(&ModrinthProjectVersionsMetadataItem(&ModrinthProjectVersionsRequest {
+                    project_id: project.id.clone(),
+                    game_versions: Some(game_versions.clone()),
+                    loaders,
+                })).await;
+
+                tracker.add_count(1);
+                tracker.notify();
+
+                let versions = match versions {
+                    Ok(versions) => versions,
+                    Err(MetaLoadError::NonOK(404)) => return Ok(ModrinthCollectionProjectStatus::NoCompatibleVersion),
+                    Err(error) => return Err(error),
+                };
+
+                let Some(version) = pick_version(&versions.0) else {
+                    return Ok(ModrinthCollectionProjectStatus::NoCompatibleVersion);
+                };
+
+                let install_file = version.files.iter().find(|file| file.primary).unwrap_or(&version.files[0]);
+                let mut hash = [0u8; 20];
+                if hex::decode_to_slice(&*install_file.hashes.sha1, &mut hash).is_ok() && installed_hashes.contains(&hash) {
+                    return Ok(ModrinthCollectionProjectStatus::AlreadyInstalled);
+                }
+
+                Ok(ModrinthCollectionProjectStatus::Compatible(version.clone()))
+            });
+        }
+
+        let statuses = futures::future::try_join_all(futures).await?;
+
+        let mut resolved: Vec<ModrinthCollectionProject> = projects.iter().zip(statuses).map(|(project, status)| {
+            ModrinthCollectionProject {
+                project_id: project.id.clone(),
+                title: project.title.clone().unwrap_or_else(|| project.id.clone()),
+                project_type: project.project_type,
+                status,
+            }
+        }).collect();
+
+        let installed_projects = futures::future::join_all(installed.iter().map(|summary| async move {
+            let version = self.modrinth_update_version(&summary.mod_summary, modrinth_loader, minecraft_version).await.ok()??;
+            Some((version.0.project_id.clone(), summary.mod_summary.name.clone()))
+        })).await;
+        let installed_projects: FxHashMap<Arc<str>, Arc<str>> = installed_projects.into_iter().flatten().collect();
+
+        mark_installed(&mut resolved, &installed_projects);
+        mark_conflicts(&mut resolved, &installed_projects);
+
+        resolved.sort_by(|a, b| lexical_sort::natural_lexical_cmp(&a.title, &b.title));
+
+        Ok(ModrinthCollectionResolution {
+            name: collection.name.clone(),
+            projects: resolved.into(),
+        })
+    }
+}
+
+fn pick_version(versions: &[ModrinthProjectVersion]) -> Option<&ModrinthProjectVersion> {
+    let candidates: Vec<&ModrinthProjectVersion> = versions.iter()
+        .filter(|version| !version.files.is_empty())
+        .filter(|version| version.status.is_none_or(|status| matches!(status, ModrinthVersionStatus::Listed | ModrinthVersionStatus::Archived)))
+        .collect();
+
+    candidates.iter()
+        .find(|version| version.version_type == Some(ModrinthVersionType::Release))
+        .or(candidates.first())
+        .copied()
+}
+
+// Projects installed at a different version than the one picked would otherwise be added as a second jar
+fn mark_installed(projects: &mut [ModrinthCollectionProject], installed_projects: &FxHashMap<Arc<str>, Arc<str>>) {
+    for project in projects {
+        if matches!(project.status, ModrinthCollectionProjectStatus::Compatible(_)) && installed_projects.contains_key(&project.project_id) {
+            project.status = ModrinthCollectionProjectStatus::AlreadyInstalled;
+        }
+    }
+}
+
+// Marks both sides of any incompatible dependency between two projects that would be installed together,
+// and projects that are incompatible with a mod already in the instance
+fn mark_conflicts(projects: &mut [ModrinthCollectionProject], installed_projects: &FxHashMap<Arc<str>, Arc<str>>) {
+    let indices: FxHashMap<Arc<str>, usize> = projects.iter().enumerate()
+        .filter(|(_, project)| matches!(project.status, ModrinthCollectionProjectStatus::Compatible(_)))
+        .map(|(index, project)| (project.project_id.clone(), index))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (index, project) in projects.iter().enumerate() {
+        let ModrinthCollectionProjectStatus::Compatible(version) = &project.status else {
+            continue;
+        };
+        for dependency in version.dependencies.iter().flatten() {
+            if dependency.dependency_type != ModrinthDependencyType::Incompatible {
+                continue;
+            }
+            let Some(project_id) = &dependency.project_id else {
+                continue;
+            };
+            if let Some(other) = indices.get(project_id) {
+                conflicts.push((index, projects[*other].title.clone()));
+                conflicts.push((*other, project.title.clone()));
+            } else if *project_id != project.project_id && let Some(name) = installed_projects.get(project_id) {
+                conflicts.push((index, format!("{name} (installed)").into()));
+            }
+        }
+    }
+
+    for (index, conflicts_with) in conflicts {
+        let project = &mut projects[index];
+        let version = match &project.status {
+            ModrinthCollectionProjectStatus::Compatible(version) => version.clone(),
+            ModrinthCollectionProjectStatus::Conflict { version, .. } => version.clone(),
+            _ => continue,
+        };
+        project.status = ModrinthCollectionProjectStatus::Conflict { version, conflicts_with };
+    }
+}
@@ -0,0 +1,90 @@
+use std::{io::{Read, Write}, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::message::{NbtCompression, NbtFile};
+
+const MAX_NBT_FILES: usize = 4096;
+
+/// Directories that are large and never contain standalone nbt files worth editing
+const SKIPPED_DIRECTORIES: &[&str] = &["region", "entities", "poi", "mods", "logs", "crash-reports", "resourcepacks", "shaderpacks", "screenshots"];
+
+pub fn list_nbt_files(dot_minecraft: &Path) -> Arc<[Arc<Path>]> {
+    let mut files = Vec::new();
+    list_nbt_files_in(dot_minecraft, 0, &mut files);
+    files.sort();
+    files.into()
+}
+
+fn list_nbt_files_in(directory: &Path, depth: usize, files: &mut Vec<Arc<Path>>) {
+    if depth > 6 || files.len() >= MAX_NBT_FILES {
+        return;
+    }
+    let Ok(read_dir) = std::fs::read_dir(directory) else {
+        return;
+    };
+
+    for entry in read_dir.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            let skipped = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| SKIPPED_DIRECTORIES.contains(&name));
+            if !skipped {
+                list_nbt_files_in(&path, depth + 1, files);
+            }
+        } else if file_type.is_file() && path.extension().is_some_and(|extension| extension == "dat" || extension == "nbt") {
+            files.push(path.into());
+            if files.len() >= MAX_NBT_FILES {
+                return;
+            }
+        }
+    }
+}
+
+pub fn read_nbt_file(path: &Path) -> anyhow::Result<NbtFile> {
+    let raw = std::fs::read(path)?;
+
+    let (compression, decompressed) = match raw.as_slice() {
+        [0x1F, 0x8B, ..] => {
+            let mut decompressed = Vec::new();
+            flate2::bufread::GzDecoder::new(raw.as_slice()).read_to_end(&mut decompressed)?;
+            (NbtCompression::Gzip, decompressed)
+        },
+        [0x78, second, ..] if u16::from_be_bytes([0x78, *second]) % 31 == 0 => {
+            let mut decompressed = Vec::new();
+            flate2::bufread::ZlibDecoder::new(raw.as_slice()).read_to_end(&mut decompressed)?;
+            (NbtCompression::Zlib, decompressed)
+        },
+        _ => (NbtCompression::None, raw),
+    };
+
+    let mut nbt_data = decompressed.as_slice();
+    let nbt = nbt::decode::read_named(&mut nbt_data)?;
+    Ok(NbtFile { nbt, compression })
+}
+
+pub fn write_nbt_file(path: &Path, file: &NbtFile) -> anyhow::Result<()> {
+    let encoded = nbt::encode::write_named(&file.nbt);
+    let bytes = match file.compression {
+        NbtCompression::None => encoded,
+        NbtCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&encoded)?;
+            encoder.finish()?
+        },
+        NbtCompression::Zlib => {
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&encoded)?;
+            encoder.finish()?
+        },
+    };
+
+    if path.is_file() {
+        let mut backup = PathBuf::from(path);
+        backup.add_extension("bak");
+        std::fs::copy(path, backup)?;
+    }
+
+    crate::write_safe(path, &bytes)?;
+    Ok(())
+}
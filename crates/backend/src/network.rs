@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use reqwest::{header::LOCATION, redirect::Policy, Request, Response, Url};
+use schema::network::{DownloadMirror, NetworkConfiguration};
+
+const MAX_MIRROR_REDIRECTS: usize = 8;
+
+pub struct NetworkSettings {
+    mirrors: RwLock<Arc<[DownloadMirror]>>,
+    clients: RwLock<HttpClients>,
+}
+
+/// Clients are rebuilt when the proxy changes, requests already in flight finish with the old ones
+struct HttpClients {
+    proxy: Option<Arc<str>>,
+    http_client: reqwest::Client,
+    redirecting_http_client: reqwest::Client,
+}
+
+impl HttpClients {
+    fn new(proxy: Option<Arc<str>>) -> Self {
+        let mut http_client = reqwest::ClientBuilder::new()
+            // .connect_timeout(Duration::from_secs(5))
+            .redirect(Policy::none())
+            .use_rustls_tls()
+            .user_agent("PandoraLauncher/0.1.0 (https://github.com/Moulberry/PandoraLauncher)");
+
+        let mut redirecting_http_client = reqwest::ClientBuilder::new()
+            .use_rustls_tls()
+            .user_agent("PandoraLauncher/0.1.0 (https://github.com/Moulberry/PandoraLauncher)");
+
+        if let Some(proxy) = proxy.as_deref().and_then(parse_proxy) {
+            http_client = http_client.proxy(proxy.clone());
+            redirecting_http_client = redirecting_http_client.proxy(proxy);
+        }
+
+        Self {
+            proxy,
+            http_client: http_client.build().unwrap(),
+            redirecting_http_client: redirecting_http_client.build().unwrap(),
+        }
+    }
+}
+
+impl NetworkSettings {
+    pub fn new(configuration: &NetworkConfiguration) -> Self {
+        let settings = Self {
+            mirrors: RwLock::new(Arc::from([])),
+            clients: RwLock::new(HttpClients::new(configured_proxy(configuration))),
+        };
+        settings.update(configuration);
+        settings
+    }
+
+    pub fn update(&self, configuration: &NetworkConfiguration) {
+        *self.mirrors.write() = configuration.mirrors.iter()
+            .filter(|mirror| mirror.enabled && !mirror.from.is_empty() && !mirror.to.is_empty())
+            .cloned()
+            .collect();
+
+        let proxy = configured_proxy(configuration);
+        if self.clients.read().proxy != proxy {
+            *self.clients.write() = HttpClients::new(proxy);
+        }
+    }
+
+    /// Client that doesn't follow redirects
+    pub fn http_client(&self) -> reqwest::Client {
+        self.clients.read().http_client.clone()
+    }
+
+    pub fn redirecting_http_client(&self) -> reqwest::Client {
+        self.clients.read().redirecting_http_client.clone()
+    }
+
+    pub fn mirrored_urls(&self, url: &Url) -> Vec<Url> {
+        let mirrors = self.mirrors.read().clone();
+
+        let mut urls = Vec::new();
+        for mirror in mirrors.iter() {
+            if let Some(rewritten) = mirror.rewrite(url.as_str())
+                && let Ok(rewritten) = Url::parse(&rewritten)
+                && !urls.contains(&rewritten)
+            {
+                urls.push(rewritten);
+            }
+        }
+        urls
+    }
+
+    /// Sends the request to each matching mirror in order, falling back to the original url
+    pub async fn execute(&self, client: &reqwest::Client, request: Request) -> reqwest::Result<Response> {
+        for url in self.mirrored_urls(request.url()) {
+            let Some(mut mirror_request) = request.try_clone() else {
+                break;
+            };
+            *mirror_request.url_mut() = url;
+
+            match execute_following_redirects(client, mirror_request).await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    eprintln!("Mirror {} responded with {}, falling back", response.url(), response.status());
+                },
+                Err(error) => {
+                    eprintln!("Error while using mirror, falling back: {error}");
+                },
+            }
+        }
+
+        client.execute(request).await
+    }
+}
+
+fn configured_proxy(configuration: &NetworkConfiguration) -> Option<Arc<str>> {
+    let proxy = configuration.proxy.as_deref()?.trim_ascii();
+    if proxy.is_empty() {
+        None
+    } else {
+        Some(proxy.into())
+    }
+}
+
+fn parse_proxy(proxy: &str) -> Option<reqwest::Proxy> {
+    match reqwest::Proxy::all(proxy) {
+        Ok(proxy) => Some(proxy.no_proxy(reqwest::NoProxy::from_string("localhost,127.0.0.1,::1"))),
+        Err(error) => {
+            eprintln!("Ignoring invalid proxy {proxy:?}: {error}");
+            None
+        },
+    }
+}
+
+// Mirrors commonly answer with a redirect to the real file, but the metadata client doesn't follow redirects
+async fn execute_following_redirects(client: &reqwest::Client, mut request: Request) -> reqwest::Result<Response> {
+    for _ in 0..MAX_MIRROR_REDIRECTS {
+        let Some(next_request) = request.try_clone() else {
+            break;
+        };
+
+        let response = client.execute(request).await?;
+        if !response.status().is_redirection() {
+            return Ok(response);
+        }
+
+        let location = response.headers().get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok());
+        let Some(location) = location else {
+            return Ok(response);
+        };
+
+        request = next_request;
+        *request.url_mut() = location;
+    }
+
+    client.execute(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_server::{TestResponse, TestServer};
+
+    #[tokio::test]
+    async fn test_proxy_update() {
+        let proxy = TestServer::start(|_| TestResponse::ok("proxied")).await;
+        let settings = NetworkSettings::new(&NetworkConfiguration::default());
+
+        settings.update(&NetworkConfiguration {
+            proxy: Some(format!(" {} ", proxy.url).into()),
+            ..Default::default()
+        });
+        let response = settings.http_client().get("http://example.invalid/file").send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "proxied");
+        assert_eq!(proxy.requests()[0].path, "http://example.invalid/file");
+
+        // Clients built before the proxy was removed keep using it, new ones don't
+        settings.update(&NetworkConfiguration::default());
+        _ = settings.redirecting_http_client().get("http://example.invalid/file").timeout(Duration::from_secs(5)).send().await;
+        assert_eq!(proxy.requests().len(), 1);
+    }
+}
@@ -0,0 +1,532 @@
+use std::{path::Path, sync::{atomic::Ordering, Arc}};
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{AtomicContentUpdateStatus, ContentUpdateStatus, InstanceID, InstancePackSummary, PackFolder, PackMetadata}, message::MessageToFrontend, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use futures::TryFutureExt;
+use rc_zip_sync::ReadZip;
+use schema::{content::ContentSource, modrinth::ModrinthLoader};
+use sha1::{Digest, Sha1};
+
+use crate::{metadata::{items::{ModrinthVersionUpdateMetadataItem, VersionUpdateParameters}, manager::MetaLoadError}, mod_metadata::{ModMetadataManager, ModUpdateAction}, BackendState};
+
+const SHADER_DIMENSIONS: &[(&str, &str)] = &[
+    ("shaders/world0", "Overworld"),
+    ("shaders/world-1", "Nether"),
+    ("shaders/world1", "End"),
+];
+
+impl BackendState {
+    pub fn set_packs_enabled(&self, id: InstanceID, folder: PackFolder, paths: Vec<Arc<Path>>, enabled: bool) {
+        if let Err(err) = self.check_pack_paths(id, &folder, &paths) {
+            self.send.send_error(format!("Unable to toggle pack: {err}"));
+            return;
+        }
+
+        let mut changed = Vec::new();
+        for path in paths {
+            let currently_enabled = path.extension().is_none_or(|extension| extension != "disabled");
+            if currently_enabled == enabled {
+                continue;
+            }
+            if path.is_dir() {
+                // Minecraft loads folders with a pack.mcmeta regardless of their name
+                self.send.send_error("Unable to toggle pack: folder packs can't be disabled");
+                continue;
+            }
+
+            let mut new_path = path.to_path_buf();
+            if enabled {
+                new_path.set_extension("");
+            } else {
+                new_path.add_extension("disabled");
+            }
+
+            if let Err(err) = std::fs::rename(&path, &new_path) {
+                self.send.send_error(format!("Unable to toggle pack: {err}"));
+                continue;
+            }
+            changed.push(path);
+            changed.push(new_path.into());
+        }
+
+        self.reload_changed_packs(id, folder, changed);
+    }
+
+    pub fn delete_packs(&self, id: InstanceID, folder: PackFolder, paths: Vec<Arc<Path>>) {
+        if let Err(err) = self.check_pack_paths(id, &folder, &paths) {
+            self.send.send_error(format!("Unable to delete pack: {err}"));
+            return;
+        }
+
+        let mut changed = Vec::new();
+        for path in paths {
+            let result = if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            if let Err(err) = result {
+                self.send.send_error(format!("Unable to delete pack: {err}"));
+                continue;
+            }
+            changed.push(path);
+        }
+
+        self.reload_changed_packs(id, folder, changed);
+    }
+
+    pub async fn pack_update_check(&self, id: InstanceID, folder: PackFolder, modal_action: ModalAction) {
+        if let Err(err) = check_pack_folder(&folder) {
+            modal_action.set_error_message(err.into());
+            modal_action.set_finished();
+            return;
+        }
+
+        let Some(version) = self.instance_state.write().instances.get_mut(id).map(|instance| instance.configuration.get().minecraft_version) else {
+            modal_action.set_error_message("Can't check for updates, unknown instance id".into());
+            modal_action.set_finished();
+            return;
+        };
+
+        let Some(packs) = self.clone().load_instance_packs(id, folder.clone()).await else {
+            modal_action.set_finished();
+            return;
+        };
+
+        let tracker = ProgressTracker::new(format!("Checking {}", folder.name().to_lowercase()).into(), self.send.clone());
+        tracker.set_total(packs.len());
+        modal_action.trackers.push(tracker.clone());
+
+        let params = VersionUpdateParameters {
+            loaders: modrinth_loaders(&folder).into(),
+            game_versions: [version].into(),
+        };
+
+        let meta = self.meta.clone();
+        let mut futures = Vec::new();
+
+        { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
+            let sources = self.mod_metadata_manager.read_content_sources();
+            for summary in packs.iter() {
+                let Some(hash) = summary.hash else {
+                    tracker.add_count(1);
+                    continue;
+                };
+                let source = sources.get(&hash).copied().unwrap_or(ContentSource::Manual);
+                let meta = &meta;
+                let params = &params;
+                let tracker = &tracker;
+                futures.push(async move {
+                    match source {
+                        ContentSource::Manual => {
+                            tracker.add_count(1);
+                            tracker.notify();
+                            Ok(Some(ModUpdateAction::ManualInstall))
+                        },
+                        // Only Modrinth is checked for pack updates
+                        ContentSource::CurseForge => {
+                            tracker.add_count(1);
+                            tracker.notify();
+                            Ok(None)
+                        },
+                        ContentSource::Modrinth => {
+                            let result = meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                sha1: hex::encode(hash).into(),
+                                params: params.clone()
+                            }).await;
+
+                            tracker.add_count(1);
+                            tracker.notify();
+
+                            if let Err(MetaLoadError::NonOK(404)) = result {
+                                return Ok(Some(ModUpdateAction::ErrorNotFound));
+                            }
+
+                            let result = result?;
+
+                            let Some(install_file) = result.0.files.iter().find(|file| file.primary).or(result.0.files.first()) else {
+                                return Ok(Some(ModUpdateAction::ErrorNotFound));
+                            };
+
+                            let mut latest_hash = [0u8; 20];
+                            let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
+                                return Ok(Some(ModUpdateAction::ErrorInvalidHash));
+                            };
+
+                            if latest_hash == hash {
+                                Ok(Some(ModUpdateAction::AlreadyUpToDate))
+                            } else {
+                                Ok(Some(ModUpdateAction::Modrinth(install_file.clone())))
+                            }
+                        },
+                    }
+                }.map_ok(move |action| action.map(|action| (summary, hash, action))));
+            }
+        }
+
+        let results: Result<Vec<_>, MetaLoadError> = futures::future::try_join_all(futures).await;
+
+        match results {
+            Ok(updates) => {
+                let mut meta_updates = self.mod_metadata_manager.updates.write();
+                for (summary, hash, action) in updates.into_iter().flatten() {
+                    summary.update_status.store(action.to_status(), Ordering::Relaxed);
+                    meta_updates.insert(hash, action);
+                }
+            },
+            Err(error) => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                modal_action.set_error_message(format!("Error checking for updates: {}", error).into());
+                modal_action.set_finished();
+                return;
+            },
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        modal_action.set_finished();
+        self.send.send(MessageToFrontend::Refresh);
+    }
+
+    pub async fn update_pack(&self, id: InstanceID, folder: PackFolder, path: Arc<Path>, modal_action: ModalAction) {
+        let content_install = match self.create_pack_update(id, &folder, &path) {
+            Ok(content_install) => content_install,
+            Err(err) => {
+                modal_action.set_error_message(format!("Can't update pack: {err}").into());
+                modal_action.set_finished();
+                return;
+            },
+        };
+
+        self.install_content(content_install, modal_action.clone()).await;
+        modal_action.set_finished();
+        self.send.send(MessageToFrontend::Refresh);
+    }
+
+    fn create_pack_update(&self, id: InstanceID, folder: &PackFolder, path: &Arc<Path>) -> Result<ContentInstall, &'static str> {
+        self.check_pack_paths(id, folder, std::slice::from_ref(path))?;
+
+        let mut instance_state = self.instance_state.write();
+        let instance = instance_state.instances.get_mut(id).ok_or("unknown instance id")?;
+        let configuration = instance.configuration.get();
+        let (loader, minecraft_version) = (configuration.loader, configuration.minecraft_version);
+
+        let summary = instance.packs.get(folder)
+            .and_then(|collection| collection.packs.as_ref())
+            .and_then(|packs| packs.iter().find(|summary| summary.path == *path))
+            .ok_or("unknown pack")?;
+        let hash = summary.hash.ok_or("folder packs can't be updated")?;
+
+        let update_info = self.mod_metadata_manager.updates.read().get(&hash).cloned().ok_or("missing update action")?;
+        let ModUpdateAction::Modrinth(modrinth_file) = update_info else {
+            return Err("no update is available");
+        };
+
+        let mut new_path = path.with_file_name(&*modrinth_file.filename);
+        if !summary.enabled {
+            new_path.add_extension("disabled");
+        }
+        if new_path.parent() != path.parent() {
+            return Err("invalid filename");
+        }
+
+        Ok(ContentInstall {
+            target: InstallTarget::Instance(id),
+            loader_hint: loader,
+            version_hint: Some(minecraft_version.into()),
+            files: [ContentInstallFile {
+                replace_old: Some(path.clone()),
+                path: ContentInstallPath::Raw(new_path.into()),
+                download: ContentDownload::Url {
+                    url: modrinth_file.url.clone(),
+                    sha1: modrinth_file.hashes.sha1.clone(),
+                    size: modrinth_file.size,
+                },
+                content_source: ContentSource::Modrinth,
+            }].into(),
+        })
+    }
+
+    fn check_pack_paths(&self, id: InstanceID, folder: &PackFolder, paths: &[Arc<Path>]) -> Result<(), &'static str> {
+        check_pack_folder(folder)?;
+
+        let instance_state = self.instance_state.read();
+        let instance = instance_state.instances.get(id).ok_or("unknown instance id")?;
+        let folder_path = instance.pack_folder_path(folder);
+        if paths.iter().any(|path| path.parent() != Some(&*folder_path)) {
+            return Err("pack isn't inside the instance's pack folder");
+        }
+        Ok(())
+    }
+
+    fn reload_changed_packs(&self, id: InstanceID, folder: PackFolder, changed: Vec<Arc<Path>>) {
+        if changed.is_empty() {
+            return;
+        }
+
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let collection = instance.pack_collection_mut(&folder);
+            for path in changed {
+                collection.mark_dirty(Some(path));
+            }
+        }
+
+        tokio::task::spawn(self.clone().load_instance_packs(id, folder));
+    }
+}
+
+/// Datapacks are addressed by world folder name, which must not escape the saves folder
+pub(crate) fn check_pack_folder(folder: &PackFolder) -> Result<(), &'static str> {
+    if let PackFolder::DataPacks { world } = folder
+        && (world.is_empty() || &**world == "." || &**world == ".." || world.contains(['/', '\\']))
+    {
+        return Err("invalid world name");
+    }
+    Ok(())
+}
+
+fn modrinth_loaders(folder: &PackFolder) -> Vec<ModrinthLoader> {
+    match folder {
+        PackFolder::ResourcePacks => vec![ModrinthLoader::Minecraft],
+        PackFolder::ShaderPacks => vec![ModrinthLoader::Iris, ModrinthLoader::Optifine, ModrinthLoader::Canvas],
+        PackFolder::DataPacks { .. } => vec![ModrinthLoader::Datapack],
+    }
+}
+
+pub(crate) fn create_instance_pack_summary(path: &Path, folder: &PackFolder, mod_metadata_manager: &ModMetadataManager) -> Option<InstancePackSummary> {
+    let filename = path.file_name()?.to_str()?;
+    if filename.starts_with('.') {
+        return None;
+    }
+
+    let (filename_without_disabled, enabled) = match filename.strip_suffix(".disabled") {
+        Some(name) => (name, false),
+        None => (filename, true),
+    };
+
+    let is_folder = path.is_dir();
+    let (hash, (metadata, png_icon)) = if is_folder {
+        let metadata = read_pack_metadata(
+            folder,
+            |name| std::fs::read(path.join(name)).ok(),
+            |name| path.join(name).is_dir(),
+        );
+        // Minecraft and shader loaders ignore folders without metadata
+        (None, metadata?)
+    } else if filename_without_disabled.ends_with(".zip") && path.is_file() {
+        let mut file = std::fs::File::open(path).ok()?;
+        let mut hasher = Sha1::new();
+        std::io::copy(&mut file, &mut hasher).ok()?;
+        let hash: [u8; 20] = hasher.finalize().into();
+
+        let metadata = file.read_zip().ok().and_then(|archive| {
+            let names: Vec<String> = archive.entries().map(|entry| entry.name.clone()).collect();
+            read_pack_metadata(
+                folder,
+                |name| archive.by_name(name).and_then(|entry| entry.bytes().ok()),
+                |name| names.iter().any(|entry| entry.strip_prefix(name).is_some_and(|rest| rest.starts_with('/'))),
+            )
+        });
+        (Some(hash), metadata.unwrap_or((PackMetadata::Unknown, None)))
+    } else {
+        return None;
+    };
+
+    let update_status = hash
+        .and_then(|hash| mod_metadata_manager.updates.read().get(&hash).map(ModUpdateAction::to_status))
+        .unwrap_or(ContentUpdateStatus::Unknown);
+
+    let filename: Arc<str> = filename.into();
+    let lowercase_filename = filename.to_lowercase();
+    let lowercase_filename = if lowercase_filename == &*filename {
+        filename.clone()
+    } else {
+        lowercase_filename.into()
+    };
+
+    Some(InstancePackSummary {
+        filename,
+        lowercase_filename,
+        path: path.into(),
+        enabled,
+        is_folder,
+        hash,
+        metadata,
+        png_icon,
+        update_status: Arc::new(AtomicContentUpdateStatus::new(update_status)),
+    })
+}
+
+/// Returns None if the pack is missing the files needed to be recognized
+fn read_pack_metadata(
+    folder: &PackFolder,
+    read_file: impl Fn(&str) -> Option<Vec<u8>>,
+    has_dir: impl Fn(&str) -> bool,
+) -> Option<(PackMetadata, Option<Arc<[u8]>>)> {
+    match folder {
+        PackFolder::ResourcePacks | PackFolder::DataPacks { .. } => {
+            let mcmeta = read_file("pack.mcmeta")?;
+            let metadata = parse_pack_mcmeta(&mcmeta).unwrap_or(PackMetadata::Unknown);
+            let png_icon = read_file("pack.png").and_then(crate::mod_metadata::resize_icon);
+            Some((metadata, png_icon))
+        },
+        PackFolder::ShaderPacks => {
+            if !has_dir("shaders") {
+                return None;
+            }
+
+            let mut profiles: Vec<Arc<str>> = Vec::new();
+            if let Some(properties) = read_file("shaders/shaders.properties") {
+                for line in String::from_utf8_lossy(&properties).lines() {
+                    let Some((key, _)) = line.trim().split_once('=') else {
+                        continue;
+                    };
+                    if let Some(profile) = key.trim().strip_prefix("profile.") && !profiles.iter().any(|existing| &**existing == profile) {
+                        profiles.push(profile.into());
+                    }
+                }
+            }
+
+            let mut dimensions: Vec<Arc<str>> = SHADER_DIMENSIONS.iter()
+                .filter(|(dir, _)| has_dir(dir))
+                .map(|(_, name)| Arc::from(*name))
+                .collect();
+            if let Some(properties) = read_file("shaders/dimension.properties") {
+                for line in String::from_utf8_lossy(&properties).lines() {
+                    let Some((key, _)) = line.trim().split_once('=') else {
+                        continue;
+                    };
+                    if let Some(dimension) = key.trim().strip_prefix("dimension.") && !dimensions.iter().any(|existing| &**existing == dimension) {
+                        dimensions.push(dimension.into());
+                    }
+                }
+            }
+
+            let metadata = PackMetadata::Shader {
+                profiles: profiles.into(),
+                dimensions: dimensions.into(),
+            };
+            Some((metadata, None))
+        },
+    }
+}
+
+fn parse_pack_mcmeta(bytes: &[u8]) -> Option<PackMetadata> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let value: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    let pack = value.get("pack")?;
+
+    let mut description = String::new();
+    if let Some(component) = pack.get("description") {
+        push_text_component(component, &mut description);
+    }
+
+    // 1.21.9 replaced pack_format with min_format/max_format, which may be [major, minor]
+    let pack_format = pack.get("pack_format")
+        .or_else(|| pack.get("min_format"))
+        .and_then(|format| format.as_i64().or_else(|| format.get(0)?.as_i64()))
+        .and_then(|format| i32::try_from(format).ok());
+
+    Some(PackMetadata::Pack {
+        description: strip_formatting_codes(description.trim()).into(),
+        pack_format,
+    })
+}
+
+fn push_text_component(component: &serde_json::Value, text: &mut String) {
+    match component {
+        serde_json::Value::String(string) => text.push_str(string),
+        serde_json::Value::Array(components) => {
+            for component in components {
+                push_text_component(component, text);
+            }
+        },
+        serde_json::Value::Object(object) => {
+            if let Some(inner) = object.get("text") {
+                push_text_component(inner, text);
+            } else if let Some(translate) = object.get("fallback").or_else(|| object.get("translate")).and_then(|value| value.as_str()) {
+                text.push_str(translate);
+            }
+            if let Some(extra) = object.get("extra") {
+                push_text_component(extra, text);
+            }
+        },
+        serde_json::Value::Number(number) => text.push_str(&number.to_string()),
+        serde_json::Value::Bool(bool) => text.push_str(if *bool { "true" } else { "false" }),
+        serde_json::Value::Null => {},
+    }
+}
+
+fn strip_formatting_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char == '§' {
+            chars.next();
+        } else {
+            stripped.push(char);
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> (String, Option<i32>) {
+        match parse_pack_mcmeta(json.as_bytes()) {
+            Some(PackMetadata::Pack { description, pack_format }) => (description.to_string(), pack_format),
+            metadata => panic!("unexpected metadata {metadata:?}"),
+        }
+    }
+
+    fn component(json: &str) -> String {
+        let mut text = String::new();
+        push_text_component(&serde_json::from_str(json).unwrap(), &mut text);
+        text
+    }
+
+    #[test]
+    fn test_string_description() {
+        assert_eq!(parse(r#"{"pack": {"pack_format": 34, "description": "  Faithful 32x  "}}"#), ("Faithful 32x".into(), Some(34)));
+        // Packs saved by some editors start with a byte order mark
+        assert_eq!(parse("\u{FEFF}{\"pack\": {\"pack_format\": 15, \"description\": \"Bom\"}}"), ("Bom".into(), Some(15)));
+    }
+
+    #[test]
+    fn test_component_descriptions() {
+        assert_eq!(parse(r#"{"pack": {"pack_format": 48, "description": ["", {"text": "Fresh ", "color": "gold"}, "Animations"]}}"#).0, "Fresh Animations");
+        assert_eq!(parse(r#"{"pack": {"pack_format": 48, "description": {"text": "Base", "extra": [" and ", {"text": "extra"}]}}}"#).0, "Base and extra");
+        assert_eq!(parse(r#"{"pack": {"pack_format": 48}}"#).0, "");
+    }
+
+    #[test]
+    fn test_pack_formats() {
+        assert_eq!(parse(r#"{"pack": {"min_format": 65, "max_format": 69, "description": ""}}"#).1, Some(65));
+        assert_eq!(parse(r#"{"pack": {"min_format": [65, 2], "max_format": [69, 0], "description": ""}}"#).1, Some(65));
+        assert_eq!(parse(r#"{"pack": {"pack_format": "34", "description": ""}}"#).1, None);
+        assert!(parse_pack_mcmeta(br#"{"filter": {}}"#).is_none());
+        assert!(parse_pack_mcmeta(b"not json").is_none());
+    }
+
+    #[test]
+    fn test_push_text_component() {
+        assert_eq!(component(r#""plain""#), "plain");
+        assert_eq!(component(r#"["a", 1, true, null, {"text": "b"}]"#), "a1trueb");
+        assert_eq!(component(r#"{"translate": "pack.description", "fallback": "Fallback"}"#), "Fallback");
+        assert_eq!(component(r#"{"translate": "pack.description"}"#), "pack.description");
+        assert_eq!(component(r#"{"text": ["nested", {"text": " text"}]}"#), "nested text");
+        assert_eq!(component(r#"{"color": "red"}"#), "");
+    }
+
+    #[test]
+    fn test_strip_formatting_codes() {
+        assert_eq!(strip_formatting_codes("§6Gold §lBold§r text"), "Gold Bold text");
+        assert_eq!(strip_formatting_codes("no codes"), "no codes");
+        // A trailing section sign has no code to remove
+        assert_eq!(strip_formatting_codes("ends with §"), "ends with ");
+        assert_eq!(strip_formatting_codes("§§a"), "a");
+        assert_eq!(parse(r#"{"pack": {"pack_format": 34, "description": "§aGreen §7pack"}}"#).0, "Green pack");
+    }
+}
@@ -0,0 +1,412 @@
+use std::{io::{BufRead, Read}, path::Path, time::SystemTime};
+
+use bridge::{instance::InstanceID, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}};
+use reqwest::StatusCode;
+use schema::paste::{PasteService, MCLOGS_API_URL};
+use serde::Deserialize;
+
+use crate::{log_reader, BackendState};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PasteError {
+    #[error("Error while uploading log:\n{0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("{0} returned non-200 status code: {1}")]
+    NotOK(&'static str, StatusCode),
+    #[error("Error while deserializing {0} response:\n{1}")]
+    InvalidResponse(&'static str, serde_json::Error),
+    #[error("{0} rejected upload: {1}")]
+    Rejected(&'static str, String),
+    #[error("{0} returned success, but no url")]
+    MissingUrl(&'static str),
+    #[error("No url configured for {0} uploads, set one in the network settings")]
+    NotConfigured(&'static str),
+}
+
+impl BackendState {
+    pub async fn upload_log_file(&self, path: &Path, modal_action: ModalAction) {
+        let content = match read_log_text(path) {
+            Ok(content) => content,
+            Err(e) => {
+                let error = format!("Unable to read file: {e}");
+                modal_action.set_error_message(log_reader::replace(&error).into());
+                modal_action.set_finished();
+                return;
+            },
+        };
+
+        self.upload_paste(content, modal_action).await;
+    }
+
+    pub async fn upload_log_bundle(&self, id: InstanceID, modal_action: ModalAction) {
+        let Some(dot_minecraft_path) = self.instance_state.read().instances.get(id).map(|instance| instance.dot_minecraft_path.clone()) else {
+            modal_action.set_error_message("Unable to find instance".into());
+            modal_action.set_finished();
+            return;
+        };
+
+        let content = create_log_bundle(&dot_minecraft_path);
+        self.upload_paste(content, modal_action).await;
+    }
+
+    async fn upload_paste(&self, mut content: String, modal_action: ModalAction) {
+        let service = self.config.write().get().paste.service.clone();
+
+        let tracker = ProgressTracker::new("Redacting sensitive information".into(), self.send.clone());
+        tracker.set_total(3);
+        tracker.notify();
+        modal_action.trackers.push(tracker.clone());
+
+        if let Some(max_size) = service.max_size() {
+            truncate_content(&mut content, max_size);
+        }
+
+        let replaced = log_reader::replace(&*content);
+
+        if replaced.trim_ascii().is_empty() {
+            modal_action.set_error_message("Log file was empty, didn't upload".into());
+            modal_action.set_finished();
+            tracker.set_finished(ProgressTrackerFinishType::Error);
+            tracker.notify();
+            return;
+        }
+
+        tracker.set_title(format!("Uploading to {}", service.name()).into());
+        tracker.set_count(1);
+        tracker.notify();
+
+        match upload(&self.downloads.http_client(), &service, replaced.into_owned()).await {
+            Ok(url) => {
+                modal_action.set_visit_url(ModalActionVisitUrl {
+                    message: format!("Open {}", url).into(),
+                    url: url.into(),
+                    prevent_auto_finish: true,
+                });
+                modal_action.set_finished();
+                tracker.set_count(3);
+                tracker.set_finished(ProgressTrackerFinishType::Normal);
+            },
+            Err(e) => {
+                modal_action.set_error_message(e.to_string().into());
+                modal_action.set_finished();
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+            },
+        }
+        tracker.notify();
+    }
+}
+
+/// Uploads the text to the paste service, returning the url it can be viewed at
+pub async fn upload(http_client: &reqwest::Client, service: &PasteService, content: String) -> Result<String, PasteError> {
+    upload_to(http_client, service, MCLOGS_API_URL, content).await
+}
+
+async fn upload_to(http_client: &reqwest::Client, service: &PasteService, mclogs_api_url: &str, content: String) -> Result<String, PasteError> {
+    let name = service.name();
+    match service {
+        PasteService::McLogs => {
+            #[derive(Deserialize)]
+            struct McLogsResponse {
+                success: bool,
+                url: Option<String>,
+                error: Option<String>,
+            }
+
+            // mclo.gs reports errors in the body, so the status code isn't checked
+            let bytes = http_client.post(format!("{mclogs_api_url}/1/log")).form(&[("content", content)]).send().await?.bytes().await?;
+            let response: McLogsResponse = serde_json::from_slice(&bytes).map_err(|e| PasteError::InvalidResponse(name, e))?;
+
+            if response.success {
+                response.url.ok_or(PasteError::MissingUrl(name))
+            } else {
+                Err(PasteError::Rejected(name, response.error.unwrap_or_else(|| "Failure returned, but missing error".into())))
+            }
+        },
+        PasteService::Hastebin { url } => {
+            #[derive(Deserialize)]
+            struct HastebinResponse {
+                key: String,
+            }
+
+            let url = url.trim_ascii().trim_end_matches('/');
+            if url.is_empty() {
+                return Err(PasteError::NotConfigured(name));
+            }
+
+            let response = http_client.post(format!("{url}/documents")).body(content).send().await?;
+            if response.status() != StatusCode::OK {
+                return Err(PasteError::NotOK(name, response.status()));
+            }
+            let bytes = response.bytes().await?;
+            let response: HastebinResponse = serde_json::from_slice(&bytes).map_err(|e| PasteError::InvalidResponse(name, e))?;
+
+            Ok(format!("{url}/{}", response.key))
+        },
+        PasteService::Custom { upload_url, view_url } => {
+            #[derive(Deserialize)]
+            struct CustomResponse {
+                url: Option<String>,
+                #[serde(alias = "id")]
+                key: Option<String>,
+            }
+
+            if upload_url.trim_ascii().is_empty() {
+                return Err(PasteError::NotConfigured(name));
+            }
+
+            let response = http_client.post(upload_url.trim_ascii()).body(content).send().await?;
+            if !response.status().is_success() {
+                return Err(PasteError::NotOK(name, response.status()));
+            }
+            let text = response.text().await?;
+            let text = text.trim_ascii();
+
+            // Accept either a json object or the plain key/url as the response
+            let key = if text.starts_with('{') {
+                let response: CustomResponse = serde_json::from_str(text).map_err(|e| PasteError::InvalidResponse(name, e))?;
+                if let Some(url) = response.url {
+                    return Ok(url);
+                }
+                response.key.ok_or(PasteError::MissingUrl(name))?
+            } else {
+                text.to_string()
+            };
+
+            if key.starts_with("https://") || key.starts_with("http://") {
+                Ok(key)
+            } else if key.is_empty() || !view_url.contains("{key}") {
+                Err(PasteError::MissingUrl(name))
+            } else {
+                Ok(view_url.trim_ascii().replace("{key}", &key))
+            }
+        },
+    }
+}
+
+/// Truncates to at most `max_size` bytes without splitting a character
+fn truncate_content(content: &mut String, max_size: usize) {
+    if content.len() > max_size {
+        let mut index = max_size;
+        while !content.is_char_boundary(index) {
+            index -= 1;
+        }
+        content.truncate(index);
+    }
+}
+
+fn create_log_bundle(dot_minecraft_path: &Path) -> String {
+    let mut content = String::new();
+
+    // The log goes last since it is the largest part and the first to be truncated
+    push_bundle_section(&mut content, "Mods", Ok(list_mods(&dot_minecraft_path.join("mods"))));
+    if let Some(crash_report) = newest_file(&dot_minecraft_path.join("crash-reports"), ".txt") {
+        let name = crash_report.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        push_bundle_section(&mut content, &name, read_log_text(&crash_report));
+    }
+    push_bundle_section(&mut content, "latest.log", read_log_text(&dot_minecraft_path.join("logs").join("latest.log")));
+
+    content
+}
+
+fn read_log_text(path: &Path) -> std::io::Result<String> {
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let buffer = reader.fill_buf()?;
+
+    let mut content = String::new();
+    if buffer.len() >= 2 && buffer[0] == 0x1F && buffer[1] == 0x8B {
+        flate2::bufread::GzDecoder::new(reader).read_to_string(&mut content)?;
+    } else {
+        reader.read_to_string(&mut content)?;
+    }
+    Ok(content)
+}
+
+fn push_bundle_section(content: &mut String, title: &str, section: std::io::Result<String>) {
+    content.push_str(&format!("==================== {title} ====================\n"));
+    match section {
+        Ok(section) => content.push_str(section.trim_ascii_end()),
+        Err(e) => content.push_str(&format!("Unable to read: {e}")),
+    }
+    content.push_str("\n\n");
+}
+
+fn list_mods(mods_path: &Path) -> String {
+    let Ok(read_dir) = std::fs::read_dir(mods_path) else {
+        return "No mods folder".into();
+    };
+
+    let mut mods = read_dir.flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".jar") || name.ends_with(".jar.disabled"))
+        .collect::<Vec<_>>();
+    mods.sort_by_key(|name| name.to_lowercase());
+
+    if mods.is_empty() {
+        "No mods installed".into()
+    } else {
+        mods.join("\n")
+    }
+}
+
+fn newest_file(folder: &Path, extension: &str) -> Option<std::path::PathBuf> {
+    std::fs::read_dir(folder).ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(|name| name.ends_with(extension)))
+        .max_by_key(|entry| entry.metadata().and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH))
+        .map(|entry| entry.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, sync::Arc};
+
+    use schema::paste::PasteService;
+
+    use super::{create_log_bundle, truncate_content, upload_to, PasteError};
+    use crate::test_server::{TestResponse, TestServer};
+
+    fn http_client() -> reqwest::Client {
+        reqwest::Client::builder().no_proxy().build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mclogs() {
+        let server = TestServer::start(|request| match request.body.as_slice() {
+            b"content=too+large" => TestResponse::ok(r#"{"success":false,"error":"Log exceeds the size limit"}"#),
+            b"content=garbage" => TestResponse::ok("<html>Bad Gateway</html>"),
+            _ => TestResponse::ok(r#"{"success":true,"id":"Ab1cD2e","url":"https://mclo.gs/Ab1cD2e","raw":"https://api.mclo.gs/1/raw/Ab1cD2e"}"#),
+        }).await;
+
+        let url = upload_to(&http_client(), &PasteService::McLogs, &server.url, "[12:00:00] [main/INFO]: Hello & goodbye\n".into()).await.unwrap();
+        assert_eq!(url, "https://mclo.gs/Ab1cD2e");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/1/log");
+        assert_eq!(requests[0].header("content-type"), Some("application/x-www-form-urlencoded"));
+        assert_eq!(requests[0].body, b"content=%5B12%3A00%3A00%5D+%5Bmain%2FINFO%5D%3A+Hello+%26+goodbye%0A");
+
+        let error = upload_to(&http_client(), &PasteService::McLogs, &server.url, "too large".into()).await.unwrap_err();
+        assert!(matches!(error, PasteError::Rejected("mclo.gs", message) if message == "Log exceeds the size limit"));
+
+        let error = upload_to(&http_client(), &PasteService::McLogs, &server.url, "garbage".into()).await.unwrap_err();
+        assert!(matches!(error, PasteError::InvalidResponse("mclo.gs", _)));
+    }
+
+    #[tokio::test]
+    async fn test_hastebin() {
+        let server = TestServer::start(|request| match request.body.as_slice() {
+            b"fail" => TestResponse::status(500),
+            _ => TestResponse::ok(r#"{"key":"moqafebiya"}"#),
+        }).await;
+
+        // Trailing slashes are ignored
+        let service = PasteService::Hastebin { url: format!("{}/", server.url).into() };
+        let url = upload_to(&http_client(), &service, "", "log contents".into()).await.unwrap();
+        assert_eq!(url, format!("{}/moqafebiya", server.url));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/documents");
+        assert_eq!(requests[0].body, b"log contents");
+
+        let error = upload_to(&http_client(), &service, "", "fail".into()).await.unwrap_err();
+        assert!(matches!(error, PasteError::NotOK("Hastebin", status) if status.as_u16() == 500));
+
+        let service = PasteService::Hastebin { url: " ".into() };
+        let error = upload_to(&http_client(), &service, "", "log contents".into()).await.unwrap_err();
+        assert!(matches!(error, PasteError::NotConfigured("Hastebin")));
+    }
+
+    #[tokio::test]
+    async fn test_custom() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/url" => TestResponse::ok(r#"{"url":"https://paste.example.com/view/full"}"#),
+            "/id" => TestResponse::ok(r#"{"id":"json-id"}"#),
+            "/key" => TestResponse::ok(r#"{"key":"json-key"}"#),
+            "/plain" => TestResponse::ok(" plain-key\n"),
+            "/plain-url" => TestResponse::ok("https://paste.example.com/plain-url"),
+            "/created" => TestResponse { status: 201, body: b"created-key".to_vec() },
+            "/empty" => TestResponse::ok(""),
+            _ => TestResponse::status(404),
+        }).await;
+
+        let upload = async |path: &str, view_url: &str| {
+            let service = PasteService::Custom {
+                upload_url: format!("{}{path}", server.url).into(),
+                view_url: Arc::from(view_url),
+            };
+            upload_to(&http_client(), &service, "", "log contents".into()).await
+        };
+
+        let view_url = "https://paste.example.com/view/{key}?raw=1";
+        assert_eq!(upload("/url", view_url).await.unwrap(), "https://paste.example.com/view/full");
+        assert_eq!(upload("/id", view_url).await.unwrap(), "https://paste.example.com/view/json-id?raw=1");
+        assert_eq!(upload("/key", view_url).await.unwrap(), "https://paste.example.com/view/json-key?raw=1");
+        assert_eq!(upload("/plain", view_url).await.unwrap(), "https://paste.example.com/view/plain-key?raw=1");
+        assert_eq!(upload("/plain-url", view_url).await.unwrap(), "https://paste.example.com/plain-url");
+        assert_eq!(upload("/created", view_url).await.unwrap(), "https://paste.example.com/view/created-key?raw=1");
+
+        assert!(matches!(upload("/empty", view_url).await, Err(PasteError::MissingUrl("Custom"))));
+        assert!(matches!(upload("/plain", "https://paste.example.com/view").await, Err(PasteError::MissingUrl("Custom"))));
+        assert!(matches!(upload("/missing", view_url).await, Err(PasteError::NotOK("Custom", _))));
+
+        assert!(server.requests().iter().all(|request| request.method == "POST" && request.body == b"log contents"));
+    }
+
+    #[test]
+    fn test_log_bundle() {
+        let dot_minecraft = std::env::temp_dir().join(format!("paste-bundle-test-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dot_minecraft);
+        for folder in ["mods", "crash-reports", "logs"] {
+            std::fs::create_dir_all(dot_minecraft.join(folder)).unwrap();
+        }
+
+        for name in ["sodium.jar", "Lithium.jar.disabled", "options.txt"] {
+            std::fs::write(dot_minecraft.join("mods").join(name), []).unwrap();
+        }
+        std::fs::write(dot_minecraft.join("crash-reports/crash-2024-01-01_12.00.00-client.txt"), "---- Minecraft Crash Report ----\n\n").unwrap();
+
+        // Rotated logs are gzip compressed, the contents are detected rather than the extension
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"[12:00:00] [main/INFO]: Loading\n").unwrap();
+        std::fs::write(dot_minecraft.join("logs/latest.log"), encoder.finish().unwrap()).unwrap();
+
+        assert_eq!(create_log_bundle(&dot_minecraft), concat!(
+            "==================== Mods ====================\n",
+            "Lithium.jar.disabled\nsodium.jar\n\n",
+            "==================== crash-2024-01-01_12.00.00-client.txt ====================\n",
+            "---- Minecraft Crash Report ----\n\n",
+            "==================== latest.log ====================\n",
+            "[12:00:00] [main/INFO]: Loading\n\n",
+        ));
+
+        std::fs::remove_dir_all(dot_minecraft.join("logs")).unwrap();
+        std::fs::remove_dir_all(dot_minecraft.join("crash-reports")).unwrap();
+        std::fs::remove_dir_all(dot_minecraft.join("mods")).unwrap();
+        let bundle = create_log_bundle(&dot_minecraft);
+        assert!(bundle.starts_with("==================== Mods ====================\nNo mods folder\n\n==================== latest.log ====================\nUnable to read: "));
+
+        std::fs::remove_dir_all(dot_minecraft).unwrap();
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut content = String::from("short");
+        truncate_content(&mut content, 10);
+        assert_eq!(content, "short");
+
+        let mut content = String::from("abcdef");
+        truncate_content(&mut content, 4);
+        assert_eq!(content, "abcd");
+
+        // Characters which would be split are dropped entirely
+        let mut content = String::from("ab€d");
+        truncate_content(&mut content, 4);
+        assert_eq!(content, "ab");
+
+        let mut content = "a".repeat(schema::paste::MCLOGS_MAX_SIZE - 1) + "éé";
+        truncate_content(&mut content, schema::paste::MCLOGS_MAX_SIZE);
+        assert_eq!(content.len(), schema::paste::MCLOGS_MAX_SIZE - 1);
+    }
+}
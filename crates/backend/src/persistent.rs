@@ -54,6 +54,10 @@ impl<T: Serialize + for <'de> Deserialize<'de>> Persistent<T> {
         &self.data
     }
 
+    pub fn set_path(&mut self, path: Arc<Path>) {
+        self.path = path;
+    }
+
     #[inline(always)]
     pub fn sanity_check_path_eq(&self, path: &Path) {
         debug_assert_eq!(path, &*self.path);
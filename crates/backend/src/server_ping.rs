@@ -0,0 +1,526 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use base64::Engine;
+use bridge::instance::ServerStatus;
+use serde::Deserialize;
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpStream};
+
+const DEFAULT_PORT: u16 = 25565;
+const TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_PACKET_LENGTH: usize = 2 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ServerPingError {
+    #[error("Invalid server address")]
+    InvalidAddress,
+    #[error("Timed out")]
+    TimedOut,
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid response from server")]
+    InvalidResponse,
+    #[error("Unable to deserialize status: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    #[serde(default)]
+    description: serde_json::Value,
+    players: Option<StatusPlayers>,
+    version: Option<StatusVersion>,
+    favicon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StatusPlayers {
+    online: i64,
+    max: i64,
+}
+
+#[derive(Deserialize)]
+struct StatusVersion {
+    name: Arc<str>,
+    protocol: i32,
+}
+
+/// Queries the server using the Server List Ping protocol, falling back to the legacy ping used before 1.7
+pub async fn ping_server(address: &str) -> Result<ServerStatus, ServerPingError> {
+    let (host, port) = split_address(address)?;
+
+    // Like the game, the SRV record is only used for addresses without a port and the handshake keeps the original address
+    let target = match port {
+        Some(_) => None,
+        None => tokio::time::timeout(TIMEOUT, crate::dns::lookup_minecraft_srv(host)).await.ok().flatten(),
+    };
+    let port = port.unwrap_or(DEFAULT_PORT);
+    let connect = target.as_ref().map(|(host, port)| (host.as_str(), *port)).unwrap_or((host, port));
+
+    let modern_error = match tokio::time::timeout(TIMEOUT, ping_modern(connect, host, port)).await {
+        Ok(Ok(status)) => return Ok(status),
+        Ok(Err(err)) if !err.is_protocol_mismatch() => return Err(err),
+        Ok(Err(err)) => err,
+        Err(_) => ServerPingError::TimedOut,
+    };
+
+    match tokio::time::timeout(TIMEOUT, ping_legacy(connect)).await {
+        Ok(Ok(status)) => Ok(status),
+        _ => Err(modern_error),
+    }
+}
+
+impl ServerPingError {
+    /// Old servers either close the connection or answer with garbage when receiving a modern handshake
+    fn is_protocol_mismatch(&self) -> bool {
+        match self {
+            ServerPingError::InvalidResponse => true,
+            ServerPingError::IoError(err) => matches!(err.kind(), std::io::ErrorKind::UnexpectedEof | std::io::ErrorKind::ConnectionReset),
+            _ => false,
+        }
+    }
+}
+
+/// Splits the address into the host and the port, if there is one
+fn split_address(address: &str) -> Result<(&str, Option<u16>), ServerPingError> {
+    let address = address.trim_ascii();
+    if address.is_empty() {
+        return Err(ServerPingError::InvalidAddress);
+    }
+
+    if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').ok_or(ServerPingError::InvalidAddress)?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => Some(port.parse().map_err(|_| ServerPingError::InvalidAddress)?),
+            None if rest.is_empty() => None,
+            None => return Err(ServerPingError::InvalidAddress),
+        };
+        return Ok((host, port));
+    }
+
+    match address.rsplit_once(':') {
+        // More than one colon means an IPv6 address without a port
+        Some((host, _)) if host.contains(':') => Ok((address, None)),
+        Some((host, port)) => Ok((host, Some(port.parse().map_err(|_| ServerPingError::InvalidAddress)?))),
+        None => Ok((address, None)),
+    }
+}
+
+async fn ping_modern(connect: (&str, u16), host: &str, port: u16) -> Result<ServerStatus, ServerPingError> {
+    let mut stream = TcpStream::connect(connect).await?;
+    stream.set_nodelay(true)?;
+
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, 0x00);
+    write_varint(&mut handshake, -1);
+    write_varint(&mut handshake, host.len() as i32);
+    handshake.extend_from_slice(host.as_bytes());
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+
+    let mut request = Vec::new();
+    write_packet(&mut request, &handshake);
+    write_packet(&mut request, &[0x00]);
+    stream.write_all(&request).await?;
+
+    let packet = read_packet(&mut stream).await?;
+    let mut packet = packet.as_slice();
+    if read_varint_slice(&mut packet)? != 0x00 {
+        return Err(ServerPingError::InvalidResponse);
+    }
+    let length = read_varint_slice(&mut packet)?;
+    let json = usize::try_from(length).ok().and_then(|length| packet.get(..length)).ok_or(ServerPingError::InvalidResponse)?;
+    let response: StatusResponse = serde_json::from_slice(json)?;
+
+    // Some servers close the connection instead of answering the ping, that shouldn't fail the whole status
+    let latency = measure_latency(&mut stream).await.ok();
+
+    let (players_online, players_max) = response.players.map(|players| (players.online, players.max)).unwrap_or_default();
+    let (version, protocol) = match response.version {
+        Some(version) => (version.name, Some(version.protocol)),
+        None => (Arc::from(""), None),
+    };
+
+    let png_icon = response.favicon.as_ref()
+        .and_then(|favicon| favicon.strip_prefix("data:image/png;base64,"))
+        .and_then(|favicon| base64::engine::general_purpose::STANDARD.decode(favicon.replace('\n', "")).ok())
+        .map(Arc::from);
+
+    let mut motd = String::new();
+    flatten_text_component(&response.description, &mut motd);
+
+    Ok(ServerStatus {
+        motd: strip_formatting(&motd).into(),
+        version: strip_formatting(&version).into(),
+        protocol,
+        players_online,
+        players_max,
+        latency,
+        png_icon,
+    })
+}
+
+async fn measure_latency(stream: &mut TcpStream) -> Result<Duration, ServerPingError> {
+    let start = Instant::now();
+    let payload = chrono::Utc::now().timestamp_millis();
+
+    let mut ping = vec![0x01];
+    ping.extend_from_slice(&payload.to_be_bytes());
+    let mut request = Vec::new();
+    write_packet(&mut request, &ping);
+    stream.write_all(&request).await?;
+
+    let packet = read_packet(stream).await?;
+    if packet.first() != Some(&0x01) || packet.get(1..9) != Some(&payload.to_be_bytes()[..]) {
+        return Err(ServerPingError::InvalidResponse);
+    }
+
+    Ok(start.elapsed())
+}
+
+async fn ping_legacy(connect: (&str, u16)) -> Result<ServerStatus, ServerPingError> {
+    let start = Instant::now();
+    let mut stream = TcpStream::connect(connect).await?;
+
+    stream.write_all(&[0xFE, 0x01]).await?;
+
+    let mut header = [0_u8; 3];
+    stream.read_exact(&mut header).await?;
+    let latency = start.elapsed();
+    if header[0] != 0xFF {
+        return Err(ServerPingError::InvalidResponse);
+    }
+
+    let length = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let mut data = vec![0_u8; length * 2];
+    stream.read_exact(&mut data).await?;
+
+    let utf16: Vec<u16> = data.chunks_exact(2).map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]])).collect();
+    let response = String::from_utf16(&utf16).map_err(|_| ServerPingError::InvalidResponse)?;
+
+    if let Some(response) = response.strip_prefix("§1\0") {
+        // 1.4 - 1.6: protocol, version, motd, online, max
+        let mut fields = response.split('\0');
+        let protocol = fields.next().and_then(|protocol| protocol.parse().ok());
+        let version = fields.next().unwrap_or_default();
+        let motd = fields.next().unwrap_or_default();
+        let players_online = fields.next().and_then(|online| online.parse().ok()).unwrap_or_default();
+        let players_max = fields.next().and_then(|max| max.parse().ok()).unwrap_or_default();
+
+        Ok(ServerStatus {
+            motd: strip_formatting(motd).into(),
+            version: version.into(),
+            protocol,
+            players_online,
+            players_max,
+            latency: Some(latency),
+            png_icon: None,
+        })
+    } else {
+        // Beta 1.8 - 1.3: motd§online§max
+        let mut fields = response.rsplitn(3, '§');
+        let players_max = fields.next().and_then(|max| max.parse().ok()).unwrap_or_default();
+        let players_online = fields.next().and_then(|online| online.parse().ok()).unwrap_or_default();
+        let motd = fields.next().ok_or(ServerPingError::InvalidResponse)?;
+
+        Ok(ServerStatus {
+            motd: motd.into(),
+            version: "".into(),
+            protocol: None,
+            players_online,
+            players_max,
+            latency: Some(latency),
+            png_icon: None,
+        })
+    }
+}
+
+fn flatten_text_component(component: &serde_json::Value, out: &mut String) {
+    match component {
+        serde_json::Value::String(text) => out.push_str(text),
+        serde_json::Value::Array(components) => {
+            for component in components {
+                flatten_text_component(component, out);
+            }
+        },
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::String(text)) = object.get("text") {
+                out.push_str(text);
+            }
+            if let Some(extra) = object.get("extra") {
+                flatten_text_component(extra, out);
+            }
+        },
+        _ => {},
+    }
+}
+
+fn strip_formatting(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+fn write_packet(buf: &mut Vec<u8>, packet: &[u8]) {
+    write_varint(buf, packet.len() as i32);
+    buf.extend_from_slice(packet);
+}
+
+fn read_varint_slice(buf: &mut &[u8]) -> Result<i32, ServerPingError> {
+    let mut value: u32 = 0;
+    for position in 0..5 {
+        let (&byte, rest) = buf.split_first().ok_or(ServerPingError::InvalidResponse)?;
+        *buf = rest;
+        value |= ((byte & 0x7F) as u32) << (position * 7);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(ServerPingError::InvalidResponse)
+}
+
+async fn read_packet(stream: &mut TcpStream) -> Result<Vec<u8>, ServerPingError> {
+    let mut length: u32 = 0;
+    for position in 0..5 {
+        let byte = stream.read_u8().await?;
+        length |= ((byte & 0x7F) as u32) << (position * 7);
+        if byte & 0x80 == 0 {
+            let length = length as usize;
+            if length == 0 || length > MAX_PACKET_LENGTH {
+                return Err(ServerPingError::InvalidResponse);
+            }
+            let mut packet = vec![0_u8; length];
+            stream.read_exact(&mut packet).await?;
+            return Ok(packet);
+        }
+    }
+    Err(ServerPingError::InvalidResponse)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use base64::Engine;
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+    use super::{ping_server, read_packet, read_varint_slice, split_address, write_packet, write_varint};
+
+    const PONG_DELAY: Duration = Duration::from_millis(50);
+
+    async fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        (listener, address)
+    }
+
+    /// Reads the handshake and status request, checking they're framed as expected
+    async fn read_status_request(stream: &mut TcpStream, port: u16) {
+        let handshake = read_packet(stream).await.unwrap();
+        let mut handshake = handshake.as_slice();
+        assert_eq!(read_varint_slice(&mut handshake).unwrap(), 0x00);
+        assert_eq!(read_varint_slice(&mut handshake).unwrap(), -1);
+        assert_eq!(read_varint_slice(&mut handshake).unwrap(), 9);
+        assert_eq!(&handshake[..9], b"127.0.0.1");
+        assert_eq!(&handshake[9..11], &port.to_be_bytes());
+        handshake = &handshake[11..];
+        assert_eq!(read_varint_slice(&mut handshake).unwrap(), 1);
+        assert!(handshake.is_empty());
+
+        assert_eq!(read_packet(stream).await.unwrap(), [0x00]);
+    }
+
+    async fn write_status_response(stream: &mut TcpStream, json: &str) {
+        let mut packet = Vec::new();
+        write_varint(&mut packet, 0x00);
+        write_varint(&mut packet, json.len() as i32);
+        packet.extend_from_slice(json.as_bytes());
+
+        let mut response = Vec::new();
+        write_packet(&mut response, &packet);
+        stream.write_all(&response).await.unwrap();
+    }
+
+    fn legacy_response(fields: &str) -> Vec<u8> {
+        let utf16: Vec<u16> = fields.encode_utf16().collect();
+        let mut response = vec![0xFF];
+        response.extend_from_slice(&(utf16.len() as u16).to_be_bytes());
+        for unit in utf16 {
+            response.extend_from_slice(&unit.to_be_bytes());
+        }
+        response
+    }
+
+    #[test]
+    fn test_varint() {
+        let cases: &[(i32, &[u8])] = &[
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (255, &[0xFF, 0x01]),
+            (25565, &[0xDD, 0xC7, 0x01]),
+            (2097151, &[0xFF, 0xFF, 0x7F]),
+            (i32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0x07]),
+            (-1, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+            (i32::MIN, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+        ];
+        for (value, encoded) in cases {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, *value);
+            assert_eq!(buf, *encoded, "encoding {value}");
+
+            let mut slice = *encoded;
+            assert_eq!(read_varint_slice(&mut slice).unwrap(), *value);
+            assert!(slice.is_empty());
+        }
+
+        assert!(read_varint_slice(&mut &[0x80, 0x80][..]).is_err());
+        assert!(read_varint_slice(&mut &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01][..]).is_err());
+    }
+
+    #[test]
+    fn test_split_address() {
+        assert_eq!(split_address("play.example.com").unwrap(), ("play.example.com", None));
+        assert_eq!(split_address(" play.example.com:25570 ").unwrap(), ("play.example.com", Some(25570)));
+        assert_eq!(split_address("play.example.com:25565").unwrap(), ("play.example.com", Some(25565)));
+        assert_eq!(split_address("[::1]:25570").unwrap(), ("::1", Some(25570)));
+        assert_eq!(split_address("[::1]").unwrap(), ("::1", None));
+        assert_eq!(split_address("::1").unwrap(), ("::1", None));
+        assert!(split_address("").is_err());
+        assert!(split_address("example.com:port").is_err());
+        assert!(split_address("[::1]25565").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ping_modern() {
+        let (listener, address) = listen().await;
+        let port = listener.local_addr().unwrap().port();
+
+        let icon = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        let favicon = format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(icon));
+        let json = serde_json::json!({
+            "version": { "name": "§aPaper 1.21.1", "protocol": 767 },
+            "players": { "max": 100, "online": 5 },
+            "description": { "text": "§6A ", "extra": [{ "text": "Minecraft" }, " Server"] },
+            "favicon": favicon,
+        }).to_string();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_status_request(&mut stream, port).await;
+            write_status_response(&mut stream, &json).await;
+
+            let ping = read_packet(&mut stream).await.unwrap();
+            assert_eq!(ping.len(), 9);
+            assert_eq!(ping[0], 0x01);
+            tokio::time::sleep(PONG_DELAY).await;
+
+            let mut response = Vec::new();
+            write_packet(&mut response, &ping);
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let status = ping_server(&address).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(&*status.motd, "A Minecraft Server");
+        assert_eq!(&*status.version, "Paper 1.21.1");
+        assert_eq!(status.protocol, Some(767));
+        assert_eq!(status.players_online, 5);
+        assert_eq!(status.players_max, 100);
+        assert_eq!(status.png_icon.as_deref(), Some(&icon[..]));
+        let latency = status.latency.unwrap();
+        assert!(latency >= PONG_DELAY && latency < Duration::from_secs(5), "latency {latency:?}");
+    }
+
+    #[tokio::test]
+    async fn test_ping_modern_without_pong() {
+        let (listener, address) = listen().await;
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_status_request(&mut stream, port).await;
+            write_status_response(&mut stream, r#"{"description":"Plain motd","players":{"max":20,"online":0}}"#).await;
+        });
+
+        let status = ping_server(&address).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(&*status.motd, "Plain motd");
+        assert_eq!(status.players_max, 20);
+        assert_eq!(status.protocol, None);
+        assert_eq!(status.latency, None);
+    }
+
+    #[tokio::test]
+    async fn test_ping_legacy_fallback() {
+        let (listener, address) = listen().await;
+
+        let server = tokio::spawn(async move {
+            // Pre-1.7 servers don't understand the handshake and close the connection
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0_u8; 64];
+            _ = stream.read(&mut buf).await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0_u8; 2];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [0xFE, 0x01]);
+            stream.write_all(&legacy_response("§1\x0078\x001.6.4\x00A §aLegacy§r Server\x003\x0016")).await.unwrap();
+        });
+
+        let status = ping_server(&address).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(&*status.motd, "A Legacy Server");
+        assert_eq!(&*status.version, "1.6.4");
+        assert_eq!(status.protocol, Some(78));
+        assert_eq!(status.players_online, 3);
+        assert_eq!(status.players_max, 16);
+        assert!(status.latency.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_ping_legacy_beta() {
+        let (listener, address) = listen().await;
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0_u8; 2];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(&legacy_response("Beta server§7§20")).await.unwrap();
+        });
+
+        let status = ping_server(&address).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(&*status.motd, "Beta server");
+        assert_eq!(status.protocol, None);
+        assert_eq!(status.players_online, 7);
+        assert_eq!(status.players_max, 20);
+    }
+}
@@ -6,9 +6,41 @@ use enumset::EnumSet;
 use rustc_hash::FxHashMap;
 use strum::IntoEnumIterator;
 
-use crate::directories::LauncherDirectories;
+use crate::{directories::LauncherDirectories, BackendState};
+
+impl BackendState {
+    /// The targets of a sync group, `None` being the default group
+    pub fn sync_targets(&self, group: Option<&str>) -> Option<EnumSet<SyncTarget>> {
+        let mut config = self.config.write();
+        let config = config.get();
+        match group {
+            None => Some(config.sync_targets),
+            Some(group) => config.sync_groups.iter().find(|sync_group| &*sync_group.name == group).map(|sync_group| sync_group.sync_targets),
+        }
+    }
+
+    /// The .minecraft folders of every instance in the sync group
+    pub fn sync_group_members(&self, group: Option<&str>) -> Vec<Arc<Path>> {
+        let mut instance_state = self.instance_state.write();
+        instance_state.instances.iter_mut()
+            .filter_map(|instance| instance.configuration.get().sync_group.is_member_of(group).then(|| instance.dot_minecraft_path.clone()))
+            .collect()
+    }
+}
+
+pub fn sync_group_dir(directories: &LauncherDirectories, group: Option<&str>) -> PathBuf {
+    match group {
+        None => directories.synced_dir.to_path_buf(),
+        Some(group) => directories.synced_dir.join("groups").join(group),
+    }
+}
 
-pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &LauncherDirectories, dot_minecraft: Arc<Path>) {
+pub fn is_valid_sync_group_name(name: &str) -> bool {
+    !name.is_empty() && name.trim_ascii() == name && name != "." && name != ".." && !name.eq_ignore_ascii_case("default")
+        && !name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|'])
+}
+
+pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, sync_dir: &Path, members: &[Arc<Path>], dot_minecraft: Arc<Path>) {
     _ = std::fs::create_dir_all(&dot_minecraft);
 
     for target in SyncTarget::iter() {
@@ -21,7 +53,7 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
                 sync_folder
             };
 
-            let target_dir = directories.synced_dir.join(non_hidden_sync_folder);
+            let target_dir = sync_dir.join(non_hidden_sync_folder);
 
             let path = dot_minecraft.join(sync_folder);
 
@@ -35,13 +67,13 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
         } else if want {
             match target {
                 SyncTarget::Options => {
-                    let fallback = &directories.synced_dir.join("fallback_options.txt");
-                    let combined = create_combined_options_txt(fallback, directories);
+                    let fallback = &sync_dir.join("fallback_options.txt");
+                    let combined = create_combined_options_txt(fallback, members);
                     _ = crate::write_safe(&fallback, combined.as_bytes());
                     _ = crate::write_safe(&dot_minecraft.join("options.txt"), combined.as_bytes());
                 },
                 SyncTarget::Servers => {
-                    if let Some(latest) = find_latest("servers.dat", directories) {
+                    if let Some(latest) = find_latest("servers.dat", members) {
                         let target = dot_minecraft.join("servers.dat");
                         if latest != target {
                             _ = std::fs::copy(latest, target);
@@ -49,7 +81,7 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
                     }
                 },
                 SyncTarget::Commands => {
-                    if let Some(latest) = find_latest("command_history.txt", directories) {
+                    if let Some(latest) = find_latest("command_history.txt", members) {
                         let target = dot_minecraft.join("command_history.txt");
                         if latest != target {
                             _ = std::fs::copy(latest, target);
@@ -57,7 +89,7 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
                     }
                 },
                 SyncTarget::Hotbars => {
-                    if let Some(latest) = find_latest("hotbar.nbt", directories) {
+                    if let Some(latest) = find_latest("hotbar.nbt", members) {
                         let target = dot_minecraft.join("hotbar.nbt");
                         if latest != target {
                             _ = std::fs::copy(latest, target);
@@ -72,20 +104,12 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
     }
 }
 
-fn find_latest(filename: &'static str, directories: &LauncherDirectories) -> Option<PathBuf> {
+fn find_latest(filename: &'static str, members: &[Arc<Path>]) -> Option<PathBuf> {
     let mut latest_time = SystemTime::UNIX_EPOCH;
     let mut latest_path = None;
 
-    let read_dir = std::fs::read_dir(&directories.instances_dir).ok()?;
-
-    for entry in read_dir {
-        let Ok(entry) = entry else {
-            continue;
-        };
-
-        let mut path = entry.path();
-        path.push(".minecraft");
-        path.push(filename);
+    for dot_minecraft in members {
+        let path = dot_minecraft.join(filename);
 
         if let Ok(metadata) = std::fs::metadata(&path) {
             let mut time = SystemTime::UNIX_EPOCH;
@@ -107,23 +131,13 @@ fn find_latest(filename: &'static str, directories: &LauncherDirectories) -> Opt
     latest_path
 }
 
-fn create_combined_options_txt(fallback: &Path, directories: &LauncherDirectories) -> String {
+fn create_combined_options_txt(fallback: &Path, members: &[Arc<Path>]) -> String {
     let mut values = read_options_txt(fallback);
 
-    let Ok(read_dir) = std::fs::read_dir(&directories.instances_dir) else {
-        return create_options_txt(values);
-    };
-
     let mut paths = Vec::new();
 
-    for entry in read_dir {
-        let Ok(entry) = entry else {
-            continue;
-        };
-
-        let mut path = entry.path();
-        path.push(".minecraft");
-        path.push("options.txt");
+    for dot_minecraft in members {
+        let path = dot_minecraft.join("options.txt");
 
         let mut time = SystemTime::UNIX_EPOCH;
 
@@ -180,17 +194,8 @@ fn read_options_txt(path: &Path) -> FxHashMap<String, String> {
     values
 }
 
-pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDirectories) -> std::io::Result<SyncState> {
-    let mut paths = Vec::new();
-
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        paths.push(path);
-    }
-
-    let total = paths.len();
+pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, sync_dir: &Path, members: &[Arc<Path>], groups: Vec<Arc<str>>) -> SyncState {
+    let total = members.len();
     let mut synced = EnumMap::default();
     let mut cannot_sync = EnumMap::default();
 
@@ -210,13 +215,13 @@ pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDire
             sync_folder
         };
 
-        let target_dir = directories.synced_dir.join(non_hidden_sync_folder);
+        let target_dir = sync_dir.join(non_hidden_sync_folder);
 
         let mut synced_count = 0;
         let mut cannot_sync_count = 0;
 
-        for path in &paths {
-            let path = path.join(sync_folder);
+        for dot_minecraft in members {
+            let path = dot_minecraft.join(sync_folder);
 
             if linking::is_targeting(&target_dir, &path) {
                 synced_count += 1;
@@ -229,29 +234,22 @@ pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDire
         cannot_sync[target] = cannot_sync_count;
     }
 
-    Ok(SyncState {
-        sync_folder: Some(directories.synced_dir.clone()),
+    SyncState {
+        sync_folder: Some(sync_dir.into()),
+        groups,
         want_sync,
         total,
         synced,
         cannot_sync
-    })
+    }
 }
 
-pub fn enable_all(target: SyncTarget, directories: &LauncherDirectories) -> std::io::Result<bool> {
+pub fn enable_all(target: SyncTarget, sync_dir: &Path, members: &[Arc<Path>]) -> std::io::Result<bool> {
     let Some(sync_folder) = target.get_folder() else {
         return Ok(true);
     };
 
-    let mut paths = Vec::new();
-
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        path.push(sync_folder);
-        paths.push(path);
-    }
+    let mut paths = members.iter().map(|dot_minecraft| dot_minecraft.join(sync_folder)).collect::<Vec<_>>();
 
     let non_hidden_sync_folder = if sync_folder.starts_with(".") {
         &sync_folder[1..]
@@ -259,7 +257,7 @@ pub fn enable_all(target: SyncTarget, directories: &LauncherDirectories) -> std:
         sync_folder
     };
 
-    let target_dir = directories.synced_dir.join(non_hidden_sync_folder);
+    let target_dir = sync_dir.join(non_hidden_sync_folder);
 
     // Exclude links that already point to target_dir
     paths.retain(|path| {
@@ -283,20 +281,12 @@ pub fn enable_all(target: SyncTarget, directories: &LauncherDirectories) -> std:
     Ok(true)
 }
 
-pub fn disable_all(target: SyncTarget, directories: &LauncherDirectories) -> std::io::Result<()> {
+pub fn disable_all(target: SyncTarget, sync_dir: &Path, members: &[Arc<Path>]) -> std::io::Result<()> {
     let Some(sync_folder) = target.get_folder() else {
         return Ok(());
     };
 
-    let mut paths = Vec::new();
-
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        path.push(sync_folder);
-        paths.push(path);
-    }
+    let paths = members.iter().map(|dot_minecraft| dot_minecraft.join(sync_folder)).collect::<Vec<_>>();
 
     let non_hidden_sync_folder = if sync_folder.starts_with(".") {
         &sync_folder[1..]
@@ -304,7 +294,7 @@ pub fn disable_all(target: SyncTarget, directories: &LauncherDirectories) -> std
         sync_folder
     };
 
-    let target_dir = directories.synced_dir.join(non_hidden_sync_folder);
+    let target_dir = sync_dir.join(non_hidden_sync_folder);
 
     for path in &paths {
         linking::unlink_dir_if_targeting(&target_dir, path)?;
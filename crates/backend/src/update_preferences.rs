@@ -0,0 +1,282 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use bridge::{
+    instance::{ContentUpdateStatus, InstanceID, InstanceModID, ModSummary},
+    message::MessageToFrontend,
+};
+use schema::{
+    content::ContentSource,
+    instance::ModUpdatePreference,
+    modrinth::{ModrinthLoader, ModrinthProjectVersion, ModrinthProjectVersionsRequest},
+};
+use ustr::Ustr;
+
+use crate::{
+    metadata::{items::ModrinthProjectVersionsMetadataItem, manager::MetaLoadError},
+    mod_metadata::ModUpdateAction,
+    BackendState,
+};
+
+impl BackendState {
+    pub async fn set_mod_update_preference(&self, id: InstanceID, mod_id: Arc<str>, preference: ModUpdatePreference) {
+        let (loader, minecraft_version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            instance.configuration.modify(|configuration| {
+                if preference.is_default() {
+                    configuration.mod_update_preferences.remove(&mod_id);
+                } else {
+                    configuration.mod_update_preferences.insert(mod_id.clone(), preference.clone());
+                }
+            });
+            let configuration = instance.configuration.get();
+            (configuration.loader, configuration.minecraft_version)
+        } else {
+            self.send.send_error("Can't change update preference, unknown instance id");
+            return;
+        };
+
+        let Some(mods) = self.clone().load_instance_mods(id).await else {
+            return;
+        };
+
+        // Re-evaluate the mod's last update check so the change shows up without checking again
+        for summary in mods.iter().filter(|summary| summary.mod_summary.id == mod_id) {
+            let mod_summary = &summary.mod_summary;
+            let status = mod_summary.update_status.load(Ordering::Relaxed);
+            let previous = self.mod_metadata_manager.updates.read().get(&mod_summary.hash).cloned();
+            let source = self.mod_metadata_manager.read_content_sources().get(&mod_summary.hash).copied();
+
+            let action = if preference.pinned {
+                Some(ModUpdateAction::Pinned)
+            } else if status == ContentUpdateStatus::Unknown {
+                None
+            } else if source == Some(ContentSource::Modrinth) {
+                self.preferred_modrinth_update_action(mod_summary, loader.as_modrinth_loader(), minecraft_version, &preference).await.ok()
+            } else {
+                previous
+                    .filter(|previous| !matches!(previous, ModUpdateAction::Pinned))
+                    .map(|previous| apply_update_preference(previous, &preference))
+            };
+
+            match action {
+                Some(action) => {
+                    mod_summary.update_status.store(action.to_status(), Ordering::Relaxed);
+                    self.mod_metadata_manager.updates.write().insert(mod_summary.hash, action);
+                },
+                None => {
+                    mod_summary.update_status.store(ContentUpdateStatus::Unknown, Ordering::Relaxed);
+                    self.mod_metadata_manager.updates.write().remove(&mod_summary.hash);
+                },
+            }
+        }
+
+        self.send.send(MessageToFrontend::Refresh);
+    }
+
+    pub async fn skip_mod_update(&self, id: InstanceID, mod_id: InstanceModID) {
+        let (summary, mut preference) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let Some(summary) = instance.try_get_mod(mod_id) else {
+                self.send.send_error("Can't skip update, unknown mod id");
+                return;
+            };
+            let summary = summary.mod_summary.clone();
+            let preference = instance.configuration.get().mod_update_preferences.get(&summary.id).cloned().unwrap_or_default();
+            (summary, preference)
+        } else {
+            self.send.send_error("Can't skip update, unknown instance id");
+            return;
+        };
+
+        let sha1 = match self.mod_metadata_manager.updates.read().get(&summary.hash) {
+            Some(ModUpdateAction::Modrinth(file)) => Some(file.hashes.sha1.clone()),
+            Some(ModUpdateAction::CurseForge(file)) => file.sha1().cloned(),
+            _ => None,
+        };
+        let Some(sha1) = sha1 else {
+            self.send.send_error("Can't skip update, no update was found for the mod");
+            return;
+        };
+
+        preference.skipped_updates = preference.skipped_updates.iter().cloned().chain(std::iter::once(sha1)).collect();
+        self.set_mod_update_preference(id, summary.id.clone(), preference).await;
+    }
+
+    /// Like [`BackendState::modrinth_update_action`], but falls back to older versions when the newest one is
+    /// skipped or from a less stable channel than the user wants
+    pub async fn preferred_modrinth_update_action(&self, summary: &ModSummary, loader: ModrinthLoader, minecraft_version: Ustr, preference: &ModUpdatePreference) -> Result<ModUpdateAction, MetaLoadError> {
+        if preference.pinned {
+            return Ok(ModUpdateAction::Pinned);
+        }
+
+        let Some(latest) = self.modrinth_update_version(summary, loader, minecraft_version).await? else {
+            return Ok(ModUpdateAction::ErrorNotFound);
+        };
+        if let Some(action) = version_update_action(summary, &latest.0, preference) {
+            return Ok(action);
+        }
+
+        let versions = self.meta.fetch(&ModrinthProjectVersionsMetadataItem(&ModrinthProjectVersionsRequest {
+            project_id: latest.0.project_id.clone(),
+            game_versions: Some([minecraft_version.as_str().into()].into()),
+            loaders: Some([loader].into()),
+        })).await?;
+
+        // Versions are sorted newest first, so reaching the installed version means there is nothing newer that is wanted
+        for version in versions.0.iter() {
+            if let Some(action) = version_update_action(summary, version, preference) {
+                return Ok(action);
+            }
+        }
+
+        Ok(ModUpdateAction::AlreadyUpToDate)
+    }
+}
+
+/// Applies the preference to an update found without considering it
+pub(crate) fn apply_update_preference(action: ModUpdateAction, preference: &ModUpdatePreference) -> ModUpdateAction {
+    match action {
+        _ if preference.pinned => ModUpdateAction::Pinned,
+        ModUpdateAction::Modrinth(file) if preference.is_skipped(&file.hashes.sha1) => ModUpdateAction::AlreadyUpToDate,
+        ModUpdateAction::CurseForge(file) if file.sha1().is_some_and(|sha1| preference.is_skipped(sha1)) => ModUpdateAction::AlreadyUpToDate,
+        action => action,
+    }
+}
+
+/// Returns `None` if the preference rejects the version
+fn version_update_action(summary: &ModSummary, version: &ModrinthProjectVersion, preference: &ModUpdatePreference) -> Option<ModUpdateAction> {
+    let install_file = version.files.iter().find(|file| file.primary).or(version.files.first())?;
+
+    let mut hash = [0u8; 20];
+    let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut hash) else {
+        return Some(ModUpdateAction::ErrorInvalidHash);
+    };
+
+    if hash == summary.hash {
+        Some(ModUpdateAction::AlreadyUpToDate)
+    } else if !preference.accepts(version.version_type) || preference.is_skipped(&install_file.hashes.sha1) {
+        None
+    } else {
+        Some(ModUpdateAction::Modrinth(install_file.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge::instance::{AtomicContentUpdateStatus, LoaderSpecificModSummary};
+    use schema::{curseforge::CurseForgeFile, modrinth::{ModrinthFile, ModrinthVersionType}};
+
+    use super::*;
+
+    const INSTALLED: &str = "1111111111111111111111111111111111111111";
+    const UPDATE: &str = "2222222222222222222222222222222222222222";
+
+    fn summary() -> ModSummary {
+        let mut hash = [0u8; 20];
+        hex::decode_to_slice(INSTALLED, &mut hash).unwrap();
+        ModSummary {
+            id: "example".into(),
+            hash,
+            name: "Example".into(),
+            lowercase_search_key: "example".into(),
+            version_str: "1.0".into(),
+            authors: "".into(),
+            png_icon: None,
+            update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+            provides: [].into(),
+            dependencies: [].into(),
+            extra: LoaderSpecificModSummary::Fabric,
+        }
+    }
+
+    fn modrinth_file(sha1: &str) -> ModrinthFile {
+        serde_json::from_value(serde_json::json!({
+            "hashes": { "sha1": sha1 },
+            "url": "https://cdn.modrinth.com/example.jar",
+            "filename": "example.jar",
+            "primary": true,
+            "size": 1,
+        })).unwrap()
+    }
+
+    fn version(sha1: &str, version_type: Option<&str>) -> ModrinthProjectVersion {
+        serde_json::from_value(serde_json::json!({
+            "id": "version",
+            "project_id": "project",
+            "version_type": version_type,
+            "files": [{
+                "hashes": { "sha1": sha1 },
+                "url": "https://cdn.modrinth.com/example.jar",
+                "filename": "example.jar",
+                "primary": true,
+                "size": 1,
+            }],
+        })).unwrap()
+    }
+
+    fn skipping(sha1: &str) -> ModUpdatePreference {
+        ModUpdatePreference { skipped_updates: [sha1.into()].into(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_apply_pinned() {
+        let pinned = ModUpdatePreference { pinned: true, ..Default::default() };
+        assert!(matches!(apply_update_preference(ModUpdateAction::Modrinth(modrinth_file(UPDATE)), &pinned), ModUpdateAction::Pinned));
+        assert!(matches!(apply_update_preference(ModUpdateAction::AlreadyUpToDate, &pinned), ModUpdateAction::Pinned));
+    }
+
+    #[test]
+    fn test_apply_skipped() {
+        let action = apply_update_preference(ModUpdateAction::Modrinth(modrinth_file(UPDATE)), &skipping(UPDATE));
+        assert!(matches!(action, ModUpdateAction::AlreadyUpToDate));
+
+        let action = apply_update_preference(ModUpdateAction::Modrinth(modrinth_file(UPDATE)), &skipping(INSTALLED));
+        assert!(matches!(action, ModUpdateAction::Modrinth(file) if &*file.hashes.sha1 == UPDATE));
+
+        let curseforge: CurseForgeFile = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "modId": 2,
+            "displayName": "Example 2.0",
+            "fileName": "example.jar",
+            "releaseType": 1,
+            "hashes": [{ "value": "ABCDEF0000000000000000000000000000000000", "algo": 1 }],
+            "fileLength": 1,
+            "downloadUrl": null,
+        })).unwrap();
+        let action = apply_update_preference(ModUpdateAction::CurseForge(curseforge), &skipping("abcdef0000000000000000000000000000000000"));
+        assert!(matches!(action, ModUpdateAction::AlreadyUpToDate));
+
+        let action = apply_update_preference(ModUpdateAction::ErrorNotFound, &skipping(UPDATE));
+        assert!(matches!(action, ModUpdateAction::ErrorNotFound));
+    }
+
+    #[test]
+    fn test_version_already_installed() {
+        // The installed version is up to date even if the preference wouldn't accept it as an update
+        let release_only = ModUpdatePreference { channel: Some(ModrinthVersionType::Release), ..Default::default() };
+        let action = version_update_action(&summary(), &version(INSTALLED, Some("alpha")), &release_only);
+        assert!(matches!(action, Some(ModUpdateAction::AlreadyUpToDate)));
+    }
+
+    #[test]
+    fn test_version_channel() {
+        let beta = ModUpdatePreference { channel: Some(ModrinthVersionType::Beta), ..Default::default() };
+        assert!(matches!(version_update_action(&summary(), &version(UPDATE, Some("release")), &beta), Some(ModUpdateAction::Modrinth(_))));
+        assert!(matches!(version_update_action(&summary(), &version(UPDATE, Some("beta")), &beta), Some(ModUpdateAction::Modrinth(_))));
+        assert!(version_update_action(&summary(), &version(UPDATE, Some("alpha")), &beta).is_none());
+        assert!(version_update_action(&summary(), &version(UPDATE, None), &beta).is_none());
+
+        let any = ModUpdatePreference::default();
+        assert!(matches!(version_update_action(&summary(), &version(UPDATE, None), &any), Some(ModUpdateAction::Modrinth(_))));
+    }
+
+    #[test]
+    fn test_version_skipped() {
+        assert!(version_update_action(&summary(), &version(UPDATE, Some("release")), &skipping(UPDATE)).is_none());
+        assert!(version_update_action(&summary(), &version(&UPDATE.to_ascii_uppercase(), Some("release")), &skipping(UPDATE)).is_none());
+    }
+
+    #[test]
+    fn test_version_invalid_hash() {
+        let action = version_update_action(&summary(), &version("not a hash", Some("release")), &ModUpdatePreference::default());
+        assert!(matches!(action, Some(ModUpdateAction::ErrorInvalidHash)));
+    }
+}
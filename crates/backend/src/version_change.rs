@@ -0,0 +1,363 @@
+use std::{ffi::OsStr, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    instance::{InstanceID, InstanceModSummary, VersionChangeCompatibility, VersionChangeModReport, VersionChangeReport, VersionChangeTarget},
+    modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType},
+};
+use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthFile, ModrinthLoader}};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ustr::Ustr;
+
+use crate::{install_content::ContentInstallError, metadata::manager::MetaLoadError, mod_metadata::ModUpdateAction, BackendState};
+
+const ROLLBACK_FOLDER: &str = "version_rollback";
+const ROLLBACK_JSON: &str = "rollback.json";
+
+/// A mod file and either its replacement as (library path, new path), or None to disable it
+type ModSwap = (Arc<Path>, Option<(PathBuf, PathBuf)>);
+
+#[derive(Error, Debug)]
+pub enum VersionChangeError {
+    #[error("Unknown instance")]
+    UnknownInstance,
+    #[error("Instance is running")]
+    InstanceRunning,
+    #[error("Instance already uses this version and loader")]
+    Unchanged,
+    #[error("There is no version change to revert")]
+    NoRollback,
+    #[error("Error checking mod compatibility:\n{0}")]
+    MetaLoadError(#[from] MetaLoadError),
+    #[error("Error downloading mod:\n{0}")]
+    ContentInstallError(#[from] ContentInstallError),
+    #[error("An I/O error occured while swapping mods:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("A serialization error occured while saving the rollback:\n{0}")]
+    SerdeError(#[from] serde_json::Error),
+}
+
+enum ModMigration {
+    Keep,
+    Replace(ModrinthFile),
+    Incompatible,
+    Unchecked,
+}
+
+/// Everything needed to undo a version change, stored in the instance folder next to the original mod jars
+#[derive(Serialize, Deserialize)]
+struct VersionRollback {
+    minecraft_version: Ustr,
+    loader: Loader,
+    /// Original paths of the replaced mods and where they were moved to, relative to the instance folder
+    /// so the rollback keeps working after the instance is renamed
+    moved: Vec<(PathBuf, PathBuf)>,
+    /// Files created by the version change, relative to the instance folder
+    added: Vec<PathBuf>,
+}
+
+impl VersionRollback {
+    /// Undoes the file changes, keeping going on errors so as much as possible is restored
+    fn restore_files(&self, root_path: &Path) -> std::io::Result<()> {
+        let mut result = Ok(());
+        for added in self.added.iter().rev() {
+            if let Err(error) = std::fs::remove_file(root_path.join(added)) && error.kind() != std::io::ErrorKind::NotFound {
+                result = Err(error);
+            }
+        }
+        for (original, moved) in self.moved.iter().rev() {
+            if let Err(error) = std::fs::rename(root_path.join(moved), root_path.join(original)) {
+                result = Err(error);
+            }
+        }
+        result
+    }
+}
+
+impl BackendState {
+    pub async fn check_instance_version_change(&self, id: InstanceID, target: VersionChangeTarget) -> Result<VersionChangeReport, VersionChangeError> {
+        let (mods, migrations) = self.check_mod_migrations(id, target).await?;
+
+        let mods = mods.iter().zip(migrations.iter()).map(|(summary, migration)| {
+            let compatibility = match migration {
+                ModMigration::Keep => VersionChangeCompatibility::Compatible,
+                ModMigration::Replace(file) => VersionChangeCompatibility::Update { filename: file.filename.clone() },
+                ModMigration::Incompatible => VersionChangeCompatibility::Incompatible,
+                ModMigration::Unchecked => VersionChangeCompatibility::Unchecked,
+            };
+            VersionChangeModReport {
+                name: summary.mod_summary.name.clone(),
+                filename: summary.filename.clone(),
+                enabled: summary.enabled,
+                compatibility,
+            }
+        }).collect();
+
+        Ok(VersionChangeReport { target, mods })
+    }
+
+    pub async fn change_instance_version(&self, id: InstanceID, target: VersionChangeTarget, disable_incompatible: bool, modal_action: &ModalAction) -> Result<(), VersionChangeError> {
+        let (mods, migrations) = self.check_mod_migrations(id, target).await?;
+
+        let (root_path, previous) = self.get_version_change_instance(id, target)?;
+
+        let tracker = ProgressTracker::new("Downloading mods".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        // Everything is downloaded before any file is touched, so a failed download leaves the instance as it was
+        let mut downloads = Vec::new();
+        let mut disabled = Vec::new();
+        for (summary, migration) in mods.iter().zip(migrations.iter()) {
+            match migration {
+                ModMigration::Replace(file) => {
+                    let tracker = &tracker;
+                    downloads.push(async move {
+                        let (library_path, hash, _) = self.download_file_into_library(Path::new(&*file.filename).into(),
+                            &file.url, &file.hashes.sha1, file.size, tracker).await?;
+                        let mut path = summary.path.with_file_name(&*file.filename);
+                        if !summary.enabled {
+                            path.add_extension("disabled");
+                        }
+                        Ok::<_, ContentInstallError>(((summary.path.clone(), Some((library_path, path))), hash))
+                    });
+                },
+                ModMigration::Incompatible if disable_incompatible && summary.enabled => {
+                    disabled.push((summary.path.clone(), None));
+                },
+                _ => {},
+            }
+        }
+
+        let downloaded = futures::future::try_join_all(downloads).await;
+        tracker.set_finished(ProgressTrackerFinishType::from_err(downloaded.is_err()));
+        tracker.notify();
+        let (mut swaps, hashes): (Vec<_>, Vec<_>) = downloaded?.into_iter().unzip();
+        swaps.extend(disabled);
+
+        let rollback_path = root_path.join(ROLLBACK_FOLDER);
+        let result = tokio::task::spawn_blocking(move || -> Result<(), VersionChangeError> {
+            // Only the most recent version change can be reverted
+            if rollback_path.exists() {
+                std::fs::remove_dir_all(&rollback_path)?;
+            }
+            std::fs::create_dir_all(&rollback_path)?;
+
+            let mut rollback = VersionRollback {
+                minecraft_version: previous.minecraft_version,
+                loader: previous.loader,
+                moved: Vec::new(),
+                added: Vec::new(),
+            };
+
+            let result = apply_swaps(&swaps, &root_path, &rollback_path, &mut rollback)
+                .map_err(VersionChangeError::from)
+                .and_then(|()| write_rollback(&rollback_path, &rollback));
+
+            if result.is_err() {
+                _ = rollback.restore_files(&root_path);
+                _ = std::fs::remove_dir_all(&rollback_path);
+            }
+            result
+        }).await.unwrap();
+
+        result?;
+
+        // The replacements skip install_content, so their source has to be recorded here for later update checks
+        self.mod_metadata_manager.set_content_sources(hashes.into_iter().map(|hash| (hash, ContentSource::Modrinth)));
+
+        let mut instance_state = self.instance_state.write();
+        if let Some(instance) = instance_state.instances.get_mut(id) {
+            instance.configuration.modify(|configuration| {
+                configuration.minecraft_version = target.minecraft_version;
+                configuration.loader = target.loader;
+            });
+        }
+        instance_state.reload_mods_immediately.insert(id);
+
+        Ok(())
+    }
+
+    pub fn get_instance_version_rollback(&self, id: InstanceID) -> Option<VersionChangeTarget> {
+        let root_path = self.instance_state.read().instances.get(id)?.root_path.clone();
+        let rollback = read_rollback(&root_path).ok()?;
+        Some(VersionChangeTarget {
+            minecraft_version: rollback.minecraft_version,
+            loader: rollback.loader,
+        })
+    }
+
+    pub async fn revert_instance_version_change(&self, id: InstanceID) -> Result<(), VersionChangeError> {
+        let root_path = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(VersionChangeError::UnknownInstance);
+            };
+            if instance.child.is_some() {
+                return Err(VersionChangeError::InstanceRunning);
+            }
+            instance.root_path.clone()
+        };
+
+        let rollback = tokio::task::spawn_blocking(move || -> Result<VersionRollback, VersionChangeError> {
+            let rollback = read_rollback(&root_path).map_err(|_| VersionChangeError::NoRollback)?;
+            rollback.restore_files(&root_path)?;
+            std::fs::remove_dir_all(root_path.join(ROLLBACK_FOLDER))?;
+            Ok(rollback)
+        }).await.unwrap()?;
+
+        let mut instance_state = self.instance_state.write();
+        if let Some(instance) = instance_state.instances.get_mut(id) {
+            instance.configuration.modify(|configuration| {
+                configuration.minecraft_version = rollback.minecraft_version;
+                configuration.loader = rollback.loader;
+            });
+        }
+        instance_state.reload_mods_immediately.insert(id);
+
+        Ok(())
+    }
+
+    fn get_version_change_instance(&self, id: InstanceID, target: VersionChangeTarget) -> Result<(Arc<Path>, VersionChangeTarget), VersionChangeError> {
+        let mut instance_state = self.instance_state.write();
+        let Some(instance) = instance_state.instances.get_mut(id) else {
+            return Err(VersionChangeError::UnknownInstance);
+        };
+        if instance.child.is_some() {
+            return Err(VersionChangeError::InstanceRunning);
+        }
+
+        let configuration = instance.configuration.get();
+        let previous = VersionChangeTarget {
+            minecraft_version: configuration.minecraft_version,
+            loader: configuration.loader,
+        };
+        if previous == target {
+            return Err(VersionChangeError::Unchanged);
+        }
+
+        Ok((instance.root_path.clone(), previous))
+    }
+
+    async fn check_mod_migrations(&self, id: InstanceID, target: VersionChangeTarget) -> Result<(Arc<[InstanceModSummary]>, Vec<ModMigration>), VersionChangeError> {
+        self.get_version_change_instance(id, target)?;
+
+        let Some(mods) = self.clone().load_instance_mods(id).await else {
+            return Err(VersionChangeError::UnknownInstance);
+        };
+
+        let modrinth_loader = target.loader.as_modrinth_loader();
+
+        let mut futures = Vec::new();
+        { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
+            let sources = self.mod_metadata_manager.read_content_sources();
+            for summary in mods.iter() {
+                let source = sources.get(&summary.mod_summary.hash).copied().unwrap_or(ContentSource::Manual);
+                futures.push(async move {
+                    let migration = if modrinth_loader == ModrinthLoader::Unknown {
+                        // Mods do nothing without a loader
+                        ModMigration::Incompatible
+                    } else if source != ContentSource::Modrinth {
+                        ModMigration::Unchecked
+                    } else {
+                        match self.modrinth_update_action(&summary.mod_summary, modrinth_loader, target.minecraft_version).await? {
+                            ModUpdateAction::AlreadyUpToDate => ModMigration::Keep,
+                            ModUpdateAction::Modrinth(file) => ModMigration::Replace(file),
+                            ModUpdateAction::ErrorNotFound => ModMigration::Incompatible,
+                            _ => ModMigration::Unchecked,
+                        }
+                    };
+                    Ok::<_, MetaLoadError>(migration)
+                });
+            }
+        }
+
+        let migrations = futures::future::try_join_all(futures).await?;
+        Ok((mods, migrations))
+    }
+}
+
+/// Moves the old mods into the rollback folder and links in their replacements, or disables them if there is no replacement
+fn apply_swaps(swaps: &[ModSwap], root_path: &Path, rollback_path: &Path, rollback: &mut VersionRollback) -> std::io::Result<()> {
+    let relative = |path: &Path| path.strip_prefix(root_path).unwrap_or(path).to_path_buf();
+
+    for (index, (original, replacement)) in swaps.iter().enumerate() {
+        let filename = original.file_name().unwrap_or(OsStr::new("mod"));
+        let moved = rollback_path.join(format!("{index}-{}", filename.to_string_lossy()));
+        std::fs::rename(original, &moved)?;
+        rollback.moved.push((relative(original), relative(&moved)));
+
+        let (from, to) = match replacement {
+            Some((library_path, path)) => (library_path.as_path(), path.clone()),
+            None => (moved.as_path(), original.with_added_extension("disabled")),
+        };
+        if to.exists() {
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", to.display())));
+        }
+        if std::fs::hard_link(from, &to).is_err() {
+            std::fs::copy(from, &to)?;
+        }
+        rollback.added.push(relative(&to));
+    }
+    Ok(())
+}
+
+fn write_rollback(rollback_path: &Path, rollback: &VersionRollback) -> Result<(), VersionChangeError> {
+    crate::write_safe(&rollback_path.join(ROLLBACK_JSON), &serde_json::to_vec(rollback)?)?;
+    Ok(())
+}
+
+fn read_rollback(root_path: &Path) -> Result<VersionRollback, VersionChangeError> {
+    let data = std::fs::read(root_path.join(ROLLBACK_FOLDER).join(ROLLBACK_JSON))?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_after_rename() {
+        let dir = std::env::temp_dir().join(format!("pandora-version-rollback-test-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        let root_path = dir.join("Instance");
+        let mods = root_path.join(".minecraft").join("mods");
+        let rollback_path = root_path.join(ROLLBACK_FOLDER);
+        std::fs::create_dir_all(&mods).unwrap();
+        std::fs::create_dir_all(&rollback_path).unwrap();
+
+        let library_path = dir.join("library.jar");
+        std::fs::write(&library_path, b"new").unwrap();
+        std::fs::write(mods.join("replaced.jar"), b"old").unwrap();
+        std::fs::write(mods.join("incompatible.jar"), b"incompatible").unwrap();
+
+        let swaps = [
+            (Arc::from(mods.join("replaced.jar")), Some((library_path, mods.join("replaced-2.0.jar")))),
+            (Arc::from(mods.join("incompatible.jar")), None),
+        ];
+        let mut rollback = VersionRollback {
+            minecraft_version: Ustr::from("1.21.1"),
+            loader: Loader::Fabric,
+            moved: Vec::new(),
+            added: Vec::new(),
+        };
+        apply_swaps(&swaps, &root_path, &rollback_path, &mut rollback).unwrap();
+        write_rollback(&rollback_path, &rollback).unwrap();
+        assert!(rollback.moved.iter().all(|(original, moved)| original.is_relative() && moved.is_relative()));
+        assert!(rollback.added.iter().all(|added| added.is_relative()));
+
+        let renamed_path = dir.join("Renamed");
+        std::fs::rename(&root_path, &renamed_path).unwrap();
+        let renamed_mods = renamed_path.join(".minecraft").join("mods");
+        assert_eq!(std::fs::read(renamed_mods.join("replaced-2.0.jar")).unwrap(), b"new");
+        assert!(renamed_mods.join("incompatible.jar.disabled").exists());
+
+        read_rollback(&renamed_path).unwrap().restore_files(&renamed_path).unwrap();
+        assert_eq!(std::fs::read(renamed_mods.join("replaced.jar")).unwrap(), b"old");
+        assert_eq!(std::fs::read(renamed_mods.join("incompatible.jar")).unwrap(), b"incompatible");
+        assert!(!renamed_mods.join("replaced-2.0.jar").exists());
+        assert!(!renamed_mods.join("incompatible.jar.disabled").exists());
+
+        _ = std::fs::remove_dir_all(&dir);
+    }
+}
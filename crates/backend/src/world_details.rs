@@ -0,0 +1,176 @@
+use std::{io::{Read, Write}, path::Path, sync::Arc};
+
+use anyhow::Context;
+use bridge::instance::{WorldDetails, WorldDifficulty, WorldEdit, WorldGameMode, WorldPlayer};
+
+pub(crate) fn read_gzip_nbt(path: &Path) -> anyhow::Result<nbt::NBT> {
+    let compressed = std::fs::read(path)?;
+
+    let mut decoder = flate2::bufread::GzDecoder::new(compressed.as_slice());
+    let mut decompressed = Vec::new();
+    decoder.read_to_end(&mut decompressed)?;
+
+    let mut nbt_data = decompressed.as_slice();
+    Ok(nbt::decode::read_named(&mut nbt_data)?)
+}
+
+pub(crate) fn write_gzip_nbt(path: &Path, nbt: &nbt::NBT) -> anyhow::Result<()> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&nbt::encode::write_named(nbt))?;
+    let compressed = encoder.finish()?;
+
+    crate::write_safe(path, &compressed)?;
+    Ok(())
+}
+
+pub fn load_world_details(world_path: &Path) -> anyhow::Result<WorldDetails> {
+    let level = read_gzip_nbt(&world_path.join("level.dat"))?;
+
+    let root = level.as_compound().context("Unable to get root compound")?;
+    let data = root.find_compound("Data").context("Unable to get Data")?;
+
+    // 1.21.6+ moved difficulty and hardcore into difficulty_settings
+    let difficulty_settings = data.find_compound("difficulty_settings");
+    let difficulty = data.find_numeric("Difficulty").and_then(WorldDifficulty::from_id).or_else(|| {
+        let name = difficulty_settings.as_ref()?.find_string("difficulty")?;
+        WorldDifficulty::ALL.into_iter().find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    });
+    let hardcore = data.find_numeric::<i8>("hardcore")
+        .or_else(|| difficulty_settings?.find_numeric("hardcore"))
+        .is_some_and(|hardcore| hardcore != 0);
+
+    let seed = data.find_compound("WorldGenSettings")
+        .and_then(|settings| settings.find_long("seed").copied())
+        .or_else(|| data.find_long("RandomSeed").copied());
+
+    let version = data.find_compound("Version");
+    let version_name = version.as_ref().and_then(|version| version.find_string("Name")).map(|name| Arc::from(name.as_str()));
+    let data_version = data.find_numeric("DataVersion");
+
+    let (enabled_datapacks, disabled_datapacks) = match data.find_compound("DataPacks") {
+        Some(datapacks) => (read_string_list(datapacks, "Enabled"), read_string_list(datapacks, "Disabled")),
+        None => (Arc::from([]), Arc::from([])),
+    };
+
+    let players = load_players(world_path, data);
+
+    Ok(WorldDetails {
+        level_name: data.find_string("LevelName").map(|name| Arc::from(name.as_str())).unwrap_or_default(),
+        game_mode: data.find_numeric("GameType").and_then(WorldGameMode::from_id),
+        difficulty,
+        hardcore,
+        allow_commands: data.find_numeric::<i8>("allowCommands").is_some_and(|allow| allow != 0),
+        seed,
+        version_name,
+        data_version,
+        size_bytes: directory_size(world_path),
+        enabled_datapacks,
+        disabled_datapacks,
+        players: players.into(),
+    })
+}
+
+pub fn edit_world(world_path: &Path, edit: WorldEdit) -> anyhow::Result<()> {
+    let level_dat = world_path.join("level.dat");
+    let mut level = read_gzip_nbt(&level_dat)?;
+
+    let mut root = level.as_compound_mut().context("Unable to get root compound")?;
+    let mut data = root.find_compound_mut("Data").context("Unable to get Data")?;
+
+    match edit {
+        WorldEdit::Rename { name } => {
+            data.insert_string("LevelName", name.to_string());
+        },
+        WorldEdit::SetAllowCommands { allow_commands } => {
+            data.insert_byte("allowCommands", allow_commands as i8);
+        },
+        WorldEdit::SetDifficulty { difficulty } => {
+            if let Some(mut difficulty_settings) = data.find_compound_mut("difficulty_settings") {
+                difficulty_settings.insert_string("difficulty", difficulty.name().to_ascii_lowercase());
+            } else {
+                data.insert_byte("Difficulty", difficulty.id());
+            }
+        },
+    }
+
+    // Keep the previous level.dat around the same way Minecraft does
+    std::fs::copy(&level_dat, world_path.join("level.dat_old"))?;
+    write_gzip_nbt(&level_dat, &level)
+}
+
+pub(crate) fn load_players(world_path: &Path, data: nbt::CompoundRef<'_>) -> Vec<WorldPlayer> {
+    let mut players = Vec::new();
+    if let Ok(read_dir) = std::fs::read_dir(world_path.join("playerdata")) {
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "dat") {
+                continue;
+            }
+            let Some(uuid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let Ok(player) = read_gzip_nbt(&path) else {
+                continue;
+            };
+            if let Some(player) = player.as_compound() {
+                players.push(read_player(uuid.into(), player));
+            }
+        }
+    }
+    if players.is_empty() && let Some(player) = data.find_compound("Player") {
+        players.push(read_player("Singleplayer".into(), player));
+    }
+    players.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+    players
+}
+
+fn read_player(uuid: Arc<str>, player: nbt::CompoundRef<'_>) -> WorldPlayer {
+    let position = player.find_list("Pos", nbt::TAG_DOUBLE_ID).and_then(|pos| {
+        Some([*pos.get_double(0)?, *pos.get_double(1)?, *pos.get_double(2)?])
+    });
+
+    let dimension = match player.find_string("Dimension") {
+        Some(dimension) => Some(Arc::from(dimension.as_str())),
+        // Before 1.16 the dimension was stored as a number
+        None => player.find_numeric::<i32>("Dimension").map(|dimension| match dimension {
+            -1 => "minecraft:the_nether".into(),
+            0 => "minecraft:overworld".into(),
+            1 => "minecraft:the_end".into(),
+            other => other.to_string().into(),
+        }),
+    };
+
+    WorldPlayer {
+        uuid,
+        position,
+        dimension,
+        health: player.find_numeric("Health"),
+        game_mode: player.find_numeric("playerGameType").and_then(WorldGameMode::from_id),
+    }
+}
+
+fn read_string_list(compound: nbt::CompoundRef<'_>, key: &str) -> Arc<[Arc<str>]> {
+    let Some(list) = compound.find_list(key, nbt::TAG_STRING_ID) else {
+        return Arc::from([]);
+    };
+    (0..list.len()).filter_map(|index| list.get_string(index)).map(|value| Arc::from(value.as_str())).collect()
+}
+
+fn directory_size(path: &Path) -> u64 {
+    let Ok(read_dir) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    let mut size = 0;
+    for entry in read_dir.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            size += directory_size(&entry.path());
+        } else if let Ok(metadata) = entry.metadata() {
+            size += metadata.len();
+        }
+    }
+    size
+}
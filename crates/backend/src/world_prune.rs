@@ -0,0 +1,278 @@
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use anyhow::Context;
+use bridge::instance::{DimensionPruneReport, WorldPruneOptions, WorldPruneReport};
+use nbt::region::{Region, RegionChunk};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+const OVERWORLD: &str = "minecraft:overworld";
+
+/// Folders next to `region` that store data per chunk and need to be pruned along with the terrain
+const CHUNK_DATA_FOLDERS: &[&str] = &["entities", "poi"];
+
+pub fn prune_world(world_path: &Path, options: &WorldPruneOptions) -> anyhow::Result<WorldPruneReport> {
+    let level = crate::world_details::read_gzip_nbt(&world_path.join("level.dat"))?;
+    let root = level.as_compound().context("Unable to get root compound")?;
+    let data = root.find_compound("Data").context("Unable to get Data")?;
+
+    let mut anchors: FxHashMap<Arc<str>, Vec<(i32, i32)>> = FxHashMap::default();
+
+    if let (Some(x), Some(z)) = (data.find_numeric::<i32>("SpawnX"), data.find_numeric::<i32>("SpawnZ")) {
+        anchors.entry(OVERWORLD.into()).or_default().push((x >> 4, z >> 4));
+    } else if let Some(spawn) = data.find_compound("spawn") && let Some(pos) = spawn.find_int_array("pos") && pos.len() == 3 {
+        // 1.21.9+ stores the spawn as a compound which can point to any dimension
+        let dimension = spawn.find_string("dimension").map(String::as_str).unwrap_or(OVERWORLD);
+        anchors.entry(dimension.into()).or_default().push((pos[0] >> 4, pos[2] >> 4));
+    }
+
+    for player in crate::world_details::load_players(world_path, data) {
+        if let Some([x, _, z]) = player.position {
+            let dimension = player.dimension.unwrap_or_else(|| OVERWORLD.into());
+            anchors.entry(dimension).or_default().push(((x.floor() as i32) >> 4, (z.floor() as i32) >> 4));
+        }
+    }
+
+    let mut dimensions = Vec::new();
+    for (dimension, folder) in find_dimensions(world_path) {
+        if let Some(filter) = &options.dimensions && !filter.contains(&dimension) {
+            continue;
+        }
+
+        // The end's main island and the nether's origin are kept when nobody has been there
+        let anchors = match anchors.get(&dimension) {
+            Some(anchors) => anchors.as_slice(),
+            None => &[(0, 0)],
+        };
+
+        let report = prune_dimension(dimension.clone(), &folder, anchors, options)
+            .with_context(|| format!("Unable to prune {dimension}"))?;
+        dimensions.push(report);
+    }
+
+    Ok(WorldPruneReport {
+        dry_run: options.dry_run,
+        dimensions: dimensions.into(),
+    })
+}
+
+fn find_dimensions(world_path: &Path) -> Vec<(Arc<str>, PathBuf)> {
+    let mut dimensions = Vec::new();
+
+    for (name, folder) in [(OVERWORLD, world_path.to_path_buf()), ("minecraft:the_nether", world_path.join("DIM-1")), ("minecraft:the_end", world_path.join("DIM1"))] {
+        if folder.join("region").is_dir() {
+            dimensions.push((name.into(), folder));
+        }
+    }
+
+    // Datapack dimensions are stored as dimensions/<namespace>/<path>
+    if let Ok(namespaces) = std::fs::read_dir(world_path.join("dimensions")) {
+        for namespace in namespaces.flatten() {
+            let namespace_name = namespace.file_name().to_string_lossy().into_owned();
+            find_namespaced_dimensions(&namespace.path(), &namespace_name, "", 0, &mut dimensions);
+        }
+    }
+
+    dimensions
+}
+
+fn find_namespaced_dimensions(folder: &Path, namespace: &str, path: &str, depth: usize, dimensions: &mut Vec<(Arc<str>, PathBuf)>) {
+    if depth > 4 {
+        return;
+    }
+    if !path.is_empty() && folder.join("region").is_dir() {
+        let name: Arc<str> = format!("{namespace}:{path}").into();
+        if !dimensions.iter().any(|(existing, _)| *existing == name) {
+            dimensions.push((name, folder.to_path_buf()));
+        }
+        return;
+    }
+
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = if path.is_empty() { name } else { format!("{path}/{name}") };
+            find_namespaced_dimensions(&entry.path(), namespace, &path, depth + 1, dimensions);
+        }
+    }
+}
+
+fn prune_dimension(dimension: Arc<str>, folder: &Path, anchors: &[(i32, i32)], options: &WorldPruneOptions) -> anyhow::Result<DimensionPruneReport> {
+    let mut report = DimensionPruneReport {
+        dimension,
+        total_chunks: 0,
+        removed_chunks: 0,
+        bytes_before: 0,
+        bytes_after: 0,
+        skipped_regions: Arc::from([]),
+    };
+
+    let mut removed = FxHashSet::default();
+    let mut skipped_regions = Vec::new();
+
+    for path in region_files(&folder.join("region")) {
+        // A corrupt region shouldn't stop the rest of the world from being pruned
+        let mut region = match Region::read(&path) {
+            Ok(region) => region,
+            Err(error) => {
+                eprintln!("Skipping corrupt region {}: {error:#}", path.display());
+                let size = std::fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+                report.bytes_before += size;
+                report.bytes_after += size;
+                skipped_regions.push(path.strip_prefix(folder).unwrap_or(&path).to_string_lossy().into());
+                continue;
+            },
+        };
+
+        let chunks = region.len();
+        region.retain(|x, z, chunk| {
+            let keep = keep_chunk(x, z, chunk, anchors, options);
+            if !keep {
+                removed.insert((x, z));
+            }
+            keep
+        });
+
+        report.total_chunks += chunks;
+        report.removed_chunks += chunks - region.len();
+        finish_region(&path, region, chunks, options.dry_run, &mut report)?;
+    }
+
+    if !removed.is_empty() {
+        for data_folder in CHUNK_DATA_FOLDERS {
+            for path in region_files(&folder.join(data_folder)) {
+                let Ok(mut region) = Region::read(&path) else {
+                    continue;
+                };
+                let chunks = region.len();
+                region.retain(|x, z, _| !removed.contains(&(x, z)));
+                finish_region(&path, region, chunks, options.dry_run, &mut report)?;
+            }
+        }
+    }
+
+    report.skipped_regions = skipped_regions.into();
+    Ok(report)
+}
+
+fn region_files(folder: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    read_dir.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| Region::coordinates_from_path(path).is_some())
+        .collect()
+}
+
+fn finish_region(path: &Path, region: Region, original_chunks: usize, dry_run: bool, report: &mut DimensionPruneReport) -> anyhow::Result<()> {
+    let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+    report.bytes_before += size;
+
+    // Untouched regions are left alone, even if they could be compacted
+    if region.len() == original_chunks {
+        report.bytes_after += size;
+        return Ok(());
+    }
+
+    if region.is_empty() {
+        if !dry_run {
+            // Writing first removes any external chunk files that belonged to the region
+            region.write(path)?;
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    report.bytes_after += region.encoded_size();
+    if !dry_run {
+        region.write(path)?;
+    }
+    Ok(())
+}
+
+fn keep_chunk(x: i32, z: i32, chunk: &RegionChunk, anchors: &[(i32, i32)], options: &WorldPruneOptions) -> bool {
+    let inhabited = options.min_inhabited_ticks.map(|min_ticks| {
+        // Chunks that can't be read are always kept
+        inhabited_time(chunk).is_none_or(|ticks| ticks >= min_ticks)
+    });
+    let nearby = options.keep_radius.map(|radius| {
+        anchors.iter().any(|(anchor_x, anchor_z)| (x - anchor_x).unsigned_abs().max((z - anchor_z).unsigned_abs()) <= radius)
+    });
+
+    // When both criteria are set a chunk only needs to satisfy one of them to be kept
+    match (inhabited, nearby) {
+        (Some(inhabited), Some(nearby)) => inhabited || nearby,
+        (Some(keep), None) | (None, Some(keep)) => keep,
+        (None, None) => true,
+    }
+}
+
+fn inhabited_time(chunk: &RegionChunk) -> Option<i64> {
+    let nbt = chunk.read_nbt().ok()?;
+    let root = nbt.as_compound()?;
+    // Before 1.18 chunk data was nested inside a Level compound
+    root.find_numeric("InhabitedTime").or_else(|| root.find_compound("Level")?.find_numeric("InhabitedTime"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bridge::instance::WorldPruneOptions;
+    use nbt::{region::{ChunkCompression, Region, RegionChunk}, NBT};
+
+    fn chunk(inhabited_time: i64) -> RegionChunk {
+        let mut nbt = NBT::new();
+        nbt.as_compound_mut().unwrap().insert_long("InhabitedTime", inhabited_time);
+        RegionChunk::from_nbt(&nbt, ChunkCompression::Zlib, 0).unwrap()
+    }
+
+    #[test]
+    fn test_prune_skips_corrupt_region() {
+        let world = std::env::temp_dir().join(format!("world-prune-test-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&world);
+        std::fs::create_dir_all(world.join("region")).unwrap();
+
+        let mut level = NBT::new();
+        let mut root = level.as_compound_mut().unwrap();
+        let mut data = root.create_compound("Data");
+        data.insert_int("SpawnX", 0);
+        data.insert_int("SpawnZ", 0);
+        crate::world_details::write_gzip_nbt(&world.join("level.dat"), &level).unwrap();
+
+        let mut region = Region::new(0, 0);
+        region.insert(0, 0, chunk(0));
+        region.insert(20, 20, chunk(0));
+        region.write(&world.join("region/r.0.0.mca")).unwrap();
+
+        // The first chunk points past the end of the file
+        let mut corrupt = vec![0_u8; 8192];
+        corrupt[..4].copy_from_slice(&[0, 0, 5, 1]);
+        let corrupt_path = world.join("region/r.1.0.mca");
+        std::fs::write(&corrupt_path, &corrupt).unwrap();
+
+        let options = WorldPruneOptions {
+            min_inhabited_ticks: None,
+            keep_radius: Some(2),
+            dimensions: None,
+            dry_run: false,
+        };
+        let report = super::prune_world(&world, &options).unwrap();
+
+        let [dimension] = &*report.dimensions else {
+            panic!("expected a single dimension");
+        };
+        assert_eq!(dimension.total_chunks, 2);
+        assert_eq!(dimension.removed_chunks, 1);
+        assert_eq!(&*dimension.skipped_regions, [Path::new("region").join("r.1.0.mca").to_string_lossy().into()]);
+
+        let region = Region::read(&world.join("region/r.0.0.mca")).unwrap();
+        assert!(region.get(0, 0).is_some() && region.get(20, 20).is_none());
+        assert_eq!(std::fs::read(&corrupt_path).unwrap(), corrupt);
+
+        std::fs::remove_dir_all(world).unwrap();
+    }
+}
@@ -0,0 +1,502 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use crate::instance::{InstanceModSummary, LoaderSpecificModSummary, ModDependencyKind, ModSummary, ModVersionRequirement};
+
+/// Ids provided by the game or the loader itself, these are never reported as missing
+const PLATFORM_IDS: &[&str] = &["java", "fabricloader", "fabric-loader", "forge", "neoforge", "quilt_loader", "javafml", "lowcodefml"];
+
+#[derive(Debug, Clone)]
+pub struct DependencyIssue {
+    pub mod_name: Arc<str>,
+    pub dependency_id: Arc<str>,
+    pub kind: DependencyIssueKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum DependencyIssueKind {
+    Missing {
+        requirement: Option<Arc<str>>,
+    },
+    WrongVersion {
+        found: Arc<str>,
+        requirement: Arc<str>,
+    },
+    Conflict {
+        with_name: Arc<str>,
+    },
+}
+
+impl DependencyIssue {
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            DependencyIssueKind::Missing { requirement: Some(requirement) } => {
+                format!("{} requires {} {}", self.mod_name, self.dependency_id, requirement)
+            },
+            DependencyIssueKind::Missing { requirement: None } => {
+                format!("{} requires {}", self.mod_name, self.dependency_id)
+            },
+            DependencyIssueKind::WrongVersion { found, requirement } => {
+                format!("{} requires {} {}, but {} is installed", self.mod_name, self.dependency_id, requirement, found)
+            },
+            DependencyIssueKind::Conflict { with_name } => {
+                format!("{} is incompatible with {}", self.mod_name, with_name)
+            },
+        }
+    }
+}
+
+struct Provider<'a> {
+    name: &'a Arc<str>,
+    version: Option<&'a Arc<str>>,
+}
+
+/// Builds the dependency graph of all enabled mods (including enabled modpack children) and returns any problems found
+pub fn check_dependencies(mods: &[InstanceModSummary], minecraft_version: &str) -> Vec<DependencyIssue> {
+    let mut active: Vec<&ModSummary> = Vec::new();
+    for instance_mod in mods {
+        if !instance_mod.enabled {
+            continue;
+        }
+        active.push(&instance_mod.mod_summary);
+
+        if let LoaderSpecificModSummary::ModrinthModpack { downloads, summaries, .. } = &instance_mod.mod_summary.extra {
+            for (download, summary) in downloads.iter().zip(summaries.iter()) {
+                if let Some(summary) = summary && !instance_mod.disabled_children.contains(&*download.path) {
+                    active.push(summary);
+                }
+            }
+        }
+    }
+
+    let mut providers: HashMap<&str, Provider> = HashMap::new();
+    for summary in &active {
+        for provided in summary.provides.iter() {
+            providers.entry(&provided.id).or_insert(Provider {
+                name: &summary.name,
+                version: provided.version.as_ref(),
+            });
+        }
+    }
+
+    let minecraft_version: Arc<str> = minecraft_version.into();
+    let minecraft_name: Arc<str> = "Minecraft".into();
+    // Snapshot versions can't be compared against release ranges
+    let is_release = minecraft_version.split('.').all(|part| part.parse::<u32>().is_ok());
+    let minecraft = Provider {
+        name: &minecraft_name,
+        version: is_release.then_some(&minecraft_version),
+    };
+
+    let mut issues = Vec::new();
+    for summary in &active {
+        for dependency in summary.dependencies.iter() {
+            if PLATFORM_IDS.contains(&&*dependency.id) {
+                continue;
+            }
+
+            let provider = if &*dependency.id == "minecraft" {
+                Some(&minecraft)
+            } else {
+                providers.get(&*dependency.id)
+            };
+
+            match dependency.kind {
+                ModDependencyKind::Required | ModDependencyKind::Optional => {
+                    let Some(provider) = provider else {
+                        if dependency.kind == ModDependencyKind::Required {
+                            issues.push(DependencyIssue {
+                                mod_name: summary.name.clone(),
+                                dependency_id: dependency.id.clone(),
+                                kind: DependencyIssueKind::Missing {
+                                    requirement: describe_requirement(&dependency.requirement),
+                                },
+                            });
+                        }
+                        continue;
+                    };
+
+                    if let Some(version) = provider.version && !requirement_matches(&dependency.requirement, version) {
+                        issues.push(DependencyIssue {
+                            mod_name: summary.name.clone(),
+                            dependency_id: dependency.id.clone(),
+                            kind: DependencyIssueKind::WrongVersion {
+                                found: version.clone(),
+                                requirement: describe_requirement(&dependency.requirement).unwrap_or_default(),
+                            },
+                        });
+                    }
+                },
+                ModDependencyKind::Incompatible => {
+                    let Some(provider) = provider else {
+                        continue;
+                    };
+                    // Mods sometimes declare themselves as incompatible with ids they also provide
+                    if Arc::ptr_eq(provider.name, &summary.name) {
+                        continue;
+                    }
+
+                    let matches = match provider.version {
+                        Some(version) => requirement_matches(&dependency.requirement, version),
+                        None => true,
+                    };
+                    if matches {
+                        issues.push(DependencyIssue {
+                            mod_name: summary.name.clone(),
+                            dependency_id: dependency.id.clone(),
+                            kind: DependencyIssueKind::Conflict {
+                                with_name: provider.name.clone(),
+                            },
+                        });
+                    }
+                },
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| a.mod_name.cmp(&b.mod_name));
+    issues
+}
+
+fn describe_requirement(requirement: &ModVersionRequirement) -> Option<Arc<str>> {
+    match requirement {
+        ModVersionRequirement::Any => None,
+        ModVersionRequirement::Fabric(predicates) => {
+            if predicates.iter().all(|predicate| predicate.trim_ascii() == "*") {
+                None
+            } else {
+                Some(predicates.join(" || ").into())
+            }
+        },
+        ModVersionRequirement::Maven(range) => Some(range.clone()),
+    }
+}
+
+pub fn requirement_matches(requirement: &ModVersionRequirement, version: &str) -> bool {
+    match requirement {
+        ModVersionRequirement::Any => true,
+        ModVersionRequirement::Fabric(predicates) => {
+            predicates.is_empty() || predicates.iter().any(|predicate| fabric_predicate_matches(predicate, version))
+        },
+        ModVersionRequirement::Maven(range) => maven_range_matches(range, version),
+    }
+}
+
+fn fabric_predicate_matches(predicate: &str, version: &str) -> bool {
+    predicate.split_whitespace().all(|term| fabric_term_matches(term, version))
+}
+
+fn fabric_term_matches(term: &str, version: &str) -> bool {
+    if term == "*" {
+        return true;
+    }
+
+    if let Some(other) = term.strip_prefix(">=") {
+        compare_versions(version, other).is_ge()
+    } else if let Some(other) = term.strip_prefix("<=") {
+        compare_versions(version, other).is_le()
+    } else if let Some(other) = term.strip_prefix('>') {
+        compare_versions(version, other).is_gt()
+    } else if let Some(other) = term.strip_prefix('<') {
+        compare_versions(version, other).is_lt()
+    } else if let Some(other) = term.strip_prefix('=') {
+        compare_versions(version, other).is_eq()
+    } else if let Some(other) = term.strip_prefix('~') {
+        // ~1.2.3 allows >=1.2.3 <1.3.0
+        let mut upper = numeric_components(other);
+        if upper.len() >= 2 {
+            upper.truncate(2);
+            upper[1] += 1;
+        } else if let Some(major) = upper.first_mut() {
+            *major += 1;
+        }
+        compare_versions(version, other).is_ge() && compare_numeric(version, &upper).is_lt()
+    } else if let Some(other) = term.strip_prefix('^') {
+        // ^1.2.3 allows >=1.2.3 <2.0.0
+        let mut upper = numeric_components(other);
+        upper.truncate(1);
+        if let Some(major) = upper.first_mut() {
+            *major += 1;
+        }
+        compare_versions(version, other).is_ge() && compare_numeric(version, &upper).is_lt()
+    } else if term.split('.').any(|part| matches!(part, "x" | "X" | "*")) {
+        let version = strip_build(version);
+        let mut version_parts = version.split(['.', '-']);
+        term.split('.').take_while(|part| !matches!(*part, "x" | "X" | "*")).all(|part| version_parts.next() == Some(part))
+    } else {
+        compare_versions(version, term).is_eq()
+    }
+}
+
+fn maven_range_matches(range: &str, version: &str) -> bool {
+    let range = range.trim_ascii();
+
+    // A plain version is a soft requirement, any version is accepted
+    let (Some(lower_inclusive), Some(upper_inclusive)) = (
+        range.chars().next().and_then(|c| match c { '[' => Some(true), '(' => Some(false), _ => None }),
+        range.chars().last().and_then(|c| match c { ']' => Some(true), ')' => Some(false), _ => None }),
+    ) else {
+        return true;
+    };
+
+    let inner = &range[1..range.len() - 1];
+    if inner.contains(['[', '(', ']', ')']) {
+        // Unions of multiple ranges aren't supported
+        return true;
+    }
+
+    let Some((lower, upper)) = inner.split_once(',') else {
+        return compare_versions(version, inner.trim_ascii()).is_eq();
+    };
+    let (lower, upper) = (lower.trim_ascii(), upper.trim_ascii());
+
+    if !lower.is_empty() {
+        let ordering = compare_versions(version, lower);
+        if ordering.is_lt() || (!lower_inclusive && ordering.is_eq()) {
+            return false;
+        }
+    }
+    if !upper.is_empty() {
+        let ordering = compare_versions(version, upper);
+        if ordering.is_gt() || (!upper_inclusive && ordering.is_eq()) {
+            return false;
+        }
+    }
+    true
+}
+
+fn strip_build(version: &str) -> &str {
+    let version = version.trim_ascii();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    version.split_once('+').map(|(version, _)| version).unwrap_or(version)
+}
+
+fn numeric_components(version: &str) -> Vec<u64> {
+    let version = strip_build(version);
+    let core = version.split_once('-').map(|(core, _)| core).unwrap_or(version);
+    core.split('.').map_while(|part| part.parse().ok()).collect()
+}
+
+fn compare_numeric(version: &str, other: &[u64]) -> Ordering {
+    let version = numeric_components(version);
+    for index in 0..version.len().max(other.len()) {
+        let ordering = version.get(index).copied().unwrap_or(0).cmp(&other.get(index).copied().unwrap_or(0));
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Loosely semver ordering that tolerates the many non-semver versions used by mods
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (strip_build(a), strip_build(b));
+    let (a_core, a_pre) = a.split_once('-').map(|(core, pre)| (core, Some(pre))).unwrap_or((a, None));
+    let (b_core, b_pre) = b.split_once('-').map(|(core, pre)| (core, Some(pre))).unwrap_or((b, None));
+
+    let ordering = compare_parts(a_core, b_core);
+    if ordering.is_ne() {
+        return ordering;
+    }
+
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a_pre), Some(b_pre)) => compare_parts(a_pre, b_pre),
+    }
+}
+
+fn compare_parts(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split(['.', '-']);
+    let mut b_parts = b.split(['.', '-']);
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(a), None) => compare_part(a, "0"),
+            (None, Some(b)) => compare_part("0", b),
+            (Some(a), Some(b)) => compare_part(a, b),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn compare_part(a: &str, b: &str) -> Ordering {
+    let a_digits = a.len() - a.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let b_digits = b.len() - b.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    match (a[..a_digits].parse::<u64>(), b[..b_digits].parse::<u64>()) {
+        (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number).then_with(|| a[a_digits..].cmp(&b[b_digits..])),
+        (Ok(_), Err(_)) => Ordering::Greater,
+        (Err(_), Ok(_)) => Ordering::Less,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::Arc};
+
+    use crate::instance::{AtomicContentUpdateStatus, ContentUpdateStatus, InstanceModID, InstanceModSummary, LoaderSpecificModSummary, ModDependency, ModDependencyKind, ModSummary, ModVersionRequirement, ProvidedMod};
+
+    use super::*;
+
+    fn create_mod(id: &str, version: &str, dependencies: &[(&str, ModDependencyKind, ModVersionRequirement)]) -> InstanceModSummary {
+        let name: Arc<str> = id.into();
+        InstanceModSummary {
+            mod_summary: Arc::new(ModSummary {
+                id: name.clone(),
+                hash: [0; 20],
+                name: name.clone(),
+                lowercase_search_key: name.clone(),
+                version_str: version.into(),
+                authors: "".into(),
+                png_icon: None,
+                update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
+                provides: [ProvidedMod { id: name.clone(), version: Some(version.into()) }].into(),
+                dependencies: dependencies.iter().map(|(id, kind, requirement)| ModDependency {
+                    id: (*id).into(),
+                    kind: *kind,
+                    requirement: requirement.clone(),
+                }).collect(),
+                extra: LoaderSpecificModSummary::Fabric,
+            }),
+            id: InstanceModID::dangling(),
+            filename: format!("{id}.jar").into(),
+            lowercase_filename: format!("{id}.jar").into(),
+            filename_hash: 0,
+            path: Path::new(&format!("{id}.jar")).into(),
+            enabled: true,
+            disabled_children: Default::default(),
+        }
+    }
+
+    fn fabric(predicates: &[&str]) -> ModVersionRequirement {
+        ModVersionRequirement::Fabric(predicates.iter().map(|predicate| Arc::from(*predicate)).collect())
+    }
+
+    #[test]
+    fn test_missing() {
+        let mods = [
+            create_mod("sodium", "0.6.0", &[
+                ("fabric-api", ModDependencyKind::Required, fabric(&[">=0.100.0"])),
+                ("fabricloader", ModDependencyKind::Required, ModVersionRequirement::Any),
+                ("modmenu", ModDependencyKind::Optional, ModVersionRequirement::Any),
+            ]),
+        ];
+        let issues = check_dependencies(&mods, "1.21.1");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(&*issues[0].dependency_id, "fabric-api");
+        assert!(matches!(&issues[0].kind, DependencyIssueKind::Missing { requirement: Some(requirement) } if &**requirement == ">=0.100.0"));
+    }
+
+    #[test]
+    fn test_disabled_provider() {
+        let mut api = create_mod("fabric-api", "0.100.0", &[]);
+        let mods = [create_mod("sodium", "0.6.0", &[("fabric-api", ModDependencyKind::Required, ModVersionRequirement::Any)]), api.clone()];
+        assert!(check_dependencies(&mods, "1.21.1").is_empty());
+
+        api.enabled = false;
+        let mods = [mods[0].clone(), api];
+        assert_eq!(check_dependencies(&mods, "1.21.1").len(), 1);
+    }
+
+    #[test]
+    fn test_wrong_version() {
+        let mods = [
+            create_mod("sodium", "0.6.0", &[("fabric-api", ModDependencyKind::Required, fabric(&[">=0.100.0"]))]),
+            create_mod("fabric-api", "0.92.2+1.20.1", &[]),
+        ];
+        let issues = check_dependencies(&mods, "1.21.1");
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0].kind, DependencyIssueKind::WrongVersion { found, .. } if &**found == "0.92.2+1.20.1"));
+    }
+
+    #[test]
+    fn test_minecraft_version() {
+        let mods = [create_mod("sodium", "0.6.0", &[("minecraft", ModDependencyKind::Required, fabric(&["~1.21"]))])];
+        assert!(check_dependencies(&mods, "1.21.1").is_empty());
+        assert_eq!(check_dependencies(&mods, "1.20.1").len(), 1);
+        // Snapshots can't be compared, so they're never reported
+        assert!(check_dependencies(&mods, "24w14a").is_empty());
+    }
+
+    #[test]
+    fn test_conflict() {
+        let mods = [
+            create_mod("sodium", "0.6.0", &[("optifabric", ModDependencyKind::Incompatible, ModVersionRequirement::Any)]),
+            create_mod("optifabric", "1.14.3", &[]),
+        ];
+        let issues = check_dependencies(&mods, "1.21.1");
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0].kind, DependencyIssueKind::Conflict { with_name } if &**with_name == "optifabric"));
+
+        // Incompatible with an older version only
+        let mods = [
+            create_mod("sodium", "0.6.0", &[("optifabric", ModDependencyKind::Incompatible, fabric(&["<1.0.0"]))]),
+            create_mod("optifabric", "1.14.3", &[]),
+        ];
+        assert!(check_dependencies(&mods, "1.21.1").is_empty());
+
+        // Declaring itself as incompatible is ignored
+        let mods = [create_mod("sodium", "0.6.0", &[("sodium", ModDependencyKind::Incompatible, ModVersionRequirement::Any)])];
+        assert!(check_dependencies(&mods, "1.21.1").is_empty());
+    }
+
+    #[test]
+    fn test_fabric_predicates() {
+        let cases = [
+            ("*", "1.0.0", true),
+            (">=1.2.0", "1.2.0", true),
+            (">=1.2.0", "1.1.9", false),
+            (">1.2.0 <2.0.0", "1.5.0", true),
+            (">1.2.0 <2.0.0", "2.0.0", false),
+            ("<=1.0", "1.0.0", true),
+            ("=1.0.0", "1.0.0+build.5", true),
+            ("~1.2.3", "1.2.9", true),
+            ("~1.2.3", "1.3.0", false),
+            ("^1.2.3", "1.9.0", true),
+            ("^1.2.3", "2.0.0", false),
+            ("1.20.x", "1.20.4", true),
+            ("1.20.x", "1.21", false),
+            ("1.0.0", "1.0.0", true),
+        ];
+        for (predicate, version, expected) in cases {
+            assert_eq!(requirement_matches(&fabric(&[predicate]), version), expected, "{predicate} {version}");
+        }
+        assert!(requirement_matches(&fabric(&["<1.0.0", ">=2.0.0"]), "2.1.0"));
+        assert!(requirement_matches(&fabric(&[]), "0.1.0"));
+    }
+
+    #[test]
+    fn test_maven_ranges() {
+        let cases = [
+            ("[1.0,2.0)", "1.0", true),
+            ("[1.0,2.0)", "2.0", false),
+            ("(1.0,2.0]", "1.0", false),
+            ("(1.0,2.0]", "2.0", true),
+            ("[47.1,)", "47.2.0", true),
+            ("[47.1,)", "47.0.5", false),
+            ("(,1.20.2]", "1.20.1", true),
+            ("[1.20.1]", "1.20.1", true),
+            ("[1.20.1]", "1.20.2", false),
+            // A plain version is only a soft requirement
+            ("1.0", "0.5", true),
+        ];
+        for (range, version, expected) in cases {
+            assert_eq!(requirement_matches(&ModVersionRequirement::Maven(range.into()), version), expected, "{range} {version}");
+        }
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v1.2.0", "1.2.0+mc1.21"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0-beta.2", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-beta.10", "1.0.0-beta.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-alpha", "1.0.0-beta"), Ordering::Less);
+        assert_eq!(compare_versions("2a", "2"), Ordering::Greater);
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameOutputLogLevel {
     Fatal,
@@ -8,3 +10,32 @@ pub enum GameOutputLogLevel {
     Trace,
     Other,
 }
+
+impl GameOutputLogLevel {
+    pub const ALL: [Self; 7] = [Self::Fatal, Self::Error, Self::Warn, Self::Info, Self::Debug, Self::Trace, Self::Other];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fatal => "FATAL",
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+            Self::Other => "OTHER",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameOutputEntry {
+    pub time: i64,
+    pub level: GameOutputLogLevel,
+    pub logger: Option<Arc<str>>,
+    pub thread: Option<Arc<str>>,
+    pub text: Arc<[Arc<str>]>,
+}
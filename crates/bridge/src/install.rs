@@ -1,6 +1,6 @@
 use std::{path::{Path, PathBuf}, sync::Arc};
 
-use schema::{content::ContentSource, loader::Loader};
+use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthProjectType, ModrinthProjectVersion}};
 
 use crate::{instance::InstanceID, safe_path::SafePath};
 
@@ -47,7 +47,37 @@ pub enum ContentDownload {
         sha1: Arc<str>,
         size: usize,
     },
+    CurseForge {
+        project_id: u32,
+        file_id: Option<u32>,
+    },
     File {
         path: PathBuf,
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ModrinthCollectionResolution {
+    pub name: Arc<str>,
+    pub projects: Arc<[ModrinthCollectionProject]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModrinthCollectionProject {
+    pub project_id: Arc<str>,
+    pub title: Arc<str>,
+    pub project_type: ModrinthProjectType,
+    pub status: ModrinthCollectionProjectStatus,
+}
+
+#[derive(Debug, Clone)]
+pub enum ModrinthCollectionProjectStatus {
+    Compatible(ModrinthProjectVersion),
+    Conflict {
+        version: ModrinthProjectVersion,
+        conflicts_with: Arc<str>,
+    },
+    AlreadyInstalled,
+    NoCompatibleVersion,
+    Unsupported,
+}
@@ -1,6 +1,8 @@
-use std::{collections::HashSet, path::Path, sync::Arc};
+use std::{collections::HashSet, path::Path, sync::Arc, time::Duration};
 
-use schema::modification::ModrinthModpackFileDownload;
+use enumset::EnumSetType;
+use schema::{loader::Loader, modification::ModrinthModpackFileDownload};
+use ustr::Ustr;
 
 use crate::safe_path::SafePath;
 
@@ -50,13 +52,186 @@ pub struct InstanceWorldSummary {
     pub png_icon: Option<Arc<[u8]>>,
 }
 
+#[derive(Debug, Clone)]
+pub struct WorldDetails {
+    pub level_name: Arc<str>,
+    pub game_mode: Option<WorldGameMode>,
+    pub difficulty: Option<WorldDifficulty>,
+    pub hardcore: bool,
+    pub allow_commands: bool,
+    pub seed: Option<i64>,
+    pub version_name: Option<Arc<str>>,
+    pub data_version: Option<i32>,
+    pub size_bytes: u64,
+    pub enabled_datapacks: Arc<[Arc<str>]>,
+    pub disabled_datapacks: Arc<[Arc<str>]>,
+    pub players: Arc<[WorldPlayer]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldPlayer {
+    pub uuid: Arc<str>,
+    pub position: Option<[f64; 3]>,
+    pub dimension: Option<Arc<str>>,
+    pub health: Option<f32>,
+    pub game_mode: Option<WorldGameMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldGameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl WorldGameMode {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Survival => "Survival",
+            Self::Creative => "Creative",
+            Self::Adventure => "Adventure",
+            Self::Spectator => "Spectator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldDifficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl WorldDifficulty {
+    pub const ALL: [Self; 4] = [Self::Peaceful, Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.get(usize::try_from(id).ok()?).copied()
+    }
+
+    pub fn id(self) -> i8 {
+        self as i8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Peaceful => "Peaceful",
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldPruneOptions {
+    /// Chunks that players have spent fewer ticks in are removed
+    pub min_inhabited_ticks: Option<i64>,
+    /// Chunks further than this many chunks from the spawn and from every player are removed
+    pub keep_radius: Option<u32>,
+    /// Only prune these dimensions, or every dimension if None
+    pub dimensions: Option<Arc<[Arc<str>]>>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldPruneReport {
+    pub dry_run: bool,
+    pub dimensions: Arc<[DimensionPruneReport]>,
+}
+
+impl WorldPruneReport {
+    pub fn removed_chunks(&self) -> usize {
+        self.dimensions.iter().map(|dimension| dimension.removed_chunks).sum()
+    }
+
+    pub fn saved_bytes(&self) -> u64 {
+        self.dimensions.iter().map(DimensionPruneReport::saved_bytes).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DimensionPruneReport {
+    pub dimension: Arc<str>,
+    pub total_chunks: usize,
+    pub removed_chunks: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Region files that couldn't be read and were left untouched
+    pub skipped_regions: Arc<[Arc<str>]>,
+}
+
+impl DimensionPruneReport {
+    pub fn saved_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum WorldEdit {
+    Rename {
+        name: Arc<str>,
+    },
+    SetAllowCommands {
+        allow_commands: bool,
+    },
+    SetDifficulty {
+        difficulty: WorldDifficulty,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct InstanceServerSummary {
+    /// Position of the server inside servers.dat, including hidden entries
+    pub index: usize,
     pub name: Arc<str>,
     pub ip: Arc<str>,
     pub png_icon: Option<Arc<[u8]>>,
 }
 
+#[derive(Debug, Clone)]
+pub enum ServerListEdit {
+    Add {
+        name: Arc<str>,
+        ip: Arc<str>,
+    },
+    Edit {
+        index: usize,
+        name: Arc<str>,
+        ip: Arc<str>,
+    },
+    Remove {
+        index: usize,
+    },
+    Swap {
+        first: usize,
+        second: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerStatus {
+    pub motd: Arc<str>,
+    pub version: Arc<str>,
+    pub protocol: Option<i32>,
+    pub players_online: i64,
+    pub players_max: i64,
+    pub latency: Option<Duration>,
+    pub png_icon: Option<Arc<[u8]>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct InstanceModSummary {
     pub mod_summary: Arc<ModSummary>,
@@ -79,9 +254,41 @@ pub struct ModSummary {
     pub authors: Arc<str>,
     pub png_icon: Option<Arc<[u8]>>,
     pub update_status: Arc<AtomicContentUpdateStatus>,
+    pub provides: Arc<[ProvidedMod]>,
+    pub dependencies: Arc<[ModDependency]>,
     pub extra: LoaderSpecificModSummary,
 }
 
+/// A mod id made available by a jar, either its own, an alias or one from a nested jar
+#[derive(Debug, Clone)]
+pub struct ProvidedMod {
+    pub id: Arc<str>,
+    pub version: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModDependency {
+    pub id: Arc<str>,
+    pub kind: ModDependencyKind,
+    pub requirement: ModVersionRequirement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModDependencyKind {
+    Required,
+    Optional,
+    Incompatible,
+}
+
+#[derive(Debug, Clone)]
+pub enum ModVersionRequirement {
+    Any,
+    /// Fabric version predicates, any of which may match
+    Fabric(Arc<[Arc<str>]>),
+    /// Maven version range as used by Forge and NeoForge
+    Maven(Arc<str>),
+}
+
 #[derive(Debug, Clone)]
 pub enum LoaderSpecificModSummary {
     Fabric,
@@ -94,6 +301,158 @@ pub enum LoaderSpecificModSummary {
     },
 }
 
+#[derive(Debug, Clone)]
+pub enum InstanceIconSelection {
+    Default,
+    Builtin(Arc<str>),
+    /// Copies a png into the instance folder
+    File(Arc<Path>),
+    /// Uses the icon of the first modpack installed in the instance
+    Modpack,
+}
+
+/// Parts of the .minecraft folder that are copied when cloning an instance
+#[derive(Debug, EnumSetType)]
+pub enum CloneInstanceContent {
+    Mods,
+    Config,
+    Saves,
+    ResourcePacks,
+}
+
+impl CloneInstanceContent {
+    pub fn name(self) -> &'static str {
+        match self {
+            CloneInstanceContent::Mods => "Mods",
+            CloneInstanceContent::Config => "Config and options",
+            CloneInstanceContent::Saves => "Saves",
+            CloneInstanceContent::ResourcePacks => "Resource packs",
+        }
+    }
+
+    /// Paths relative to the .minecraft folder
+    pub fn paths(self) -> &'static [&'static str] {
+        match self {
+            CloneInstanceContent::Mods => &["mods"],
+            CloneInstanceContent::Config => &["config", "options.txt"],
+            CloneInstanceContent::Saves => &["saves"],
+            CloneInstanceContent::ResourcePacks => &["resourcepacks"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionChangeTarget {
+    pub minecraft_version: Ustr,
+    pub loader: Loader,
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionChangeReport {
+    pub target: VersionChangeTarget,
+    pub mods: Arc<[VersionChangeModReport]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionChangeModReport {
+    pub name: Arc<str>,
+    pub filename: Arc<str>,
+    pub enabled: bool,
+    pub compatibility: VersionChangeCompatibility,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionChangeCompatibility {
+    /// The installed file already supports the new version and loader
+    Compatible,
+    /// A different file from the same Modrinth project supports the new version and loader
+    Update { filename: Arc<str> },
+    Incompatible,
+    /// Not installed from Modrinth, so compatibility is unknown
+    Unchecked,
+}
+
+/// A mod with an update found by the last update check
+#[derive(Debug, Clone)]
+pub struct PendingModUpdate {
+    pub mod_id: InstanceModID,
+    pub name: Arc<str>,
+    pub old_version: Arc<str>,
+    pub new_version: Arc<str>,
+    pub changelog: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModpackUpdatePreview {
+    pub name: Arc<str>,
+    pub old_version: Arc<str>,
+    pub new_version: Arc<str>,
+    pub changelog: Option<Arc<str>>,
+    pub files: Arc<[ModpackFileChange]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModpackFileChange {
+    /// Relative to the .minecraft folder
+    pub path: Arc<str>,
+    pub kind: ModpackFileChangeKind,
+    pub is_override: bool,
+    /// The file on disk differs from the one the current modpack version would place there
+    pub user_modified: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModpackFileChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A folder of packs inside an instance, datapacks belong to a world which is identified by its folder name inside saves
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PackFolder {
+    ResourcePacks,
+    ShaderPacks,
+    DataPacks { world: Arc<str> },
+}
+
+impl PackFolder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackFolder::ResourcePacks => "Resource Packs",
+            PackFolder::ShaderPacks => "Shader Packs",
+            PackFolder::DataPacks { .. } => "Datapacks",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstancePackSummary {
+    pub filename: Arc<str>,
+    pub lowercase_filename: Arc<str>,
+    pub path: Arc<Path>,
+    pub enabled: bool,
+    pub is_folder: bool,
+    /// Sha1 of the pack's zip, folders aren't hashed
+    pub hash: Option<[u8; 20]>,
+    pub metadata: PackMetadata,
+    pub png_icon: Option<Arc<[u8]>>,
+    pub update_status: Arc<AtomicContentUpdateStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PackMetadata {
+    /// Read from pack.mcmeta, used by resource packs and datapacks
+    Pack {
+        description: Arc<str>,
+        pack_format: Option<i32>,
+    },
+    Shader {
+        profiles: Arc<[Arc<str>]>,
+        dimensions: Arc<[Arc<str>]>,
+    },
+    Unknown,
+}
 
 #[atomic_enum::atomic_enum]
 #[derive(PartialEq, Eq)]
@@ -103,13 +462,15 @@ pub enum ContentUpdateStatus {
     ErrorNotFound,
     ErrorInvalidHash,
     AlreadyUpToDate,
+    Pinned,
     Modrinth,
+    CurseForge,
 }
 
 impl ContentUpdateStatus {
     pub fn can_update(&self) -> bool {
         match self {
-            ContentUpdateStatus::Modrinth => true,
+            ContentUpdateStatus::Modrinth | ContentUpdateStatus::CurseForge => true,
             _ => false,
         }
     }
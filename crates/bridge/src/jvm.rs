@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct JvmRecommendation {
+    pub memory_min: u32,
+    pub memory_max: u32,
+    /// Why the memory was recommended, e.g. the mod count and loader
+    pub reasons: Arc<[Arc<str>]>,
+    pub enabled_mods: usize,
+    pub total_system_memory: Option<u32>,
+    pub available_system_memory: Option<u32>,
+    /// Major version of the java binary the instance will launch with, if it could be determined
+    pub java_major_version: Option<u32>,
+}
+
+pub struct JvmFlagPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub flags: &'static str,
+    pub min_java: u32,
+    /// Flags that are only needed before the given java version
+    pub legacy_flags: Option<(u32, &'static str)>,
+}
+
+impl JvmFlagPreset {
+    pub fn supports(&self, java_major_version: Option<u32>) -> bool {
+        java_major_version.is_none_or(|java| java >= self.min_java)
+    }
+
+    pub fn flags_for(&self, java_major_version: Option<u32>) -> String {
+        match (self.legacy_flags, java_major_version) {
+            (Some((before, legacy_flags)), Some(java)) if java < before => format!("{} {}", self.flags, legacy_flags),
+            _ => self.flags.to_string(),
+        }
+    }
+}
+
+pub const JVM_FLAG_PRESETS: &[JvmFlagPreset] = &[
+    JvmFlagPreset {
+        name: "G1 Tuned",
+        description: "Low pause G1 settings based on Aikar's flags, works well for most instances",
+        flags: "-XX:+UseG1GC -XX:+ParallelRefProcEnabled -XX:MaxGCPauseMillis=200 -XX:+UnlockExperimentalVMOptions -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:G1NewSizePercent=30 -XX:G1MaxNewSizePercent=40 -XX:G1HeapRegionSize=8M -XX:G1ReservePercent=20 -XX:G1HeapWastePercent=5 -XX:G1MixedGCCountTarget=4 -XX:InitiatingHeapOccupancyPercent=15 -XX:G1MixedGCLiveThresholdPercent=90 -XX:SurvivorRatio=32 -XX:+PerfDisableSharedMem -XX:MaxTenuringThreshold=1",
+        min_java: 8,
+        legacy_flags: None,
+    },
+    JvmFlagPreset {
+        name: "ZGC Generational",
+        description: "Near pauseless collector, best with plenty of spare memory",
+        flags: "-XX:+UseZGC -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+PerfDisableSharedMem",
+        min_java: 21,
+        // ZGC is always generational from Java 24
+        legacy_flags: Some((24, "-XX:+ZGenerational")),
+    },
+    JvmFlagPreset {
+        name: "Shenandoah",
+        description: "Low pause collector with a smaller memory overhead than ZGC",
+        flags: "-XX:+UseShenandoahGC -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+PerfDisableSharedMem",
+        min_java: 12,
+        legacy_flags: None,
+    },
+];
+
+const GARBAGE_COLLECTORS: &[&str] = &["UseG1GC", "UseZGC", "UseShenandoahGC", "UseParallelGC", "UseSerialGC", "UseConcMarkSweepGC", "UseEpsilonGC"];
+
+/// Options that make the jvm refuse to start, along with the first java version that doesn't accept them
+const REMOVED_OPTIONS: &[(&str, u32)] = &[
+    ("AggressiveOpts", 12),
+    ("UseParNewGC", 10),
+    ("CMSIncrementalMode", 9),
+    ("UseCMSCompactAtFullCollection", 9),
+];
+
+/// Options along with the first java version that supports them without unlocking experimental options
+const MIN_JAVA_OPTIONS: &[(&str, u32)] = &[
+    ("UseZGC", 15),
+    ("ZGenerational", 21),
+    ("UseShenandoahGC", 12),
+];
+
+/// Returns problems with user-provided jvm flags, such as conflicting collectors or options unsupported by the java version
+pub fn check_jvm_flags(flags: &str, java_major_version: Option<u32>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut collectors = Vec::new();
+
+    for flag in flags.split_ascii_whitespace() {
+        if flag.starts_with("-Xmx") || flag.starts_with("-Xms") {
+            warnings.push(format!("{flag} overrides the memory setting, use the memory setting instead"));
+            continue;
+        }
+
+        let Some(option) = flag.strip_prefix("-XX:") else {
+            continue;
+        };
+        let (enabled, name) = if let Some(name) = option.strip_prefix('+') {
+            (true, name)
+        } else if let Some(name) = option.strip_prefix('-') {
+            (false, name)
+        } else {
+            (true, option.split_once('=').map(|(name, _)| name).unwrap_or(option))
+        };
+
+        if enabled && GARBAGE_COLLECTORS.contains(&name) && !collectors.contains(&name) {
+            collectors.push(name);
+        }
+
+        let Some(java) = java_major_version else {
+            continue;
+        };
+
+        if let Some((_, removed_in)) = REMOVED_OPTIONS.iter().find(|(option, _)| *option == name) && java >= *removed_in {
+            warnings.push(format!("{flag} was removed in Java {removed_in}, the game won't start with Java {java}"));
+        }
+        if let Some((_, min_java)) = MIN_JAVA_OPTIONS.iter().find(|(option, _)| *option == name) && java < *min_java {
+            warnings.push(format!("{flag} requires Java {min_java} or newer, the instance uses Java {java}"));
+        }
+    }
+
+    if collectors.len() > 1 {
+        warnings.push(format!("Multiple garbage collectors are enabled ({}), the game won't start", collectors.join(", ")));
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removed_options() {
+        let cases = [
+            ("-XX:+AggressiveOpts", Some(11), 0),
+            ("-XX:+AggressiveOpts", Some(12), 1),
+            ("-XX:+UseParNewGC", Some(8), 0),
+            ("-XX:+UseParNewGC", Some(10), 1),
+            ("-XX:-CMSIncrementalMode", Some(17), 1),
+            ("-XX:+UseCMSCompactAtFullCollection", Some(9), 1),
+            // Without a known java version nothing can be said
+            ("-XX:+AggressiveOpts", None, 0),
+        ];
+        for (flags, java, warnings) in cases {
+            assert_eq!(check_jvm_flags(flags, java).len(), warnings, "{flags} with {java:?}");
+        }
+    }
+
+    #[test]
+    fn test_min_java_options() {
+        let cases = [
+            ("-XX:+UseZGC", Some(11), 1),
+            ("-XX:+UseZGC", Some(15), 0),
+            ("-XX:+UseZGC -XX:+ZGenerational", Some(17), 1),
+            ("-XX:+UseZGC -XX:+ZGenerational", Some(21), 0),
+            ("-XX:+UseShenandoahGC", Some(8), 1),
+            ("-XX:+UseShenandoahGC", Some(12), 0),
+            ("-XX:+UseShenandoahGC", None, 0),
+        ];
+        for (flags, java, warnings) in cases {
+            assert_eq!(check_jvm_flags(flags, java).len(), warnings, "{flags} with {java:?}");
+        }
+    }
+
+    #[test]
+    fn test_collectors() {
+        assert!(check_jvm_flags("-XX:+UseG1GC -XX:MaxGCPauseMillis=200", Some(21)).is_empty());
+        // Enabling the same collector twice or disabling another is fine
+        assert!(check_jvm_flags("-XX:+UseG1GC -XX:+UseG1GC -XX:-UseZGC", Some(21)).is_empty());
+
+        let warnings = check_jvm_flags("-XX:+UseG1GC -XX:+UseZGC", None);
+        assert_eq!(warnings, ["Multiple garbage collectors are enabled (UseG1GC, UseZGC), the game won't start"]);
+    }
+
+    #[test]
+    fn test_memory_flags() {
+        let warnings = check_jvm_flags("-Xmx4G -Xms2G -Xss4M", Some(21));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("-Xmx4G"));
+        assert!(warnings[1].starts_with("-Xms2G"));
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in JVM_FLAG_PRESETS {
+            let java = Some(preset.min_java);
+            assert!(preset.supports(java), "{}", preset.name);
+            assert!(!preset.supports(Some(preset.min_java - 1)), "{}", preset.name);
+            assert!(check_jvm_flags(&preset.flags_for(java), java).is_empty(), "{}", preset.name);
+        }
+
+        let zgc = JVM_FLAG_PRESETS.iter().find(|preset| preset.name == "ZGC Generational").unwrap();
+        assert!(zgc.flags_for(Some(21)).contains("-XX:+ZGenerational"));
+        assert!(!zgc.flags_for(Some(24)).contains("-XX:+ZGenerational"));
+    }
+}
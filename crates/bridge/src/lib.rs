@@ -1,8 +1,10 @@
 pub mod account;
+pub mod dependencies;
 pub mod game_output;
 pub mod handle;
 pub mod install;
 pub mod instance;
+pub mod jvm;
 pub mod keep_alive;
 pub mod message;
 pub mod meta;
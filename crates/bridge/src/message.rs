@@ -1,14 +1,14 @@
 use std::{ffi::OsString, path::Path, sync::Arc};
 
 use enumset::{EnumSet, EnumSetType};
-use schema::{instance::{InstanceConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceMemoryConfiguration}, loader::Loader};
+use schema::{curseforge::{CurseForgeConfiguration, CurseForgeSearchRequest, CurseForgeSearchResult}, instance::{GameConfiguration, InstanceConfiguration, InstanceEnvironmentVariablesConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceMemoryConfiguration, InstanceOrganization, ModUpdatePreference, InstanceResolutionConfiguration, InstanceSetting, InstanceSyncGroup}, loader::Loader, network::NetworkConfiguration, paste::PasteConfiguration, redaction::{RedactionConfiguration, RedactionRule}};
 use ustr::Ustr;
 use uuid::Uuid;
 
 use crate::{
-    account::Account, game_output::GameOutputLogLevel, install::ContentInstall, instance::{
-        InstanceID, InstanceModID, InstanceModSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary,
-    }, keep_alive::{KeepAlive, KeepAliveHandle}, meta::{MetadataRequest, MetadataResult}, modal_action::ModalAction
+    account::Account, game_output::{GameOutputEntry, GameOutputLogLevel}, install::{ContentInstall, ModrinthCollectionResolution}, instance::{
+        CloneInstanceContent, InstanceID, InstanceIconSelection, InstanceModID, InstanceModSummary, ModpackUpdatePreview, PendingModUpdate, InstancePackSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, PackFolder, ServerListEdit, ServerStatus, VersionChangeReport, VersionChangeTarget, WorldDetails, WorldEdit, WorldPruneOptions, WorldPruneReport,
+    }, jvm::JvmRecommendation, keep_alive::{KeepAlive, KeepAliveHandle}, meta::{MetadataRequest, MetadataResult}, modal_action::ModalAction
 };
 
 #[derive(Debug)]
@@ -29,17 +29,82 @@ pub enum MessageToBackend {
         id: InstanceID,
         name: Ustr,
     },
+    CloneInstance {
+        id: InstanceID,
+        name: Ustr,
+        contents: EnumSet<CloneInstanceContent>,
+        modal_action: ModalAction,
+    },
+    CheckInstanceVersionChange {
+        id: InstanceID,
+        target: VersionChangeTarget,
+        channel: tokio::sync::oneshot::Sender<Result<VersionChangeReport, Arc<str>>>,
+    },
+    ChangeInstanceVersion {
+        id: InstanceID,
+        target: VersionChangeTarget,
+        disable_incompatible: bool,
+        modal_action: ModalAction,
+    },
+    GetInstanceVersionRollback {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Option<VersionChangeTarget>>,
+    },
+    RevertInstanceVersionChange {
+        id: InstanceID,
+        modal_action: ModalAction,
+    },
     SetInstanceMemory {
         id: InstanceID,
-        memory: InstanceMemoryConfiguration,
+        memory: InstanceSetting<InstanceMemoryConfiguration>,
     },
     SetInstanceJvmFlags {
         id: InstanceID,
-        jvm_flags: InstanceJvmFlagsConfiguration,
+        jvm_flags: InstanceSetting<InstanceJvmFlagsConfiguration>,
     },
     SetInstanceJvmBinary {
         id: InstanceID,
-        jvm_binary: InstanceJvmBinaryConfiguration,
+        jvm_binary: InstanceSetting<InstanceJvmBinaryConfiguration>,
+    },
+    GetJvmRecommendation {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<JvmRecommendation>,
+    },
+    SetInstanceResolution {
+        id: InstanceID,
+        resolution: InstanceSetting<InstanceResolutionConfiguration>,
+    },
+    SetInstanceEnvironmentVariables {
+        id: InstanceID,
+        environment_variables: InstanceSetting<InstanceEnvironmentVariablesConfiguration>,
+    },
+    SetInstanceSyncGroup {
+        id: InstanceID,
+        sync_group: InstanceSyncGroup,
+    },
+    SetInstanceGroup {
+        id: InstanceID,
+        group: Option<Arc<str>>,
+    },
+    SetInstanceTags {
+        id: InstanceID,
+        tags: Arc<[Arc<str>]>,
+    },
+    SetInstanceFavourite {
+        id: InstanceID,
+        favourite: bool,
+    },
+    SetInstanceIcon {
+        id: InstanceID,
+        icon: InstanceIconSelection,
+    },
+    GetInstanceNotes {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<str>>,
+    },
+    SetInstanceNotes {
+        id: InstanceID,
+        notes: Arc<str>,
     },
     KillInstance {
         id: InstanceID,
@@ -58,6 +123,50 @@ pub enum MessageToBackend {
     RequestLoadMods {
         id: InstanceID,
     },
+    RequestLoadPacks {
+        id: InstanceID,
+        folder: PackFolder,
+    },
+    GetWorldDetails {
+        id: InstanceID,
+        world: Arc<Path>,
+        channel: tokio::sync::oneshot::Sender<Result<WorldDetails, Arc<str>>>,
+    },
+    EditWorld {
+        id: InstanceID,
+        world: Arc<Path>,
+        edit: WorldEdit,
+        channel: tokio::sync::oneshot::Sender<Result<WorldDetails, Arc<str>>>,
+    },
+    PruneWorld {
+        id: InstanceID,
+        world: Arc<Path>,
+        options: WorldPruneOptions,
+        channel: tokio::sync::oneshot::Sender<Result<WorldPruneReport, Arc<str>>>,
+    },
+    GetNbtFiles {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<[Arc<Path>]>>,
+    },
+    ReadNbtFile {
+        id: InstanceID,
+        path: Arc<Path>,
+        channel: tokio::sync::oneshot::Sender<Result<NbtFile, Arc<str>>>,
+    },
+    WriteNbtFile {
+        id: InstanceID,
+        path: Arc<Path>,
+        file: NbtFile,
+        channel: tokio::sync::oneshot::Sender<Result<(), Arc<str>>>,
+    },
+    EditServerList {
+        id: InstanceID,
+        edit: ServerListEdit,
+    },
+    PingServer {
+        address: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<Result<ServerStatus, Arc<str>>>,
+    },
     SetModEnabled {
         id: InstanceID,
         mod_ids: Vec<InstanceModID>,
@@ -73,33 +182,161 @@ pub enum MessageToBackend {
         id: InstanceID,
         mod_ids: Vec<InstanceModID>,
     },
+    SetPackEnabled {
+        id: InstanceID,
+        folder: PackFolder,
+        paths: Vec<Arc<Path>>,
+        enabled: bool,
+    },
+    DeletePack {
+        id: InstanceID,
+        folder: PackFolder,
+        paths: Vec<Arc<Path>>,
+    },
     InstallContent {
         content: ContentInstall,
         modal_action: ModalAction,
     },
+    ResolveModrinthCollection {
+        instance: InstanceID,
+        collection: Arc<str>,
+        modal_action: ModalAction,
+        channel: tokio::sync::oneshot::Sender<ModrinthCollectionResolution>,
+    },
+    SearchCurseForge {
+        request: CurseForgeSearchRequest,
+        channel: tokio::sync::oneshot::Sender<Result<Arc<CurseForgeSearchResult>, Arc<str>>>,
+    },
+    ImportCurseForgeModpack {
+        path: Arc<Path>,
+        modal_action: ModalAction,
+    },
     DownloadAllMetadata,
     UpdateCheck { instance: InstanceID, modal_action: ModalAction },
+    InstallMissingDependencies {
+        instance: InstanceID,
+        modal_action: ModalAction,
+    },
     UpdateMod {
         instance: InstanceID,
         mod_id: InstanceModID,
         modal_action: ModalAction,
     },
+    GetPendingModUpdates {
+        instance: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Result<Arc<[PendingModUpdate]>, Arc<str>>>,
+    },
+    UpdateAllMods {
+        instance: InstanceID,
+        mod_ids: Arc<[InstanceModID]>,
+        modal_action: ModalAction,
+    },
+    /// Names of the mods updated by the most recent batch update, if it can be reverted
+    GetModUpdateRollback {
+        instance: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Option<Arc<[Arc<str>]>>>,
+    },
+    RevertModUpdates {
+        instance: InstanceID,
+        modal_action: ModalAction,
+    },
+    SetModUpdatePreference {
+        instance: InstanceID,
+        /// The [`crate::instance::ModSummary::id`], preferences apply to every version of the mod
+        mod_summary_id: Arc<str>,
+        preference: ModUpdatePreference,
+    },
+    /// Skips the update found for the mod by the last update check
+    SkipModUpdate {
+        instance: InstanceID,
+        mod_id: InstanceModID,
+    },
+    GetModpackUpdatePreview {
+        instance: InstanceID,
+        mod_id: InstanceModID,
+        channel: tokio::sync::oneshot::Sender<Result<ModpackUpdatePreview, Arc<str>>>,
+    },
+    UpdateModpack {
+        instance: InstanceID,
+        mod_id: InstanceModID,
+        /// Modified files the user chose to keep instead of taking the new version's
+        keep_files: Arc<[Arc<str>]>,
+        modal_action: ModalAction,
+    },
+    PackUpdateCheck {
+        instance: InstanceID,
+        folder: PackFolder,
+        modal_action: ModalAction,
+    },
+    UpdatePack {
+        instance: InstanceID,
+        folder: PackFolder,
+        path: Arc<Path>,
+        modal_action: ModalAction,
+    },
     Sleep5s,
     ReadLog {
         path: Arc<Path>,
-        send: tokio::sync::mpsc::Sender<Arc<str>>
+        send: tokio::sync::mpsc::Sender<GameOutputEntry>
     },
     GetLogFiles {
         instance: InstanceID,
         channel: tokio::sync::oneshot::Sender<LogFiles>,
     },
+    /// Sync group messages use `None` for the default group
     GetSyncState {
+        group: Option<Arc<str>>,
         channel: tokio::sync::oneshot::Sender<SyncState>,
     },
     SetSyncing {
+        group: Option<Arc<str>>,
         target: SyncTarget,
         value: bool,
     },
+    GetSyncGroups {
+        channel: tokio::sync::oneshot::Sender<Vec<Arc<str>>>,
+    },
+    CreateSyncGroup {
+        name: Arc<str>,
+    },
+    DeleteSyncGroup {
+        name: Arc<str>,
+    },
+    GetNetworkConfiguration {
+        channel: tokio::sync::oneshot::Sender<NetworkConfiguration>,
+    },
+    SetNetworkConfiguration {
+        configuration: NetworkConfiguration,
+    },
+    GetCurseForgeConfiguration {
+        channel: tokio::sync::oneshot::Sender<CurseForgeConfiguration>,
+    },
+    SetCurseForgeConfiguration {
+        configuration: CurseForgeConfiguration,
+    },
+    GetPasteConfiguration {
+        channel: tokio::sync::oneshot::Sender<PasteConfiguration>,
+    },
+    SetPasteConfiguration {
+        configuration: PasteConfiguration,
+    },
+    GetRedactionConfiguration {
+        channel: tokio::sync::oneshot::Sender<RedactionConfiguration>,
+    },
+    SetRedactionConfiguration {
+        configuration: RedactionConfiguration,
+    },
+    GetGameConfiguration {
+        channel: tokio::sync::oneshot::Sender<GameConfiguration>,
+    },
+    SetGameConfiguration {
+        configuration: GameConfiguration,
+    },
+    TestRedactionRules {
+        rules: Vec<RedactionRule>,
+        sample: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<Result<Arc<str>, Arc<str>>>,
+    },
     CleanupOldLogFiles {
         instance: InstanceID,
     },
@@ -107,6 +344,15 @@ pub enum MessageToBackend {
         path: Arc<Path>,
         modal_action: ModalAction,
     },
+    /// Uploads latest.log, the newest crash report and the mod list as a single paste
+    UploadLogBundle {
+        instance: InstanceID,
+        modal_action: ModalAction,
+    },
+    ExportLog {
+        path: Arc<Path>,
+        lines: Arc<[Arc<str>]>,
+    },
     AddNewAccount {
         modal_action: ModalAction,
     },
@@ -122,6 +368,8 @@ pub enum MessageToFrontend {
         name: Ustr,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        organization: InstanceOrganization,
+        icon: Option<Arc<[u8]>>,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
@@ -134,6 +382,8 @@ pub enum MessageToFrontend {
         name: Ustr,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        organization: InstanceOrganization,
+        icon: Option<Arc<[u8]>>,
         status: InstanceStatus,
     },
     InstanceWorldsUpdated {
@@ -148,6 +398,12 @@ pub enum MessageToFrontend {
         id: InstanceID,
         mods: Arc<[InstanceModSummary]>,
     },
+    InstancePacksUpdated {
+        id: InstanceID,
+        folder: PackFolder,
+        state: Arc<AtomicBridgeDataLoadState>,
+        packs: Arc<[InstancePackSummary]>,
+    },
     CreateGameOutputWindow {
         id: usize,
         keep_alive: KeepAlive,
@@ -156,6 +412,8 @@ pub enum MessageToFrontend {
         id: usize,
         time: i64,
         level: GameOutputLogLevel,
+        logger: Option<Arc<str>>,
+        thread: Option<Arc<str>>,
         text: Arc<[Arc<str>]>,
     },
     AddNotification {
@@ -178,6 +436,19 @@ pub enum MessageToFrontend {
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NbtCompression {
+    None,
+    Gzip,
+    Zlib,
+}
+
+#[derive(Debug, Clone)]
+pub struct NbtFile {
+    pub nbt: nbt::NBT,
+    pub compression: NbtCompression,
+}
+
 #[derive(Debug, Default)]
 pub struct LogFiles {
     pub paths: Vec<Arc<Path>>,
@@ -187,6 +458,7 @@ pub struct LogFiles {
 #[derive(Debug, Default)]
 pub struct SyncState {
     pub sync_folder: Option<Arc<Path>>,
+    pub groups: Vec<Arc<str>>,
     pub want_sync: EnumSet<SyncTarget>,
     pub total: usize,
     pub synced: enum_map::EnumMap<SyncTarget, usize>,
@@ -50,4 +50,8 @@ impl SafePath {
     pub fn file_name(&self) -> Option<&str> {
         self.0.file_name()
     }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
 }
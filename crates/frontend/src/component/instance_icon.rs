@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use gpui::{prelude::*, *};
+use gpui_component::ActiveTheme as _;
+use schema::instance::InstanceIcon;
+
+use crate::{entity::instance::InstanceEntry, png_render_cache};
+
+/// Names of the svgs in `assets/icons` that can be chosen as an instance icon
+pub const BUILTIN_INSTANCE_ICONS: &[&str] = &[
+    "box", "anvil", "swords", "diamond", "tree-pine", "mountain", "carrot", "cat", "bug", "compass", "map", "house",
+    "heart", "star", "sun", "moon", "zap", "wand-sparkles", "users", "server", "cpu", "palette", "ice-cream-cone", "award",
+];
+
+pub fn render(instance: &InstanceEntry, size: u32, cx: &mut App) -> AnyElement {
+    render_icon(&instance.organization.icon, instance.icon.as_ref(), size, cx)
+}
+
+pub fn render_icon(icon: &InstanceIcon, png: Option<&Arc<[u8]>>, size: u32, cx: &mut App) -> AnyElement {
+    match (icon, png) {
+        (InstanceIcon::Builtin(name), _) => render_builtin(name, size, cx),
+        (InstanceIcon::Custom, Some(png)) => {
+            let resize = png_render_cache::ImageTransformation::Resize { width: size, height: size };
+            png_render_cache::render_with_transform(Arc::clone(png), resize, cx)
+                .size(px(size as f32))
+                .rounded_sm()
+                .into_any_element()
+        },
+        _ => svg()
+            .path("icons/box.svg")
+            .size(px(size as f32))
+            .text_color(cx.theme().muted_foreground)
+            .into_any_element(),
+    }
+}
+
+pub fn render_builtin(name: &str, size: u32, cx: &App) -> AnyElement {
+    svg()
+        .path(SharedString::new(format!("icons/{name}.svg")))
+        .size(px(size as f32))
+        .text_color(cx.theme().foreground)
+        .into_any_element()
+}
@@ -1,79 +1,332 @@
-use bridge::handle::BackendHandle;
+use std::{cmp::Ordering, sync::Arc};
+
+use bridge::{handle::BackendHandle, message::MessageToBackend};
 use gpui::{prelude::*, *};
 use gpui_component::{
-    Sizable,
+    IconName, Sizable,
     button::{Button, ButtonVariants},
     h_flex,
     table::{Column, ColumnSort, TableDelegate, TableState},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    component::instance_icon,
     entity::{
-        instance::{InstanceAddedEvent, InstanceEntry, InstanceModifiedEvent, InstanceRemovedEvent}, DataEntities
-    }, pages::instance::instance_page::InstanceSubpageType, root, ui
+        instance::{InstanceAddedEvent, InstanceEntry, InstanceModifiedEvent, InstanceMovedToTopEvent, InstanceRemovedEvent}, DataEntities
+    }, interface_config::InterfaceConfig, pages::instance::instance_page::InstanceSubpageType, root, ui
 };
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSortMode {
+    /// The order instances were created or launched in
+    #[default]
+    Recent,
+    Name,
+    LastPlayed,
+    Version,
+    Loader,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceSort {
+    pub mode: InstanceSortMode,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSection {
+    Favourites,
+    Group(SharedString),
+    Ungrouped,
+}
+
+impl InstanceSection {
+    fn of(entry: &InstanceEntry) -> Self {
+        if entry.organization.favourite {
+            InstanceSection::Favourites
+        } else if let Some(group) = &entry.organization.group {
+            InstanceSection::Group(SharedString::new(group.clone()))
+        } else {
+            InstanceSection::Ungrouped
+        }
+    }
+
+    fn label(&self) -> SharedString {
+        match self {
+            InstanceSection::Favourites => "Favourites".into(),
+            InstanceSection::Group(name) => name.clone(),
+            InstanceSection::Ungrouped => "Ungrouped".into(),
+        }
+    }
+
+    fn display_order(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (InstanceSection::Group(a), InstanceSection::Group(b)) => lexical_sort::natural_lexical_cmp(a, b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            InstanceSection::Favourites => 0,
+            InstanceSection::Group(_) => 1,
+            InstanceSection::Ungrouped => 2,
+        }
+    }
+}
+
+enum FilterTerm {
+    Text(String),
+    Tag(String),
+    Loader(String),
+    Version(String),
+    Group(String),
+}
+
+/// Search query such as `skyblock tag:friends loader:fabric version:1.21`, all terms need to match
+#[derive(Default)]
+struct InstanceFilter {
+    terms: Vec<FilterTerm>,
+}
+
+impl InstanceFilter {
+    fn parse(query: &str) -> Self {
+        let terms = query.split_whitespace().map(|term| {
+            let lower = term.to_lowercase();
+            if let Some((key, value)) = lower.split_once(':') && !value.is_empty() {
+                let value = value.to_string();
+                match key {
+                    "tag" => return FilterTerm::Tag(value),
+                    "loader" => return FilterTerm::Loader(value),
+                    "version" => return FilterTerm::Version(value),
+                    "group" => return FilterTerm::Group(value),
+                    _ => {},
+                }
+            }
+            FilterTerm::Text(lower)
+        }).collect();
+        Self { terms }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    fn matches(&self, entry: &InstanceEntry) -> bool {
+        let group = entry.organization.group.as_deref().map(str::to_lowercase);
+        self.terms.iter().all(|term| match term {
+            FilterTerm::Text(text) => {
+                entry.name.to_lowercase().contains(text) || group.as_ref().is_some_and(|group| group.contains(text))
+            },
+            FilterTerm::Tag(tag) => entry.organization.tags.iter().any(|existing| existing.to_lowercase().starts_with(tag)),
+            FilterTerm::Loader(loader) => entry.configuration.loader.name().to_lowercase().starts_with(loader),
+            FilterTerm::Version(version) => entry.configuration.minecraft_version.to_lowercase().starts_with(version),
+            FilterTerm::Group(name) => group.as_ref().is_some_and(|group| group.contains(name)),
+        })
+    }
+}
+
+enum InstanceRow {
+    Header {
+        section: InstanceSection,
+        count: usize,
+        collapsed: bool,
+    },
+    Instance(InstanceEntry),
+}
+
 pub struct InstanceList {
     columns: Vec<Column>,
     items: Vec<InstanceEntry>,
+    rows: Vec<InstanceRow>,
+    sort: InstanceSort,
+    filter: InstanceFilter,
     backend_handle: BackendHandle,
     _instance_added_subscription: Subscription,
     _instance_removed_subscription: Subscription,
     _instance_modified_subscription: Subscription,
+    _instance_moved_to_top_subscription: Subscription,
 }
 
 impl InstanceList {
     pub fn create_table(data: &DataEntities, window: &mut Window, cx: &mut App) -> Entity<TableState<Self>> {
         let instances = data.instances.clone();
         let items = instances.read(cx).entries.values().map(|i| i.read(cx).clone()).collect();
+        let sort = InterfaceConfig::get(cx).instance_sort;
         cx.new(|cx| {
             let _instance_added_subscription = cx.subscribe::<_, InstanceAddedEvent>(&instances, |table: &mut TableState<InstanceList>, _, event, cx| {
-                table.delegate_mut().items.insert(0, event.instance.clone());
+                let delegate = table.delegate_mut();
+                delegate.items.insert(0, event.instance.clone());
+                delegate.refresh_rows(cx);
                 cx.notify();
             });
             let _instance_removed_subscription = cx.subscribe::<_, InstanceRemovedEvent>(&instances, |table, _, event, cx| {
-                table.delegate_mut().items.retain(|instance| {
+                let delegate = table.delegate_mut();
+                delegate.items.retain(|instance| {
                     instance.id != event.id
                 });
+                delegate.refresh_rows(cx);
                 cx.notify();
             });
             let _instance_modified_subscription = cx.subscribe::<_, InstanceModifiedEvent>(&instances, |table, _, event, cx| {
-                if let Some(entry) = table.delegate_mut().items.iter_mut().find(|entry| entry.id == event.instance.id) {
+                let delegate = table.delegate_mut();
+                if let Some(entry) = delegate.items.iter_mut().find(|entry| entry.id == event.instance.id) {
                     *entry = event.instance.clone();
+                    delegate.refresh_rows(cx);
                     cx.notify();
                 }
             });
-            let instance_list = Self {
+            let _instance_moved_to_top_subscription = cx.subscribe::<_, InstanceMovedToTopEvent>(&instances, |table, _, event, cx| {
+                let delegate = table.delegate_mut();
+                if let Some(index) = delegate.items.iter().position(|entry| entry.id == event.instance.id) {
+                    let entry = delegate.items.remove(index);
+                    delegate.items.insert(0, entry);
+                    delegate.refresh_rows(cx);
+                    cx.notify();
+                }
+            });
+
+            let sortable = |column: Column, mode: InstanceSortMode| {
+                match (sort.mode == mode, sort.descending) {
+                    (false, _) => column.sortable(),
+                    (true, false) => column.ascending(),
+                    (true, true) => column.descending(),
+                }
+            };
+
+            let mut instance_list = Self {
                 columns: vec![
                     Column::new("controls", "")
-                        .width(150.)
+                        .width(220.)
                         .fixed_left()
                         .movable(false)
                         .resizable(false),
-                    Column::new("name", "Name")
+                    sortable(Column::new("name", "Name"), InstanceSortMode::Name)
                         .width(150.)
                         .fixed_left()
-                        .sortable()
                         .resizable(true),
-                    Column::new("version", "Version")
+                    sortable(Column::new("version", "Version"), InstanceSortMode::Version)
                         .width(150.)
                         .fixed_left()
-                        .sortable()
                         .resizable(true),
-                    Column::new("loader", "Loader")
+                    sortable(Column::new("loader", "Loader"), InstanceSortMode::Loader)
                         .width(150.)
                         .fixed_left()
                         .resizable(true),
+                    sortable(Column::new("last_played", "Last Played"), InstanceSortMode::LastPlayed)
+                        .width(150.)
+                        .resizable(true),
+                    Column::new("tags", "Tags")
+                        .width(200.)
+                        .resizable(true),
                 ],
                 items,
+                rows: Vec::new(),
+                sort,
+                filter: InstanceFilter::default(),
                 backend_handle: data.backend_handle.clone(),
                 _instance_added_subscription,
                 _instance_removed_subscription,
                 _instance_modified_subscription,
+                _instance_moved_to_top_subscription,
             };
+            instance_list.refresh_rows(cx);
             TableState::new(instance_list, window, cx)
         })
     }
+
+    pub fn set_search_query(&mut self, query: &str, cx: &App) {
+        self.filter = InstanceFilter::parse(query);
+        self.refresh_rows(cx);
+    }
+
+    fn toggle_section(&mut self, section: InstanceSection, cx: &mut App) {
+        let collapsed = &mut InterfaceConfig::get_mut(cx).collapsed_instance_sections;
+        if let Some(index) = collapsed.iter().position(|existing| *existing == section) {
+            collapsed.remove(index);
+        } else {
+            collapsed.push(section);
+        }
+        self.refresh_rows(cx);
+    }
+
+    fn refresh_rows(&mut self, cx: &App) {
+        let mut entries: Vec<&InstanceEntry> = self.items.iter().filter(|entry| self.filter.matches(entry)).collect();
+
+        let sort = self.sort;
+        entries.sort_by(|a, b| {
+            let ordering = match sort.mode {
+                InstanceSortMode::Recent => Ordering::Equal,
+                InstanceSortMode::Name => lexical_sort::natural_lexical_cmp(&a.name, &b.name),
+                InstanceSortMode::LastPlayed => b.organization.last_played.cmp(&a.organization.last_played),
+                InstanceSortMode::Version => lexical_sort::natural_lexical_cmp(&a.configuration.minecraft_version, &b.configuration.minecraft_version),
+                InstanceSortMode::Loader => a.configuration.loader.name().cmp(b.configuration.loader.name())
+                    .then_with(|| lexical_sort::natural_lexical_cmp(&a.name, &b.name)),
+            };
+            if sort.descending { ordering.reverse() } else { ordering }
+        });
+
+        let mut sections: Vec<(InstanceSection, Vec<&InstanceEntry>)> = Vec::new();
+        for entry in entries {
+            let section = InstanceSection::of(entry);
+            if let Some((_, section_entries)) = sections.iter_mut().find(|(existing, _)| *existing == section) {
+                section_entries.push(entry);
+            } else {
+                sections.push((section, vec![entry]));
+            }
+        }
+        sections.sort_by(|(a, _), (b, _)| a.display_order(b));
+
+        self.rows.clear();
+
+        // Without favourites or groups there's nothing to categorize, so keep the list flat
+        if sections.iter().all(|(section, _)| *section == InstanceSection::Ungrouped) {
+            self.rows.extend(sections.into_iter().flat_map(|(_, entries)| entries).map(|entry| InstanceRow::Instance(entry.clone())));
+            return;
+        }
+
+        let collapsed_sections = &InterfaceConfig::get(cx).collapsed_instance_sections;
+        for (section, entries) in sections {
+            // Searching expands all sections so matches aren't hidden
+            let collapsed = self.filter.is_empty() && collapsed_sections.contains(&section);
+            self.rows.push(InstanceRow::Header { section, count: entries.len(), collapsed });
+            if !collapsed {
+                self.rows.extend(entries.into_iter().map(|entry| InstanceRow::Instance(entry.clone())));
+            }
+        }
+    }
+
+    fn groups(&self) -> Arc<[SharedString]> {
+        let mut groups: Vec<SharedString> = Vec::new();
+        for group in self.items.iter().filter_map(|item| item.organization.group.as_ref()) {
+            if !groups.iter().any(|existing| existing.as_str() == &**group) {
+                groups.push(SharedString::new(group.clone()));
+            }
+        }
+        groups.sort_by(|a, b| lexical_sort::natural_lexical_cmp(a, b));
+        groups.into()
+    }
+
+    fn render_header(&self, row_ix: usize, section: &InstanceSection, count: usize, collapsed: bool, cx: &mut Context<TableState<Self>>) -> AnyElement {
+        let table = cx.entity();
+        let section = section.clone();
+        Button::new(("section", row_ix))
+            .ghost()
+            .small()
+            .icon(if collapsed { IconName::ChevronRight } else { IconName::ChevronDown })
+            .label(SharedString::new(format!("{} ({})", section.label(), count)))
+            .on_click(move |_, _, cx| {
+                let section = section.clone();
+                table.update(cx, |table, cx| {
+                    table.delegate_mut().toggle_section(section, cx);
+                    cx.notify();
+                });
+            })
+            .into_any_element()
+    }
 }
 
 impl TableDelegate for InstanceList {
@@ -82,7 +335,7 @@ impl TableDelegate for InstanceList {
     }
 
     fn rows_count(&self, _cx: &App) -> usize {
-        self.items.len()
+        self.rows.len()
     }
 
     fn column(&self, col_ix: usize, _cx: &App) -> gpui_component::table::Column {
@@ -94,55 +347,101 @@ impl TableDelegate for InstanceList {
         col_ix: usize,
         sort: gpui_component::table::ColumnSort,
         _window: &mut Window,
-        _cx: &mut Context<TableState<Self>>,
+        cx: &mut Context<TableState<Self>>,
     ) {
-        if let Some(col) = self.columns.get_mut(col_ix) {
-            match col.key.as_ref() {
-                "name" => self.items.sort_by(|a, b| match sort {
-                    ColumnSort::Descending => lexical_sort::natural_lexical_cmp(&a.name, &b.name).reverse(),
-                    _ => lexical_sort::natural_lexical_cmp(&a.name, &b.name),
-                }),
-                "version" => self.items.sort_by(|a, b| match sort {
-                    ColumnSort::Descending => lexical_sort::natural_lexical_cmp(&a.configuration.minecraft_version, &b.configuration.minecraft_version).reverse(),
-                    _ => lexical_sort::natural_lexical_cmp(&a.configuration.minecraft_version, &b.configuration.minecraft_version),
-                }),
-                _ => {},
-            }
-        }
+        let Some(col) = self.columns.get(col_ix) else {
+            return;
+        };
+        let mode = match col.key.as_ref() {
+            "name" => InstanceSortMode::Name,
+            "version" => InstanceSortMode::Version,
+            "loader" => InstanceSortMode::Loader,
+            "last_played" => InstanceSortMode::LastPlayed,
+            _ => return,
+        };
+        self.sort = match sort {
+            ColumnSort::Default => InstanceSort::default(),
+            ColumnSort::Ascending => InstanceSort { mode, descending: false },
+            ColumnSort::Descending => InstanceSort { mode, descending: true },
+        };
+        InterfaceConfig::get_mut(cx).instance_sort = self.sort;
+        self.refresh_rows(cx);
     }
 
-    fn render_td(&mut self, row_ix: usize, col_ix: usize, _window: &mut Window, _cx: &mut Context<TableState<Self>>) -> impl IntoElement {
-        let item = &self.items[row_ix];
-        if let Some(col) = self.columns.get(col_ix) {
-            match col.key.as_ref() {
-                "name" => item.name.clone().into_any_element(),
-                "version" => item.configuration.minecraft_version.as_str().into_any_element(),
-                "controls" => {
-                    let backend_handle = self.backend_handle.clone();
-                    h_flex()
-                        .size_full()
-                        .gap_2()
-                        .border_r_4()
-                        .child(Button::new("start").w(relative(0.5)).small().success().label("Start").on_click({
-                            let name = item.name.clone();
-                            let id = item.id;
-                            move |_, window, cx| {
-                                root::start_instance(id, name.clone(), None, &backend_handle, window, cx);
-                            }
-                        }))
-                        .child(Button::new("view").w(relative(0.5)).small().info().label("View").on_click({
-                            let id = item.id;
-                            move |_, window, cx| {
-                                root::switch_page(ui::PageType::InstancePage(id, InstanceSubpageType::Quickplay), None, window, cx);
-                            }
-                        }))
-                        .into_any_element()
-                },
-                "loader" => item.configuration.loader.name().into_any_element(),
-                _ => "Unknown".into_any_element(),
-            }
-        } else {
-            "Unknown".into_any_element()
+    fn render_td(&mut self, row_ix: usize, col_ix: usize, _window: &mut Window, cx: &mut Context<TableState<Self>>) -> impl IntoElement {
+        let Some(col) = self.columns.get(col_ix) else {
+            return "Unknown".into_any_element();
+        };
+        let item = match &self.rows[row_ix] {
+            InstanceRow::Header { section, count, collapsed } => {
+                if col.key.as_ref() == "controls" {
+                    return self.render_header(row_ix, section, *count, *collapsed, cx);
+                } else {
+                    return div().into_any_element();
+                }
+            },
+            InstanceRow::Instance(item) => item,
+        };
+        match col.key.as_ref() {
+            "name" => h_flex()
+                .gap_2()
+                .child(instance_icon::render(item, 20, cx))
+                .child(item.name.clone())
+                .into_any_element(),
+            "version" => item.configuration.minecraft_version.as_str().into_any_element(),
+            "controls" => {
+                let backend_handle = self.backend_handle.clone();
+                let favourite = item.organization.favourite;
+                h_flex()
+                    .size_full()
+                    .gap_2()
+                    .border_r_4()
+                    .child(Button::new("start").w(relative(0.35)).small().success().label("Start").on_click({
+                        let name = item.name.clone();
+                        let id = item.id;
+                        let backend_handle = backend_handle.clone();
+                        move |_, window, cx| {
+                            root::start_instance(id, name.clone(), None, &backend_handle, window, cx);
+                        }
+                    }))
+                    .child(Button::new("view").w(relative(0.35)).small().info().label("View").on_click({
+                        let id = item.id;
+                        move |_, window, cx| {
+                            root::switch_page(ui::PageType::InstancePage(id, InstanceSubpageType::Quickplay), None, window, cx);
+                        }
+                    }))
+                    .child(Button::new("favourite").small().icon(IconName::Star).map(|button| {
+                        if favourite { button.warning() } else { button.ghost() }
+                    }).on_click({
+                        let id = item.id;
+                        let backend_handle = backend_handle.clone();
+                        move |_, _, _| {
+                            backend_handle.send(MessageToBackend::SetInstanceFavourite { id, favourite: !favourite });
+                        }
+                    }))
+                    .child(Button::new("organize").small().ghost().icon(IconName::Ellipsis).on_click({
+                        let id = item.id;
+                        let name = item.name.clone();
+                        let organization = item.organization.clone();
+                        let groups = self.groups();
+                        move |_, window, cx| {
+                            crate::modals::organize_instance::open(id, name.clone(), &organization, groups.clone(), backend_handle.clone(), window, cx);
+                        }
+                    }))
+                    .into_any_element()
+            },
+            "loader" => item.configuration.loader.name().into_any_element(),
+            "last_played" => {
+                if let Some(last_played) = item.organization.last_played
+                    && let Some(date_time) = chrono::DateTime::from_timestamp_millis(last_played)
+                {
+                    SharedString::new(date_time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()).into_any_element()
+                } else {
+                    "Never".into_any_element()
+                }
+            },
+            "tags" => SharedString::new(item.organization.tags.join(", ")).into_any_element(),
+            _ => "Unknown".into_any_element(),
         }
     }
 }
@@ -1,13 +1,14 @@
 use std::{path::Path, sync::Arc};
 
 use bridge::{
-    instance::{InstanceID, InstanceModSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary},
+    instance::{InstanceID, InstanceModSummary, InstancePackSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, PackFolder},
     message::AtomicBridgeDataLoadState,
 };
 use gpui::{prelude::*, *};
 use gpui_component::select::SelectItem;
 use indexmap::IndexMap;
-use schema::{instance::InstanceConfiguration, loader::Loader};
+use rustc_hash::FxHashMap;
+use schema::{instance::{InstanceConfiguration, InstanceOrganization}, loader::Loader};
 
 pub struct InstanceEntries {
     pub entries: IndexMap<InstanceID, Entity<InstanceEntry>>,
@@ -20,6 +21,8 @@ impl InstanceEntries {
         name: SharedString,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        organization: InstanceOrganization,
+        icon: Option<Arc<[u8]>>,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
@@ -31,6 +34,8 @@ impl InstanceEntries {
                 name,
                 dot_minecraft_folder,
                 configuration,
+                organization,
+                icon,
                 status: InstanceStatus::NotRunning,
                 worlds_state,
                 worlds: cx.new(|_| [].into()),
@@ -38,6 +43,7 @@ impl InstanceEntries {
                 servers: cx.new(|_| [].into()),
                 mods_state,
                 mods: cx.new(|_| [].into()),
+                packs: cx.new(|_| FxHashMap::default()),
             };
 
             entries.entries.insert_before(0, id, cx.new(|_| instance.clone()));
@@ -75,6 +81,8 @@ impl InstanceEntries {
         name: SharedString,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        organization: InstanceOrganization,
+        icon: Option<Arc<[u8]>>,
         status: InstanceStatus,
         cx: &mut App,
     ) {
@@ -84,6 +92,8 @@ impl InstanceEntries {
                     instance.name = name.clone();
                     instance.dot_minecraft_folder = dot_minecraft_folder.clone();
                     instance.configuration = configuration.clone();
+                    instance.organization = organization.clone();
+                    instance.icon = icon.clone();
                     instance.status = status;
                     cx.notify();
 
@@ -144,6 +154,26 @@ impl InstanceEntries {
         });
     }
 
+    pub fn set_packs(
+        entity: &Entity<Self>,
+        id: InstanceID,
+        folder: PackFolder,
+        state: Arc<AtomicBridgeDataLoadState>,
+        packs: Arc<[InstancePackSummary]>,
+        cx: &mut App,
+    ) {
+        entity.update(cx, |entries, cx| {
+            if let Some(instance) = entries.entries.get_mut(&id) {
+                instance.update(cx, |instance, cx| {
+                    instance.packs.update(cx, |existing_packs, cx| {
+                        existing_packs.insert(folder, InstancePacks { state, packs });
+                        cx.notify();
+                    })
+                });
+            }
+        });
+    }
+
     pub fn move_to_top(entity: &Entity<Self>, id: InstanceID, cx: &mut App) {
         entity.update(cx, |entries, cx| {
             if let Some(index) = entries.entries.get_index_of(&id) {
@@ -163,6 +193,9 @@ pub struct InstanceEntry {
     pub name: SharedString,
     pub dot_minecraft_folder: Arc<Path>,
     pub configuration: InstanceConfiguration,
+    pub organization: InstanceOrganization,
+    /// Contents of the `icon.png` in the instance folder
+    pub icon: Option<Arc<[u8]>>,
     pub status: InstanceStatus,
     pub worlds_state: Arc<AtomicBridgeDataLoadState>,
     pub worlds: Entity<Arc<[InstanceWorldSummary]>>,
@@ -170,6 +203,14 @@ pub struct InstanceEntry {
     pub servers: Entity<Arc<[InstanceServerSummary]>>,
     pub mods_state: Arc<AtomicBridgeDataLoadState>,
     pub mods: Entity<Arc<[InstanceModSummary]>>,
+    /// Only contains the pack folders that have been requested
+    pub packs: Entity<FxHashMap<PackFolder, InstancePacks>>,
+}
+
+#[derive(Clone)]
+pub struct InstancePacks {
+    pub state: Arc<AtomicBridgeDataLoadState>,
+    pub packs: Arc<[InstancePackSummary]>,
 }
 
 impl SelectItem for InstanceEntry {
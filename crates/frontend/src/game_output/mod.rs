@@ -1,16 +1,17 @@
-use std::{cell::RefCell, collections::HashMap, num::NonZeroUsize, ops::Range, rc::Rc, sync::Arc};
+use std::{cell::RefCell, collections::HashMap, num::NonZeroUsize, ops::Range, path::Path, rc::Rc, sync::Arc};
 
 use ftree::FenwickTree;
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::Button, h_flex, input::{Input, InputEvent, InputState}, scroll::{Scrollbar, ScrollbarHandle}, v_flex, ActiveTheme as _, Icon, IconName, Sizable
+    button::Button, h_flex, input::{Input, InputEvent, InputState}, notification::NotificationType, scroll::{Scrollbar, ScrollbarHandle}, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Selectable, Sizable, WindowExt
 };
 use lru::LruCache;
+use regex::Regex;
 use rustc_hash::FxBuildHasher;
 
-use bridge::{game_output::GameOutputLogLevel, keep_alive::KeepAlive};
+use bridge::{game_output::GameOutputLogLevel, handle::BackendHandle, keep_alive::KeepAlive, message::MessageToBackend};
 
-use crate::CloseWindow;
+use crate::{root, CloseWindow};
 
 struct CachedShapedLogLevels {
     fatal: Arc<ShapedLine>,
@@ -35,13 +36,79 @@ pub struct GameOutputItemState {
     item_sizes: FenwickTree<usize>,
     total_line_count: usize,
     cached_shaped_lines: CachedShapedLines,
-    search_query: SharedString,
+    filter: GameOutputFilter,
+    matches: Vec<usize>,
+    current_match: Option<usize>,
+}
+
+struct PendingGameOutput {
+    time: i64,
+    level: GameOutputLogLevel,
+    logger: Option<Arc<str>>,
+    thread: Option<Arc<str>>,
+    text: Arc<[Arc<str>]>,
+}
+
+#[derive(Clone)]
+enum GameOutputSearch {
+    Text(SharedString),
+    Regex(Regex),
+}
+
+impl GameOutputSearch {
+    fn find(&self, text: &[Arc<str>]) -> Option<(usize, Range<usize>)> {
+        for (line_index, line) in text.iter().enumerate() {
+            let found = match self {
+                Self::Text(query) => line.find(query.as_str()).map(|found| found..found+query.len()),
+                Self::Regex(regex) => regex.find(line).filter(|found| !found.is_empty()).map(|found| found.range()),
+            };
+            if let Some(found) = found {
+                return Some((line_index, found));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Default)]
+struct GameOutputFilter {
+    search: Option<GameOutputSearch>,
+    show_non_matching: bool,
+    hidden_levels: Vec<GameOutputLogLevel>,
+    logger: SharedString,
+    thread: SharedString,
+}
+
+impl GameOutputFilter {
+    /// Returns whether the item should be shown, along with the search match to highlight
+    fn apply(&self, level: GameOutputLogLevel, logger: Option<&str>, thread: Option<&str>, text: &[Arc<str>]) -> (bool, Option<(usize, Range<usize>)>) {
+        if self.hidden_levels.contains(&level)
+            || !contains_ignore_case(logger, &self.logger)
+            || !contains_ignore_case(thread, &self.thread)
+        {
+            return (false, None);
+        }
+
+        let Some(search) = &self.search else {
+            return (true, None);
+        };
+
+        let found = search.find(text);
+        (found.is_some() || self.show_non_matching, found)
+    }
+}
+
+fn contains_ignore_case(value: Option<&str>, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    value.is_some_and(|value| value.to_lowercase().contains(&query.to_lowercase()))
 }
 
 pub struct GameOutput {
     font: Font,
     scroll_state: Rc<RefCell<GameOutputScrollState>>,
-    pending: Vec<(i64, GameOutputLogLevel, Arc<[Arc<str>]>)>,
+    pending: Vec<PendingGameOutput>,
     item_state: Option<GameOutputItemState>,
     time_column_width: Pixels,
     level_column_width: Pixels,
@@ -70,7 +137,9 @@ impl Default for GameOutput {
                     last_time_millis: 0,
                     item_lines: LruCache::with_hasher(NonZeroUsize::new(256).unwrap(), FxBuildHasher),
                 },
-                search_query: SharedString::new_static(""),
+                filter: GameOutputFilter::default(),
+                matches: Vec::new(),
+                current_match: None,
             }),
             time_column_width: Default::default(),
             level_column_width: Default::default(),
@@ -80,8 +149,8 @@ impl Default for GameOutput {
 }
 
 impl GameOutput {
-    pub fn add(&mut self, time: i64, level: GameOutputLogLevel, text: Arc<[Arc<str>]>) {
-        self.pending.push((time, level, text));
+    pub fn add(&mut self, time: i64, level: GameOutputLogLevel, logger: Option<Arc<str>>, thread: Option<Arc<str>>, text: Arc<[Arc<str>]>) {
+        self.pending.push(PendingGameOutput { time, level, logger, thread, text });
     }
 
     fn shape_log_level(
@@ -126,7 +195,7 @@ impl GameOutput {
         let Some(item_state) = &mut self.item_state else {
             return;
         };
-        for (time, level, text) in self.pending.drain(..) {
+        for PendingGameOutput { time, level, logger, thread, text } in self.pending.drain(..) {
             let shaped_level = match level {
                 GameOutputLogLevel::Fatal => self.shaped_log_levels.as_ref().unwrap().fatal.clone(),
                 GameOutputLogLevel::Error => self.shaped_log_levels.as_ref().unwrap().error.clone(),
@@ -137,31 +206,31 @@ impl GameOutput {
                 GameOutputLogLevel::Other => self.shaped_log_levels.as_ref().unwrap().other.clone(),
             };
 
-            let mut highlighted_text = None;
+            let (visible, highlighted_text) = item_state.filter.apply(level, logger.as_deref(), thread.as_deref(), &text);
+            let index = item_state.items.len();
+
+            if !visible {
+                // Item doesn't match the filter, push skipped item
+                let backup_total_lines_while_skipped = text.len();
+                item_state.item_sizes.push(0);
+                item_state.items.push(GameOutputItem {
+                    time: TimeShapedLine::Timestamp(time),
+                    level: shaped_level.clone(),
+                    log_level: level,
+                    logger,
+                    thread,
+                    text: text.clone(),
+                    index,
+                    backup_total_lines_while_skipped,
+                    total_lines: 0,
+                    highlighted_text: None,
+                    skip: true,
+                });
+                continue;
+            }
 
-            if !item_state.search_query.is_empty() {
-                for (line_index, line) in text.iter().enumerate() {
-                    if let Some(found) = line.find(item_state.search_query.as_str()) {
-                        highlighted_text = Some((line_index, found..found+item_state.search_query.as_str().len()));
-                        break;
-                    }
-                }
-                if highlighted_text.is_none() {
-                    // Item doesn't match search query, push skipped item
-                    let backup_total_lines_while_skipped = text.len();
-                    item_state.item_sizes.push(0);
-                    item_state.items.push(GameOutputItem {
-                        time: TimeShapedLine::Timestamp(time),
-                        level: shaped_level.clone(),
-                        text: text.clone(),
-                        index: item_state.items.len(),
-                        backup_total_lines_while_skipped,
-                        total_lines: 0,
-                        highlighted_text: None,
-                        skip: true,
-                    });
-                    continue;
-                }
+            if highlighted_text.is_some() {
+                item_state.matches.push(index);
             }
 
             let total_lines = text.len();
@@ -170,8 +239,11 @@ impl GameOutput {
             item_state.items.push(GameOutputItem {
                 time: TimeShapedLine::Timestamp(time),
                 level: shaped_level.clone(),
+                log_level: level,
+                logger,
+                thread,
                 text: text.clone(),
-                index: item_state.items.len(),
+                index,
                 backup_total_lines_while_skipped: total_lines,
                 total_lines,
                 highlighted_text,
@@ -181,6 +253,71 @@ impl GameOutput {
     }
 }
 
+impl GameOutputItemState {
+    fn apply_filter(&mut self, filter: GameOutputFilter) {
+        let mut lengths = Vec::with_capacity(self.items.len());
+        self.total_line_count = 0;
+        self.matches.clear();
+        self.current_match = None;
+
+        for item in &mut self.items {
+            if item.skip {
+                item.total_lines = item.backup_total_lines_while_skipped;
+            }
+
+            let (visible, highlighted_text) = filter.apply(item.log_level, item.logger.as_deref(), item.thread.as_deref(), &item.text);
+            if visible {
+                if highlighted_text.is_some() {
+                    self.matches.push(item.index);
+                }
+                item.highlighted_text = highlighted_text;
+                item.skip = false;
+
+                lengths.push(item.total_lines);
+                self.total_line_count += item.total_lines;
+            } else {
+                item.backup_total_lines_while_skipped = item.total_lines;
+                item.total_lines = 0;
+                item.highlighted_text = None;
+                item.skip = true;
+
+                lengths.push(0);
+            }
+        }
+
+        self.item_sizes = FenwickTree::from_iter(lengths.into_iter());
+        self.cached_shaped_lines.item_lines.clear();
+        self.filter = filter;
+    }
+
+    fn export_lines(&self) -> Vec<Arc<str>> {
+        let mut lines = Vec::new();
+        for item in self.items.iter().filter(|item| !item.skip) {
+            let (TimeShapedLine::Timestamp(time) | TimeShapedLine::Shaped(_, time)) = &item.time;
+            let date_time = chrono::DateTime::from_timestamp_millis(*time).unwrap_or_default().with_timezone(&chrono::Local);
+
+            let mut prefix = format!("[{}] [", date_time.time().format("%H:%M:%S%.3f"));
+            if let Some(thread) = &item.thread {
+                prefix.push_str(thread);
+                prefix.push('/');
+            }
+            prefix.push_str(item.log_level.name());
+            prefix.push(']');
+            if let Some(logger) = &item.logger {
+                prefix.push_str(" (");
+                prefix.push_str(logger);
+                prefix.push(')');
+            }
+
+            let mut text = item.text.iter();
+            let first = text.next().map(|line| &**line).unwrap_or_default();
+            lines.push(format!("{prefix}: {first}").into());
+            lines.extend(text.cloned());
+        }
+        lines
+    }
+}
+
 pub struct GameOutputList {
     interactivity: Interactivity,
     game_output: Entity<GameOutput>,
@@ -188,12 +325,15 @@ pub struct GameOutputList {
 
 enum TimeShapedLine {
     Timestamp(i64),
-    Shaped(Arc<ShapedLine>),
+    Shaped(Arc<ShapedLine>, i64),
 }
 
 struct GameOutputItem {
     time: TimeShapedLine,
     level: Arc<ShapedLine>,
+    log_level: GameOutputLogLevel,
+    logger: Option<Arc<str>>,
+    thread: Option<Arc<str>>,
 
     text: Arc<[Arc<str>]>,
     index: usize,
@@ -763,7 +903,7 @@ fn paint_lines<'a, const REVERSE: bool>(
         // Shape time text if needed
         if let TimeShapedLine::Timestamp(timestamp) = item.time {
             if let Some(last_shaped_time) = &cache.last_time && cache.last_time_millis == timestamp {
-                item.time = TimeShapedLine::Shaped(Arc::clone(last_shaped_time));
+                item.time = TimeShapedLine::Shaped(Arc::clone(last_shaped_time), timestamp);
             } else {
                 let date_time = chrono::DateTime::from_timestamp_millis(timestamp).unwrap().with_timezone(&chrono::Local);
                 let time = format!("{}", date_time.time().format("%H:%M:%S%.3f"));
@@ -777,7 +917,7 @@ fn paint_lines<'a, const REVERSE: bool>(
                 };
                 let shaped_time = Arc::new(window.text_system().shape_line(time.into(), font_size, &[time_run], None));
 
-                item.time = TimeShapedLine::Shaped(Arc::clone(&shaped_time));
+                item.time = TimeShapedLine::Shaped(Arc::clone(&shaped_time), timestamp);
 
                 *time_column_width = (*time_column_width).max(shaped_time.width + font_size / 2.0);
 
@@ -791,7 +931,7 @@ fn paint_lines<'a, const REVERSE: bool>(
         if REVERSE {
             time_origin.y -= (line_count - 1) * line_height;
         }
-        if let TimeShapedLine::Shaped(shaped_time) = &item.time {
+        if let TimeShapedLine::Shaped(shaped_time, _) = &item.time {
             _ = shaped_time.paint(time_origin, line_height, TextAlign::Left, None, window, cx);
         }
 
@@ -826,11 +966,18 @@ fn paint_lines<'a, const REVERSE: bool>(
 
 pub struct GameOutputRoot {
     scroll_handler: ScrollHandler,
-    _keep_alive: KeepAlive,
+    keep_alive: Option<KeepAlive>,
+    log_path: Option<Arc<Path>>,
     game_output: Entity<GameOutput>,
+    backend_handle: BackendHandle,
     search_state: Entity<InputState>,
+    logger_state: Entity<InputState>,
+    thread_state: Entity<InputState>,
+    use_regex: bool,
+    show_non_matching: bool,
+    hidden_levels: Vec<GameOutputLogLevel>,
     _search_task: Task<()>,
-    _search_input_subscription: Subscription,
+    _input_subscriptions: [Subscription; 3],
     focus_handle: FocusHandle,
 }
 
@@ -939,32 +1086,69 @@ impl GameOutputRoot {
     pub fn new(
         keep_alive: KeepAlive,
         game_output: Entity<GameOutput>,
+        backend_handle: BackendHandle,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let root = Self::create(Some(keep_alive), None, game_output, backend_handle, window, cx);
+        root.focus_handle.focus(window, cx);
+        root
+    }
+
+    /// Shows a log file read from disk inside another view
+    pub fn new_for_log_file(
+        path: Arc<Path>,
+        game_output: Entity<GameOutput>,
+        backend_handle: BackendHandle,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::create(None, Some(path), game_output, backend_handle, window, cx)
+    }
+
+    fn create(
+        keep_alive: Option<KeepAlive>,
+        log_path: Option<Arc<Path>>,
+        game_output: Entity<GameOutput>,
+        backend_handle: BackendHandle,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let scroll_state = Rc::clone(&game_output.read(cx).scroll_state);
 
         let search_state = cx.new(|cx| InputState::new(window, cx).placeholder("Search").clean_on_escape());
+        let logger_state = cx.new(|cx| InputState::new(window, cx).placeholder("Logger").clean_on_escape());
+        let thread_state = cx.new(|cx| InputState::new(window, cx).placeholder("Thread").clean_on_escape());
 
-        let _search_input_subscription = cx.subscribe_in(&search_state, window, Self::on_search_input_event);
+        let _input_subscriptions = [
+            cx.subscribe_in(&search_state, window, Self::on_filter_input_event),
+            cx.subscribe_in(&logger_state, window, Self::on_filter_input_event),
+            cx.subscribe_in(&thread_state, window, Self::on_filter_input_event),
+        ];
 
         let focus_handle = cx.focus_handle();
-        focus_handle.focus(window, cx);
 
         Self {
             scroll_handler: ScrollHandler { state: scroll_state },
-            _keep_alive: keep_alive,
+            keep_alive,
+            log_path,
             game_output,
+            backend_handle,
             search_state,
+            logger_state,
+            thread_state,
+            use_regex: false,
+            show_non_matching: false,
+            hidden_levels: Vec::new(),
             _search_task: Task::ready(()),
-            _search_input_subscription,
+            _input_subscriptions,
             focus_handle,
         }
     }
 
-    fn on_search_input_event(
+    fn on_filter_input_event(
         &mut self,
-        state: &Entity<InputState>,
+        _state: &Entity<InputState>,
         event: &InputEvent,
         window: &mut Window,
         cx: &mut Context<Self>,
@@ -972,83 +1156,110 @@ impl GameOutputRoot {
         let InputEvent::PressEnter { secondary: false } = event else {
             return;
         };
+        self.apply_filter(window, cx);
+    }
+
+    fn create_filter(&self, cx: &App) -> Result<GameOutputFilter, regex::Error> {
+        let search_pattern = self.search_state.read(cx).value();
+        let search = if search_pattern.trim().is_empty() {
+            None
+        } else if self.use_regex {
+            Some(GameOutputSearch::Regex(Regex::new(&search_pattern)?))
+        } else {
+            Some(GameOutputSearch::Text(search_pattern))
+        };
+
+        Ok(GameOutputFilter {
+            search,
+            show_non_matching: self.show_non_matching,
+            hidden_levels: self.hidden_levels.clone(),
+            logger: self.logger_state.read(cx).value().trim().to_string().into(),
+            thread: self.thread_state.read(cx).value().trim().to_string().into(),
+        })
+    }
+
+    /// The item state is moved into the search task while a filter is being applied
+    fn is_searching(&self, cx: &App) -> bool {
+        self.game_output.read(cx).item_state.is_none()
+    }
+
+    fn apply_filter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_searching(cx) {
+            return;
+        }
+
+        let filter = match self.create_filter(cx) {
+            Ok(filter) => filter,
+            Err(error) => {
+                window.push_notification((NotificationType::Error, SharedString::from(format!("Invalid regex: {error}"))), cx);
+                return;
+            },
+        };
 
         let item_state = self.game_output.update(cx, |game_output, _| game_output.item_state.take());
 
         let Some(mut item_state) = item_state else {
-            return; // Already searching
+            return;
         };
 
-        let search_pattern = state.read(cx).value();
-        if search_pattern.trim().is_empty() {
-            self._search_task = cx.spawn_in(window, async move |this, window| {
-                let mut lengths = Vec::new();
-                item_state.total_line_count = 0;
-                for item in &mut item_state.items {
-                    if item.skip {
-                        item.total_lines = item.backup_total_lines_while_skipped;
-                    }
+        self._search_task = cx.spawn_in(window, async move |this, window| {
+            item_state.apply_filter(filter);
 
-                    item.skip = false;
-                    item.highlighted_text = None;
+            this.update_in(window, |this, window, cx| {
+                this.game_output.update(cx, |game_output, _| {
+                    game_output.item_state = Some(item_state);
+                });
+                this.search_state.update(cx, |input, cx| input.set_loading(false, window, cx));
+                cx.notify();
+            }).unwrap();
+        });
 
-                    item_state.total_line_count += item.total_lines;
-                    lengths.push(item.total_lines);
-                }
-                item_state.item_sizes = FenwickTree::from_iter(lengths.into_iter());
-                item_state.cached_shaped_lines.item_lines.clear();
-                item_state.search_query = SharedString::new_static("");
+        self.search_state.update(cx, |input, cx| input.set_loading(true, window, cx));
+    }
 
-                this.update_in(window, |this, window, cx| {
-                    this.game_output.update(cx, |game_output, _| {
-                        game_output.item_state = Some(item_state);
-                    });
-                    this.search_state.update(cx, |input, cx| input.set_loading(false, window, cx));
-                    cx.notify();
-                }).unwrap();
-            });
-        } else {
-            self._search_task = cx.spawn_in(window, async move |this, window| {
-                let mut lengths = Vec::new();
-                item_state.total_line_count = 0;
-                for item in &mut item_state.items {
-                    let mut contains = None;
-                    for (line_index, line) in item.text.iter().enumerate() {
-                        if let Some(found) = line.find(search_pattern.as_str()) {
-                            contains = Some((line_index, found..found+search_pattern.as_str().len()));
-                            break;
-                        }
-                    }
-                    if contains.is_some() {
-                        lengths.push(item.total_lines);
-                        item_state.total_line_count += item.total_lines;
+    fn jump_to_match(&mut self, forward: bool, cx: &mut Context<Self>) {
+        self.game_output.update(cx, |game_output, _| {
+            let Some(item_state) = &mut game_output.item_state else {
+                return;
+            };
+            let count = item_state.matches.len();
+            if count == 0 {
+                return;
+            }
 
-                        item.highlighted_text = contains;
-                        item.skip = false;
-                    } else {
-                        item.backup_total_lines_while_skipped = item.total_lines;
-                        item.total_lines = 0;
-                        lengths.push(0);
+            let next = match item_state.current_match {
+                None if forward => 0,
+                None => count - 1,
+                Some(current) if forward => (current + 1) % count,
+                Some(current) => (current + count - 1) % count,
+            };
+            item_state.current_match = Some(next);
 
-                        item.skip = true;
-                    }
-                }
-                item_state.item_sizes = FenwickTree::from_iter(lengths.into_iter());
-                item_state.cached_shaped_lines.item_lines.clear();
-                item_state.search_query = search_pattern;
+            let line = item_state.item_sizes.prefix_sum(item_state.matches[next], 0);
+            let mut scroll_state = game_output.scroll_state.borrow_mut();
+            let offset = (line * scroll_state.line_height - scroll_state.bounds_y / 3.0).max(Pixels::ZERO);
+            scroll_state.set_offset(-offset);
+        });
+        cx.notify();
+    }
 
-                this.update_in(window, |this, window, cx| {
-                    this.game_output.update(cx, |game_output, _| {
-                        game_output.item_state = Some(item_state);
-                    });
-                    this.search_state.update(cx, |input, cx| input.set_loading(false, window, cx));
-                    cx.notify();
-                })
-                .unwrap();
-            });
-        }
+    fn export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(lines) = self.game_output.read(cx).item_state.as_ref().map(GameOutputItemState::export_lines) else {
+            return;
+        };
 
-        state.update(cx, |input, cx| input.set_loading(true, window, cx));
+        let directory = std::env::current_dir().unwrap_or_default();
+        let receiver = cx.prompt_for_new_path(&directory, Some("game-output.log"));
+        let backend_handle = self.backend_handle.clone();
+        window.spawn(cx, async move |_| {
+            let Ok(Ok(Some(path))) = receiver.await else {
+                return;
+            };
+            backend_handle.send(MessageToBackend::ExportLog {
+                path: path.into(),
+                lines: lines.into(),
+            });
+        }).detach();
     }
 }
 
@@ -1056,6 +1267,14 @@ impl Render for GameOutputRoot {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let search = Input::new(&self.search_state).prefix(Icon::new(IconName::Search).small());
 
+        let (match_count, current_match) = self.game_output.read(cx).item_state.as_ref()
+            .map(|item_state| (item_state.matches.len(), item_state.current_match))
+            .unwrap_or_default();
+        let match_label = match current_match {
+            Some(current) => format!("{}/{match_count}", current + 1),
+            None => format!("{match_count} matches"),
+        };
+
         let bar = h_flex()
             .w_full()
             .rounded(cx.theme().radius)
@@ -1063,6 +1282,20 @@ impl Render for GameOutputRoot {
             .flex_1()
             .gap_4()
             .child(search)
+            .child(Button::new("regex").label(".*").tooltip("Use regular expression").selected(self.use_regex).on_click(cx.listener(|root, _, window, cx| {
+                if root.is_searching(cx) {
+                    return;
+                }
+                root.use_regex = !root.use_regex;
+                root.apply_filter(window, cx);
+            })))
+            .child(Button::new("previous-match").icon(Icon::default().path("icons/chevron-up.svg")).tooltip("Previous match").disabled(match_count == 0).on_click(cx.listener(|root, _, _, cx| {
+                root.jump_to_match(false, cx);
+            })))
+            .child(Button::new("next-match").icon(Icon::default().path("icons/chevron-down.svg")).tooltip("Next match").disabled(match_count == 0).on_click(cx.listener(|root, _, _, cx| {
+                root.jump_to_match(true, cx);
+            })))
+            .child(div().flex_none().text_color(cx.theme().muted_foreground).child(SharedString::from(match_label)))
             .child(Button::new("top").label("Go to Top").on_click(cx.listener(|root, _, _, cx| {
                 let mut state = root.scroll_handler.state.borrow_mut();
                 state.scrolling = GameOutputScrolling::Top { offset: Pixels::ZERO };
@@ -1073,13 +1306,54 @@ impl Render for GameOutputRoot {
                 state.scrolling = GameOutputScrolling::Bottom;
                 cx.notify();
             })))
-            .child(Button::new("upload").label("Upload"));
+            .child(Button::new("export").label("Export").on_click(cx.listener(|root, _, window, cx| {
+                root.export(window, cx);
+            })))
+            .child(Button::new("upload").label("Upload").disabled(self.log_path.is_none()).on_click(cx.listener(|root, _, window, cx| {
+                if let Some(path) = root.log_path.clone() {
+                    root::upload_log_file(path, &root.backend_handle, window, cx);
+                }
+            })));
+
+        let level_buttons = GameOutputLogLevel::ALL.into_iter().enumerate().map(|(index, level)| {
+            Button::new(("level", index))
+                .small()
+                .label(level.name())
+                .selected(!self.hidden_levels.contains(&level))
+                .on_click(cx.listener(move |root, _, window, cx| {
+                    if root.is_searching(cx) {
+                        return;
+                    }
+                    if let Some(position) = root.hidden_levels.iter().position(|hidden| *hidden == level) {
+                        root.hidden_levels.remove(position);
+                    } else {
+                        root.hidden_levels.push(level);
+                    }
+                    root.apply_filter(window, cx);
+                }))
+        }).collect::<Vec<_>>();
+
+        let filters = h_flex()
+            .w_full()
+            .gap_2()
+            .children(level_buttons)
+            .child(div().w_48().child(Input::new(&self.logger_state).small()))
+            .child(div().w_48().child(Input::new(&self.thread_state).small()))
+            .child(Button::new("show-non-matching").small().label("Show non-matching lines").selected(self.show_non_matching).on_click(cx.listener(|root, _, window, cx| {
+                if root.is_searching(cx) {
+                    return;
+                }
+                root.show_non_matching = !root.show_non_matching;
+                root.apply_filter(window, cx);
+            })));
+
+        let standalone = self.keep_alive.is_some();
 
         v_flex()
             .size_full()
-            .border_12()
+            .when(standalone, |this| this.border_12())
             .gap_4()
-            .child(bar)
+            .child(v_flex().gap_2().child(bar).child(filters))
             .child(
                 h_flex()
                     .size_full()
@@ -1112,8 +1386,8 @@ impl Render for GameOutputRoot {
                 }
             }))
             .track_focus(&self.focus_handle)
-            .on_action(|_: &CloseWindow, window, _| {
+            .when(standalone, |this| this.on_action(|_: &CloseWindow, window, _| {
                 window.remove_window();
-            })
+            }))
     }
 }
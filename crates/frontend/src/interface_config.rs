@@ -4,7 +4,7 @@ use gpui::{App, SharedString, Task};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
-use crate::ui::SerializedPageType;
+use crate::{component::instance_list::{InstanceSection, InstanceSort}, ui::SerializedPageType};
 
 struct InterfaceConfigHolder {
     config: InterfaceConfig,
@@ -20,6 +20,10 @@ pub struct InterfaceConfig {
     pub active_theme: SharedString,
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub main_page: SerializedPageType,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub instance_sort: InstanceSort,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub collapsed_instance_sections: Vec<InstanceSection>,
 }
 
 impl InterfaceConfig {
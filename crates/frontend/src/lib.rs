@@ -209,6 +209,22 @@ pub(crate) fn labelled(label: &'static str, element: impl IntoElement) -> Div {
     gpui_component::v_flex().gap_0p5().child(div().text_sm().font_medium().child(label)).child(element)
 }
 
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 pub(crate) fn open_folder(path: &Path, window: &mut Window, cx: &mut App) {
     if path.is_dir() {
         if let Err(err) = open::that_detached(path) {
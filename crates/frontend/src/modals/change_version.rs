@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, VersionChangeCompatibility, VersionChangeReport, VersionChangeTarget}, message::MessageToBackend, meta::MetadataRequest, modal_action::ModalAction
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, select::{SearchableVec, Select, SelectState}, skeleton::Skeleton, spinner::Spinner, v_flex, ActiveTheme, Disableable, Selectable, Sizable, WindowExt
+};
+use schema::{loader::Loader, version_manifest::{MinecraftVersionManifest, MinecraftVersionType}};
+
+use crate::entity::metadata::{AsMetadataResult, FrontendMetadata, FrontendMetadataResult, FrontendMetadataState};
+
+const LOADERS: [Loader; 4] = [Loader::Vanilla, Loader::Fabric, Loader::Forge, Loader::NeoForge];
+
+pub fn open(
+    instance: InstanceID,
+    current: VersionChangeTarget,
+    metadata: &Entity<FrontendMetadata>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let versions = FrontendMetadata::request(metadata, MetadataRequest::MinecraftVersionManifest, cx);
+
+    let view = cx.new(|cx| {
+        let _versions_subscription = cx.observe(&versions, |_, _, cx| cx.notify());
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::GetInstanceVersionRollback { id: instance, channel: send });
+        let _rollback_task = cx.spawn(async move |view, cx| {
+            let Ok(Some(rollback)) = recv.await else {
+                return;
+            };
+            _ = view.update(cx, |view, cx| {
+                view.rollback = Some(rollback);
+                cx.notify();
+            });
+        });
+
+        ChangeVersionView {
+            instance,
+            current,
+            backend_handle,
+            versions,
+            version_select: None,
+            loader: current.loader,
+            disable_incompatible: true,
+            rollback: None,
+            report: None,
+            loading: false,
+            _task: None,
+            _rollback_task,
+            _versions_subscription,
+        }
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct ChangeVersionView {
+    instance: InstanceID,
+    current: VersionChangeTarget,
+    backend_handle: BackendHandle,
+    versions: Entity<FrontendMetadataState>,
+    version_select: Option<Entity<SelectState<SearchableVec<SharedString>>>>,
+    loader: Loader,
+    disable_incompatible: bool,
+    rollback: Option<VersionChangeTarget>,
+    report: Option<Result<VersionChangeReport, Arc<str>>>,
+    loading: bool,
+    _task: Option<Task<()>>,
+    _rollback_task: Task<()>,
+    _versions_subscription: Subscription,
+}
+
+impl ChangeVersionView {
+    fn target(&self, cx: &App) -> Option<VersionChangeTarget> {
+        let version = self.version_select.as_ref()?.read(cx).selected_value()?;
+        Some(VersionChangeTarget {
+            minecraft_version: version.as_str().into(),
+            loader: self.loader,
+        })
+    }
+
+    fn check(&mut self, target: VersionChangeTarget, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::CheckInstanceVersionChange {
+            id: self.instance,
+            target,
+            channel: send,
+        });
+
+        self.loading = true;
+        cx.notify();
+
+        self._task = Some(cx.spawn(async move |view, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Checking was cancelled".into()));
+            _ = view.update(cx, |view, cx| {
+                view.loading = false;
+                view.report = Some(result);
+                cx.notify();
+            });
+        }));
+    }
+
+    fn render_versions(&mut self, window: &mut Window, cx: &mut Context<Self>) -> AnyElement {
+        if let Some(version_select) = &self.version_select {
+            return Select::new(version_select).title_prefix("Minecraft Version: ").into_any_element();
+        }
+
+        let result: FrontendMetadataResult<MinecraftVersionManifest> = self.versions.read(cx).result();
+        match result {
+            FrontendMetadataResult::Loading => Skeleton::new().w_full().min_h_8().max_h_8().rounded_md().into_any_element(),
+            FrontendMetadataResult::Error(error) => div().text_color(cx.theme().danger).child(error).into_any_element(),
+            FrontendMetadataResult::Loaded(manifest) => {
+                let current = self.current.minecraft_version;
+                let versions: Vec<SharedString> = manifest.versions.iter()
+                    .filter(|version| !matches!(version.r#type, MinecraftVersionType::Snapshot) || version.id.as_str() == current.as_str())
+                    .map(|version| SharedString::from(version.id.as_str()))
+                    .collect();
+                let current = SharedString::from(current.as_str());
+                let version_select = cx.new(|cx| {
+                    let mut state = SelectState::new(SearchableVec::new(versions), None, window, cx).searchable(true);
+                    state.set_selected_value(&current, window, cx);
+                    state
+                });
+                cx.observe(&version_select, |_, _, cx| cx.notify()).detach();
+                self.version_select = Some(version_select.clone());
+                Select::new(&version_select).title_prefix("Minecraft Version: ").into_any_element()
+            },
+        }
+    }
+
+    fn render(&mut self, dialog: Dialog, window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let dialog = dialog.title("Change Version").w(px(560.0));
+
+        let muted = cx.theme().muted_foreground;
+
+        let versions = self.render_versions(window, cx);
+
+        let loaders = LOADERS.iter()
+            .fold(ButtonGroup::new("loader").outline(), |group, loader| {
+                group.child(Button::new(loader.name()).label(loader.name()).selected(self.loader == *loader))
+            })
+            .on_click(cx.listener(|view, selected: &Vec<usize>, _, cx| {
+                if let Some(loader) = selected.first().and_then(|index| LOADERS.get(*index)) {
+                    view.loader = *loader;
+                    cx.notify();
+                }
+            }));
+
+        let mut content = v_flex()
+            .gap_2()
+            .child(div().text_sm().text_color(muted)
+                .child(format!("Currently {} {}. Mods from Modrinth are checked for versions supporting the new version and loader, replaced mods are kept so the change can be reverted.",
+                    self.current.minecraft_version, self.current.loader.name())))
+            .child(crate::labelled("Version", versions))
+            .child(crate::labelled("Modloader", loaders));
+
+        let target = self.target(cx).filter(|target| *target != self.current);
+
+        match &self.report {
+            _ if self.loading => {
+                content = content.child(div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large)));
+            },
+            Some(Err(error)) => {
+                content = content.child(div().text_color(cx.theme().danger).child(SharedString::new(error.clone())));
+            },
+            Some(Ok(report)) if Some(report.target) == target => {
+                let count = |matches: fn(&VersionChangeCompatibility) -> bool| {
+                    report.mods.iter().filter(|report| matches(&report.compatibility)).count()
+                };
+                let heading = format!("{} compatible, {} updated, {} incompatible, {} unknown",
+                    count(|c| *c == VersionChangeCompatibility::Compatible),
+                    count(|c| matches!(c, VersionChangeCompatibility::Update { .. })),
+                    count(|c| *c == VersionChangeCompatibility::Incompatible),
+                    count(|c| *c == VersionChangeCompatibility::Unchecked));
+
+                let theme = cx.theme();
+                let rows = report.mods.iter().map(|report| {
+                    let (status, color): (SharedString, Hsla) = match &report.compatibility {
+                        VersionChangeCompatibility::Compatible => ("Compatible".into(), theme.green),
+                        VersionChangeCompatibility::Update { filename } => (format!("Update to {filename}").into(), theme.blue),
+                        VersionChangeCompatibility::Incompatible => ("Incompatible".into(), theme.danger),
+                        VersionChangeCompatibility::Unchecked => ("Not from Modrinth".into(), muted),
+                    };
+                    h_flex()
+                        .gap_2()
+                        .child(div().flex_1().truncate().when(!report.enabled, |this| this.text_color(muted)).child(SharedString::new(report.name.clone())))
+                        .child(div().text_sm().text_color(color).child(status))
+                }).collect::<Vec<_>>();
+
+                content = content
+                    .child(crate::labelled("Mods", v_flex()
+                        .gap_1()
+                        .child(div().font_medium().child(heading))
+                        .child(v_flex().id("mods").max_h_64().overflow_y_scroll().children(rows))))
+                    .child(Checkbox::new("disable_incompatible")
+                        .label("Disable incompatible mods")
+                        .checked(self.disable_incompatible)
+                        .on_click(cx.listener(|view, value, _, cx| {
+                            view.disable_incompatible = *value;
+                            cx.notify();
+                        })));
+            },
+            _ => {},
+        }
+
+        let checked = matches!(&self.report, Some(Ok(report)) if Some(report.target) == target);
+
+        let mut buttons = h_flex()
+            .gap_2()
+            .child(Button::new("check").info().label("Check compatibility").disabled(self.loading || target.is_none()).on_click(cx.listener(move |view, _, _, cx| {
+                if let Some(target) = target {
+                    view.check(target, cx);
+                }
+            })))
+            .child(Button::new("change").danger().label("Change version").disabled(self.loading || !checked).on_click(cx.listener(move |view, _, window, cx| {
+                let Some(target) = target else {
+                    return;
+                };
+                let modal_action = ModalAction::default();
+                view.backend_handle.send(MessageToBackend::ChangeInstanceVersion {
+                    id: view.instance,
+                    target,
+                    disable_incompatible: view.disable_incompatible,
+                    modal_action: modal_action.clone(),
+                });
+                window.close_dialog(cx);
+                crate::modals::generic::show_modal(window, cx, "Changing version".into(), "Error changing version".into(), modal_action);
+            })));
+
+        if let Some(rollback) = self.rollback {
+            let label = format!("Revert to {} {}", rollback.minecraft_version, rollback.loader.name());
+            buttons = buttons.child(Button::new("revert").label(label).disabled(self.loading).on_click(cx.listener(|view, _, window, cx| {
+                let modal_action = ModalAction::default();
+                view.backend_handle.send(MessageToBackend::RevertInstanceVersionChange {
+                    id: view.instance,
+                    modal_action: modal_action.clone(),
+                });
+                window.close_dialog(cx);
+                crate::modals::generic::show_modal(window, cx, "Reverting version change".into(), "Error reverting version change".into(), modal_action);
+            })));
+        }
+
+        dialog.child(content.child(buttons))
+    }
+}
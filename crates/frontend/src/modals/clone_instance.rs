@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+
+use bridge::{handle::BackendHandle, instance::{CloneInstanceContent, InstanceID}, message::MessageToBackend, modal_action::ModalAction};
+use enumset::EnumSet;
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, input::{Input, InputState}, v_flex, Disableable, WindowExt
+};
+
+pub fn open(
+    instance: InstanceID,
+    instance_name: SharedString,
+    instance_names: Arc<[SharedString]>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let contents = Arc::new(Mutex::new(EnumSet::<CloneInstanceContent>::all()));
+
+    let name_input = cx.new(|cx| {
+        let mut input = InputState::new(window, cx);
+        input.set_value(SharedString::new(format!("{} (copy)", instance_name)), window, cx);
+        input
+    });
+
+    let title = SharedString::new(format!("Clone Instance: {}", instance_name));
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let name = name_input.read(cx).value();
+        let name = name.trim_ascii();
+        let name_error = if !crate::is_valid_instance_name(name) {
+            Some("Name is invalid")
+        } else if instance_names.iter().any(|existing| existing.as_str() == name) {
+            Some("Name is already used")
+        } else {
+            None
+        };
+
+        let selected = *contents.lock().unwrap();
+        let checkboxes = v_flex().gap_1().children(EnumSet::<CloneInstanceContent>::all().iter().enumerate().map(|(index, content)| {
+            Checkbox::new(("content", index))
+                .label(content.name())
+                .checked(selected.contains(content))
+                .on_click({
+                    let contents = contents.clone();
+                    move |checked, _, _| {
+                        let mut contents = contents.lock().unwrap();
+                        if *checked {
+                            contents.insert(content);
+                        } else {
+                            contents.remove(content);
+                        }
+                    }
+                })
+        }));
+
+        let mut content = v_flex()
+            .gap_2()
+            .child(crate::labelled("Name", Input::new(&name_input)));
+        if let Some(name_error) = name_error {
+            content = content.child(div().text_sm().text_color(gpui::red()).child(name_error));
+        }
+
+        dialog
+            .title(title.clone())
+            .child(content
+                .child(crate::labelled("Copy", checkboxes))
+                .child(Button::new("clone").success().label("Clone").disabled(name_error.is_some()).on_click({
+                    let name_input = name_input.clone();
+                    let contents = contents.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        let name = name_input.read(cx).value();
+                        let modal_action = ModalAction::default();
+                        backend_handle.send(MessageToBackend::CloneInstance {
+                            id: instance,
+                            name: name.trim_ascii().into(),
+                            contents: *contents.lock().unwrap(),
+                            modal_action: modal_action.clone(),
+                        });
+                        window.close_dialog(cx);
+                        crate::modals::generic::show_modal(window, cx, "Cloning instance".into(), "Error cloning instance".into(), modal_action);
+                    }
+                })))
+    });
+}
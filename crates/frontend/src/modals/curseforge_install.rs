@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::InstanceID, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, dialog::Dialog, h_flex, input::{Input, InputEvent, InputState}, scroll::ScrollableElement, skeleton::Skeleton, spinner::Spinner, v_flex, ActiveTheme, Sizable, WindowExt
+};
+use schema::{content::ContentSource, curseforge::{CurseForgeSearchRequest, CurseForgeSearchResult, CURSEFORGE_CLASS_MODS, CURSEFORGE_CLASS_RESOURCEPACKS, CURSEFORGE_CLASS_SHADERS}, loader::Loader};
+use ustr::Ustr;
+
+use crate::root;
+
+pub fn open(
+    instance: InstanceID,
+    loader: Loader,
+    minecraft_version: Ustr,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let search = cx.new(|cx| {
+        let input_state = cx.new(|cx| InputState::new(window, cx).placeholder("Search CurseForge..."));
+        cx.subscribe_in(&input_state, window, CurseForgeSearch::on_input).detach();
+
+        CurseForgeSearch {
+            instance,
+            loader,
+            minecraft_version,
+            backend_handle,
+            input_state,
+            class_id: CURSEFORGE_CLASS_MODS,
+            result: None,
+            _search_task: None,
+        }
+    });
+    search.update(cx, |search, cx| search.search(window, cx));
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        search.update(cx, |search, cx| search.render(dialog, window, cx))
+    });
+}
+
+struct CurseForgeSearch {
+    instance: InstanceID,
+    loader: Loader,
+    minecraft_version: Ustr,
+    backend_handle: BackendHandle,
+    input_state: Entity<InputState>,
+    class_id: u32,
+    result: Option<Result<Arc<CurseForgeSearchResult>, Arc<str>>>,
+    _search_task: Option<Task<()>>,
+}
+
+impl CurseForgeSearch {
+    fn on_input(&mut self, _: &Entity<InputState>, event: &InputEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if let InputEvent::PressEnter { .. } = event {
+            self.search(window, cx);
+        }
+    }
+
+    fn search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.input_state.read(cx).value();
+        let query = query.trim_ascii();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::SearchCurseForge {
+            request: CurseForgeSearchRequest {
+                query: if query.is_empty() { None } else { Some(query.into()) },
+                class_id: self.class_id,
+                game_version: Some(self.minecraft_version.as_str().into()),
+                loader: self.loader,
+                index: 0,
+                page_size: 30,
+            },
+            channel: send,
+        });
+
+        self.result = None;
+        self._search_task = Some(cx.spawn_in(window, async move |search, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Search was cancelled".into()));
+            _ = search.update(cx, |search, cx| {
+                search.result = Some(result);
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let muted = cx.theme().muted_foreground;
+        let danger = cx.theme().danger;
+
+        let classes = [
+            (CURSEFORGE_CLASS_MODS, "Mods"),
+            (CURSEFORGE_CLASS_RESOURCEPACKS, "Resource Packs"),
+            (CURSEFORGE_CLASS_SHADERS, "Shaders"),
+        ].into_iter().map(|(class_id, label)| {
+            let button = Button::new(("class", class_id as usize)).small().label(label).on_click(cx.listener(move |search, _, window, cx| {
+                search.class_id = class_id;
+                search.search(window, cx);
+            }));
+            if self.class_id == class_id {
+                button.info()
+            } else {
+                button
+            }
+        });
+
+        let results = match &self.result {
+            None => div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large)).into_any_element(),
+            Some(Err(error)) => div().text_color(danger).child(SharedString::new(error.clone())).into_any_element(),
+            Some(Ok(result)) if result.data.is_empty() => div().text_color(muted).child("No results").into_any_element(),
+            Some(Ok(result)) => {
+                let rows = result.data.iter().map(|project| {
+                    let image = if let Some(thumbnail_url) = project.logo.as_ref().and_then(|logo| logo.thumbnail_url.clone()) {
+                        gpui::img(SharedUri::from(thumbnail_url))
+                            .with_fallback(|| Skeleton::new().rounded_lg().size_12().into_any_element())
+                    } else {
+                        gpui::img(ImageSource::Resource(Resource::Embedded("images/default_mod.png".into())))
+                    };
+
+                    let author = project.authors.first().map(|author| format!("by {} · ", author.name)).unwrap_or_default();
+                    let subtitle = format!("{}{}", author, crate::pages::modrinth_page::format_downloads(project.download_count as usize));
+
+                    let project_id = project.id;
+                    h_flex()
+                        .gap_3()
+                        .child(image.rounded_lg().size_12().min_w_12().min_h_12())
+                        .child(v_flex()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(SharedString::new(project.name.clone()))
+                            .child(div().text_sm().text_color(muted).child(subtitle))
+                            .when_some(project.summary.clone(), |this, summary| {
+                                this.child(div().text_sm().line_clamp(2).child(SharedString::new(summary)))
+                            }))
+                        .child(Button::new(("install", project_id as usize)).success().small().label("Install").on_click(cx.listener(move |search, _, window, cx| {
+                            search.install(project_id, window, cx);
+                        })))
+                });
+                v_flex().gap_3().children(rows).into_any_element()
+            },
+        };
+
+        let content = v_flex()
+            .gap_2()
+            .child(h_flex()
+                .gap_2()
+                .child(div().flex_1().child(Input::new(&self.input_state)))
+                .child(Button::new("search").info().label("Search").on_click(cx.listener(|search, _, window, cx| {
+                    search.search(window, cx);
+                }))))
+            .child(h_flex().gap_2().children(classes))
+            .child(div().id("curseforge-results").h_96().child(results).overflow_y_scrollbar());
+
+        dialog.title("Add from CurseForge").w(px(640.0)).child(content)
+    }
+
+    fn install(&mut self, project_id: u32, window: &mut Window, cx: &mut Context<Self>) {
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(self.instance),
+            loader_hint: self.loader,
+            version_hint: Some(self.minecraft_version.as_str().into()),
+            files: [ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Automatic,
+                download: ContentDownload::CurseForge {
+                    project_id,
+                    file_id: None,
+                },
+                content_source: ContentSource::CurseForge,
+            }].into(),
+        };
+
+        window.close_dialog(cx);
+        root::start_install(content_install, &self.backend_handle, window, cx);
+    }
+}
@@ -0,0 +1,65 @@
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceServerSummary, ServerListEdit}, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, input::{Input, InputState}, notification::NotificationType, v_flex, WindowExt
+};
+
+pub fn open(
+    instance: InstanceID,
+    existing: Option<InstanceServerSummary>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let name_input = cx.new(|cx| {
+        let mut input = InputState::new(window, cx).placeholder("Minecraft Server");
+        if let Some(existing) = &existing {
+            input.set_value(SharedString::new(existing.name.clone()), window, cx);
+        }
+        input
+    });
+    let address_input = cx.new(|cx| {
+        let mut input = InputState::new(window, cx).placeholder("play.example.com");
+        if let Some(existing) = &existing {
+            input.set_value(SharedString::new(existing.ip.clone()), window, cx);
+        }
+        input
+    });
+
+    let title = if existing.is_some() { "Edit Server" } else { "Add Server" };
+    let index = existing.map(|existing| existing.index);
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog
+            .title(title)
+            .child(v_flex()
+                .gap_2()
+                .child(crate::labelled("Server Name", Input::new(&name_input)))
+                .child(crate::labelled("Server Address", Input::new(&address_input)))
+                .child(Button::new("save").success().label("Save").on_click({
+                    let name_input = name_input.clone();
+                    let address_input = address_input.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        let address = address_input.read(cx).value();
+                        let address = address.trim_ascii();
+                        if address.is_empty() {
+                            window.push_notification((NotificationType::Error, "Server address can't be empty"), cx);
+                            return;
+                        }
+
+                        let name = name_input.read(cx).value();
+                        let name = if name.trim_ascii().is_empty() { "Minecraft Server" } else { name.trim_ascii() };
+
+                        let edit = match index {
+                            Some(index) => ServerListEdit::Edit { index, name: name.into(), ip: address.into() },
+                            None => ServerListEdit::Add { name: name.into(), ip: address.into() },
+                        };
+                        backend_handle.send(MessageToBackend::EditServerList { id: instance, edit });
+                        window.close_dialog(cx);
+                    }
+                })))
+    });
+}
@@ -0,0 +1,91 @@
+use bridge::{handle::BackendHandle, instance::{InstanceID, InstanceIconSelection}, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, v_flex, Icon, IconName, WindowExt
+};
+
+use crate::component::instance_icon::BUILTIN_INSTANCE_ICONS;
+
+pub fn open(
+    instance: InstanceID,
+    instance_name: SharedString,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let title = SharedString::new(format!("Change Icon: {}", instance_name));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let builtin_icons = h_flex().gap_1().flex_wrap().children(BUILTIN_INSTANCE_ICONS.iter().enumerate().map(|(index, name)| {
+            Button::new(("builtin", index))
+                .ghost()
+                .icon(Icon::empty().path(SharedString::new(format!("icons/{name}.svg"))))
+                .on_click({
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        backend_handle.send(MessageToBackend::SetInstanceIcon {
+                            id: instance,
+                            icon: InstanceIconSelection::Builtin((*name).into()),
+                        });
+                        window.close_dialog(cx);
+                    }
+                })
+        }));
+
+        let choose_file = Button::new("choose_file").info().icon(IconName::FolderOpen).label("Choose PNG").on_click({
+            let backend_handle = backend_handle.clone();
+            move |_, window, cx| {
+                let receiver = cx.prompt_for_paths(PathPromptOptions {
+                    files: true,
+                    directories: false,
+                    multiple: false,
+                    prompt: Some("Select PNG icon".into())
+                });
+
+                let backend_handle = backend_handle.clone();
+                cx.spawn(async move |_| {
+                    let Ok(Ok(Some(paths))) = receiver.await else {
+                        return;
+                    };
+                    let Some(path) = paths.into_iter().next() else {
+                        return;
+                    };
+                    backend_handle.send(MessageToBackend::SetInstanceIcon {
+                        id: instance,
+                        icon: InstanceIconSelection::File(path.into()),
+                    });
+                }).detach();
+                window.close_dialog(cx);
+            }
+        });
+
+        let use_modpack = Button::new("use_modpack").label("Use modpack icon").on_click({
+            let backend_handle = backend_handle.clone();
+            move |_, window, cx| {
+                backend_handle.send(MessageToBackend::SetInstanceIcon {
+                    id: instance,
+                    icon: InstanceIconSelection::Modpack,
+                });
+                window.close_dialog(cx);
+            }
+        });
+
+        let reset = Button::new("reset").danger().label("Reset").on_click({
+            let backend_handle = backend_handle.clone();
+            move |_, window, cx| {
+                backend_handle.send(MessageToBackend::SetInstanceIcon {
+                    id: instance,
+                    icon: InstanceIconSelection::Default,
+                });
+                window.close_dialog(cx);
+            }
+        });
+
+        dialog
+            .title(title.clone())
+            .child(v_flex()
+                .gap_3()
+                .child(crate::labelled("Built-in", builtin_icons))
+                .child(h_flex().gap_2().child(choose_file).child(use_modpack).child(reset)))
+    });
+}
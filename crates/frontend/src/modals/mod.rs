@@ -1,5 +1,17 @@
 pub mod generic;
+pub mod instance_icon;
 pub mod modrinth_install;
 pub mod modrinth_install_auto;
+pub mod modrinth_collection;
+pub mod modpack_update;
+pub mod change_version;
+pub mod clone_instance;
+pub mod curseforge_install;
 pub mod delete_instance;
+pub mod edit_server;
+pub mod organize_instance;
+pub mod prune_world;
 pub mod settings;
+pub mod update_all;
+pub mod update_preferences;
+pub mod world_details;
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceModID, ModpackFileChangeKind, ModpackUpdatePreview}, message::MessageToBackend, modal_action::ModalAction
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, spinner::Spinner, text::TextView, v_flex, ActiveTheme, Sizable, WindowExt
+};
+use rustc_hash::FxHashSet;
+
+pub fn open(
+    instance: InstanceID,
+    mod_id: InstanceModID,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let view = cx.new(|cx| {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::GetModpackUpdatePreview { instance, mod_id, channel: send });
+
+        let _task = cx.spawn(async move |view, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Preview was cancelled".into()));
+            _ = view.update(cx, |view: &mut ModpackUpdateView, cx| {
+                if let Ok(preview) = &result {
+                    // Keep the user's changes by default
+                    view.keep_files = preview.files.iter()
+                        .filter(|file| file.user_modified)
+                        .map(|file| file.path.clone())
+                        .collect();
+                }
+                view.preview = Some(result);
+                cx.notify();
+            });
+        });
+
+        ModpackUpdateView {
+            instance,
+            mod_id,
+            backend_handle,
+            preview: None,
+            keep_files: FxHashSet::default(),
+            _task,
+        }
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct ModpackUpdateView {
+    instance: InstanceID,
+    mod_id: InstanceModID,
+    backend_handle: BackendHandle,
+    preview: Option<Result<ModpackUpdatePreview, Arc<str>>>,
+    keep_files: FxHashSet<Arc<str>>,
+    _task: Task<()>,
+}
+
+impl ModpackUpdateView {
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let dialog = dialog.w(px(640.0));
+
+        let preview = match &self.preview {
+            None => {
+                return dialog.title("Update Modpack")
+                    .child(div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large)));
+            },
+            Some(Err(error)) => {
+                return dialog.title("Update Modpack")
+                    .child(div().text_color(cx.theme().danger).child(SharedString::new(error.clone())));
+            },
+            Some(Ok(preview)) => preview,
+        };
+
+        let theme = cx.theme();
+        let (muted, green, danger, blue, warning) = (theme.muted_foreground, theme.green, theme.danger, theme.blue, theme.warning);
+
+        let rows = preview.files.iter().enumerate().map(|(index, file)| {
+            let (kind, color): (&'static str, Hsla) = match file.kind {
+                ModpackFileChangeKind::Added => ("Added", green),
+                ModpackFileChangeKind::Removed => ("Removed", danger),
+                ModpackFileChangeKind::Changed => ("Changed", blue),
+            };
+
+            let mut row = h_flex()
+                .gap_2()
+                .child(div().w_16().text_sm().text_color(color).child(kind))
+                .child(div().flex_1().truncate().child(SharedString::new(file.path.clone())));
+            if file.is_override {
+                row = row.child(div().text_sm().text_color(muted).child("Override"));
+            }
+            if file.user_modified {
+                let path = file.path.clone();
+                row = row.child(Checkbox::new(("keep", index))
+                    .label("Keep mine")
+                    .checked(self.keep_files.contains(&file.path))
+                    .on_click(cx.listener(move |view, checked, _, cx| {
+                        if *checked {
+                            view.keep_files.insert(path.clone());
+                        } else {
+                            view.keep_files.remove(&path);
+                        }
+                        cx.notify();
+                    })));
+            }
+            row
+        }).collect::<Vec<_>>();
+
+        let modified = preview.files.iter().filter(|file| file.user_modified).count();
+
+        let mut content = v_flex()
+            .gap_2()
+            .child(div().font_medium().child(format!("{} → {}", preview.old_version, preview.new_version)));
+
+        if let Some(changelog) = &preview.changelog {
+            content = content.child(crate::labelled("Changelog", div()
+                .id("changelog")
+                .max_h_64()
+                .overflow_y_scroll()
+                .child(TextView::markdown("changelog", SharedString::new(changelog.clone())))));
+        }
+
+        let files = if rows.is_empty() {
+            div().text_sm().text_color(muted).child("No files change").into_any_element()
+        } else {
+            v_flex()
+                .gap_1()
+                .when(modified > 0, |this| this.child(div().text_sm().text_color(warning)
+                    .child(format!("{modified} changed files were modified by you, ticked files are kept and no longer overwritten by the modpack"))))
+                .child(v_flex().id("files").max_h_64().overflow_y_scroll().children(rows))
+                .into_any_element()
+        };
+
+        content = content
+            .child(crate::labelled("Files", files))
+            .child(Button::new("update").success().label("Update").on_click(cx.listener(|view, _, window, cx| {
+                let modal_action = ModalAction::default();
+                view.backend_handle.send(MessageToBackend::UpdateModpack {
+                    instance: view.instance,
+                    mod_id: view.mod_id,
+                    keep_files: view.keep_files.iter().cloned().collect(),
+                    modal_action: modal_action.clone(),
+                });
+                window.close_dialog(cx);
+                crate::modals::generic::show_notification(window, cx, "Error updating modpack".into(), modal_action);
+            })));
+
+        dialog.title(SharedString::new(format!("Update {}", preview.name))).child(content)
+    }
+}
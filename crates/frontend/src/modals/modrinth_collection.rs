@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget, ModrinthCollectionProjectStatus, ModrinthCollectionResolution}, instance::InstanceID, message::MessageToBackend, modal_action::ModalAction, safe_path::SafePath
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, input::{Input, InputState}, notification::NotificationType, scroll::ScrollableElement, v_flex, ActiveTheme, Disableable, WindowExt
+};
+use relative_path::RelativePath;
+use rustc_hash::FxHashSet;
+use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthCollection, ModrinthDependencyType, ModrinthProjectType}};
+use ustr::Ustr;
+
+use crate::{modals, root};
+
+pub fn open(
+    instance: InstanceID,
+    loader: Loader,
+    minecraft_version: Ustr,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let input_state = cx.new(|cx| InputState::new(window, cx).placeholder("https://modrinth.com/collection/..."));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog
+            .title("Add from Modrinth collection")
+            .child(v_flex()
+                .gap_2()
+                .child(Input::new(&input_state))
+                .child(Button::new("load").success().label("Load collection").on_click({
+                    let input_state = input_state.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        let value = input_state.read(cx).value();
+                        let Some(collection) = ModrinthCollection::parse_id(&value) else {
+                            window.push_notification((NotificationType::Error, "Invalid collection id or url"), cx);
+                            return;
+                        };
+
+                        window.close_dialog(cx);
+                        resolve(instance, loader, minecraft_version, collection, backend_handle.clone(), window, cx);
+                    }
+                })))
+    });
+}
+
+fn resolve(
+    instance: InstanceID,
+    loader: Loader,
+    minecraft_version: Ustr,
+    collection: Arc<str>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let modal_action = ModalAction::default();
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::ResolveModrinthCollection {
+        instance,
+        collection,
+        modal_action: modal_action.clone(),
+        channel: send,
+    });
+
+    modals::generic::show_modal(window, cx, "Loading collection".into(), "Error loading collection".into(), modal_action);
+
+    window.spawn(cx, async move |cx| {
+        let Ok(resolution) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| {
+            window.close_all_dialogs(cx);
+
+            let review = CollectionReview::new(instance, loader, minecraft_version, resolution, backend_handle);
+            let review = cx.new(|_| review);
+            window.open_dialog(cx, move |dialog, window, cx| {
+                review.update(cx, |review, cx| review.render(dialog, window, cx))
+            });
+        });
+    }).detach();
+}
+
+struct CollectionReview {
+    instance: InstanceID,
+    loader: Loader,
+    minecraft_version: Ustr,
+    resolution: ModrinthCollectionResolution,
+    selected: FxHashSet<Arc<str>>,
+    install_dependencies: bool,
+    backend_handle: BackendHandle,
+}
+
+impl CollectionReview {
+    fn new(instance: InstanceID, loader: Loader, minecraft_version: Ustr, resolution: ModrinthCollectionResolution, backend_handle: BackendHandle) -> Self {
+        // Conflicting projects are left unselected so the user has to pick which one they want
+        let selected = resolution.projects.iter()
+            .filter(|project| matches!(project.status, ModrinthCollectionProjectStatus::Compatible(_)))
+            .map(|project| project.project_id.clone())
+            .collect();
+
+        Self {
+            instance,
+            loader,
+            minecraft_version,
+            resolution,
+            selected,
+            install_dependencies: true,
+            backend_handle,
+        }
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let theme = cx.theme();
+        let muted = theme.muted_foreground;
+        let danger = theme.danger;
+
+        let rows = self.resolution.projects.iter().enumerate().map(|(index, project)| {
+            let (selectable, status, color) = match &project.status {
+                ModrinthCollectionProjectStatus::Compatible(version) => {
+                    let name = version.version_number.clone().or(version.name.clone()).unwrap_or(version.id.clone());
+                    (true, SharedString::new(name), muted)
+                },
+                ModrinthCollectionProjectStatus::Conflict { conflicts_with, .. } => {
+                    (true, SharedString::new(format!("Conflicts with {}", conflicts_with)), danger)
+                },
+                ModrinthCollectionProjectStatus::AlreadyInstalled => {
+                    (false, SharedString::new_static("Already installed"), muted)
+                },
+                ModrinthCollectionProjectStatus::NoCompatibleVersion => {
+                    (false, SharedString::new(format!("No version for {} {}", self.loader.name(), self.minecraft_version)), danger)
+                },
+                ModrinthCollectionProjectStatus::Unsupported => {
+                    (false, SharedString::new(format!("Can't install {} into an instance", project.project_type.as_str())), danger)
+                },
+            };
+
+            let project_id = project.project_id.clone();
+            h_flex()
+                .gap_2()
+                .child(Checkbox::new(("collection-project", index))
+                    .checked(self.selected.contains(&project.project_id))
+                    .disabled(!selectable)
+                    .on_click(cx.listener(move |review, value, _, _| {
+                        if *value {
+                            review.selected.insert(project_id.clone());
+                        } else {
+                            review.selected.remove(&project_id);
+                        }
+                    })))
+                .child(v_flex()
+                    .child(SharedString::new(project.title.clone()))
+                    .child(div().text_sm().text_color(color).child(status)))
+        });
+
+        let missing_dependencies = self.missing_dependencies();
+
+        let content = v_flex()
+            .gap_2()
+            .child(div().id("collection-projects").max_h_96().child(v_flex().gap_2().children(rows)).overflow_y_scrollbar())
+            .when(!missing_dependencies.is_empty(), |content| {
+                content.child(Checkbox::new("install_deps").checked(self.install_dependencies).label(if missing_dependencies.len() == 1 {
+                    SharedString::new_static("Install 1 dependency")
+                } else {
+                    SharedString::new(format!("Install {} dependencies", missing_dependencies.len()))
+                }).on_click(cx.listener(|review, value, _, _| {
+                    review.install_dependencies = *value;
+                })))
+            })
+            .child(Button::new("install").success().label(SharedString::new(format!("Install {} projects", self.selected.len()))).on_click(cx.listener(|review, _, window, cx| {
+                review.install(window, cx);
+            })));
+
+        dialog.title(SharedString::new(format!("Install {}", self.resolution.name))).child(content)
+    }
+
+    fn missing_dependencies(&self) -> Vec<(Arc<str>, Option<Arc<str>>)> {
+        let mut seen: FxHashSet<Arc<str>> = self.resolution.projects.iter().map(|project| project.project_id.clone()).collect();
+
+        let mut missing = Vec::new();
+        for project in self.resolution.projects.iter() {
+            if !self.selected.contains(&project.project_id) {
+                continue;
+            }
+            let (ModrinthCollectionProjectStatus::Compatible(version) | ModrinthCollectionProjectStatus::Conflict { version, .. }) = &project.status else {
+                continue;
+            };
+            for dependency in version.dependencies.iter().flatten() {
+                if dependency.dependency_type != ModrinthDependencyType::Required {
+                    continue;
+                }
+                if let Some(project_id) = &dependency.project_id && seen.insert(project_id.clone()) {
+                    missing.push((project_id.clone(), dependency.version_id.clone()));
+                }
+            }
+        }
+        missing
+    }
+
+    fn install(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut files = Vec::new();
+
+        for project in self.resolution.projects.iter() {
+            if !self.selected.contains(&project.project_id) {
+                continue;
+            }
+            let (ModrinthCollectionProjectStatus::Compatible(version) | ModrinthCollectionProjectStatus::Conflict { version, .. }) = &project.status else {
+                continue;
+            };
+
+            let install_file = version.files.iter().find(|file| file.primary).unwrap_or(&version.files[0]);
+
+            let directory = match project.project_type {
+                ModrinthProjectType::Mod => "mods",
+                ModrinthProjectType::Resourcepack => "resourcepacks",
+                ModrinthProjectType::Shader => "shaderpacks",
+                ModrinthProjectType::Modpack | ModrinthProjectType::Other => continue,
+            };
+
+            let Some(path) = SafePath::from_relative_path(&RelativePath::new(directory).join(&*install_file.filename)) else {
+                window.push_notification((NotificationType::Error, SharedString::new(format!("Invalid/dangerous filename for {}", project.title))), cx);
+                return;
+            };
+
+            files.push(ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Safe(path),
+                download: ContentDownload::Url {
+                    url: install_file.url.clone(),
+                    sha1: install_file.hashes.sha1.clone(),
+                    size: install_file.size,
+                },
+                content_source: ContentSource::Modrinth,
+            });
+        }
+
+        if self.install_dependencies {
+            for (project_id, version_id) in self.missing_dependencies() {
+                files.push(ContentInstallFile {
+                    replace_old: None,
+                    path: ContentInstallPath::Automatic,
+                    download: ContentDownload::Modrinth { project_id, version_id },
+                    content_source: ContentSource::Modrinth,
+                });
+            }
+        }
+
+        if files.is_empty() {
+            window.push_notification((NotificationType::Error, "Nothing selected to install"), cx);
+            return;
+        }
+
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(self.instance),
+            loader_hint: self.loader,
+            version_hint: Some(self.minecraft_version.as_str().into()),
+            files: files.into(),
+        };
+
+        window.close_dialog(cx);
+        root::start_install(content_install, &self.backend_handle, window, cx);
+    }
+}
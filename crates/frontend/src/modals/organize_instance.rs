@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, input::{Input, InputState}, v_flex, Sizable, WindowExt
+};
+use schema::instance::InstanceOrganization;
+
+pub fn open(
+    instance: InstanceID,
+    instance_name: SharedString,
+    organization: &InstanceOrganization,
+    existing_groups: Arc<[SharedString]>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let group_input = cx.new(|cx| {
+        let mut input = InputState::new(window, cx).placeholder("No group");
+        if let Some(group) = &organization.group {
+            input.set_value(SharedString::new(group.clone()), window, cx);
+        }
+        input
+    });
+    let tags_input = cx.new(|cx| {
+        let mut input = InputState::new(window, cx).placeholder("modded, friends, testing");
+        input.set_value(SharedString::new(organization.tags.join(", ")), window, cx);
+        input
+    });
+
+    let title = SharedString::new(format!("Organize Instance: {}", instance_name));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let group_buttons = h_flex().gap_1().flex_wrap().children(existing_groups.iter().enumerate().map(|(index, group)| {
+            Button::new(("group", index)).small().outline().label(group.clone()).on_click({
+                let group_input = group_input.clone();
+                let group = group.clone();
+                move |_, window, cx| {
+                    group_input.update(cx, |input, cx| {
+                        input.set_value(group.clone(), window, cx);
+                    });
+                }
+            })
+        }));
+
+        dialog
+            .title(title.clone())
+            .child(v_flex()
+                .gap_2()
+                .child(crate::labelled("Group", v_flex().gap_1().child(Input::new(&group_input)).child(group_buttons)))
+                .child(crate::labelled("Tags (comma separated)", Input::new(&tags_input)))
+                .child(Button::new("save").success().label("Save").on_click({
+                    let group_input = group_input.clone();
+                    let tags_input = tags_input.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        let group = group_input.read(cx).value();
+                        let group = group.trim_ascii();
+                        backend_handle.send(MessageToBackend::SetInstanceGroup {
+                            id: instance,
+                            group: if group.is_empty() { None } else { Some(group.into()) },
+                        });
+
+                        let tags = tags_input.read(cx).value();
+                        backend_handle.send(MessageToBackend::SetInstanceTags {
+                            id: instance,
+                            tags: tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(Arc::from).collect(),
+                        });
+
+                        window.close_dialog(cx);
+                    }
+                })))
+    });
+}
@@ -0,0 +1,222 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, WorldPruneOptions, WorldPruneReport}, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, input::{Input, InputEvent, InputState}, notification::NotificationType, spinner::Spinner, v_flex, ActiveTheme, Disableable, Sizable, WindowExt
+};
+use rustc_hash::FxHashSet;
+
+const TICKS_PER_SECOND: i64 = 20;
+
+pub fn open(
+    instance: InstanceID,
+    world: Arc<Path>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let view = cx.new(|cx| {
+        let inhabited_input = cx.new(|cx| InputState::new(window, cx).placeholder("Disabled").default_value("30"));
+        let radius_input = cx.new(|cx| InputState::new(window, cx).placeholder("Disabled"));
+
+        cx.subscribe(&inhabited_input, PruneWorldView::on_input).detach();
+        cx.subscribe(&radius_input, PruneWorldView::on_input).detach();
+
+        PruneWorldView {
+            instance,
+            world,
+            backend_handle,
+            inhabited_input,
+            radius_input,
+            excluded_dimensions: FxHashSet::default(),
+            report: None,
+            loading: false,
+            _task: None,
+        }
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct PruneWorldView {
+    instance: InstanceID,
+    world: Arc<Path>,
+    backend_handle: BackendHandle,
+    inhabited_input: Entity<InputState>,
+    radius_input: Entity<InputState>,
+    excluded_dimensions: FxHashSet<Arc<str>>,
+    report: Option<Result<WorldPruneReport, Arc<str>>>,
+    loading: bool,
+    _task: Option<Task<()>>,
+}
+
+impl PruneWorldView {
+    fn on_input(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        // A preview is only valid for the options it was made with
+        if let InputEvent::Change = event && !self.loading {
+            self.report = None;
+            cx.notify();
+        }
+    }
+
+    fn options(&self, dry_run: bool, cx: &App) -> Result<WorldPruneOptions, &'static str> {
+        let inhabited = self.inhabited_input.read(cx).value();
+        let min_inhabited_ticks = match inhabited.trim_ascii() {
+            "" => None,
+            value => Some(value.parse::<i64>().map_err(|_| "Minimum time must be a whole number of seconds")? * TICKS_PER_SECOND),
+        };
+
+        let radius = self.radius_input.read(cx).value();
+        let keep_radius = match radius.trim_ascii() {
+            "" => None,
+            value => Some(value.parse::<u32>().map_err(|_| "Radius must be a whole number of chunks")?),
+        };
+
+        if min_inhabited_ticks.is_none() && keep_radius.is_none() {
+            return Err("Set a minimum time or a radius to prune by");
+        }
+
+        let dimensions = match &self.report {
+            Some(Ok(report)) if !self.excluded_dimensions.is_empty() => Some(report.dimensions.iter()
+                .map(|dimension| dimension.dimension.clone())
+                .filter(|dimension| !self.excluded_dimensions.contains(dimension))
+                .collect()),
+            _ => None,
+        };
+
+        Ok(WorldPruneOptions {
+            min_inhabited_ticks,
+            keep_radius,
+            dimensions,
+            dry_run,
+        })
+    }
+
+    fn run(&mut self, dry_run: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let options = match self.options(dry_run, cx) {
+            Ok(options) => options,
+            Err(error) => {
+                window.push_notification((NotificationType::Error, error), cx);
+                return;
+            },
+        };
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::PruneWorld {
+            id: self.instance,
+            world: self.world.clone(),
+            options,
+            channel: send,
+        });
+
+        self.loading = true;
+        cx.notify();
+
+        self._task = Some(cx.spawn_in(window, async move |view, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Pruning was cancelled".into()));
+            _ = view.update_in(cx, |view, window, cx| {
+                view.loading = false;
+                if !dry_run && let Ok(report) = &result {
+                    let message = format!("Removed {} chunks, saving {}", report.removed_chunks(), crate::format_bytes(report.saved_bytes()));
+                    window.push_notification((NotificationType::Success, SharedString::from(message)), cx);
+                    view.excluded_dimensions.clear();
+                }
+                view.report = Some(result);
+                cx.notify();
+            });
+        }));
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let dialog = dialog.title("Prune World").w(px(560.0));
+
+        let muted = cx.theme().muted_foreground;
+
+        let mut content = v_flex()
+            .gap_2()
+            .child(div().text_sm().text_color(muted)
+                .child("Removes chunks that players barely visited or that are far away from the spawn and every player. Removed chunks are generated again when visited."))
+            .child(crate::labelled("Minimum time spent in chunk (seconds)", Input::new(&self.inhabited_input)))
+            .child(crate::labelled("Keep radius around spawn and players (chunks)", Input::new(&self.radius_input)));
+
+        match &self.report {
+            _ if self.loading => {
+                content = content.child(div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large)));
+            },
+            None => {},
+            Some(Err(error)) => {
+                content = content.child(div().text_color(cx.theme().danger).child(SharedString::new(error.clone())));
+            },
+            Some(Ok(report)) => {
+                let dimensions = report.dimensions.iter().enumerate().map(|(index, dimension)| {
+                    let name = dimension.dimension.clone();
+                    let summary = format!("{} of {} chunks, {} of {}",
+                        dimension.removed_chunks,
+                        dimension.total_chunks,
+                        crate::format_bytes(dimension.saved_bytes()),
+                        crate::format_bytes(dimension.bytes_before));
+
+                    let row = h_flex().gap_2();
+                    let row = if report.dry_run {
+                        row.child(Checkbox::new(("prune-dimension", index))
+                            .checked(!self.excluded_dimensions.contains(&name))
+                            .on_click(cx.listener(move |view, value, _, cx| {
+                                if *value {
+                                    view.excluded_dimensions.remove(&name);
+                                } else {
+                                    view.excluded_dimensions.insert(name.clone());
+                                }
+                                cx.notify();
+                            })))
+                    } else {
+                        row
+                    };
+                    let row = row.child(div().flex_1().child(SharedString::new(dimension.dimension.clone())))
+                        .child(div().text_color(muted).child(summary));
+
+                    if dimension.skipped_regions.is_empty() {
+                        row.into_any_element()
+                    } else {
+                        let skipped = format!("Skipped unreadable regions: {}", dimension.skipped_regions.join(", "));
+                        v_flex().child(row).child(div().text_sm().text_color(cx.theme().danger).child(skipped)).into_any_element()
+                    }
+                }).collect::<Vec<_>>();
+
+                let saved = report.dimensions.iter()
+                    .filter(|dimension| !self.excluded_dimensions.contains(&dimension.dimension))
+                    .map(|dimension| dimension.saved_bytes())
+                    .sum::<u64>();
+                let heading = if report.dry_run {
+                    format!("Pruning would save {}", crate::format_bytes(saved))
+                } else {
+                    format!("Pruning saved {}", crate::format_bytes(saved))
+                };
+
+                content = content.child(crate::labelled("Result", v_flex()
+                    .gap_1()
+                    .child(div().font_medium().child(heading))
+                    .children(dimensions)));
+            },
+        }
+
+        let previewed = matches!(&self.report, Some(Ok(report)) if report.dry_run && report.removed_chunks() > 0);
+        let all_excluded = matches!(&self.report, Some(Ok(report)) if report.dimensions.iter().all(|dimension| self.excluded_dimensions.contains(&dimension.dimension)));
+
+        let buttons = h_flex()
+            .gap_2()
+            .child(Button::new("preview").info().label("Preview").disabled(self.loading).on_click(cx.listener(|view, _, window, cx| {
+                view.excluded_dimensions.clear();
+                view.run(true, window, cx);
+            })))
+            .child(Button::new("prune").danger().label("Prune").disabled(self.loading || !previewed || all_excluded).on_click(cx.listener(|view, _, window, cx| {
+                view.run(false, window, cx);
+            })));
+
+        dialog.child(content.child(buttons))
+    }
+}
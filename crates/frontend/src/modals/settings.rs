@@ -1,16 +1,66 @@
 use std::{path::Path, sync::Arc};
 
-use gpui::*;
-use gpui_component::{button::{Button, ButtonVariants}, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, tab::{Tab, TabBar, TabVariant}, v_flex, ActiveTheme, IconName, ThemeRegistry};
+use bridge::{handle::BackendHandle, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState}, notification::{Notification, NotificationType}, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, spinner::Spinner, tab::{Tab, TabBar}, v_flex, ActiveTheme, Disableable, Icon, IconName, Selectable, Sizable, ThemeRegistry, WindowExt};
+use schema::{curseforge::{CurseForgeConfiguration, CURSEFORGE_API_URL}, instance::{GameConfiguration, InstanceMemoryConfiguration, InstanceResolutionConfiguration}, network::{DownloadConfiguration, DownloadMirror, NetworkConfiguration, BMCLAPI_MIRRORS}, paste::{PasteConfiguration, PasteService}, redaction::{RedactionConfiguration, RedactionRule}};
 
-use crate::interface_config::InterfaceConfig;
+use crate::{entity::DataEntities, interface_config::InterfaceConfig};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SettingsPage {
+    Interface,
+    Network,
+    Logs,
+    Game,
+}
 
 struct Settings {
+    page: SettingsPage,
+    backend_handle: BackendHandle,
     theme_folder: Arc<Path>,
     theme_select: Entity<SelectState<SearchableVec<SharedString>>>,
+    network: Option<NetworkConfiguration>,
+    proxy_input: Entity<InputState>,
+    mirror_from_input: Entity<InputState>,
+    mirror_to_input: Entity<InputState>,
+    max_concurrent_input: Entity<InputState>,
+    max_per_host_input: Entity<InputState>,
+    max_retries_input: Entity<InputState>,
+    bandwidth_limit_input: Entity<InputState>,
+    curseforge: Option<CurseForgeConfiguration>,
+    curseforge_api_key_input: Entity<InputState>,
+    curseforge_api_url_input: Entity<InputState>,
+    paste: Option<PasteConfiguration>,
+    paste_url_input: Entity<InputState>,
+    paste_view_url_input: Entity<InputState>,
+    redaction: Option<RedactionConfiguration>,
+    /// Parse errors of the stored rules, by index. Invalid rules are skipped by the backend
+    redaction_rule_errors: Vec<Option<SharedString>>,
+    redaction_pattern_input: Entity<InputState>,
+    redaction_replacement_input: Entity<InputState>,
+    redaction_sample_input: Entity<InputState>,
+    redaction_test_result: Option<Result<Arc<str>, Arc<str>>>,
+    game: Option<GameConfiguration>,
+    memory_min_input: Entity<InputState>,
+    memory_max_input: Entity<InputState>,
+    jvm_flags_input: Entity<InputState>,
+    resolution_width_input: Entity<InputState>,
+    resolution_height_input: Entity<InputState>,
+    environment_variables_input: Entity<InputState>,
+    _get_network_configuration_task: Task<()>,
+    _get_curseforge_configuration_task: Task<()>,
+    _get_paste_configuration_task: Task<()>,
+    _get_redaction_configuration_task: Task<()>,
+    _test_redaction_task: Task<()>,
+    _get_game_configuration_task: Task<()>,
+    _select_jvm_binary_task: Task<()>,
 }
 
-pub fn build_settings_sheet(theme_folder: Arc<Path>, window: &mut Window, cx: &mut App) -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
+pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut App) -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
+    let theme_folder = data.theme_folder.clone();
+    let backend_handle = data.backend_handle.clone();
+
     let settings = cx.new(|cx| {
         let theme_select_delegate = SearchableVec::new(ThemeRegistry::global(cx).sorted_themes()
             .iter().map(|cfg| cfg.name.clone()).collect::<Vec<_>>());
@@ -35,23 +85,206 @@ pub fn build_settings_sheet(theme_folder: Arc<Path>, window: &mut Window, cx: &m
             gpui_component::Theme::global_mut(cx).apply_config(&theme);
         }).detach();
 
+        let proxy_input = cx.new(|cx| InputState::new(window, cx).placeholder("socks5://127.0.0.1:1080"));
+        cx.subscribe(&proxy_input, Settings::on_proxy_input).detach();
+        let mirror_from_input = cx.new(|cx| InputState::new(window, cx).placeholder("https://libraries.minecraft.net"));
+        let mirror_to_input = cx.new(|cx| InputState::new(window, cx).placeholder("https://mirror.example.com/maven"));
+
+        let max_concurrent_input = cx.new(|cx| InputState::new(window, cx).placeholder(DownloadConfiguration::DEFAULT_MAX_CONCURRENT.to_string()));
+        cx.subscribe(&max_concurrent_input, Settings::on_max_concurrent_input).detach();
+        let max_per_host_input = cx.new(|cx| InputState::new(window, cx).placeholder(DownloadConfiguration::DEFAULT_MAX_PER_HOST.to_string()));
+        cx.subscribe(&max_per_host_input, Settings::on_max_per_host_input).detach();
+        let max_retries_input = cx.new(|cx| InputState::new(window, cx).placeholder(DownloadConfiguration::DEFAULT_MAX_RETRIES.to_string()));
+        cx.subscribe(&max_retries_input, Settings::on_max_retries_input).detach();
+        let bandwidth_limit_input = cx.new(|cx| InputState::new(window, cx).placeholder("Unlimited"));
+        cx.subscribe(&bandwidth_limit_input, Settings::on_bandwidth_limit_input).detach();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_network_configuration_task = cx.spawn_in(window, async move |settings, cx| {
+            let result: NetworkConfiguration = recv.await.unwrap_or_default();
+            let _ = settings.update_in(cx, move |settings, window, cx| {
+                let proxy = result.proxy.clone().unwrap_or_default();
+                let downloads = result.downloads.clone();
+                settings.network = Some(result);
+                settings.proxy_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(proxy), window, cx);
+                });
+                settings.max_concurrent_input.update(cx, |input, cx| {
+                    input.set_value(downloads.max_concurrent.to_string(), window, cx);
+                });
+                settings.max_per_host_input.update(cx, |input, cx| {
+                    input.set_value(downloads.max_per_host.to_string(), window, cx);
+                });
+                settings.max_retries_input.update(cx, |input, cx| {
+                    input.set_value(downloads.max_retries.to_string(), window, cx);
+                });
+                settings.bandwidth_limit_input.update(cx, |input, cx| {
+                    input.set_value(downloads.bandwidth_limit_kib.map(|limit| limit.to_string()).unwrap_or_default(), window, cx);
+                });
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetNetworkConfiguration {
+            channel: send,
+        });
+
+        let curseforge_api_key_input = cx.new(|cx| InputState::new(window, cx).placeholder("API key from console.curseforge.com"));
+        cx.subscribe(&curseforge_api_key_input, Settings::on_curseforge_api_key_input).detach();
+        let curseforge_api_url_input = cx.new(|cx| InputState::new(window, cx).placeholder(CURSEFORGE_API_URL));
+        cx.subscribe(&curseforge_api_url_input, Settings::on_curseforge_api_url_input).detach();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_curseforge_configuration_task = cx.spawn_in(window, async move |settings, cx| {
+            let result: CurseForgeConfiguration = recv.await.unwrap_or_default();
+            let _ = settings.update_in(cx, move |settings, window, cx| {
+                let api_key = result.api_key.clone().unwrap_or_default();
+                let api_url = result.api_url.clone().unwrap_or_default();
+                settings.curseforge = Some(result);
+                settings.curseforge_api_key_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(api_key), window, cx);
+                });
+                settings.curseforge_api_url_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(api_url), window, cx);
+                });
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetCurseForgeConfiguration {
+            channel: send,
+        });
+
+        let paste_url_input = cx.new(|cx| InputState::new(window, cx).placeholder("https://paste.example.com"));
+        cx.subscribe(&paste_url_input, Settings::on_paste_url_input).detach();
+        let paste_view_url_input = cx.new(|cx| InputState::new(window, cx).placeholder("https://paste.example.com/{key}"));
+        cx.subscribe(&paste_view_url_input, Settings::on_paste_url_input).detach();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_paste_configuration_task = cx.spawn_in(window, async move |settings, cx| {
+            let result: PasteConfiguration = recv.await.unwrap_or_default();
+            let _ = settings.update_in(cx, move |settings, window, cx| {
+                let (url, view_url) = match &result.service {
+                    PasteService::McLogs => (None, None),
+                    PasteService::Hastebin { url } => (Some(url.clone()), None),
+                    PasteService::Custom { upload_url, view_url } => (Some(upload_url.clone()), Some(view_url.clone())),
+                };
+                settings.paste = Some(result);
+                settings.paste_url_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(url.unwrap_or_default()), window, cx);
+                });
+                settings.paste_view_url_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(view_url.unwrap_or_default()), window, cx);
+                });
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetPasteConfiguration {
+            channel: send,
+        });
+
+        let redaction_pattern_input = cx.new(|cx| InputState::new(window, cx).placeholder("Pattern, e.g. 192\\.168\\.\\d+\\.\\d+"));
+        let redaction_replacement_input = cx.new(|cx| InputState::new(window, cx).placeholder("Replacement, e.g. *****"));
+        let redaction_sample_input = cx.new(|cx| InputState::new(window, cx).auto_grow(3, 8).placeholder("Paste a sample log line to test the rules against"));
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_redaction_configuration_task = cx.spawn(async move |settings, cx| {
+            let result: RedactionConfiguration = recv.await.unwrap_or_default();
+            let _ = settings.update(cx, move |settings, cx| {
+                settings.redaction_rule_errors = result.rules.iter().map(|rule| redaction_pattern_error(&rule.pattern)).collect();
+                settings.redaction = Some(result);
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetRedactionConfiguration {
+            channel: send,
+        });
+
+        let memory_min_input = cx.new(|cx| InputState::new(window, cx).placeholder(InstanceMemoryConfiguration::DEFAULT_MIN.to_string()));
+        cx.subscribe(&memory_min_input, Settings::on_memory_input).detach();
+        let memory_max_input = cx.new(|cx| InputState::new(window, cx).placeholder(InstanceMemoryConfiguration::DEFAULT_MAX.to_string()));
+        cx.subscribe(&memory_max_input, Settings::on_memory_input).detach();
+        let jvm_flags_input = cx.new(|cx| InputState::new(window, cx).auto_grow(1, 8).placeholder("-XX:+UseZGC"));
+        cx.subscribe(&jvm_flags_input, Settings::on_jvm_flags_input).detach();
+        let resolution_width_input = cx.new(|cx| InputState::new(window, cx).placeholder(InstanceResolutionConfiguration::DEFAULT_WIDTH.to_string()));
+        cx.subscribe(&resolution_width_input, Settings::on_resolution_input).detach();
+        let resolution_height_input = cx.new(|cx| InputState::new(window, cx).placeholder(InstanceResolutionConfiguration::DEFAULT_HEIGHT.to_string()));
+        cx.subscribe(&resolution_height_input, Settings::on_resolution_input).detach();
+        let environment_variables_input = cx.new(|cx| InputState::new(window, cx).auto_grow(2, 8).placeholder("KEY=VALUE"));
+        cx.subscribe(&environment_variables_input, Settings::on_environment_variables_input).detach();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_game_configuration_task = cx.spawn_in(window, async move |settings, cx| {
+            let result: GameConfiguration = recv.await.unwrap_or_default();
+            let _ = settings.update_in(cx, move |settings, window, cx| {
+                let game = result.clone();
+                settings.game = Some(result);
+                settings.memory_min_input.update(cx, |input, cx| {
+                    input.set_value(game.memory.value.min.to_string(), window, cx);
+                });
+                settings.memory_max_input.update(cx, |input, cx| {
+                    input.set_value(game.memory.value.max.to_string(), window, cx);
+                });
+                settings.jvm_flags_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(game.jvm_flags.value.flags), window, cx);
+                });
+                settings.resolution_width_input.update(cx, |input, cx| {
+                    input.set_value(game.resolution.value.width.to_string(), window, cx);
+                });
+                settings.resolution_height_input.update(cx, |input, cx| {
+                    input.set_value(game.resolution.value.height.to_string(), window, cx);
+                });
+                settings.environment_variables_input.update(cx, |input, cx| {
+                    input.set_value(SharedString::new(game.environment_variables.value.variables), window, cx);
+                });
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetGameConfiguration {
+            channel: send,
+        });
+
         Settings {
+            page: SettingsPage::Interface,
+            backend_handle,
             theme_folder,
-            theme_select
+            theme_select,
+            network: None,
+            proxy_input,
+            mirror_from_input,
+            mirror_to_input,
+            max_concurrent_input,
+            max_per_host_input,
+            max_retries_input,
+            bandwidth_limit_input,
+            curseforge: None,
+            curseforge_api_key_input,
+            curseforge_api_url_input,
+            paste: None,
+            paste_url_input,
+            paste_view_url_input,
+            redaction: None,
+            redaction_rule_errors: Vec::new(),
+            redaction_pattern_input,
+            redaction_replacement_input,
+            redaction_sample_input,
+            redaction_test_result: None,
+            game: None,
+            memory_min_input,
+            memory_max_input,
+            jvm_flags_input,
+            resolution_width_input,
+            resolution_height_input,
+            environment_variables_input,
+            _get_network_configuration_task,
+            _get_curseforge_configuration_task,
+            _get_paste_configuration_task,
+            _get_redaction_configuration_task,
+            _test_redaction_task: Task::ready(()),
+            _get_game_configuration_task,
+            _select_jvm_binary_task: Task::ready(()),
         }
     });
 
     move |sheet, window, cx| {
-        let tab_bar = TabBar::new("bar")
-            .prefix(div().w_4())
-            .selected_index(0)
-            .underline()
-            .child(Tab::new().label("Interface"))
-            // .child(Tab::new().label("Game"))
-            .on_click(|index, window, cx| {
-                // todo: switch
-            });
-
         sheet
             .title("Settings")
             .overlay_top(crate::root::sheet_margin_top(window))
@@ -59,14 +292,248 @@ pub fn build_settings_sheet(theme_folder: Arc<Path>, window: &mut Window, cx: &m
             .child(v_flex()
                 .border_t_1()
                 .border_color(cx.theme().border)
-                .child(tab_bar)
                 .child(settings.clone())
             )
     }
 }
 
-impl Render for Settings {
-    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+impl Settings {
+    fn on_proxy_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value();
+            let proxy = if value.trim_ascii().is_empty() {
+                None
+            } else {
+                Some(Arc::from(value.trim_ascii()))
+            };
+
+            self.modify_network(cx, |network| {
+                network.proxy = proxy;
+            });
+        }
+    }
+
+    fn on_max_concurrent_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event && let Ok(value) = state.read(cx).value().trim_ascii().parse::<usize>() && value > 0 {
+            self.modify_network(cx, |network| {
+                network.downloads.max_concurrent = value;
+            });
+        }
+    }
+
+    fn on_max_per_host_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event && let Ok(value) = state.read(cx).value().trim_ascii().parse::<usize>() && value > 0 {
+            self.modify_network(cx, |network| {
+                network.downloads.max_per_host = value;
+            });
+        }
+    }
+
+    fn on_max_retries_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event && let Ok(value) = state.read(cx).value().trim_ascii().parse::<u32>() {
+            self.modify_network(cx, |network| {
+                network.downloads.max_retries = value;
+            });
+        }
+    }
+
+    fn on_bandwidth_limit_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value();
+            let limit = if value.trim_ascii().is_empty() {
+                None
+            } else if let Ok(limit) = value.trim_ascii().parse::<u32>() && limit > 0 {
+                Some(limit)
+            } else {
+                return;
+            };
+
+            self.modify_network(cx, |network| {
+                network.downloads.bandwidth_limit_kib = limit;
+            });
+        }
+    }
+
+    fn modify_network(&mut self, cx: &mut Context<Self>, func: impl FnOnce(&mut NetworkConfiguration)) {
+        let Some(network) = &mut self.network else {
+            return;
+        };
+
+        let old = network.clone();
+        (func)(network);
+
+        if *network != old {
+            self.backend_handle.send(MessageToBackend::SetNetworkConfiguration {
+                configuration: network.clone(),
+            });
+            cx.notify();
+        }
+    }
+
+    fn on_curseforge_api_key_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value();
+            let api_key = if value.trim_ascii().is_empty() {
+                None
+            } else {
+                Some(Arc::from(value.trim_ascii()))
+            };
+
+            self.modify_curseforge(|curseforge| {
+                curseforge.api_key = api_key;
+            });
+        }
+    }
+
+    fn on_curseforge_api_url_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value();
+            let api_url = if value.trim_ascii().is_empty() {
+                None
+            } else {
+                Some(Arc::from(value.trim_ascii()))
+            };
+
+            self.modify_curseforge(|curseforge| {
+                curseforge.api_url = api_url;
+            });
+        }
+    }
+
+    fn modify_curseforge(&mut self, func: impl FnOnce(&mut CurseForgeConfiguration)) {
+        let Some(curseforge) = &mut self.curseforge else {
+            return;
+        };
+
+        let old = curseforge.clone();
+        (func)(curseforge);
+
+        if *curseforge != old {
+            self.backend_handle.send(MessageToBackend::SetCurseForgeConfiguration {
+                configuration: curseforge.clone(),
+            });
+        }
+    }
+
+    fn on_paste_url_input(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let Some(paste) = &self.paste else {
+                return;
+            };
+            let service = self.paste_service(paste.service.name(), cx);
+            self.modify_paste(cx, |paste| {
+                paste.service = service;
+            });
+        }
+    }
+
+    /// Creates the service with the given name from the current url inputs
+    fn paste_service(&self, name: &str, cx: &App) -> PasteService {
+        let url: Arc<str> = self.paste_url_input.read(cx).value().trim_ascii().into();
+        let view_url: Arc<str> = self.paste_view_url_input.read(cx).value().trim_ascii().into();
+        match name {
+            "Hastebin" => PasteService::Hastebin { url },
+            "Custom" => PasteService::Custom { upload_url: url, view_url },
+            _ => PasteService::McLogs,
+        }
+    }
+
+    fn modify_paste(&mut self, cx: &mut Context<Self>, func: impl FnOnce(&mut PasteConfiguration)) {
+        let Some(paste) = &mut self.paste else {
+            return;
+        };
+
+        let old = paste.clone();
+        (func)(paste);
+
+        if *paste != old {
+            self.backend_handle.send(MessageToBackend::SetPasteConfiguration {
+                configuration: paste.clone(),
+            });
+            cx.notify();
+        }
+    }
+
+    fn render_paste(&self, cx: &Context<Self>) -> Div {
+        let Some(paste) = &self.paste else {
+            return v_flex().child(Spinner::new());
+        };
+
+        let muted = cx.theme().muted_foreground;
+        let selected = paste.service.name();
+
+        let services = ["mclo.gs", "Hastebin", "Custom"].into_iter().enumerate().map(|(index, name)| {
+            Button::new(("paste-service", index)).small().label(name).selected(selected == name).on_click(cx.listener(move |settings, _, _, cx| {
+                let service = settings.paste_service(name, cx);
+                settings.modify_paste(cx, |paste| {
+                    paste.service = service;
+                });
+            }))
+        }).collect::<Vec<_>>();
+
+        let mut content = v_flex()
+            .gap_1()
+            .child(h_flex().gap_2().children(services));
+
+        match &paste.service {
+            PasteService::McLogs => {
+                content = content.child(div().text_sm().text_color(muted).child("Logs larger than 11MB are truncated"));
+            },
+            PasteService::Hastebin { .. } => {
+                content = content
+                    .child(Input::new(&self.paste_url_input).small())
+                    .child(div().text_sm().text_color(muted).child("Server implementing the hastebin api, logs are posted to /documents"));
+            },
+            PasteService::Custom { .. } => {
+                content = content
+                    .child(Input::new(&self.paste_url_input).small())
+                    .child(Input::new(&self.paste_view_url_input).small())
+                    .child(div().text_sm().text_color(muted).child("Logs are posted as plain text to the upload url. The returned key replaces {key} in the view url"));
+            },
+        }
+
+        content
+    }
+
+    fn modify_redaction(&mut self, cx: &mut Context<Self>, func: impl FnOnce(&mut RedactionConfiguration)) {
+        let Some(redaction) = &mut self.redaction else {
+            return;
+        };
+
+        let old = redaction.clone();
+        (func)(redaction);
+
+        if *redaction != old {
+            self.redaction_rule_errors = redaction.rules.iter().map(|rule| redaction_pattern_error(&rule.pattern)).collect();
+            self.backend_handle.send(MessageToBackend::SetRedactionConfiguration {
+                configuration: redaction.clone(),
+            });
+            cx.notify();
+        }
+    }
+
+    fn test_redaction(&mut self, cx: &mut Context<Self>) {
+        let Some(redaction) = &self.redaction else {
+            return;
+        };
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::TestRedactionRules {
+            rules: redaction.rules.clone(),
+            sample: (&*self.redaction_sample_input.read(cx).value()).into(),
+            channel: send,
+        });
+
+        self._test_redaction_task = cx.spawn(async move |settings, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Unable to test rules".into()));
+            let _ = settings.update(cx, move |settings, cx| {
+                settings.redaction_test_result = Some(result);
+                cx.notify();
+            });
+        });
+    }
+
+    fn render_interface(&mut self) -> Div {
         v_flex()
             .px_4()
             .py_3()
@@ -87,4 +554,428 @@ impl Render for Settings {
                 }
             }))
     }
+
+    fn render_network(&mut self, cx: &mut Context<Self>) -> Div {
+        let Some(network) = &self.network else {
+            return v_flex().px_4().py_3().child(Spinner::new().with_size(gpui_component::Size::Large));
+        };
+
+        let muted = cx.theme().muted_foreground;
+
+        let mirrors = network.mirrors.iter().enumerate().map(|(index, mirror)| {
+            h_flex()
+                .gap_2()
+                .child(Checkbox::new(("mirror-enabled", index)).checked(mirror.enabled).on_click(cx.listener(move |settings, value, _, cx| {
+                    settings.modify_network(cx, |network| {
+                        if let Some(mirror) = network.mirrors.get_mut(index) {
+                            mirror.enabled = *value;
+                        }
+                    });
+                })))
+                .child(v_flex()
+                    .flex_1()
+                    .overflow_x_hidden()
+                    .text_sm()
+                    .child(SharedString::new(mirror.from.clone()))
+                    .child(div().text_color(muted).child(SharedString::new(mirror.to.clone()))))
+                .child(Button::new(("mirror-remove", index)).danger().small().icon(Icon::default().path("icons/trash-2.svg")).on_click(cx.listener(move |settings, _, _, cx| {
+                    settings.modify_network(cx, |network| {
+                        if index < network.mirrors.len() {
+                            network.mirrors.remove(index);
+                        }
+                    });
+                })))
+        });
+
+        v_flex()
+            .px_4()
+            .py_3()
+            .gap_3()
+            .child(crate::labelled(
+                "Proxy",
+                v_flex()
+                    .gap_1()
+                    .child(Input::new(&self.proxy_input))
+                    .child(div().text_sm().text_color(muted).child("Supports http, https and socks5 proxies. Restart the launcher to apply changes"))
+            ))
+            .child(h_flex()
+                .gap_3()
+                .child(crate::labelled("Max downloads", Input::new(&self.max_concurrent_input)))
+                .child(crate::labelled("Max per host", Input::new(&self.max_per_host_input)))
+                .child(crate::labelled("Retries", Input::new(&self.max_retries_input)))
+                .child(crate::labelled("Bandwidth limit (KiB/s)", Input::new(&self.bandwidth_limit_input)))
+            )
+            .child(crate::labelled(
+                "CurseForge",
+                v_flex()
+                    .gap_1()
+                    .child(Input::new(&self.curseforge_api_key_input))
+                    .child(Input::new(&self.curseforge_api_url_input).small())
+                    .child(div().text_sm().text_color(muted).child("An API key is required to search and download from CurseForge"))
+            ))
+            .child(crate::labelled("Log uploads", self.render_paste(cx)))
+            .child(crate::labelled(
+                "Download mirrors",
+                v_flex()
+                    .gap_2()
+                    .child(div().text_sm().text_color(muted).child("Downloads starting with a prefix are tried from the mirror first, falling back to the original url"))
+                    .children(mirrors)
+            ))
+            .child(v_flex()
+                .gap_1()
+                .child(Input::new(&self.mirror_from_input).small())
+                .child(Input::new(&self.mirror_to_input).small())
+                .child(h_flex()
+                    .gap_2()
+                    .child(Button::new("mirror-add").success().icon(IconName::Plus).label("Add mirror").on_click(cx.listener(|settings, _, window, cx| {
+                        let from = settings.mirror_from_input.read(cx).value();
+                        let to = settings.mirror_to_input.read(cx).value();
+                        if from.trim_ascii().is_empty() || to.trim_ascii().is_empty() {
+                            return;
+                        }
+
+                        settings.modify_network(cx, |network| {
+                            network.mirrors.push(DownloadMirror {
+                                enabled: true,
+                                from: from.trim_ascii().into(),
+                                to: to.trim_ascii().into(),
+                            });
+                        });
+
+                        settings.mirror_from_input.update(cx, |input, cx| input.set_value("", window, cx));
+                        settings.mirror_to_input.update(cx, |input, cx| input.set_value("", window, cx));
+                    })))
+                    .child(Button::new("mirror-add-bmclapi").info().label("Add BMCLAPI mirrors").on_click(cx.listener(|settings, _, _, cx| {
+                        settings.modify_network(cx, |network| {
+                            for (from, to) in BMCLAPI_MIRRORS {
+                                if network.mirrors.iter().any(|mirror| &*mirror.from == *from) {
+                                    continue;
+                                }
+                                network.mirrors.push(DownloadMirror {
+                                    enabled: true,
+                                    from: (*from).into(),
+                                    to: (*to).into(),
+                                });
+                            }
+                        });
+                    })))
+                )
+            )
+    }
+}
+
+impl Settings {
+    fn render_logs(&mut self, cx: &mut Context<Self>) -> Div {
+        let Some(redaction) = &self.redaction else {
+            return v_flex().px_4().py_3().child(Spinner::new().with_size(gpui_component::Size::Large));
+        };
+
+        let muted = cx.theme().muted_foreground;
+
+        let rules = redaction.rules.iter().enumerate().map(|(index, rule)| {
+            let error = self.redaction_rule_errors.get(index).cloned().flatten();
+            h_flex()
+                .gap_2()
+                .child(Checkbox::new(("redaction-enabled", index)).checked(rule.enabled).on_click(cx.listener(move |settings, value, _, cx| {
+                    settings.modify_redaction(cx, |redaction| {
+                        if let Some(rule) = redaction.rules.get_mut(index) {
+                            rule.enabled = *value;
+                        }
+                    });
+                })))
+                .child(v_flex()
+                    .flex_1()
+                    .overflow_x_hidden()
+                    .text_sm()
+                    .child(div().when(error.is_some(), |this| this.text_color(cx.theme().danger)).child(SharedString::new(rule.pattern.clone())))
+                    .child(div().text_color(muted).child(SharedString::new(rule.replacement.clone())))
+                    .when_some(error, |this, error| {
+                        this.child(div().text_xs().text_color(cx.theme().danger).child(SharedString::from(format!("Invalid pattern, this rule is skipped: {error}"))))
+                    }))
+                .child(Button::new(("redaction-remove", index)).danger().small().icon(Icon::default().path("icons/trash-2.svg")).on_click(cx.listener(move |settings, _, _, cx| {
+                    settings.modify_redaction(cx, |redaction| {
+                        if index < redaction.rules.len() {
+                            redaction.rules.remove(index);
+                        }
+                    });
+                })))
+        });
+
+        let test_result = match &self.redaction_test_result {
+            None => None,
+            Some(Ok(result)) => Some(div().text_sm().p_2().border_1().rounded(cx.theme().radius).border_color(cx.theme().border)
+                .child(SharedString::new(result.clone()))),
+            Some(Err(error)) => Some(div().text_sm().text_color(cx.theme().danger).child(SharedString::new(error.clone()))),
+        };
+
+        v_flex()
+            .px_4()
+            .py_3()
+            .gap_3()
+            .child(crate::labelled(
+                "Redaction rules",
+                v_flex()
+                    .gap_2()
+                    .child(div().text_sm().text_color(muted).child("Applied to game output, log files and uploads after the built-in token and username redactions. Replacements can reference groups, e.g. $1"))
+                    .children(rules)
+            ))
+            .child(v_flex()
+                .gap_1()
+                .child(Input::new(&self.redaction_pattern_input).small())
+                .child(Input::new(&self.redaction_replacement_input).small())
+                .child(Button::new("redaction-add").success().icon(IconName::Plus).label("Add rule").on_click(cx.listener(|settings, _, window, cx| {
+                    let pattern = settings.redaction_pattern_input.read(cx).value();
+                    let replacement = settings.redaction_replacement_input.read(cx).value();
+                    if pattern.is_empty() {
+                        return;
+                    }
+                    if let Some(error) = redaction_pattern_error(&pattern) {
+                        window.push_notification((NotificationType::Error, SharedString::from(format!("Invalid pattern: {error}"))), cx);
+                        return;
+                    }
+
+                    settings.modify_redaction(cx, |redaction| {
+                        redaction.rules.push(RedactionRule {
+                            enabled: true,
+                            pattern: (&*pattern).into(),
+                            replacement: (&*replacement).into(),
+                        });
+                    });
+
+                    settings.redaction_pattern_input.update(cx, |input, cx| input.set_value("", window, cx));
+                    settings.redaction_replacement_input.update(cx, |input, cx| input.set_value("", window, cx));
+                    settings.test_redaction(cx);
+                })))
+            )
+            .child(crate::labelled(
+                "Test",
+                v_flex()
+                    .gap_1()
+                    .child(Input::new(&self.redaction_sample_input))
+                    .child(Button::new("redaction-test").info().label("Test rules").on_click(cx.listener(|settings, _, _, cx| {
+                        settings.test_redaction(cx);
+                    })))
+                    .children(test_result)
+            ))
+    }
+}
+
+/// Regex errors point at the position over several lines, the last line holds the reason
+fn redaction_pattern_error(pattern: &str) -> Option<SharedString> {
+    let error = regex::Regex::new(pattern).err()?.to_string();
+    let reason = error.lines().last().unwrap_or_default();
+    Some(SharedString::from(reason.strip_prefix("error: ").unwrap_or(reason).to_string()))
+}
+
+impl Settings {
+    fn on_memory_input(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event
+            && let Ok(min) = self.memory_min_input.read(cx).value().trim_ascii().parse::<u32>()
+            && let Ok(max) = self.memory_max_input.read(cx).value().trim_ascii().parse::<u32>()
+        {
+            self.modify_game(cx, |game| {
+                game.memory.value.min = min;
+                game.memory.value.max = max;
+            });
+        }
+    }
+
+    fn on_jvm_flags_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let flags: Arc<str> = (&*state.read(cx).value()).into();
+            self.modify_game(cx, |game| {
+                game.jvm_flags.value.flags = flags;
+            });
+        }
+    }
+
+    fn on_resolution_input(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event
+            && let Ok(width) = self.resolution_width_input.read(cx).value().trim_ascii().parse::<u32>() && width > 0
+            && let Ok(height) = self.resolution_height_input.read(cx).value().trim_ascii().parse::<u32>() && height > 0
+        {
+            self.modify_game(cx, |game| {
+                game.resolution.value.width = width;
+                game.resolution.value.height = height;
+            });
+        }
+    }
+
+    fn on_environment_variables_input(&mut self, state: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let variables: Arc<str> = (&*state.read(cx).value()).into();
+            self.modify_game(cx, |game| {
+                game.environment_variables.value.variables = variables;
+            });
+        }
+    }
+
+    fn modify_game(&mut self, cx: &mut Context<Self>, func: impl FnOnce(&mut GameConfiguration)) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+
+        let old = game.clone();
+        (func)(game);
+
+        if *game != old {
+            self.backend_handle.send(MessageToBackend::SetGameConfiguration {
+                configuration: game.clone(),
+            });
+            cx.notify();
+        }
+    }
+
+    fn select_jvm_binary(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Select JVM binary".into())
+        });
+
+        let entity = cx.entity();
+        self._select_jvm_binary_task = window.spawn(cx, async move |cx| {
+            let Ok(result) = receiver.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&entity, move |settings, window, cx| {
+                match result {
+                    Ok(Some(paths)) => {
+                        let path = paths.first().map(|path| path.as_path().into());
+                        settings.modify_game(cx, |game| {
+                            game.jvm_binary.value.path = path;
+                        });
+                    },
+                    Ok(None) => {},
+                    Err(error) => {
+                        let notification = Notification::new()
+                            .autohide(false)
+                            .with_type(NotificationType::Error)
+                            .title(format!("{}", error));
+                        window.push_notification(notification, cx);
+                    },
+                }
+            });
+        });
+    }
+
+    fn render_game(&mut self, cx: &mut Context<Self>) -> Div {
+        let Some(game) = &self.game else {
+            return v_flex().px_4().py_3().child(Spinner::new().with_size(gpui_component::Size::Large));
+        };
+
+        let muted = cx.theme().muted_foreground;
+
+        let jvm_binary_label = if let Some(path) = &game.jvm_binary.value.path {
+            SharedString::new(path.to_string_lossy())
+        } else {
+            SharedString::new_static("<unset>")
+        };
+
+        v_flex()
+            .px_4()
+            .py_3()
+            .gap_3()
+            .child(div().text_sm().text_color(muted).child("Defaults for every instance, instances can override these in their own settings"))
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("memory").label("Set Memory").checked(game.memory.enabled).on_click(cx.listener(|settings, value, _, cx| {
+                    settings.modify_game(cx, |game| {
+                        game.memory.enabled = *value;
+                    });
+                })))
+                .child(h_flex()
+                    .gap_3()
+                    .child(crate::labelled("Min (MiB)", Input::new(&self.memory_min_input).small().disabled(!game.memory.enabled)))
+                    .child(crate::labelled("Max (MiB)", Input::new(&self.memory_max_input).small().disabled(!game.memory.enabled))))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("jvm_flags").label("Add JVM Flags").checked(game.jvm_flags.enabled).on_click(cx.listener(|settings, value, _, cx| {
+                    settings.modify_game(cx, |game| {
+                        game.jvm_flags.enabled = *value;
+                    });
+                })))
+                .child(Input::new(&self.jvm_flags_input).disabled(!game.jvm_flags.enabled))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("jvm_binary").label("Custom JVM Binary").checked(game.jvm_binary.enabled).on_click(cx.listener(|settings, value, _, cx| {
+                    settings.modify_game(cx, |game| {
+                        game.jvm_binary.enabled = *value;
+                    });
+                })))
+                .child(Button::new("select_jvm_binary").success().label(jvm_binary_label).disabled(!game.jvm_binary.enabled).on_click(cx.listener(|settings, _, window, cx| {
+                    settings.select_jvm_binary(window, cx);
+                })))
+                .child(div().text_sm().text_color(muted).child("When unset, the Java runtime recommended by Mojang for the version is downloaded"))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("resolution").label("Custom Resolution").checked(game.resolution.enabled).on_click(cx.listener(|settings, value, _, cx| {
+                    settings.modify_game(cx, |game| {
+                        game.resolution.enabled = *value;
+                    });
+                })))
+                .child(h_flex()
+                    .gap_3()
+                    .child(crate::labelled("Width", Input::new(&self.resolution_width_input).small().disabled(!game.resolution.enabled)))
+                    .child(crate::labelled("Height", Input::new(&self.resolution_height_input).small().disabled(!game.resolution.enabled))))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("environment_variables").label("Environment Variables").checked(game.environment_variables.enabled).on_click(cx.listener(|settings, value, _, cx| {
+                    settings.modify_game(cx, |game| {
+                        game.environment_variables.enabled = *value;
+                    });
+                })))
+                .child(Input::new(&self.environment_variables_input).disabled(!game.environment_variables.enabled))
+                .child(div().text_sm().text_color(muted).child("One KEY=VALUE pair per line"))
+            )
+    }
+}
+
+impl Render for Settings {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let selected_index = match self.page {
+            SettingsPage::Interface => 0,
+            SettingsPage::Network => 1,
+            SettingsPage::Logs => 2,
+            SettingsPage::Game => 3,
+        };
+
+        let tab_bar = TabBar::new("bar")
+            .prefix(div().w_4())
+            .selected_index(selected_index)
+            .underline()
+            .child(Tab::new().label("Interface"))
+            .child(Tab::new().label("Network"))
+            .child(Tab::new().label("Logs"))
+            .child(Tab::new().label("Game"))
+            .on_click(cx.listener(|settings, index, _, cx| {
+                let page = match *index {
+                    0 => SettingsPage::Interface,
+                    1 => SettingsPage::Network,
+                    2 => SettingsPage::Logs,
+                    3 => SettingsPage::Game,
+                    _ => {
+                        return;
+                    },
+                };
+                settings.page = page;
+                cx.notify();
+            }));
+
+        let content = match self.page {
+            SettingsPage::Interface => self.render_interface(),
+            SettingsPage::Network => self.render_network(cx),
+            SettingsPage::Logs => self.render_logs(cx),
+            SettingsPage::Game => self.render_game(cx),
+        };
+
+        v_flex()
+            .child(tab_bar)
+            .child(content)
+    }
 }
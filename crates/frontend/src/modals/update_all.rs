@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceModID, PendingModUpdate}, message::MessageToBackend, modal_action::ModalAction
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, spinner::Spinner, text::TextView, v_flex, ActiveTheme, Disableable, Sizable, WindowExt
+};
+use rustc_hash::FxHashSet;
+
+pub fn open(
+    instance: InstanceID,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let view = cx.new(|cx| {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::GetPendingModUpdates { instance, channel: send });
+        let _task = cx.spawn(async move |view, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Listing updates was cancelled".into()));
+            _ = view.update(cx, |view: &mut UpdateAllView, cx| {
+                view.updates = Some(result);
+                cx.notify();
+            });
+        });
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::GetModUpdateRollback { instance, channel: send });
+        let _rollback_task = cx.spawn(async move |view, cx| {
+            let Ok(Some(rollback)) = recv.await else {
+                return;
+            };
+            _ = view.update(cx, |view: &mut UpdateAllView, cx| {
+                view.rollback = Some(rollback);
+                cx.notify();
+            });
+        });
+
+        UpdateAllView {
+            instance,
+            backend_handle,
+            updates: None,
+            unticked: FxHashSet::default(),
+            changelog: None,
+            rollback: None,
+            _task,
+            _rollback_task,
+        }
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct UpdateAllView {
+    instance: InstanceID,
+    backend_handle: BackendHandle,
+    updates: Option<Result<Arc<[PendingModUpdate]>, Arc<str>>>,
+    unticked: FxHashSet<InstanceModID>,
+    /// The mod whose changelog is shown
+    changelog: Option<InstanceModID>,
+    rollback: Option<Arc<[Arc<str>]>>,
+    _task: Task<()>,
+    _rollback_task: Task<()>,
+}
+
+impl UpdateAllView {
+    fn render_updates(&self, updates: &[PendingModUpdate], cx: &mut Context<Self>) -> AnyElement {
+        let muted = cx.theme().muted_foreground;
+
+        if updates.is_empty() {
+            return div().text_sm().text_color(muted).child("No updates found, check for updates first").into_any_element();
+        }
+
+        let rows = updates.iter().enumerate().map(|(index, update)| {
+            let mod_id = update.mod_id;
+            let mut row = v_flex().child(h_flex()
+                .gap_2()
+                .child(Checkbox::new(("update", index))
+                    .checked(!self.unticked.contains(&mod_id))
+                    .on_click(cx.listener(move |view, checked, _, cx| {
+                        if *checked {
+                            view.unticked.remove(&mod_id);
+                        } else {
+                            view.unticked.insert(mod_id);
+                        }
+                        cx.notify();
+                    })))
+                .child(div().flex_1().truncate().child(SharedString::new(update.name.clone())))
+                .child(div().text_sm().text_color(muted).child(format!("{} → {}", update.old_version, update.new_version)))
+                .when(update.changelog.is_some(), |this| {
+                    this.child(Button::new(("changelog", index)).label("Changelog").ghost().small().on_click(cx.listener(move |view, _, _, cx| {
+                        view.changelog = if view.changelog == Some(mod_id) {
+                            None
+                        } else {
+                            Some(mod_id)
+                        };
+                        cx.notify();
+                    })))
+                }));
+
+            if self.changelog == Some(mod_id) && let Some(changelog) = &update.changelog {
+                row = row.child(div()
+                    .id(("changelog-text", index))
+                    .ml_6()
+                    .max_h_48()
+                    .overflow_y_scroll()
+                    .child(TextView::markdown(("changelog-markdown", index), SharedString::new(changelog.clone()))));
+            }
+            row
+        }).collect::<Vec<_>>();
+
+        v_flex().id("updates").gap_1().max_h_64().overflow_y_scroll().children(rows).into_any_element()
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let dialog = dialog.title("Update Mods").w(px(640.0));
+
+        let updates = match &self.updates {
+            None => {
+                return dialog.child(div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large)));
+            },
+            Some(Err(error)) => {
+                return dialog.child(div().text_color(cx.theme().danger).child(SharedString::new(error.clone())));
+            },
+            Some(Ok(updates)) => updates.clone(),
+        };
+
+        let selected: Arc<[InstanceModID]> = updates.iter()
+            .map(|update| update.mod_id)
+            .filter(|mod_id| !self.unticked.contains(mod_id))
+            .collect();
+
+        let label = match selected.len() {
+            1 => SharedString::new_static("Update 1 mod"),
+            count => SharedString::new(format!("Update {count} mods")),
+        };
+
+        let mut buttons = h_flex()
+            .gap_2()
+            .child(Button::new("update").success().label(label).disabled(selected.is_empty()).on_click(cx.listener(move |view, _, window, cx| {
+                let modal_action = ModalAction::default();
+                view.backend_handle.send(MessageToBackend::UpdateAllMods {
+                    instance: view.instance,
+                    mod_ids: selected.clone(),
+                    modal_action: modal_action.clone(),
+                });
+                window.close_dialog(cx);
+                crate::modals::generic::show_modal(window, cx, "Updating mods".into(), "Error updating mods".into(), modal_action);
+            })));
+
+        if let Some(rollback) = &self.rollback {
+            let label = match rollback.len() {
+                1 => SharedString::new_static("Revert last update (1 mod)"),
+                count => SharedString::new(format!("Revert last update ({count} mods)")),
+            };
+            buttons = buttons.child(Button::new("revert").label(label).tooltip(SharedString::new(rollback.join(", "))).on_click(cx.listener(|view, _, window, cx| {
+                let modal_action = ModalAction::default();
+                view.backend_handle.send(MessageToBackend::RevertModUpdates {
+                    instance: view.instance,
+                    modal_action: modal_action.clone(),
+                });
+                window.close_dialog(cx);
+                crate::modals::generic::show_modal(window, cx, "Reverting mod updates".into(), "Error reverting mod updates".into(), modal_action);
+            })));
+        }
+
+        dialog.child(v_flex()
+            .gap_2()
+            .child(self.render_updates(&updates, cx))
+            .child(buttons))
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceModID, ModSummary}, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, dialog::Dialog, h_flex, v_flex, ActiveTheme, Selectable, Sizable, WindowExt
+};
+use schema::{instance::ModUpdatePreference, modrinth::ModrinthVersionType};
+
+const CHANNELS: [Option<ModrinthVersionType>; 4] = [None, Some(ModrinthVersionType::Release), Some(ModrinthVersionType::Beta), Some(ModrinthVersionType::Alpha)];
+
+pub fn open(
+    instance: InstanceID,
+    mod_id: InstanceModID,
+    mod_summary: &ModSummary,
+    preference: ModUpdatePreference,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let view = cx.new(|_| UpdatePreferencesView {
+        instance,
+        mod_id,
+        mod_summary_id: mod_summary.id.clone(),
+        name: SharedString::new(mod_summary.name.clone()),
+        can_skip: mod_summary.update_status.load(Ordering::Relaxed).can_update(),
+        preference,
+        backend_handle,
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct UpdatePreferencesView {
+    instance: InstanceID,
+    mod_id: InstanceModID,
+    mod_summary_id: Arc<str>,
+    name: SharedString,
+    can_skip: bool,
+    preference: ModUpdatePreference,
+    backend_handle: BackendHandle,
+}
+
+impl UpdatePreferencesView {
+    fn set(&mut self, preference: ModUpdatePreference, cx: &mut Context<Self>) {
+        self.preference = preference.clone();
+        self.backend_handle.send(MessageToBackend::SetModUpdatePreference {
+            instance: self.instance,
+            mod_summary_id: self.mod_summary_id.clone(),
+            preference,
+        });
+        cx.notify();
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let muted = cx.theme().muted_foreground;
+
+        let channels = CHANNELS.iter()
+            .fold(ButtonGroup::new("channel").outline(), |group, channel| {
+                let name = channel.map(ModrinthVersionType::name).unwrap_or("Any");
+                group.child(Button::new(name).label(name).selected(self.preference.channel == *channel))
+            })
+            .on_click(cx.listener(|view, selected: &Vec<usize>, _, cx| {
+                if let Some(channel) = selected.first().and_then(|index| CHANNELS.get(*index)) {
+                    let preference = ModUpdatePreference { channel: *channel, ..view.preference.clone() };
+                    view.set(preference, cx);
+                }
+            }));
+
+        let mut content = v_flex()
+            .gap_2()
+            .child(div().text_sm().text_color(muted)
+                .child("Preferences are saved for this instance and apply to every version of the mod."))
+            .child(Checkbox::new("pinned")
+                .label("Pin, never update this mod")
+                .checked(self.preference.pinned)
+                .on_click(cx.listener(|view, checked, _, cx| {
+                    let preference = ModUpdatePreference { pinned: *checked, ..view.preference.clone() };
+                    view.set(preference, cx);
+                })))
+            .child(crate::labelled("Release channel", v_flex()
+                .gap_1()
+                .child(channels)
+                .child(div().text_sm().text_color(muted).child("Only versions at least as stable are offered, this applies to mods from Modrinth"))));
+
+        let skipped = self.preference.skipped_updates.len();
+        if skipped > 0 {
+            let label = if skipped == 1 {
+                SharedString::new_static("1 skipped update")
+            } else {
+                SharedString::new(format!("{skipped} skipped updates"))
+            };
+            content = content.child(h_flex()
+                .gap_2()
+                .child(label)
+                .child(Button::new("clear").label("Clear").small().on_click(cx.listener(|view, _, _, cx| {
+                    let preference = ModUpdatePreference { skipped_updates: Arc::from([]), ..view.preference.clone() };
+                    view.set(preference, cx);
+                }))));
+        }
+
+        if self.can_skip && !self.preference.pinned {
+            content = content.child(Button::new("skip").warning().label("Skip this update").on_click(cx.listener(|view, _, window, cx| {
+                view.backend_handle.send(MessageToBackend::SkipModUpdate {
+                    instance: view.instance,
+                    mod_id: view.mod_id,
+                });
+                window.close_dialog(cx);
+            })));
+        }
+
+        dialog.title(SharedString::new(format!("Update Preferences: {}", self.name))).w(px(480.0)).child(content)
+    }
+}
@@ -0,0 +1,188 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, WorldDetails, WorldDifficulty, WorldEdit}, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, dialog::Dialog, h_flex, input::{Input, InputState}, scroll::ScrollableElement, spinner::Spinner, switch::Switch, v_flex, ActiveTheme, Sizable, WindowExt
+};
+
+pub fn open(
+    instance: InstanceID,
+    world: Arc<Path>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let view = cx.new(|cx| {
+        let name_input = cx.new(|cx| InputState::new(window, cx));
+
+        let mut view = WorldDetailsView {
+            instance,
+            world,
+            backend_handle,
+            name_input,
+            details: None,
+            _task: None,
+        };
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        view.backend_handle.send(MessageToBackend::GetWorldDetails {
+            id: view.instance,
+            world: view.world.clone(),
+            channel: send,
+        });
+        view.await_details(recv, window, cx);
+
+        view
+    });
+
+    window.open_dialog(cx, move |dialog, window, cx| {
+        view.update(cx, |view, cx| view.render(dialog, window, cx))
+    });
+}
+
+struct WorldDetailsView {
+    instance: InstanceID,
+    world: Arc<Path>,
+    backend_handle: BackendHandle,
+    name_input: Entity<InputState>,
+    details: Option<Result<WorldDetails, Arc<str>>>,
+    _task: Option<Task<()>>,
+}
+
+impl WorldDetailsView {
+    fn await_details(&mut self, recv: tokio::sync::oneshot::Receiver<Result<WorldDetails, Arc<str>>>, window: &mut Window, cx: &mut Context<Self>) {
+        self._task = Some(cx.spawn_in(window, async move |view, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Loading was cancelled".into()));
+            _ = view.update_in(cx, |view, window, cx| {
+                if let Ok(details) = &result {
+                    view.name_input.update(cx, |input, cx| {
+                        input.set_value(SharedString::new(details.level_name.clone()), window, cx);
+                    });
+                }
+                view.details = Some(result);
+                cx.notify();
+            });
+        }));
+    }
+
+    fn edit(&mut self, edit: WorldEdit, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::EditWorld {
+            id: self.instance,
+            world: self.world.clone(),
+            edit,
+            channel: send,
+        });
+        self.await_details(recv, window, cx);
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let folder = self.world.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let dialog = dialog.title(SharedString::from(folder)).w(px(560.0));
+
+        let details = match &self.details {
+            None => return dialog.child(div().p_4().child(Spinner::new().with_size(gpui_component::Size::Large))),
+            Some(Err(error)) => return dialog.child(div().text_color(cx.theme().danger).child(SharedString::new(error.clone()))),
+            Some(Ok(details)) => details.clone(),
+        };
+
+        let muted = cx.theme().muted_foreground;
+
+        let row = |label: &'static str, value: String| {
+            h_flex().gap_2().child(div().w_32().text_color(muted).child(label)).child(value)
+        };
+
+        let version = match (&details.version_name, details.data_version) {
+            (Some(name), Some(data_version)) => format!("{name} (data version {data_version})"),
+            (Some(name), None) => name.to_string(),
+            (None, Some(data_version)) => format!("Data version {data_version}"),
+            (None, None) => "Unknown".to_string(),
+        };
+
+        let rename = h_flex()
+            .gap_2()
+            .child(div().flex_1().child(Input::new(&self.name_input)))
+            .child(Button::new("rename").info().label("Rename").on_click(cx.listener(|view, _, window, cx| {
+                let name = view.name_input.read(cx).value();
+                let name = name.trim_ascii();
+                if !name.is_empty() {
+                    view.edit(WorldEdit::Rename { name: name.into() }, window, cx);
+                }
+            })));
+
+        let difficulty_buttons = WorldDifficulty::ALL.into_iter().map(|difficulty| {
+            let button = Button::new(("difficulty", difficulty.id() as usize)).small().label(difficulty.name())
+                .on_click(cx.listener(move |view, _, window, cx| {
+                    view.edit(WorldEdit::SetDifficulty { difficulty }, window, cx);
+                }));
+            if details.difficulty == Some(difficulty) {
+                button.info()
+            } else {
+                button
+            }
+        }).collect::<Vec<_>>();
+
+        let cheats = h_flex()
+            .gap_2()
+            .child(Switch::new("allow-commands")
+                .checked(details.allow_commands)
+                .on_click(cx.listener(|view, checked: &bool, window, cx| {
+                    view.edit(WorldEdit::SetAllowCommands { allow_commands: *checked }, window, cx);
+                })))
+            .child("Allow cheats");
+
+        let datapacks = |datapacks: &Arc<[Arc<str>]>| {
+            if datapacks.is_empty() {
+                "None".to_string()
+            } else {
+                datapacks.join(", ")
+            }
+        };
+
+        let players = details.players.iter().map(|player| {
+            let mut info = Vec::new();
+            if let Some(dimension) = &player.dimension {
+                info.push(dimension.to_string());
+            }
+            if let Some([x, y, z]) = player.position {
+                info.push(format!("{x:.0}, {y:.0}, {z:.0}"));
+            }
+            if let Some(health) = player.health {
+                info.push(format!("{health:.1} health"));
+            }
+            if let Some(game_mode) = player.game_mode {
+                info.push(game_mode.name().to_string());
+            }
+
+            v_flex()
+                .child(SharedString::new(player.uuid.clone()))
+                .child(div().text_sm().text_color(muted).child(info.join(" · ")))
+        });
+
+        let content = v_flex()
+            .gap_2()
+            .child(crate::labelled("Name", rename))
+            .child(row("Game mode", details.game_mode.map(|game_mode| game_mode.name()).unwrap_or("Unknown").to_string()))
+            .child(row("Hardcore", if details.hardcore { "Yes" } else { "No" }.to_string()))
+            .child(row("Seed", details.seed.map(|seed| seed.to_string()).unwrap_or_else(|| "Unknown".to_string())))
+            .child(row("Version", version))
+            .child(row("Size on disk", crate::format_bytes(details.size_bytes)))
+            .child(row("Datapacks", datapacks(&details.enabled_datapacks)))
+            .child(row("Disabled datapacks", datapacks(&details.disabled_datapacks)))
+            .child(crate::labelled("Difficulty", h_flex().gap_2().children(difficulty_buttons)))
+            .child(cheats)
+            .child(crate::labelled("Players", div()
+                .id("world-players")
+                .max_h_48()
+                .overflow_y_scrollbar()
+                .child(v_flex().gap_1().children(players))))
+            .child(Button::new("prune").label("Prune World...").on_click(cx.listener(|view, _, window, cx| {
+                crate::modals::prune_world::open(view.instance, view.world.clone(), view.backend_handle.clone(), window, cx);
+            })));
+
+        dialog.child(content)
+    }
+}
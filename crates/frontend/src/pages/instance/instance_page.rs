@@ -10,8 +10,9 @@ use gpui_component::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    entity::{instance::InstanceEntry, DataEntities},
-    pages::instance::{logs_subpage::InstanceLogsSubpage, mods_subpage::InstanceModsSubpage, quickplay_subpage::InstanceQuickplaySubpage, settings_subpage::InstanceSettingsSubpage},
+    component::instance_icon,
+    entity::{instance::{InstanceEntries, InstanceEntry}, metadata::FrontendMetadata, DataEntities},
+    pages::instance::{logs_subpage::InstanceLogsSubpage, mods_subpage::InstanceModsSubpage, nbt_editor_subpage::InstanceNbtEditorSubpage, notes_subpage::InstanceNotesSubpage, packs_subpage::{InstancePacksSubpage, PacksSubpageKind}, quickplay_subpage::InstanceQuickplaySubpage, settings_subpage::InstanceSettingsSubpage},
     root, ui,
 };
 
@@ -20,6 +21,8 @@ pub struct InstancePage {
     backend_handle: BackendHandle,
     title: SharedString,
     instance: Entity<InstanceEntry>,
+    instances: Entity<InstanceEntries>,
+    metadata: Entity<FrontendMetadata>,
     subpage: InstanceSubpage,
     _instance_subscription: Subscription,
 }
@@ -33,13 +36,15 @@ impl InstancePage {
             page.title = instance.title().into();
         });
 
-        let subpage = subpage.create(&instance, data.backend_handle.clone(), window, cx);
+        let subpage = subpage.create(&instance, data.backend_handle.clone(), &data.metadata, window, cx);
 
         Self {
             breadcrumb,
             backend_handle: data.backend_handle.clone(),
             title: instance.read(cx).title().into(),
             instance,
+            instances: data.instances.clone(),
+            metadata: data.metadata.clone(),
             subpage,
             _instance_subscription,
         }
@@ -49,7 +54,7 @@ impl InstancePage {
         if page_type == self.subpage.page_type() {
             return;
         }
-        self.subpage = page_type.create(&self.instance, self.backend_handle.clone(), window, cx);
+        self.subpage = page_type.create(&self.instance, self.backend_handle.clone(), &self.metadata, window, cx);
     }
 }
 
@@ -59,11 +64,22 @@ impl Render for InstancePage {
             InstanceSubpage::Quickplay(_) => 0,
             InstanceSubpage::Logs(_) => 1,
             InstanceSubpage::Mods(_) => 2,
-            InstanceSubpage::Settings(_) => 3,
+            InstanceSubpage::ResourcePacks(_) => 3,
+            InstanceSubpage::ShaderPacks(_) => 4,
+            InstanceSubpage::Datapacks(_) => 5,
+            InstanceSubpage::Notes(_) => 6,
+            InstanceSubpage::Settings(_) => 7,
+            InstanceSubpage::NbtEditor(_) => 8,
         };
 
         let play_icon = Icon::empty().path("icons/play.svg");
 
+        let (icon, png) = {
+            let instance = self.instance.read(cx);
+            (instance.organization.icon.clone(), instance.icon.clone())
+        };
+        let icon = instance_icon::render_icon(&icon, png.as_ref(), 32, cx);
+
         let instance = self.instance.read(cx);
         let id = instance.id;
         let name = instance.name.clone();
@@ -100,8 +116,33 @@ impl Render for InstancePage {
             }
         });
 
+        let clone_button = Button::new("clone_instance")
+            .icon(IconName::Copy)
+            .label("Clone")
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instances = self.instances.clone();
+                let name = instance.name.clone();
+                move |_, window, cx| {
+                    let instance_names = instances.read(cx).entries.values().map(|entry| entry.read(cx).name.clone()).collect();
+                    crate::modals::clone_instance::open(id, name.clone(), instance_names, backend_handle.clone(), window, cx);
+                }
+            });
+
+        let icon_button = div()
+            .id("instance_icon")
+            .cursor_pointer()
+            .child(icon)
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let name = instance.name.clone();
+                move |_, window, cx| {
+                    crate::modals::instance_icon::open(id, name.clone(), backend_handle.clone(), window, cx);
+                }
+            });
+
         let breadcrumb = (self.breadcrumb)().child(self.title.clone());
-        ui::page(cx, h_flex().gap_8().child(breadcrumb).child(h_flex().gap_3().child(button).child(open_dot_minecraft_button)))
+        ui::page(cx, h_flex().gap_8().child(h_flex().gap_3().child(icon_button).child(breadcrumb)).child(h_flex().gap_3().child(button).child(open_dot_minecraft_button).child(clone_button)))
             .child(
                 TabBar::new("bar")
                     .prefix(div().w_4())
@@ -110,13 +151,23 @@ impl Render for InstancePage {
                     .child(Tab::new().label("Quickplay"))
                     .child(Tab::new().label("Logs"))
                     .child(Tab::new().label("Mods"))
+                    .child(Tab::new().label("Resource Packs"))
+                    .child(Tab::new().label("Shader Packs"))
+                    .child(Tab::new().label("Datapacks"))
+                    .child(Tab::new().label("Notes"))
                     .child(Tab::new().label("Settings"))
+                    .child(Tab::new().label("NBT Editor"))
                     .on_click(cx.listener(|page, index, window, cx| {
                         let page_type = match *index {
                             0 => InstanceSubpageType::Quickplay,
                             1 => InstanceSubpageType::Logs,
                             2 => InstanceSubpageType::Mods,
-                            3 => InstanceSubpageType::Settings,
+                            3 => InstanceSubpageType::ResourcePacks,
+                            4 => InstanceSubpageType::ShaderPacks,
+                            5 => InstanceSubpageType::Datapacks,
+                            6 => InstanceSubpageType::Notes,
+                            7 => InstanceSubpageType::Settings,
+                            8 => InstanceSubpageType::NbtEditor,
                             _ => {
                                 return;
                             },
@@ -134,7 +185,12 @@ pub enum InstanceSubpageType {
     Quickplay,
     Logs,
     Mods,
+    ResourcePacks,
+    ShaderPacks,
+    Datapacks,
+    Notes,
     Settings,
+    NbtEditor,
 }
 
 impl InstanceSubpageType {
@@ -142,6 +198,7 @@ impl InstanceSubpageType {
         self,
         instance: &Entity<InstanceEntry>,
         backend_handle: BackendHandle,
+        metadata: &Entity<FrontendMetadata>,
         window: &mut gpui::Window,
         cx: &mut App
     ) -> InstanceSubpage {
@@ -155,8 +212,23 @@ impl InstanceSubpageType {
             InstanceSubpageType::Mods => InstanceSubpage::Mods(cx.new(|cx| {
                 InstanceModsSubpage::new(instance, backend_handle, window, cx)
             })),
+            InstanceSubpageType::ResourcePacks => InstanceSubpage::ResourcePacks(cx.new(|cx| {
+                InstancePacksSubpage::new(PacksSubpageKind::ResourcePacks, instance, backend_handle, window, cx)
+            })),
+            InstanceSubpageType::ShaderPacks => InstanceSubpage::ShaderPacks(cx.new(|cx| {
+                InstancePacksSubpage::new(PacksSubpageKind::ShaderPacks, instance, backend_handle, window, cx)
+            })),
+            InstanceSubpageType::Datapacks => InstanceSubpage::Datapacks(cx.new(|cx| {
+                InstancePacksSubpage::new(PacksSubpageKind::DataPacks, instance, backend_handle, window, cx)
+            })),
+            InstanceSubpageType::Notes => InstanceSubpage::Notes(cx.new(|cx| {
+                InstanceNotesSubpage::new(instance, backend_handle, window, cx)
+            })),
             InstanceSubpageType::Settings => InstanceSubpage::Settings(cx.new(|cx| {
-                InstanceSettingsSubpage::new(instance, backend_handle, window, cx)
+                InstanceSettingsSubpage::new(instance, backend_handle, metadata.clone(), window, cx)
+            })),
+            InstanceSubpageType::NbtEditor => InstanceSubpage::NbtEditor(cx.new(|cx| {
+                InstanceNbtEditorSubpage::new(instance, backend_handle, window, cx)
             })),
         }
     }
@@ -167,7 +239,12 @@ pub enum InstanceSubpage {
     Quickplay(Entity<InstanceQuickplaySubpage>),
     Logs(Entity<InstanceLogsSubpage>),
     Mods(Entity<InstanceModsSubpage>),
+    ResourcePacks(Entity<InstancePacksSubpage>),
+    ShaderPacks(Entity<InstancePacksSubpage>),
+    Datapacks(Entity<InstancePacksSubpage>),
+    Notes(Entity<InstanceNotesSubpage>),
     Settings(Entity<InstanceSettingsSubpage>),
+    NbtEditor(Entity<InstanceNbtEditorSubpage>),
 }
 
 impl InstanceSubpage {
@@ -176,7 +253,12 @@ impl InstanceSubpage {
             InstanceSubpage::Quickplay(_) => InstanceSubpageType::Quickplay,
             InstanceSubpage::Logs(_) => InstanceSubpageType::Logs,
             InstanceSubpage::Mods(_) => InstanceSubpageType::Mods,
+            InstanceSubpage::ResourcePacks(_) => InstanceSubpageType::ResourcePacks,
+            InstanceSubpage::ShaderPacks(_) => InstanceSubpageType::ShaderPacks,
+            InstanceSubpage::Datapacks(_) => InstanceSubpageType::Datapacks,
+            InstanceSubpage::Notes(_) => InstanceSubpageType::Notes,
             InstanceSubpage::Settings(_) => InstanceSubpageType::Settings,
+            InstanceSubpage::NbtEditor(_) => InstanceSubpageType::NbtEditor,
         }
     }
 
@@ -185,7 +267,12 @@ impl InstanceSubpage {
             Self::Quickplay(entity) => entity.into_any_element(),
             Self::Logs(entity) => entity.into_any_element(),
             Self::Mods(entity) => entity.into_any_element(),
+            Self::ResourcePacks(entity) => entity.into_any_element(),
+            Self::ShaderPacks(entity) => entity.into_any_element(),
+            Self::Datapacks(entity) => entity.into_any_element(),
+            Self::Notes(entity) => entity.into_any_element(),
             Self::Settings(entity) => entity.into_any_element(),
+            Self::NbtEditor(entity) => entity.into_any_element(),
         }
     }
 }
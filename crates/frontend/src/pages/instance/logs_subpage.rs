@@ -1,19 +1,19 @@
 use std::{path::Path, sync::Arc};
 
 use bridge::{
-    handle::BackendHandle, instance::InstanceID, message::{LogFiles, MessageToBackend}
+    game_output::GameOutputEntry, handle::BackendHandle, instance::InstanceID, message::{LogFiles, MessageToBackend}
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
     button::{Button, ButtonVariants}, h_flex, select::{Select, SelectEvent, SelectState}, spinner::Spinner, v_flex, ActiveTheme as _, Sizable
 };
 
-use crate::{component::{named_dropdown::{NamedDropdown, NamedDropdownItem}, readonly_text_field::{ReadonlyTextField, ReadonlyTextFieldWithControls}}, entity::instance::InstanceEntry, root};
+use crate::{component::named_dropdown::{NamedDropdown, NamedDropdownItem}, entity::instance::InstanceEntry, game_output::{GameOutput, GameOutputRoot}, root};
 
 pub struct InstanceLogsSubpage {
     instance: InstanceID,
     backend_handle: BackendHandle,
-    log_content: Option<Entity<ReadonlyTextFieldWithControls>>,
+    log_content: Option<Entity<GameOutputRoot>>,
     no_available_logs: bool,
     available_logs: Option<Entity<SelectState<NamedDropdown<Arc<Path>>>>>,
     clean_old_logs_text: Option<SharedString>,
@@ -87,37 +87,28 @@ impl InstanceLogsSubpage {
                         page.last_selected_path = selected.clone();
 
                         if let Some(selected) = selected {
-                            let (send, mut recv) = tokio::sync::mpsc::channel::<Arc<str>>(256);
+                            let (send, mut recv) = tokio::sync::mpsc::channel::<GameOutputEntry>(256);
 
-                            let text_field = cx.new(move |_| ReadonlyTextField::default());
+                            let game_output = cx.new(|_| GameOutput::default());
+                            let backend_handle = page.backend_handle.clone();
+                            let game_output_root = cx.new(|cx| {
+                                GameOutputRoot::new_for_log_file(selected.clone(), game_output.clone(), backend_handle, window, cx)
+                            });
 
-                            let text_field2 = text_field.clone();
                             page._read_log_task = Some(cx.spawn(async move |_, cx| {
-                                while let Some(message) = recv.recv().await {
-                                    let _ = cx.update_entity(&text_field2, |text_field, _| {
-                                        text_field.add(message);
+                                while let Some(entry) = recv.recv().await {
+                                    let _ = cx.update_entity(&game_output, |game_output, _| {
+                                        game_output.add(entry.time, entry.level, entry.logger, entry.thread, entry.text);
                                     });
                                 }
-                                let _ = cx.update_entity(&text_field2, |text_field, _| {
-                                    text_field.shrink_to_fit();
-                                });
                             }));
 
                             page.backend_handle.send(MessageToBackend::ReadLog {
-                                path: selected.clone(),
+                                path: selected,
                                 send,
                             });
 
-                            let backend_handle = page.backend_handle.clone();
-                            page.log_content = Some(cx.new(move |cx| {
-                                ReadonlyTextFieldWithControls::new(text_field, Box::new(move |div| {
-                                    let backend_handle = backend_handle.clone();
-                                    let selected = selected.clone();
-                                    div.child(Button::new("upload").label("Upload").on_click(move |_, window, cx| {
-                                        root::upload_log_file(selected.clone(), &backend_handle, window, cx);
-                                    }))
-                                }), window, cx)
-                            }));
+                            page.log_content = Some(game_output_root);
                         } else {
                             page._read_log_task = None;
                             page.log_content = None;
@@ -180,12 +171,25 @@ impl Render for InstanceLogsSubpage {
             }
 
             if let Some(log_content) = self.log_content.clone() {
-                content = content.child(log_content);
+                // The game output view draws its own border around the log lines
+                content = div().size_full().child(log_content);
             } else if self.available_logs.is_some() {
                 content = content.child(h_flex().justify_center().size_full().text_lg().child("Select log file"));
             }
         }
 
+        if !self.no_available_logs {
+            header = header.child(Button::new("upload-bundle").label("Upload bundle").info().compact().small()
+                .tooltip("Uploads latest.log, the newest crash report and the mod list together")
+                .on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let instance = self.instance;
+                    move |_, window, cx| {
+                        root::upload_log_bundle(instance, &backend_handle, window, cx);
+                    }
+                }));
+        }
+
         if let Some(clean_old_logs_text) = self.clean_old_logs_text.clone() {
             header = header.child(Button::new("cleanold").label(clean_old_logs_text).success().compact().small().on_click({
                 let backend_handle = self.backend_handle.clone();
@@ -1,5 +1,8 @@
 pub mod instance_page;
 pub mod logs_subpage;
 pub mod mods_subpage;
+pub mod nbt_editor_subpage;
+pub mod notes_subpage;
+pub mod packs_subpage;
 pub mod quickplay_subpage;
 pub mod settings_subpage;
@@ -1,17 +1,17 @@
-use std::{hash::{DefaultHasher, Hash, Hasher}, path::Path, sync::{
+use std::{collections::BTreeMap, hash::{DefaultHasher, Hash, Hasher}, path::Path, sync::{
     atomic::{AtomicU64, AtomicUsize, Ordering}, Arc
 }};
 
 use bridge::{
-    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{AtomicContentUpdateStatus, InstanceID, InstanceModID, InstanceModSummary, LoaderSpecificModSummary, ModSummary}, message::{AtomicBridgeDataLoadState, MessageToBackend}, serial::AtomicOptionSerial
+    dependencies::{check_dependencies, DependencyIssue, DependencyIssueKind}, handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{AtomicContentUpdateStatus, InstanceID, InstanceModID, InstanceModSummary, LoaderSpecificModSummary, ModSummary}, message::{AtomicBridgeDataLoadState, MessageToBackend}, serial::AtomicOptionSerial
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    breadcrumb::{Breadcrumb, BreadcrumbItem}, button::{Button, ButtonVariants}, h_flex, input::{SelectAll}, list::{ListDelegate, ListItem, ListState}, notification::{Notification, NotificationType}, switch::Switch, v_flex, ActiveTheme as _, Icon, IconName, IndexPath, Sizable, WindowExt
+    breadcrumb::{Breadcrumb, BreadcrumbItem}, button::{Button, ButtonVariants}, h_flex, input::{SelectAll}, list::{ListDelegate, ListItem, ListState}, notification::{Notification, NotificationType}, scroll::ScrollableElement, switch::Switch, v_flex, ActiveTheme as _, Icon, IconName, IndexPath, Sizable, StyledExt, WindowExt
 };
 use parking_lot::Mutex;
 use rustc_hash::FxHashSet;
-use schema::{content::ContentSource, loader::Loader};
+use schema::{content::ContentSource, instance::ModUpdatePreference, loader::Loader};
 use ustr::Ustr;
 
 use crate::{entity::instance::InstanceEntry, png_render_cache, root};
@@ -37,6 +37,7 @@ impl InstanceModsSubpage {
         window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> Self {
+        let instance_entity = instance.clone();
         let instance = instance.read(cx);
         let instance_title = instance.title().into();
         let instance_loader = instance.configuration.loader;
@@ -58,6 +59,9 @@ impl InstanceModsSubpage {
             selected: FxHashSet::default(),
             selected_range: FxHashSet::default(),
             last_clicked_non_range: None,
+            minecraft_version: instance_version,
+            dependency_issues: Arc::from([]),
+            update_preferences: instance.configuration.mod_update_preferences.clone(),
         };
         mods_list_delegate.set_mods(instance.mods.read(cx));
 
@@ -69,6 +73,10 @@ impl InstanceModsSubpage {
                 list.delegate_mut().set_mods(actual_mods);
                 cx.notify();
             }).detach();
+            cx.observe(&instance_entity, |list: &mut ListState<ModsListDelegate>, instance, cx| {
+                list.delegate_mut().update_preferences = instance.read(cx).configuration.mod_update_preferences.clone();
+                cx.notify();
+            }).detach();
 
             ListState::new(mods_list_delegate, window, cx).selectable(false).searchable(true)
         });
@@ -108,6 +116,13 @@ impl Render for InstanceModsSubpage {
                     crate::root::start_update_check(instance_id, &backend_handle, window, cx);
                 }
             }))
+            .child(Button::new("updateall").label("Update all").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance_id = self.instance;
+                move |_, window, cx| {
+                    crate::modals::update_all::open(instance_id, backend_handle.clone(), window, cx);
+                }
+            }))
             .child(Button::new("addmr").label("Add from Modrinth").success().compact().small().on_click({
                 let instance = self.instance;
                 let instance_title = self.instance_title.clone();
@@ -128,6 +143,24 @@ impl Render for InstanceModsSubpage {
                     root::switch_page(page, Some(Box::new(breadcrumb)), window, cx);
                 }
             }))
+            .child(Button::new("addcollection").label("Add from collection").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance = self.instance;
+                let loader = self.instance_loader;
+                let version = self.instance_version;
+                move |_, window, cx| {
+                    crate::modals::modrinth_collection::open(instance, loader, version, backend_handle.clone(), window, cx);
+                }
+            }))
+            .child(Button::new("addcurseforge").label("Add from CurseForge").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance = self.instance;
+                let loader = self.instance_loader;
+                let version = self.instance_version;
+                move |_, window, cx| {
+                    crate::modals::curseforge_install::open(instance, loader, version, backend_handle.clone(), window, cx);
+                }
+            }))
             .child(Button::new("addfile").label("Add from file").success().compact().small().on_click({
                 let backend_handle = self.backend_handle.clone();
                 let instance = self.instance;
@@ -179,11 +212,15 @@ impl Render for InstanceModsSubpage {
                 })
             }));
 
+        let dependency_issues = self.mod_list.read(cx).delegate().dependency_issues.clone();
+
         let mod_list = self.mod_list.clone();
         let mod_list_for_click = mod_list.clone();
         let mod_list_for_select_all = mod_list;
 
-        v_flex().p_4().size_full().child(header).child(
+        v_flex().p_4().size_full().child(header)
+            .when(!dependency_issues.is_empty(), |this| this.child(self.render_dependency_issues(&dependency_issues, cx)))
+            .child(
             div()
                 .id("mod-list-area")
                 .size_full()
@@ -222,6 +259,46 @@ impl Render for InstanceModsSubpage {
     }
 }
 
+impl InstanceModsSubpage {
+    fn render_dependency_issues(&self, issues: &[DependencyIssue], cx: &App) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let has_missing = issues.iter().any(|issue| matches!(issue.kind, DependencyIssueKind::Missing { .. }));
+
+        let title = if issues.len() == 1 {
+            SharedString::new_static("1 dependency problem")
+        } else {
+            SharedString::new(format!("{} dependency problems", issues.len()))
+        };
+
+        v_flex()
+            .mb_2()
+            .p_2()
+            .gap_1()
+            .border_1()
+            .rounded(theme.radius)
+            .border_color(theme.warning)
+            .child(h_flex()
+                .gap_3()
+                .child(Icon::default().path("icons/triangle-alert.svg").text_color(theme.warning))
+                .child(div().font_semibold().child(title))
+                .when(has_missing, |this| {
+                    let backend_handle = self.backend_handle.clone();
+                    let instance = self.instance;
+                    this.child(Button::new("install-missing").label("Install missing from Modrinth").success().compact().small().on_click(move |_, window, cx| {
+                        root::install_missing_dependencies(instance, &backend_handle, window, cx);
+                    }))
+                }))
+            .child(v_flex()
+                .id("dependency-issues")
+                .max_h_32()
+                .text_sm()
+                .text_color(theme.muted_foreground)
+                .children(issues.iter().map(|issue| SharedString::new(issue.describe())))
+                .overflow_y_scrollbar())
+    }
+}
+
 #[derive(Clone)]
 struct ModEntryChild {
     summary: Arc<ModSummary>,
@@ -250,6 +327,9 @@ pub struct ModsListDelegate {
     selected: FxHashSet<u64>,
     selected_range: FxHashSet<u64>,
     last_clicked_non_range: Option<u64>,
+    minecraft_version: Ustr,
+    dependency_issues: Arc<[DependencyIssue]>,
+    update_preferences: BTreeMap<Arc<str>, ModUpdatePreference>,
 }
 
 impl ModsListDelegate {
@@ -333,11 +413,21 @@ impl ModsListDelegate {
                 Button::new(("update", element_id)).icon(Icon::default().path("icons/check.svg"))
                     .tooltip("Mod is up-to-date as of last check")
             ),
-            bridge::instance::ContentUpdateStatus::Modrinth => {
+            bridge::instance::ContentUpdateStatus::Pinned => Some(
+                Button::new(("update", element_id)).icon(Icon::default().path("icons/eye-off.svg"))
+                    .tooltip("Mod is pinned - updates are ignored")
+            ),
+            status @ (bridge::instance::ContentUpdateStatus::Modrinth | bridge::instance::ContentUpdateStatus::CurseForge) => {
                 let loading = self.updating.lock().contains(&element_id);
+                let is_modpack = matches!(summary.mod_summary.extra, LoaderSpecificModSummary::ModrinthModpack { .. });
+                let tooltip = if status == bridge::instance::ContentUpdateStatus::CurseForge {
+                    "Download update from CurseForge"
+                } else {
+                    "Download update from Modrinth"
+                };
                 Some(
                     Button::new(("update", element_id)).success().loading(loading).icon(Icon::default().path("icons/download.svg"))
-                        .tooltip("Download update from Modrinth").on_click({
+                        .tooltip(tooltip).on_click({
                             let backend_handle = self.backend_handle.clone();
                             let updating = self.updating.clone();
                             cx.listener(move |this, _, window, cx| {
@@ -355,6 +445,9 @@ impl ModsListDelegate {
                                     delegate.selected.clear();
                                     delegate.selected_range.clear();
                                     delegate.last_clicked_non_range = None;
+                                } else if is_modpack {
+                                    // Modpacks can change files outside of mods, so the update is previewed first
+                                    crate::modals::modpack_update::open(id, mod_id, backend_handle.clone(), window, cx);
                                 } else {
                                     updating.insert(element_id);
                                     crate::root::update_single_mod(id, mod_id, &backend_handle, window, cx);
@@ -434,8 +527,22 @@ impl ModsListDelegate {
             .border_1()
             .when(selected, |content| content.border_color(cx.theme().selection).bg(cx.theme().selection.alpha(0.2)));
 
-        if let Some(update_button) = update_button {
-            item_content = item_content.child(h_flex().absolute().right_4().gap_2().child(update_button).child(delete_button))
+        let preference = self.update_preferences.get(&summary.mod_summary.id);
+        if update_button.is_some() || preference.is_some() {
+            let preferences_button = Button::new(("preferences", element_id)).icon(Icon::default().path("icons/sliders-vertical.svg"))
+                .tooltip("Update preferences").on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let mod_summary = summary.mod_summary.clone();
+                    let preference = preference.cloned().unwrap_or_default();
+                    move |_, window, cx| {
+                        cx.stop_propagation();
+                        crate::modals::update_preferences::open(id, mod_id, &mod_summary, preference.clone(), backend_handle.clone(), window, cx);
+                    }
+                });
+            item_content = item_content.child(h_flex().absolute().right_4().gap_2()
+                .children(update_button)
+                .child(preferences_button)
+                .child(delete_button))
         } else {
             item_content = item_content.child(delete_button.absolute().right_4())
         }
@@ -596,6 +703,8 @@ impl ModsListDelegate {
             authors: "Unknown".into(),
             png_icon: None,
             update_status: Arc::new(AtomicContentUpdateStatus::new(bridge::instance::ContentUpdateStatus::Unknown)),
+            provides: Arc::from([]),
+            dependencies: Arc::from([]),
             extra: LoaderSpecificModSummary::Fabric,
         });
 
@@ -640,6 +749,7 @@ impl ModsListDelegate {
         }
         drop(updating);
 
+        self.dependency_issues = check_dependencies(actual_mods, &self.minecraft_version).into();
         self.mods = mods.clone();
         self.children = children;
         self.searched = None;
@@ -0,0 +1,586 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Context as _;
+use bridge::{
+    handle::BackendHandle, instance::InstanceID, message::{MessageToBackend, NbtCompression, NbtFile}
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, input::{Input, InputState}, notification::NotificationType, scroll::ScrollableElement, v_flex, ActiveTheme as _, Icon, Sizable, WindowExt
+};
+use nbt::{CompoundRefMut, ListRefMut, NBTRef, TagType};
+use rustc_hash::FxHashSet;
+
+use crate::entity::instance::InstanceEntry;
+
+const MAX_ROWS: usize = 2000;
+
+pub struct InstanceNbtEditorSubpage {
+    instance: InstanceID,
+    dot_minecraft: Arc<Path>,
+    backend_handle: BackendHandle,
+    files: Option<Arc<[Arc<Path>]>>,
+    open: Option<OpenNbtFile>,
+    loading: bool,
+    error: Option<SharedString>,
+    expanded: FxHashSet<Vec<NbtPathSegment>>,
+    selected: Option<Vec<NbtPathSegment>>,
+    value_input: Entity<InputState>,
+    _files_task: Option<Task<()>>,
+    _file_task: Option<Task<()>>,
+}
+
+struct OpenNbtFile {
+    path: Arc<Path>,
+    root_name: String,
+    compression: NbtCompression,
+    root: NbtValue,
+    dirty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NbtPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl InstanceNbtEditorSubpage {
+    pub fn new(
+        instance: &Entity<InstanceEntry>,
+        backend_handle: BackendHandle,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let instance = instance.read(cx);
+
+        let mut subpage = Self {
+            instance: instance.id,
+            dot_minecraft: instance.dot_minecraft_folder.clone(),
+            backend_handle,
+            files: None,
+            open: None,
+            loading: false,
+            error: None,
+            expanded: FxHashSet::default(),
+            selected: None,
+            value_input: cx.new(|cx| InputState::new(window, cx)),
+            _files_task: None,
+            _file_task: None,
+        };
+        subpage.reload_files(window, cx);
+        subpage
+    }
+
+    fn reload_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::GetNbtFiles { id: self.instance, channel: send });
+
+        self.files = None;
+        self._files_task = Some(cx.spawn_in(window, async move |subpage, cx| {
+            let files = recv.await.unwrap_or_else(|_| Arc::from([]));
+            _ = subpage.update(cx, |subpage, cx| {
+                subpage.files = Some(files);
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_file(&mut self, path: Arc<Path>, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::ReadNbtFile { id: self.instance, path: path.clone(), channel: send });
+
+        self.loading = true;
+        self.error = None;
+        self._file_task = Some(cx.spawn_in(window, async move |subpage, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Loading was cancelled".into()));
+            _ = subpage.update(cx, |subpage, cx| {
+                subpage.loading = false;
+                match result {
+                    Ok(file) => {
+                        let root = file.nbt.as_compound().map(|root| NbtValue::from_ref(NBTRef::Compound(root)))
+                            .unwrap_or(NbtValue::Compound(Vec::new()));
+                        subpage.open = Some(OpenNbtFile {
+                            path,
+                            root_name: file.nbt.root_name.clone(),
+                            compression: file.compression,
+                            root,
+                            dirty: false,
+                        });
+                        subpage.expanded.clear();
+                        subpage.expanded.insert(Vec::new());
+                        subpage.selected = None;
+                    },
+                    Err(error) => {
+                        subpage.open = None;
+                        subpage.error = Some(SharedString::new(error));
+                    },
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(open) = &self.open else {
+            return;
+        };
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::WriteNbtFile {
+            id: self.instance,
+            path: open.path.clone(),
+            file: NbtFile {
+                nbt: open.root.to_nbt(open.root_name.clone()),
+                compression: open.compression,
+            },
+            channel: send,
+        });
+
+        self._file_task = Some(cx.spawn_in(window, async move |subpage, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Saving was cancelled".into()));
+            _ = subpage.update_in(cx, |subpage, window, cx| {
+                match result {
+                    Ok(()) => {
+                        if let Some(open) = &mut subpage.open {
+                            open.dirty = false;
+                        }
+                        window.push_notification((NotificationType::Success, "Saved, the previous version was kept as a .bak file"), cx);
+                    },
+                    Err(error) => {
+                        window.push_notification((NotificationType::Error, SharedString::new(error)), cx);
+                    },
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn select(&mut self, path: Vec<NbtPathSegment>, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(value) = self.open.as_ref().and_then(|open| open.root.get(&path)) else {
+            return;
+        };
+        let Some(text) = value.edit_text() else {
+            window.push_notification((NotificationType::Error, "Unable to convert the value to SNBT"), cx);
+            return;
+        };
+        self.value_input.update(cx, |input, cx| input.set_value(text, window, cx));
+        self.selected = Some(path);
+        cx.notify();
+    }
+
+    fn apply(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.value_input.read(cx).value();
+        let (Some(open), Some(path)) = (&mut self.open, &self.selected) else {
+            return;
+        };
+        let Some(value) = open.root.get_mut(path) else {
+            return;
+        };
+
+        match value.parse_as(&text) {
+            Ok(new_value) => {
+                *value = new_value;
+                open.dirty = true;
+            },
+            Err(error) => {
+                window.push_notification((NotificationType::Error, SharedString::from(error)), cx);
+            },
+        }
+        cx.notify();
+    }
+
+    fn remove_selected(&mut self, cx: &mut Context<Self>) {
+        let (Some(open), Some(path)) = (&mut self.open, &self.selected) else {
+            return;
+        };
+        if open.root.remove(path) {
+            open.dirty = true;
+            self.selected = None;
+        }
+        cx.notify();
+    }
+
+    fn render_rows(&self, value: &NbtValue, path: &mut Vec<NbtPathSegment>, label: SharedString, depth: usize, rows: &mut Vec<AnyElement>, cx: &mut Context<Self>) {
+        if rows.len() >= MAX_ROWS {
+            return;
+        }
+
+        let radius = cx.theme().radius;
+        let selection = cx.theme().selection;
+        let muted = cx.theme().muted_foreground;
+        let selected = self.selected.as_ref() == Some(&*path);
+        let expanded = self.expanded.contains(path);
+        let is_container = matches!(value, NbtValue::List(..) | NbtValue::Compound(..));
+
+        let chevron = if !is_container {
+            div().size_4().into_any_element()
+        } else if expanded {
+            Icon::default().path("icons/chevron-down.svg").size_4().into_any_element()
+        } else {
+            Icon::default().path("icons/chevron-right.svg").size_4().into_any_element()
+        };
+
+        let row_path = path.clone();
+        let row = h_flex()
+            .id(("nbt-row", rows.len()))
+            .gap_2()
+            .pl(px(depth as f32 * 16.0))
+            .rounded(radius)
+            .when(selected, |row| row.bg(selection))
+            .child(chevron)
+            .child(label)
+            .child(div().text_color(muted).child(value.tag_type().name()))
+            .child(div().overflow_hidden().text_ellipsis().child(value.display_text()))
+            .on_click(cx.listener(move |subpage, _, window, cx| {
+                if is_container && !subpage.expanded.remove(&row_path) {
+                    subpage.expanded.insert(row_path.clone());
+                }
+                subpage.select(row_path.clone(), window, cx);
+            }));
+        rows.push(row.into_any_element());
+
+        if !expanded {
+            return;
+        }
+
+        match value {
+            NbtValue::Compound(entries) => {
+                for (key, child) in entries {
+                    path.push(NbtPathSegment::Key(key.clone()));
+                    self.render_rows(child, path, SharedString::from(key.clone()), depth + 1, rows, cx);
+                    path.pop();
+                }
+            },
+            NbtValue::List(_, values) => {
+                for (index, child) in values.iter().enumerate() {
+                    path.push(NbtPathSegment::Index(index));
+                    self.render_rows(child, path, SharedString::from(format!("[{index}]")), depth + 1, rows, cx);
+                    path.pop();
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+impl Render for InstanceNbtEditorSubpage {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        let files = match &self.files {
+            None => v_flex().child(div().text_color(cx.theme().muted_foreground).child("Loading...")),
+            Some(files) if files.is_empty() => v_flex().child(div().text_color(cx.theme().muted_foreground).child("No .dat or .nbt files found")),
+            Some(files) => {
+                let selected_path = self.open.as_ref().map(|open| open.path.clone());
+                let buttons = files.iter().enumerate().map(|(index, path)| {
+                    let relative = path.strip_prefix(&self.dot_minecraft).unwrap_or(path).to_string_lossy().into_owned();
+                    let button = Button::new(("file", index)).small().label(relative).on_click(cx.listener({
+                        let path = path.clone();
+                        move |subpage, _, window, cx| {
+                            subpage.open_file(path.clone(), window, cx);
+                        }
+                    }));
+                    if selected_path.as_ref() == Some(path) {
+                        button.info()
+                    } else {
+                        button.ghost()
+                    }
+                }).collect::<Vec<_>>();
+                v_flex().gap_1().items_start().children(buttons)
+            },
+        };
+
+        let file_list = v_flex()
+            .w_1_4()
+            .h_full()
+            .gap_2()
+            .child(h_flex()
+                .child(div().flex_1().text_lg().child("Files"))
+                .child(Button::new("refresh-files").small().label("Refresh").on_click(cx.listener(|subpage, _, window, cx| {
+                    subpage.reload_files(window, cx);
+                }))))
+            .child(div().id("nbt-files").flex_1().overflow_y_scrollbar().child(files));
+
+        let editor = if self.loading {
+            v_flex().child(div().text_color(cx.theme().muted_foreground).child("Loading..."))
+        } else if let Some(error) = &self.error {
+            v_flex().child(div().text_color(cx.theme().danger).child(error.clone()))
+        } else if let Some(open) = &self.open {
+            let mut rows = Vec::new();
+            let root_label = SharedString::from(open.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default());
+            self.render_rows(&open.root, &mut Vec::new(), root_label, 0, &mut rows, cx);
+            let truncated = rows.len() >= MAX_ROWS;
+
+            let header = h_flex()
+                .gap_2()
+                .child(div().flex_1().text_lg().child(SharedString::from(format!("{}{}", open.path.display(), if open.dirty { " *" } else { "" }))))
+                .child(Button::new("save").success().label("Save").on_click(cx.listener(|subpage, _, window, cx| {
+                    subpage.save(window, cx);
+                })));
+
+            let selected_panel = self.selected.as_ref().and_then(|path| Some((path, open.root.get(path)?))).map(|(path, value)| {
+                let is_container = matches!(value, NbtValue::List(..) | NbtValue::Compound(..));
+                let title = if is_container { "Edit as SNBT" } else { "Edit value" };
+                v_flex()
+                    .gap_2()
+                    .child(div().text_sm().child(SharedString::from(format!("{} · {}", title, value.tag_type().name()))))
+                    .child(h_flex()
+                        .gap_2()
+                        .child(div().flex_1().child(Input::new(&self.value_input)))
+                        .child(Button::new("apply").info().label("Apply").on_click(cx.listener(|subpage, _, window, cx| {
+                            subpage.apply(window, cx);
+                        })))
+                        .when(!path.is_empty(), |buttons| {
+                            buttons.child(Button::new("remove").danger().icon(Icon::default().path("icons/trash-2.svg")).on_click(cx.listener(|subpage, _, _, cx| {
+                                subpage.remove_selected(cx);
+                            })))
+                        }))
+            });
+
+            v_flex()
+                .gap_2()
+                .size_full()
+                .child(header)
+                .child(div()
+                    .id("nbt-tree")
+                    .flex_1()
+                    .border_1()
+                    .rounded(cx.theme().radius)
+                    .border_color(cx.theme().border)
+                    .p_1()
+                    .overflow_y_scrollbar()
+                    .child(v_flex().children(rows))
+                    .when(truncated, |tree| tree.child(div().text_color(cx.theme().muted_foreground).child("Too many entries, collapse some to see more"))))
+                .children(selected_panel)
+        } else {
+            v_flex().child(div().text_color(cx.theme().muted_foreground).child("Select a file to edit"))
+        };
+
+        h_flex().p_4().gap_4().size_full().items_start().child(file_list).child(editor.flex_1().h_full())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum NbtValue {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(TagType, Vec<NbtValue>),
+    Compound(Vec<(String, NbtValue)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl NbtValue {
+    fn from_ref(value: NBTRef<'_>) -> Self {
+        match value {
+            NBTRef::Byte(value) => Self::Byte(*value),
+            NBTRef::Short(value) => Self::Short(*value),
+            NBTRef::Int(value) => Self::Int(*value),
+            NBTRef::Long(value) => Self::Long(*value),
+            NBTRef::Float(value) => Self::Float(*value),
+            NBTRef::Double(value) => Self::Double(*value),
+            NBTRef::ByteArray(value) => Self::ByteArray(value.clone()),
+            NBTRef::String(value) => Self::String(value.clone()),
+            NBTRef::List(list) => Self::List(list.children_type(), list.iter().map(Self::from_ref).collect()),
+            NBTRef::Compound(compound) => Self::Compound(compound.entries().map(|(key, value)| (key.to_string(), Self::from_ref(value))).collect()),
+            NBTRef::IntArray(value) => Self::IntArray(value.clone()),
+            NBTRef::LongArray(value) => Self::LongArray(value.clone()),
+        }
+    }
+
+    fn tag_type(&self) -> TagType {
+        match self {
+            Self::Byte(_) => nbt::TAG_BYTE_ID,
+            Self::Short(_) => nbt::TAG_SHORT_ID,
+            Self::Int(_) => nbt::TAG_INT_ID,
+            Self::Long(_) => nbt::TAG_LONG_ID,
+            Self::Float(_) => nbt::TAG_FLOAT_ID,
+            Self::Double(_) => nbt::TAG_DOUBLE_ID,
+            Self::ByteArray(_) => nbt::TAG_BYTE_ARRAY_ID,
+            Self::String(_) => nbt::TAG_STRING_ID,
+            Self::List(..) => nbt::TAG_LIST_ID,
+            Self::Compound(_) => nbt::TAG_COMPOUND_ID,
+            Self::IntArray(_) => nbt::TAG_INT_ARRAY_ID,
+            Self::LongArray(_) => nbt::TAG_LONG_ARRAY_ID,
+        }
+    }
+
+    fn to_nbt(&self, root_name: String) -> nbt::NBT {
+        let mut nbt = nbt::NBT::new_named(root_name);
+        if let Self::Compound(entries) = self
+            && let Some(mut root) = nbt.as_compound_mut()
+        {
+            for (key, value) in entries {
+                value.write_into_compound(key, &mut root);
+            }
+        }
+        nbt
+    }
+
+    fn write_into_compound(&self, key: &str, compound: &mut CompoundRefMut<'_>) {
+        match self {
+            Self::Byte(value) => compound.insert_byte(key, *value),
+            Self::Short(value) => compound.insert_short(key, *value),
+            Self::Int(value) => compound.insert_int(key, *value),
+            Self::Long(value) => compound.insert_long(key, *value),
+            Self::Float(value) => compound.insert_float(key, *value),
+            Self::Double(value) => compound.insert_double(key, *value),
+            Self::ByteArray(value) => compound.insert_byte_array(key, value.clone()),
+            Self::String(value) => compound.insert_string(key, value.clone()),
+            Self::List(type_id, values) => {
+                let mut list = compound.create_list(key, *type_id);
+                for value in values {
+                    value.write_into_list(&mut list);
+                }
+            },
+            Self::Compound(entries) => {
+                let mut child = compound.create_compound(key);
+                for (key, value) in entries {
+                    value.write_into_compound(key, &mut child);
+                }
+            },
+            Self::IntArray(value) => compound.insert_int_array(key, value.clone()),
+            Self::LongArray(value) => compound.insert_long_array(key, value.clone()),
+        }
+    }
+
+    fn write_into_list(&self, list: &mut ListRefMut<'_>) {
+        match self {
+            Self::Byte(value) => list.insert_byte(*value),
+            Self::Short(value) => list.insert_short(*value),
+            Self::Int(value) => list.insert_int(*value),
+            Self::Long(value) => list.insert_long(*value),
+            Self::Float(value) => list.insert_float(*value),
+            Self::Double(value) => list.insert_double(*value),
+            Self::ByteArray(value) => list.insert_byte_array(value.clone()),
+            Self::String(value) => list.insert_string(value.clone()),
+            Self::List(type_id, values) => {
+                let mut child = list.create_list(*type_id);
+                for value in values {
+                    value.write_into_list(&mut child);
+                }
+            },
+            Self::Compound(entries) => {
+                let mut child = list.create_compound();
+                for (key, value) in entries {
+                    value.write_into_compound(key, &mut child);
+                }
+            },
+            Self::IntArray(value) => list.insert_int_array(value.clone()),
+            Self::LongArray(value) => list.insert_long_array(value.clone()),
+        }
+    }
+
+    fn to_snbt(&self) -> Option<String> {
+        let mut nbt = nbt::NBT::new();
+        self.write_into_compound("value", &mut nbt.as_compound_mut()?);
+        let value = nbt.as_compound()?.find("value")?;
+        Some(nbt::stringified::ref_to_snbt_string(value))
+    }
+
+    fn from_snbt(snbt: &str) -> anyhow::Result<Self> {
+        let nbt = nbt::stringified::from_snbt(&format!("{{value: {snbt}}}"))?;
+        let value = nbt.as_compound().and_then(|root| root.find("value")).context("Missing value")?;
+        Ok(Self::from_ref(value))
+    }
+
+    fn display_text(&self) -> SharedString {
+        match self {
+            Self::Byte(value) => value.to_string().into(),
+            Self::Short(value) => value.to_string().into(),
+            Self::Int(value) => value.to_string().into(),
+            Self::Long(value) => value.to_string().into(),
+            Self::Float(value) => value.to_string().into(),
+            Self::Double(value) => value.to_string().into(),
+            Self::String(value) => value.clone().into(),
+            Self::ByteArray(values) => format!("{} bytes", values.len()).into(),
+            Self::IntArray(values) => format!("{} ints", values.len()).into(),
+            Self::LongArray(values) => format!("{} longs", values.len()).into(),
+            Self::List(_, values) => format!("{} entries", values.len()).into(),
+            Self::Compound(entries) => format!("{} entries", entries.len()).into(),
+        }
+    }
+
+    fn edit_text(&self) -> Option<SharedString> {
+        match self {
+            Self::String(value) => Some(value.clone().into()),
+            Self::ByteArray(..) | Self::IntArray(..) | Self::LongArray(..) | Self::List(..) | Self::Compound(..) => self.to_snbt().map(SharedString::from),
+            _ => Some(self.display_text()),
+        }
+    }
+
+    /// Parses the text as a value of the same type, strings are taken verbatim and everything else is SNBT
+    fn parse_as(&self, text: &str) -> Result<Self, String> {
+        fn number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+            text.trim_ascii().parse().map_err(|_| format!("Invalid number: {}", text.trim_ascii()))
+        }
+
+        let value = match self {
+            Self::Byte(_) => Self::Byte(number(text)?),
+            Self::Short(_) => Self::Short(number(text)?),
+            Self::Int(_) => Self::Int(number(text)?),
+            Self::Long(_) => Self::Long(number(text)?),
+            Self::Float(_) => Self::Float(number(text)?),
+            Self::Double(_) => Self::Double(number(text)?),
+            Self::String(_) => Self::String(text.to_string()),
+            _ => Self::from_snbt(text).map_err(|err| format!("Invalid SNBT: {err}"))?,
+        };
+
+        if value.tag_type() != self.tag_type() {
+            return Err(format!("Expected {}, got {}", self.tag_type().name(), value.tag_type().name()));
+        }
+        if let (Self::List(expected, _), Self::List(actual, values)) = (self, &value)
+            && expected != actual && !values.is_empty()
+        {
+            return Err(format!("Expected a list of {}, got a list of {}", expected.name(), actual.name()));
+        }
+        Ok(value)
+    }
+
+    fn get(&self, path: &[NbtPathSegment]) -> Option<&Self> {
+        let Some((first, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        let child = match (self, first) {
+            (Self::Compound(entries), NbtPathSegment::Key(key)) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value)?,
+            (Self::List(_, values), NbtPathSegment::Index(index)) => values.get(*index)?,
+            _ => return None,
+        };
+        child.get(rest)
+    }
+
+    fn get_mut(&mut self, path: &[NbtPathSegment]) -> Option<&mut Self> {
+        let Some((first, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        let child = match (self, first) {
+            (Self::Compound(entries), NbtPathSegment::Key(key)) => entries.iter_mut().find(|(name, _)| name == key).map(|(_, value)| value)?,
+            (Self::List(_, values), NbtPathSegment::Index(index)) => values.get_mut(*index)?,
+            _ => return None,
+        };
+        child.get_mut(rest)
+    }
+
+    fn remove(&mut self, path: &[NbtPathSegment]) -> bool {
+        let Some((last, parent)) = path.split_last() else {
+            return false;
+        };
+        match (self.get_mut(parent), last) {
+            (Some(Self::Compound(entries)), NbtPathSegment::Key(key)) => {
+                let length = entries.len();
+                entries.retain(|(name, _)| name != key);
+                entries.len() != length
+            },
+            (Some(Self::List(_, values)), NbtPathSegment::Index(index)) if *index < values.len() => {
+                values.remove(*index);
+                true
+            },
+            _ => false,
+        }
+    }
+}
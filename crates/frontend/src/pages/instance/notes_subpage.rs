@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, input::{Input, InputState}, spinner::Spinner, text::TextView, v_flex, ActiveTheme as _, Sizable
+};
+
+use crate::entity::instance::InstanceEntry;
+
+pub struct InstanceNotesSubpage {
+    instance: InstanceID,
+    backend_handle: BackendHandle,
+    /// None while the notes are being loaded
+    notes: Option<SharedString>,
+    editing: bool,
+    notes_input: Entity<InputState>,
+    _get_notes_task: Task<()>,
+}
+
+impl InstanceNotesSubpage {
+    pub fn new(
+        instance: &Entity<InstanceEntry>,
+        backend_handle: BackendHandle,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let instance_id = instance.read(cx).id;
+
+        let notes_input = cx.new(|cx| InputState::new(window, cx).auto_grow(12, 40).placeholder("Write notes in markdown, e.g. server addresses or which mods to update"));
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_notes_task = cx.spawn_in(window, async move |page, cx| {
+            let notes: Arc<str> = recv.await.unwrap_or_default();
+            let _ = page.update_in(cx, move |page, window, cx| {
+                let notes = SharedString::new(notes);
+                page.notes_input.update(cx, |input, cx| {
+                    input.set_value(notes.clone(), window, cx);
+                });
+                page.notes = Some(notes);
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetInstanceNotes { id: instance_id, channel: send });
+
+        Self {
+            instance: instance_id,
+            backend_handle,
+            notes: None,
+            editing: false,
+            notes_input,
+            _get_notes_task,
+        }
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let notes = self.notes_input.read(cx).value();
+        self.backend_handle.send(MessageToBackend::SetInstanceNotes {
+            id: self.instance,
+            notes: Arc::from(notes.as_str()),
+        });
+        self.notes = Some(notes);
+        self.editing = false;
+        cx.notify();
+    }
+
+    fn cancel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let notes = self.notes.clone().unwrap_or_default();
+        self.notes_input.update(cx, |input, cx| {
+            input.set_value(notes, window, cx);
+        });
+        self.editing = false;
+        cx.notify();
+    }
+}
+
+impl Render for InstanceNotesSubpage {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        let theme = cx.theme();
+
+        let mut header = h_flex()
+            .gap_3()
+            .mb_1()
+            .ml_1()
+            .child(div().text_lg().child("Notes"));
+
+        let Some(notes) = self.notes.clone() else {
+            return v_flex().p_4().size_full().child(header).child(Spinner::new().with_size(gpui_component::Size::Large));
+        };
+
+        if self.editing {
+            header = header
+                .child(Button::new("save").label("Save").success().compact().small().on_click(cx.listener(|this, _, _, cx| {
+                    this.save(cx);
+                })))
+                .child(Button::new("cancel").label("Cancel").compact().small().on_click(cx.listener(|this, _, window, cx| {
+                    this.cancel(window, cx);
+                })));
+        } else {
+            header = header.child(Button::new("edit").label("Edit").info().compact().small().on_click(cx.listener(|this, _, window, cx| {
+                this.editing = true;
+                this.notes_input.update(cx, |input, cx| {
+                    input.focus(window, cx);
+                });
+                cx.notify();
+            })));
+        }
+
+        let body = if self.editing {
+            div().child(Input::new(&self.notes_input)).into_any_element()
+        } else if notes.trim_ascii().is_empty() {
+            div().text_color(theme.muted_foreground).child("This instance has no notes yet").into_any_element()
+        } else {
+            TextView::markdown("notes", notes).into_any_element()
+        };
+
+        v_flex()
+            .p_4()
+            .size_full()
+            .child(header)
+            .child(div()
+                .id("notes-area")
+                .size_full()
+                .p_2()
+                .overflow_y_scroll()
+                .border_1()
+                .rounded_lg()
+                .border_color(theme.border)
+                .child(body))
+    }
+}
@@ -0,0 +1,514 @@
+use std::{path::{Path, PathBuf}, sync::{atomic::Ordering, Arc}};
+
+use bridge::{
+    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{ContentUpdateStatus, InstanceID, InstancePackSummary, InstanceWorldSummary, PackFolder, PackMetadata}, message::{AtomicBridgeDataLoadState, MessageToBackend}, serial::AtomicOptionSerial
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    breadcrumb::{Breadcrumb, BreadcrumbItem}, button::{Button, ButtonVariants}, h_flex, list::{ListDelegate, ListItem, ListState}, notification::{Notification, NotificationType}, switch::Switch, v_flex, ActiveTheme as _, Disableable, Icon, IconName, IndexPath, Selectable, Sizable, WindowExt
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{content::ContentSource, loader::Loader};
+use ustr::Ustr;
+
+use crate::{entity::instance::{InstanceEntry, InstancePacks}, png_render_cache, root};
+
+use super::instance_page::InstanceSubpageType;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PacksSubpageKind {
+    ResourcePacks,
+    ShaderPacks,
+    DataPacks,
+}
+
+impl PacksSubpageKind {
+    fn subpage_type(self) -> InstanceSubpageType {
+        match self {
+            PacksSubpageKind::ResourcePacks => InstanceSubpageType::ResourcePacks,
+            PacksSubpageKind::ShaderPacks => InstanceSubpageType::ShaderPacks,
+            PacksSubpageKind::DataPacks => InstanceSubpageType::Datapacks,
+        }
+    }
+}
+
+pub struct InstancePacksSubpage {
+    instance: InstanceID,
+    instance_title: SharedString,
+    instance_loader: Loader,
+    instance_version: Ustr,
+    kind: PacksSubpageKind,
+    /// None for datapacks until a world has been selected
+    folder: Option<PackFolder>,
+    requested_folder: Option<PackFolder>,
+    packs: Entity<FxHashMap<PackFolder, InstancePacks>>,
+    worlds: Entity<Arc<[InstanceWorldSummary]>>,
+    worlds_state: Arc<AtomicBridgeDataLoadState>,
+    dot_minecraft_folder: Arc<Path>,
+    backend_handle: BackendHandle,
+    pack_list: Entity<ListState<PacksListDelegate>>,
+    packs_serial: AtomicOptionSerial,
+    worlds_serial: AtomicOptionSerial,
+    _add_from_file_task: Option<Task<()>>,
+}
+
+impl InstancePacksSubpage {
+    pub fn new(
+        kind: PacksSubpageKind,
+        instance: &Entity<InstanceEntry>,
+        backend_handle: BackendHandle,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let instance = instance.read(cx);
+        let instance_id = instance.id;
+
+        let folder = match kind {
+            PacksSubpageKind::ResourcePacks => Some(PackFolder::ResourcePacks),
+            PacksSubpageKind::ShaderPacks => Some(PackFolder::ShaderPacks),
+            PacksSubpageKind::DataPacks => None,
+        };
+
+        let mut packs_list_delegate = PacksListDelegate {
+            id: instance_id,
+            backend_handle: backend_handle.clone(),
+            folder: folder.clone(),
+            packs: Arc::from([]),
+            searched: Vec::new(),
+            last_query: SharedString::new_static(""),
+            confirming_delete: None,
+            updating: FxHashSet::default(),
+        };
+        packs_list_delegate.set_packs_from(instance.packs.read(cx));
+
+        let packs = instance.packs.clone();
+        let worlds = instance.worlds.clone();
+
+        let packs_for_list = packs.clone();
+        let pack_list = cx.new(move |cx| {
+            cx.observe(&packs_for_list, |list: &mut ListState<PacksListDelegate>, packs, cx| {
+                list.delegate_mut().set_packs_from(packs.read(cx));
+                cx.notify();
+            }).detach();
+
+            ListState::new(packs_list_delegate, window, cx).selectable(false).searchable(true)
+        });
+
+        cx.observe(&worlds, |_, _, cx| cx.notify()).detach();
+
+        Self {
+            instance: instance_id,
+            instance_title: instance.title().into(),
+            instance_loader: instance.configuration.loader,
+            instance_version: instance.configuration.minecraft_version,
+            kind,
+            folder,
+            requested_folder: None,
+            packs,
+            worlds,
+            worlds_state: Arc::clone(&instance.worlds_state),
+            dot_minecraft_folder: instance.dot_minecraft_folder.clone(),
+            backend_handle,
+            pack_list,
+            packs_serial: AtomicOptionSerial::default(),
+            worlds_serial: AtomicOptionSerial::default(),
+            _add_from_file_task: None,
+        }
+    }
+
+    fn select_world(&mut self, world: Arc<str>, cx: &mut Context<Self>) {
+        let folder = PackFolder::DataPacks { world };
+        self.folder = Some(folder.clone());
+
+        let packs = self.packs.clone();
+        self.pack_list.update(cx, |list, cx| {
+            let delegate = list.delegate_mut();
+            delegate.folder = Some(folder);
+            delegate.confirming_delete = None;
+            delegate.set_packs_from(packs.read(cx));
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn relative_folder(folder: &PackFolder) -> PathBuf {
+        match folder {
+            PackFolder::ResourcePacks => PathBuf::from("resourcepacks"),
+            PackFolder::ShaderPacks => PathBuf::from("shaderpacks"),
+            PackFolder::DataPacks { world } => Path::new("saves").join(&**world).join("datapacks"),
+        }
+    }
+
+    fn render_world_selector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let selected_world = match &self.folder {
+            Some(PackFolder::DataPacks { world }) => Some(world.clone()),
+            _ => None,
+        };
+
+        let worlds = self.worlds.read(cx).clone();
+
+        h_flex()
+            .flex_wrap()
+            .gap_2()
+            .mb_2()
+            .ml_1()
+            .child(div().text_color(cx.theme().muted_foreground).child("World:"))
+            .when(worlds.is_empty(), |this| this.child(div().text_color(cx.theme().muted_foreground).child("This instance has no worlds")))
+            .children(worlds.iter().enumerate().filter_map(|(index, world)| {
+                let folder_name: Arc<str> = world.level_path.file_name()?.to_str()?.into();
+                let selected = selected_world.as_ref() == Some(&folder_name);
+                Some(Button::new(("world", index)).label(SharedString::from(world.title.clone())).compact().small().selected(selected).on_click(cx.listener(move |this, _, _, cx| {
+                    this.select_world(folder_name.clone(), cx);
+                })))
+            }))
+    }
+}
+
+impl Render for InstancePacksSubpage {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        if self.kind == PacksSubpageKind::DataPacks {
+            let state = self.worlds_state.load(Ordering::SeqCst);
+            if state.should_send_load_request() {
+                self.backend_handle.send_with_serial(MessageToBackend::RequestLoadWorlds { id: self.instance }, &self.worlds_serial);
+            }
+        }
+
+        if let Some(folder) = &self.folder {
+            let should_send_load_request = match self.packs.read(cx).get(folder) {
+                Some(packs) => packs.state.load(Ordering::SeqCst).should_send_load_request(),
+                None => self.requested_folder.as_ref() != Some(folder),
+            };
+            if should_send_load_request {
+                self.requested_folder = Some(folder.clone());
+                self.backend_handle.send_with_serial(MessageToBackend::RequestLoadPacks { id: self.instance, folder: folder.clone() }, &self.packs_serial);
+            }
+        }
+
+        let title = match self.kind {
+            PacksSubpageKind::ResourcePacks => "Resource Packs",
+            PacksSubpageKind::ShaderPacks => "Shader Packs",
+            PacksSubpageKind::DataPacks => "Datapacks",
+        };
+
+        let mut header = h_flex()
+            .gap_3()
+            .mb_1()
+            .ml_1()
+            .child(div().text_lg().child(title));
+
+        if let Some(folder) = self.folder.clone() {
+            header = header.child(Button::new("update").label("Check for updates").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance_id = self.instance;
+                let folder = folder.clone();
+                move |_, window, cx| {
+                    crate::root::start_pack_update_check(instance_id, folder.clone(), &backend_handle, window, cx);
+                }
+            }));
+
+            if self.kind != PacksSubpageKind::DataPacks {
+                header = header.child(Button::new("addmr").label("Add from Modrinth").success().compact().small().on_click({
+                    let instance = self.instance;
+                    let instance_title = self.instance_title.clone();
+                    let subpage_type = self.kind.subpage_type();
+                    move |_, window, cx| {
+                        let page = crate::ui::PageType::Modrinth { installing_for: Some(instance) };
+
+                        let instance_title = instance_title.clone();
+                        let breadcrumb = move || {
+                            let instances_item = BreadcrumbItem::new("Instances").on_click(|_, window, cx| {
+                                root::switch_page(crate::ui::PageType::Instances, None, window, cx);
+                            });
+                            let instance_item = BreadcrumbItem::new(instance_title.clone()).on_click(move |_, window, cx| {
+                                root::switch_page(crate::ui::PageType::InstancePage(instance, subpage_type), None, window, cx);
+                            });
+                            Breadcrumb::new().text_xl().child(instances_item).child(instance_item)
+                        };
+
+                        root::switch_page(page, Some(Box::new(breadcrumb)), window, cx);
+                    }
+                }));
+            }
+
+            header = header.child(Button::new("addfile").label("Add from file").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance = self.instance;
+                let relative_folder = Self::relative_folder(&folder);
+                cx.listener(move |this, _, window, cx| {
+                    let receiver = cx.prompt_for_paths(PathPromptOptions {
+                        files: true,
+                        directories: false,
+                        multiple: true,
+                        prompt: Some("Select packs to install".into())
+                    });
+
+                    let backend_handle = backend_handle.clone();
+                    let relative_folder = relative_folder.clone();
+                    let entity = cx.entity();
+                    let add_from_file_task = window.spawn(cx, async move |cx| {
+                        let Ok(result) = receiver.await else {
+                            return;
+                        };
+                        _ = cx.update_window_entity(&entity, move |this, window, cx| {
+                            match result {
+                                Ok(Some(paths)) => {
+                                    let content_install = ContentInstall {
+                                        target: InstallTarget::Instance(instance),
+                                        loader_hint: this.instance_loader,
+                                        version_hint: Some(this.instance_version.into()),
+                                        files: paths.into_iter().filter_map(|path| {
+                                            Some(ContentInstallFile {
+                                                replace_old: None,
+                                                path: ContentInstallPath::Raw(relative_folder.join(path.file_name()?).into()),
+                                                download: ContentDownload::File { path },
+                                                content_source: ContentSource::Manual,
+                                            })
+                                        }).collect(),
+                                    };
+                                    crate::root::start_install(content_install, &backend_handle, window, cx);
+                                },
+                                Ok(None) => {},
+                                Err(error) => {
+                                    let error = format!("{}", error);
+                                    let notification = Notification::new()
+                                        .autohide(false)
+                                        .with_type(NotificationType::Error)
+                                        .title(error);
+                                    window.push_notification(notification, cx);
+                                },
+                            }
+                        });
+                    });
+                    this._add_from_file_task = Some(add_from_file_task);
+                })
+            }));
+
+            header = header.child(Button::new("openfolder").label("Open folder").info().compact().small().on_click({
+                let path = self.dot_minecraft_folder.join(Self::relative_folder(&folder));
+                move |_, window, cx| {
+                    crate::open_folder(&path, window, cx);
+                }
+            }));
+        }
+
+        let mut content = v_flex().p_4().size_full().child(header);
+
+        if self.kind == PacksSubpageKind::DataPacks {
+            content = content.child(self.render_world_selector(cx));
+        }
+
+        let theme = cx.theme();
+        if self.folder.is_some() {
+            let pack_list = self.pack_list.clone();
+            content.child(
+                div()
+                    .id("pack-list-area")
+                    .size_full()
+                    .border_1()
+                    .rounded(theme.radius)
+                    .border_color(theme.border)
+                    .child(self.pack_list.clone())
+                    .on_click(move |_, _, cx| {
+                        cx.update_entity(&pack_list, |list, cx| {
+                            if list.delegate_mut().confirming_delete.take().is_some() {
+                                cx.notify();
+                            }
+                        })
+                    })
+            )
+        } else {
+            content.child(div().ml_1().text_color(theme.muted_foreground).child("Select a world to manage its datapacks"))
+        }
+    }
+}
+
+pub struct PacksListDelegate {
+    id: InstanceID,
+    backend_handle: BackendHandle,
+    folder: Option<PackFolder>,
+    packs: Arc<[InstancePackSummary]>,
+    searched: Vec<InstancePackSummary>,
+    last_query: SharedString,
+    confirming_delete: Option<Arc<Path>>,
+    updating: FxHashSet<Arc<Path>>,
+}
+
+impl PacksListDelegate {
+    fn set_packs_from(&mut self, packs: &FxHashMap<PackFolder, InstancePacks>) {
+        let packs = self.folder.as_ref()
+            .and_then(|folder| packs.get(folder))
+            .map(|packs| packs.packs.clone())
+            .unwrap_or_else(|| Arc::from([]));
+
+        // Updated packs replace the old file, so anything no longer present has finished updating
+        self.updating.retain(|path| packs.iter().any(|pack| &pack.path == path));
+        self.packs = packs;
+        self.searched = self.filter(&self.last_query);
+    }
+
+    fn filter(&self, query: &str) -> Vec<InstancePackSummary> {
+        let query = query.to_lowercase();
+        self.packs.iter().filter(|pack| {
+            if pack.lowercase_filename.contains(&query) {
+                return true;
+            }
+            match &pack.metadata {
+                PackMetadata::Pack { description, .. } => description.to_lowercase().contains(&query),
+                _ => false,
+            }
+        }).cloned().collect()
+    }
+}
+
+impl ListDelegate for PacksListDelegate {
+    type Item = ListItem;
+
+    fn items_count(&self, _section: usize, _cx: &App) -> usize {
+        self.searched.len()
+    }
+
+    fn render_item(&mut self, ix: IndexPath, _window: &mut Window, cx: &mut Context<ListState<Self>>) -> Option<Self::Item> {
+        let summary = self.searched.get(ix.row)?.clone();
+        let folder = self.folder.clone()?;
+
+        let icon = if let Some(png_icon) = summary.png_icon.as_ref() {
+            png_render_cache::render(Arc::clone(png_icon), cx)
+        } else {
+            gpui::img(ImageSource::Resource(Resource::Embedded("images/default_mod.png".into())))
+        };
+
+        const GRAY: Hsla = Hsla { h: 0.0, s: 0.0, l: 0.5, a: 1.0};
+
+        let mut description = v_flex()
+            .flex_1()
+            .overflow_hidden()
+            .child(SharedString::from(summary.filename.clone()));
+
+        match &summary.metadata {
+            PackMetadata::Pack { description: pack_description, pack_format } => {
+                if !pack_description.is_empty() {
+                    description = description.child(div().text_color(GRAY).line_clamp(2).child(SharedString::from(pack_description.clone())));
+                }
+                if let Some(pack_format) = pack_format {
+                    description = description.child(div().text_color(GRAY).child(SharedString::from(format!("Pack format {pack_format}"))));
+                }
+            },
+            PackMetadata::Shader { profiles, dimensions } => {
+                if !profiles.is_empty() {
+                    description = description.child(div().text_color(GRAY).child(SharedString::from(format!("Profiles: {}", profiles.join(", ")))));
+                }
+                if !dimensions.is_empty() {
+                    description = description.child(div().text_color(GRAY).child(SharedString::from(format!("Dimensions: {}", dimensions.join(", ")))));
+                }
+            },
+            PackMetadata::Unknown => {},
+        }
+
+        let id = self.id;
+        let path = summary.path.clone();
+
+        let delete_button = if self.confirming_delete.as_ref() == Some(&path) {
+            Button::new(("delete", ix.row)).danger().icon(IconName::Check).tooltip("Confirm deletion").on_click({
+                let backend_handle = self.backend_handle.clone();
+                let folder = folder.clone();
+                let path = path.clone();
+                cx.listener(move |this, _, _, cx| {
+                    cx.stop_propagation();
+                    this.delegate_mut().confirming_delete = None;
+                    backend_handle.send(MessageToBackend::DeletePack { id, folder: folder.clone(), paths: vec![path.clone()] });
+                })
+            })
+        } else {
+            let path = path.clone();
+            Button::new(("delete", ix.row)).danger().icon(Icon::default().path("icons/trash-2.svg")).tooltip("Delete pack").on_click(cx.listener(move |this, _, _, cx| {
+                cx.stop_propagation();
+                this.delegate_mut().confirming_delete = Some(path.clone());
+                cx.notify();
+            }))
+        };
+
+        let update_button = match summary.update_status.load(Ordering::Relaxed) {
+            ContentUpdateStatus::Unknown | ContentUpdateStatus::Pinned => None,
+            ContentUpdateStatus::ManualInstall => Some(
+                Button::new(("update", ix.row)).warning().icon(Icon::default().path("icons/file-question-mark.svg"))
+                    .tooltip("Pack was installed manually - cannot automatically update")
+            ),
+            ContentUpdateStatus::ErrorNotFound => Some(
+                Button::new(("update", ix.row)).danger().icon(Icon::default().path("icons/triangle-alert.svg"))
+                    .tooltip("Error while checking updates - 404 not found")
+            ),
+            ContentUpdateStatus::ErrorInvalidHash => Some(
+                Button::new(("update", ix.row)).danger().icon(Icon::default().path("icons/triangle-alert.svg"))
+                    .tooltip("Error while checking updates - returned invalid hash")
+            ),
+            ContentUpdateStatus::AlreadyUpToDate => Some(
+                Button::new(("update", ix.row)).icon(Icon::default().path("icons/check.svg"))
+                    .tooltip("Pack is up-to-date as of last check")
+            ),
+            status @ (ContentUpdateStatus::Modrinth | ContentUpdateStatus::CurseForge) => {
+                let loading = self.updating.contains(&path);
+                let tooltip = if status == ContentUpdateStatus::CurseForge {
+                    "Download update from CurseForge"
+                } else {
+                    "Download update from Modrinth"
+                };
+                Some(
+                    Button::new(("update", ix.row)).success().loading(loading).icon(Icon::default().path("icons/download.svg"))
+                        .tooltip(tooltip).on_click({
+                            let backend_handle = self.backend_handle.clone();
+                            let folder = folder.clone();
+                            let path = path.clone();
+                            cx.listener(move |this, _, window, cx| {
+                                cx.stop_propagation();
+                                this.delegate_mut().updating.insert(path.clone());
+                                crate::root::update_single_pack(id, folder.clone(), path.clone(), &backend_handle, window, cx);
+                            })
+                        })
+                )
+            },
+        };
+
+        let toggle_control = Switch::new(("toggle", ix.row))
+            .checked(summary.enabled)
+            .disabled(summary.is_folder)
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let folder = folder.clone();
+                let path = path.clone();
+                move |checked, _, _| {
+                    backend_handle.send(MessageToBackend::SetPackEnabled {
+                        id,
+                        folder: folder.clone(),
+                        paths: vec![path.clone()],
+                        enabled: *checked,
+                    });
+                }
+            })
+            .px_2();
+
+        let buttons = h_flex()
+            .gap_2()
+            .when_some(update_button, |this, update_button| this.child(update_button))
+            .child(delete_button);
+
+        let item_content = h_flex()
+            .gap_1()
+            .child(toggle_control)
+            .child(icon.size_16().min_w_16().min_h_16().grayscale(!summary.enabled))
+            .when(!summary.enabled, |this| this.line_through())
+            .child(description)
+            .child(buttons);
+
+        Some(ListItem::new(ix).p_1().child(item_content))
+    }
+
+    fn set_selected_index(&mut self, _ix: Option<IndexPath>, _window: &mut Window, _cx: &mut Context<ListState<Self>>) {
+    }
+
+    fn perform_search(&mut self, query: &str, _window: &mut Window, _cx: &mut Context<ListState<Self>>) -> Task<()> {
+        self.last_query = SharedString::new(query);
+        self.searched = self.filter(query);
+
+        Task::ready(())
+    }
+}
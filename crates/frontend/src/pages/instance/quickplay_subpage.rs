@@ -5,19 +5,20 @@ use std::{
 
 use bridge::{
     handle::BackendHandle,
-    instance::{InstanceID, InstanceServerSummary, InstanceWorldSummary},
+    instance::{InstanceID, InstanceServerSummary, InstanceWorldSummary, ServerListEdit, ServerStatus},
     message::{AtomicBridgeDataLoadState, MessageToBackend, QuickPlayLaunch}, serial::AtomicOptionSerial,
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    ActiveTheme as _, Icon, IndexPath,
+    ActiveTheme as _, Icon, IconName, IndexPath, Sizable,
     button::{Button, ButtonVariants},
     h_flex,
     list::{ListDelegate, ListItem, ListState},
     v_flex,
 };
+use rustc_hash::FxHashMap;
 
-use crate::{entity::instance::InstanceEntry, png_render_cache, root};
+use crate::{entity::instance::InstanceEntry, modals, png_render_cache, root};
 
 pub struct InstanceQuickplaySubpage {
     instance: InstanceID,
@@ -57,6 +58,8 @@ impl InstanceQuickplaySubpage {
             backend_handle: backend_handle.clone(),
             servers: instance.servers.read(cx).to_vec(),
             searched: instance.servers.read(cx).to_vec(),
+            statuses: FxHashMap::default(),
+            confirming_remove: None,
         };
 
         let worlds = instance.worlds.clone();
@@ -81,6 +84,7 @@ impl InstanceQuickplaySubpage {
                 let delegate = list.delegate_mut();
                 delegate.servers = servers.clone();
                 delegate.searched = servers;
+                delegate.confirming_remove = None;
                 cx.notify();
             }).detach();
 
@@ -102,7 +106,6 @@ impl InstanceQuickplaySubpage {
 
 impl Render for InstanceQuickplaySubpage {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
-        let theme = cx.theme();
 
         let state = self.worlds_state.load(Ordering::SeqCst);
         if state.should_send_load_request() {
@@ -115,7 +118,26 @@ impl Render for InstanceQuickplaySubpage {
         }
 
         let worlds_header = div().mb_1().ml_1().text_lg().child("Worlds");
-        let servers_header = div().mb_1().ml_1().text_lg().child("Servers");
+        let servers_header = h_flex()
+            .mb_1()
+            .ml_1()
+            .gap_2()
+            .child(div().flex_1().text_lg().child("Servers"))
+            .child(Button::new("refresh-servers").small().label("Refresh").on_click(cx.listener(|this, _, _, cx| {
+                this.server_list.update(cx, |list, cx| {
+                    list.delegate_mut().statuses.clear();
+                    cx.notify();
+                });
+            })))
+            .child(Button::new("add-server").small().success().icon(IconName::Plus).label("Add Server").on_click({
+                let instance = self.instance;
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    modals::edit_server::open(instance, None, backend_handle.clone(), window, cx);
+                }
+            }));
+
+        let theme = cx.theme();
 
         v_flex().p_4().gap_4().size_full().child(
             h_flex()
@@ -188,6 +210,14 @@ impl ListDelegate for WorldsListDelegate {
         let name = self.name.clone();
         let backend_handle = self.backend_handle.clone();
         let target = summary.level_path.file_name().unwrap().to_owned();
+        let details_button = Button::new(("details", ix.row)).small().icon(Icon::default().path("icons/info.svg")).tooltip("World details").on_click({
+            let level_path = summary.level_path.clone();
+            let backend_handle = self.backend_handle.clone();
+            move |_, window, cx| {
+                cx.stop_propagation();
+                modals::world_details::open(id, level_path.clone(), backend_handle.clone(), window, cx);
+            }
+        });
         let item = ListItem::new(ix).p_1().child(
             h_flex()
                 .gap_1()
@@ -206,7 +236,8 @@ impl ListDelegate for WorldsListDelegate {
                         .px_2(),
                 )
                 .child(icon.size_16().min_w_16().min_h_16())
-                .child(description),
+                .child(description.flex_1())
+                .child(details_button),
         );
 
         Some(item)
@@ -228,6 +259,42 @@ pub struct ServersListDelegate {
     backend_handle: BackendHandle,
     servers: Vec<InstanceServerSummary>,
     searched: Vec<InstanceServerSummary>,
+    statuses: FxHashMap<Arc<str>, Option<Result<ServerStatus, Arc<str>>>>,
+    confirming_remove: Option<usize>,
+}
+
+impl ServersListDelegate {
+    fn ping(&mut self, address: Arc<str>, cx: &mut Context<ListState<Self>>) {
+        self.statuses.insert(address.clone(), None);
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::PingServer { address: address.clone(), channel: send });
+
+        cx.spawn(async move |list, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("Ping was cancelled".into()));
+            _ = list.update(cx, |list, cx| {
+                list.delegate_mut().statuses.insert(address, Some(result));
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn swap_with_neighbour(&self, index: usize, up: bool) {
+        let Some(position) = self.servers.iter().position(|server| server.index == index) else {
+            return;
+        };
+        let neighbour = if up {
+            position.checked_sub(1).and_then(|position| self.servers.get(position))
+        } else {
+            self.servers.get(position + 1)
+        };
+        if let Some(neighbour) = neighbour {
+            self.backend_handle.send(MessageToBackend::EditServerList {
+                id: self.id,
+                edit: ServerListEdit::Swap { first: index, second: neighbour.index },
+            });
+        }
+    }
 }
 
 impl ListDelegate for ServersListDelegate {
@@ -238,17 +305,50 @@ impl ListDelegate for ServersListDelegate {
     }
 
     fn render_item(&mut self, ix: IndexPath, _window: &mut Window, cx: &mut Context<ListState<Self>>) -> Option<Self::Item> {
-        let summary = self.searched.get(ix.row)?;
+        let summary = self.searched.get(ix.row)?.clone();
+
+        let status = match self.statuses.get(&summary.ip) {
+            Some(status) => status.clone(),
+            None => {
+                self.ping(summary.ip.clone(), cx);
+                None
+            },
+        };
 
-        let icon = if let Some(png_icon) = summary.png_icon.as_ref() {
-            png_render_cache::render(Arc::clone(png_icon), cx)
+        let png_icon = summary.png_icon.clone().or_else(|| status.as_ref().and_then(|status| status.as_ref().ok()?.png_icon.clone()));
+        let icon = if let Some(png_icon) = png_icon {
+            png_render_cache::render(png_icon, cx)
         } else {
             gpui::img(ImageSource::Resource(Resource::Embedded("images/default_world.png".into())))
         };
 
+        const GRAY: Hsla = Hsla { h: 0.0, s: 0.0, l: 0.5, a: 1.0};
+
+        let status_element = match &status {
+            None => div().text_color(GRAY).child("Pinging...").into_any_element(),
+            Some(Err(error)) => div().text_color(cx.theme().danger).child(SharedString::from(format!("Can't connect to server: {error}"))).into_any_element(),
+            Some(Ok(status)) => {
+                let mut details = format!("{}/{} players", status.players_online, status.players_max);
+                if !status.version.is_empty() {
+                    details.push_str(" · ");
+                    details.push_str(&status.version);
+                }
+                if let Some(latency) = status.latency {
+                    details.push_str(&format!(" · {} ms", latency.as_millis()));
+                }
+                v_flex()
+                    .child(div().line_clamp(2).child(SharedString::from(status.motd.clone())))
+                    .child(div().text_color(GRAY).child(SharedString::from(details)))
+                    .into_any_element()
+            },
+        };
+
         let description = v_flex()
+            .flex_1()
+            .overflow_hidden()
             .child(SharedString::from(summary.name.clone()))
-            .child(div().text_color(Hsla { h: 0.0, s: 0.0, l: 0.5, a: 1.0}).child(SharedString::from(summary.ip.clone())));
+            .child(div().text_color(GRAY).child(SharedString::from(summary.ip.clone())))
+            .child(status_element);
 
         let play_icon = Icon::empty().path("icons/play.svg");
 
@@ -256,6 +356,45 @@ impl ListDelegate for ServersListDelegate {
         let name = self.name.clone();
         let backend_handle = self.backend_handle.clone();
         let target = OsString::from(summary.ip.to_string());
+        let index = summary.index;
+
+        let remove_button = if self.confirming_remove == Some(index) {
+            Button::new(("remove", ix.row)).danger().icon(IconName::Check).tooltip("Confirm removal").on_click({
+                let backend_handle = self.backend_handle.clone();
+                cx.listener(move |this, _, _, cx| {
+                    cx.stop_propagation();
+                    this.delegate_mut().confirming_remove = None;
+                    backend_handle.send(MessageToBackend::EditServerList { id, edit: ServerListEdit::Remove { index } });
+                })
+            })
+        } else {
+            Button::new(("remove", ix.row)).danger().icon(Icon::default().path("icons/trash-2.svg")).tooltip("Remove server").on_click(cx.listener(move |this, _, _, cx| {
+                cx.stop_propagation();
+                this.delegate_mut().confirming_remove = Some(index);
+                cx.notify();
+            }))
+        };
+
+        let buttons = h_flex()
+            .gap_1()
+            .child(Button::new(("up", ix.row)).small().icon(Icon::default().path("icons/arrow-up.svg")).tooltip("Move up").on_click(cx.listener(move |this, _, _, cx| {
+                cx.stop_propagation();
+                this.delegate().swap_with_neighbour(index, true);
+            })))
+            .child(Button::new(("down", ix.row)).small().icon(Icon::default().path("icons/arrow-down.svg")).tooltip("Move down").on_click(cx.listener(move |this, _, _, cx| {
+                cx.stop_propagation();
+                this.delegate().swap_with_neighbour(index, false);
+            })))
+            .child(Button::new(("edit", ix.row)).small().icon(Icon::default().path("icons/settings-2.svg")).tooltip("Edit server").on_click({
+                let summary = summary.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    cx.stop_propagation();
+                    modals::edit_server::open(id, Some(summary.clone()), backend_handle.clone(), window, cx);
+                }
+            }))
+            .child(remove_button.small());
+
         let item = ListItem::new(ix).p_1().child(
             h_flex()
                 .gap_1()
@@ -274,7 +413,8 @@ impl ListDelegate for ServersListDelegate {
                         .px_2(),
                 )
                 .child(icon.size_16().min_w_16().min_h_16())
-                .child(description),
+                .child(description)
+                .child(buttons),
         );
 
         Some(item)
@@ -1,15 +1,15 @@
 use std::{borrow::Cow, path::Path, sync::Arc};
 
 use bridge::{
-    handle::BackendHandle, instance::InstanceID, message::MessageToBackend
+    handle::BackendHandle, instance::{InstanceID, VersionChangeTarget}, jvm::{check_jvm_flags, JvmRecommendation, JVM_FLAG_PRESETS}, message::MessageToBackend
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState, NumberInput, NumberInputEvent}, notification::{Notification, NotificationType}, v_flex, ActiveTheme as _, Disableable, Sizable, WindowExt
+    button::{Button, ButtonGroup, ButtonVariants}, h_flex, input::{Input, InputEvent, InputState, NumberInput, NumberInputEvent}, notification::{Notification, NotificationType}, v_flex, ActiveTheme as _, Disableable, Selectable, Sizable, WindowExt
 };
-use schema::instance::{InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceMemoryConfiguration};
+use schema::instance::{InstanceEnvironmentVariablesConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceMemoryConfiguration, InstanceResolutionConfiguration, InstanceSetting, InstanceSettingMode, InstanceSyncGroup};
 
-use crate::entity::instance::InstanceEntry;
+use crate::entity::{instance::InstanceEntry, metadata::FrontendMetadata};
 
 #[derive(PartialEq, Eq)]
 enum NewNameChangeState {
@@ -22,65 +22,135 @@ pub struct InstanceSettingsSubpage {
     instance: Entity<InstanceEntry>,
     instance_id: InstanceID,
     new_name_input_state: Entity<InputState>,
-    memory_override_enabled: bool,
+    memory_mode: InstanceSettingMode,
     memory_min_input_state: Entity<InputState>,
     memory_max_input_state: Entity<InputState>,
-    jvm_flags_enabled: bool,
+    jvm_flags_mode: InstanceSettingMode,
     jvm_flags_input_state: Entity<InputState>,
-    jvm_binary_enabled: bool,
+    jvm_binary_mode: InstanceSettingMode,
     jvm_binary_path: Option<Arc<Path>>,
+    resolution_mode: InstanceSettingMode,
+    resolution_width_input_state: Entity<InputState>,
+    resolution_height_input_state: Entity<InputState>,
+    environment_variables_mode: InstanceSettingMode,
+    environment_variables_input_state: Entity<InputState>,
+    sync_group: InstanceSyncGroup,
+    sync_groups: Vec<Arc<str>>,
+    jvm_recommendation: Option<JvmRecommendation>,
     new_name_change_state: NewNameChangeState,
     backend_handle: BackendHandle,
+    metadata: Entity<FrontendMetadata>,
     _select_file_task: Task<()>,
+    _get_sync_groups_task: Task<()>,
+    _get_jvm_recommendation_task: Task<()>,
 }
 
 impl InstanceSettingsSubpage {
     pub fn new(
         instance: &Entity<InstanceEntry>,
         backend_handle: BackendHandle,
+        metadata: Entity<FrontendMetadata>,
         window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> Self {
+        cx.observe(instance, |_, _, cx| cx.notify()).detach();
+
         let new_name_input_state = cx.new(|cx| InputState::new(window, cx));
         cx.subscribe(&new_name_input_state, Self::on_new_name_input).detach();
 
         let entry = instance.read(cx);
         let instance_id = entry.id;
 
-        let memory = entry.configuration.memory.unwrap_or_default();
+        let memory = entry.configuration.memory.clone().unwrap_or_default();
         let jvm_flags = entry.configuration.jvm_flags.clone().unwrap_or_default();
         let jvm_binary = entry.configuration.jvm_binary.clone().unwrap_or_default();
+        let resolution = entry.configuration.resolution.clone().unwrap_or_default();
+        let environment_variables = entry.configuration.environment_variables.clone().unwrap_or_default();
 
         let memory_min_input_state = cx.new(|cx| {
-            InputState::new(window, cx).default_value(memory.min.to_string())
+            InputState::new(window, cx).default_value(memory.value.min.to_string())
         });
         cx.subscribe_in(&memory_min_input_state, window, Self::on_memory_step).detach();
         cx.subscribe(&memory_min_input_state, Self::on_memory_changed).detach();
         let memory_max_input_state = cx.new(|cx| {
-            InputState::new(window, cx).default_value(memory.max.to_string())
+            InputState::new(window, cx).default_value(memory.value.max.to_string())
         });
         cx.subscribe_in(&memory_max_input_state, window, Self::on_memory_step).detach();
         cx.subscribe(&memory_max_input_state, Self::on_memory_changed).detach();
 
         let jvm_flags_input_state = cx.new(|cx| {
-            InputState::new(window, cx).auto_grow(1, 8).default_value(jvm_flags.flags)
+            InputState::new(window, cx).auto_grow(1, 8).default_value(jvm_flags.value.flags)
         });
         cx.subscribe(&jvm_flags_input_state, Self::on_jvm_flags_changed).detach();
 
+        let resolution_width_input_state = cx.new(|cx| {
+            InputState::new(window, cx).default_value(resolution.value.width.to_string())
+        });
+        cx.subscribe(&resolution_width_input_state, Self::on_resolution_changed).detach();
+        let resolution_height_input_state = cx.new(|cx| {
+            InputState::new(window, cx).default_value(resolution.value.height.to_string())
+        });
+        cx.subscribe(&resolution_height_input_state, Self::on_resolution_changed).detach();
+
+        let environment_variables_input_state = cx.new(|cx| {
+            InputState::new(window, cx).auto_grow(2, 8).placeholder("KEY=VALUE").default_value(environment_variables.value.variables.clone())
+        });
+        cx.subscribe(&environment_variables_input_state, Self::on_environment_variables_changed).detach();
+
+        let sync_group = entry.configuration.sync_group.clone();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_sync_groups_task = cx.spawn(async move |page, cx| {
+            let result: Vec<Arc<str>> = recv.await.unwrap_or_default();
+            let _ = page.update(cx, move |page, cx| {
+                page.sync_groups = result;
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetSyncGroups {
+            channel: send,
+        });
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let _get_jvm_recommendation_task = cx.spawn(async move |page, cx| {
+            let Ok(result) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                page.jvm_recommendation = Some(result);
+                cx.notify();
+            });
+        });
+        backend_handle.send(MessageToBackend::GetJvmRecommendation {
+            id: instance_id,
+            channel: send,
+        });
+
         Self {
             instance: instance.clone(),
             instance_id,
             new_name_input_state,
-            memory_override_enabled: memory.enabled,
+            memory_mode: memory.mode,
             memory_min_input_state,
             memory_max_input_state,
-            jvm_flags_enabled: jvm_flags.enabled,
+            jvm_flags_mode: jvm_flags.mode,
             jvm_flags_input_state,
-            jvm_binary_enabled: jvm_binary.enabled,
-            jvm_binary_path: jvm_binary.path.clone(),
+            jvm_binary_mode: jvm_binary.mode,
+            jvm_binary_path: jvm_binary.value.path.clone(),
+            resolution_mode: resolution.mode,
+            resolution_width_input_state,
+            resolution_height_input_state,
+            environment_variables_mode: environment_variables.mode,
+            environment_variables_input_state,
+            sync_group,
+            sync_groups: Vec::new(),
+            jvm_recommendation: None,
             new_name_change_state: NewNameChangeState::NoChange,
             backend_handle,
-            _select_file_task: Task::ready(())
+            metadata,
+            _select_file_task: Task::ready(()),
+            _get_sync_groups_task,
+            _get_jvm_recommendation_task,
         }
     }
 }
@@ -157,14 +227,16 @@ impl InstanceSettingsSubpage {
         }
     }
 
-    fn get_memory_configuration(&self, cx: &App) -> InstanceMemoryConfiguration {
+    fn get_memory_configuration(&self, cx: &App) -> InstanceSetting<InstanceMemoryConfiguration> {
         let min = self.memory_min_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
         let max = self.memory_max_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
 
-        InstanceMemoryConfiguration {
-            enabled: self.memory_override_enabled,
-            min,
-            max
+        InstanceSetting {
+            mode: self.memory_mode,
+            value: InstanceMemoryConfiguration {
+                min,
+                max
+            },
         }
     }
 
@@ -182,21 +254,183 @@ impl InstanceSettingsSubpage {
         }
     }
 
-    fn get_jvm_flags_configuration(&self, cx: &App) -> InstanceJvmFlagsConfiguration {
+    fn get_jvm_flags_configuration(&self, cx: &App) -> InstanceSetting<InstanceJvmFlagsConfiguration> {
         let flags = self.jvm_flags_input_state.read(cx).value();
 
-        InstanceJvmFlagsConfiguration {
-            enabled: self.jvm_flags_enabled,
-            flags: flags.into(),
+        InstanceSetting {
+            mode: self.jvm_flags_mode,
+            value: InstanceJvmFlagsConfiguration {
+                flags: flags.into(),
+            },
+        }
+    }
+
+    fn render_memory_recommendation(&self, cx: &Context<Self>) -> Option<impl IntoElement> {
+        let recommendation = self.jvm_recommendation.as_ref()?;
+        let theme = cx.theme();
+
+        let mut warnings = Vec::new();
+        if self.memory_mode.is_override() {
+            let max = self.get_memory_configuration(cx).value.max;
+            if let Some(total) = recommendation.total_system_memory && max > total {
+                warnings.push(div().text_color(theme.danger).child(SharedString::new(format!("Max memory is more than the {total} MiB of RAM in this system"))));
+            } else if let Some(available) = recommendation.available_system_memory && max > available {
+                warnings.push(div().text_color(theme.warning).child(SharedString::new(format!("Max memory is more than the {available} MiB of RAM currently available"))));
+            }
+            if max < recommendation.memory_max / 2 {
+                warnings.push(div().text_color(theme.warning).child(SharedString::new(format!("Max memory is much lower than the recommended {} MiB", recommendation.memory_max))));
+            }
+        }
+
+        let recommended = format!("Recommended: {} - {} MiB ({})", recommendation.memory_min, recommendation.memory_max, recommendation.reasons.join(", "));
+        let (min, max) = (recommendation.memory_min, recommendation.memory_max);
+
+        Some(v_flex()
+            .gap_1()
+            .text_sm()
+            .child(h_flex()
+                .gap_2()
+                .child(div().text_color(theme.muted_foreground).child(SharedString::new(recommended)))
+                .child(Button::new("use_recommended_memory").small().info().label("Use recommended").on_click(cx.listener(move |page, _, window, cx| {
+                    page.memory_mode = InstanceSettingMode::Override;
+                    page.memory_min_input_state.update(cx, |input, cx| input.set_value(min.to_string(), window, cx));
+                    page.memory_max_input_state.update(cx, |input, cx| input.set_value(max.to_string(), window, cx));
+                    page.backend_handle.send(MessageToBackend::SetInstanceMemory {
+                        id: page.instance_id,
+                        memory: page.get_memory_configuration(cx)
+                    });
+                    cx.notify();
+                }))))
+            .children(warnings))
+    }
+
+    fn render_jvm_flag_presets(&self, cx: &Context<Self>) -> impl IntoElement {
+        let java_major_version = self.jvm_recommendation.as_ref().and_then(|recommendation| recommendation.java_major_version);
+        let theme = cx.theme();
+
+        let warnings = if self.jvm_flags_mode.is_override() {
+            check_jvm_flags(&self.jvm_flags_input_state.read(cx).value(), java_major_version)
+        } else {
+            Vec::new()
+        };
+
+        let presets = JVM_FLAG_PRESETS.iter().enumerate().map(|(index, preset)| {
+            let supported = preset.supports(java_major_version);
+            let tooltip = if supported {
+                SharedString::new_static(preset.description)
+            } else {
+                SharedString::new(format!("Requires Java {} or newer", preset.min_java))
+            };
+            Button::new(("jvm_preset", index)).small().label(preset.name).tooltip(tooltip).disabled(!supported).on_click(cx.listener(move |page, _, window, cx| {
+                let flags = preset.flags_for(java_major_version);
+                page.jvm_flags_mode = InstanceSettingMode::Override;
+                page.jvm_flags_input_state.update(cx, |input, cx| input.set_value(flags, window, cx));
+                page.backend_handle.send(MessageToBackend::SetInstanceJvmFlags {
+                    id: page.instance_id,
+                    jvm_flags: page.get_jvm_flags_configuration(cx)
+                });
+                cx.notify();
+            }))
+        }).collect::<Vec<_>>();
+
+        let java_label = match java_major_version {
+            Some(java) => SharedString::new(format!("Presets for Java {java}:")),
+            None => SharedString::new_static("Presets:"),
+        };
+
+        v_flex()
+            .gap_1()
+            .text_sm()
+            .child(h_flex()
+                .gap_1()
+                .flex_wrap()
+                .child(div().text_color(theme.muted_foreground).child(java_label))
+                .children(presets))
+            .children(warnings.into_iter().map(|warning| div().text_color(theme.warning).child(SharedString::new(warning))))
+    }
+
+    fn get_jvm_binary_configuration(&self) -> InstanceSetting<InstanceJvmBinaryConfiguration> {
+        InstanceSetting {
+            mode: self.jvm_binary_mode,
+            value: InstanceJvmBinaryConfiguration {
+                path: self.jvm_binary_path.clone(),
+            },
+        }
+    }
+
+    pub fn on_resolution_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceResolution {
+                id: self.instance_id,
+                resolution: self.get_resolution_configuration(cx)
+            });
+        }
+    }
+
+    fn get_resolution_configuration(&self, cx: &App) -> InstanceSetting<InstanceResolutionConfiguration> {
+        let width = self.resolution_width_input_state.read(cx).value().parse::<u32>().unwrap_or(InstanceResolutionConfiguration::DEFAULT_WIDTH);
+        let height = self.resolution_height_input_state.read(cx).value().parse::<u32>().unwrap_or(InstanceResolutionConfiguration::DEFAULT_HEIGHT);
+
+        InstanceSetting {
+            mode: self.resolution_mode,
+            value: InstanceResolutionConfiguration {
+                width,
+                height,
+            },
+        }
+    }
+
+    pub fn on_environment_variables_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceEnvironmentVariables {
+                id: self.instance_id,
+                environment_variables: self.get_environment_variables_configuration(cx)
+            });
         }
     }
 
-    fn get_jvm_binary_configuration(&self) -> InstanceJvmBinaryConfiguration {
-        InstanceJvmBinaryConfiguration {
-            enabled: self.jvm_binary_enabled,
-            path: self.jvm_binary_path.clone(),
+    fn get_environment_variables_configuration(&self, cx: &App) -> InstanceSetting<InstanceEnvironmentVariablesConfiguration> {
+        let variables = self.environment_variables_input_state.read(cx).value();
+
+        InstanceSetting {
+            mode: self.environment_variables_mode,
+            value: InstanceEnvironmentVariablesConfiguration {
+                variables: variables.into(),
+            },
         }
     }
+
+    fn render_setting_mode(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        mode: InstanceSettingMode,
+        set_mode: fn(&mut Self, InstanceSettingMode, &mut Context<Self>),
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let modes = InstanceSettingMode::ALL.iter().enumerate()
+            .fold(ButtonGroup::new(id).outline(), |group, (index, option)| {
+                group.child(Button::new((id, index)).small().label(option.name()).selected(mode == *option))
+            })
+            .on_click(cx.listener(move |page, selected: &Vec<usize>, _, cx| {
+                if let Some(new_mode) = selected.first().and_then(|index| InstanceSettingMode::ALL.get(*index)) && *new_mode != mode {
+                    set_mode(page, *new_mode, cx);
+                    cx.notify();
+                }
+            }));
+
+        h_flex().gap_2().child(label).child(modes)
+    }
 }
 
 impl Render for InstanceSettingsSubpage {
@@ -209,9 +443,37 @@ impl Render for InstanceSettingsSubpage {
             .ml_1()
             .child(div().text_lg().child("Settings"));
 
-        let memory_override_enabled = self.memory_override_enabled;
-        let jvm_flags_enabled = self.jvm_flags_enabled;
-        let jvm_binary_enabled = self.jvm_binary_enabled;
+        let memory_override = self.memory_mode.is_override();
+        let jvm_flags_override = self.jvm_flags_mode.is_override();
+        let jvm_binary_override = self.jvm_binary_mode.is_override();
+        let resolution_override = self.resolution_mode.is_override();
+        let environment_variables_override = self.environment_variables_mode.is_override();
+
+        let mut sync_group_options = vec![
+            (SharedString::new_static("Default"), InstanceSyncGroup::Default),
+            (SharedString::new_static("None"), InstanceSyncGroup::Disabled),
+        ];
+        for group in &self.sync_groups {
+            sync_group_options.push((SharedString::new(group.clone()), InstanceSyncGroup::Named(group.clone())));
+        }
+        let sync_group_buttons = sync_group_options.into_iter().enumerate().map(|(index, (label, sync_group))| {
+            Button::new(("sync_group", index)).small().label(label).selected(self.sync_group == sync_group).on_click(cx.listener(move |page, _, _, cx| {
+                if page.sync_group != sync_group {
+                    page.sync_group = sync_group.clone();
+                    page.backend_handle.send(MessageToBackend::SetInstanceSyncGroup {
+                        id: page.instance_id,
+                        sync_group: sync_group.clone(),
+                    });
+                    cx.notify();
+                }
+            }))
+        }).collect::<Vec<_>>();
+
+        let configuration = &self.instance.read(cx).configuration;
+        let current_version = VersionChangeTarget {
+            minecraft_version: configuration.minecraft_version,
+            loader: configuration.loader,
+        };
 
         let jvm_binary_label = if let Some(path) = &self.jvm_binary_path {
             SharedString::new(path.to_string_lossy())
@@ -250,52 +512,56 @@ impl Render for InstanceSettingsSubpage {
             )
             .child(v_flex()
                 .gap_1()
-                .child(Checkbox::new("memory").label("Set Memory").checked(memory_override_enabled).on_click(cx.listener(|page, value, _, cx| {
-                    if page.memory_override_enabled != *value {
-                        page.memory_override_enabled = *value;
-                        page.backend_handle.send(MessageToBackend::SetInstanceMemory {
-                            id: page.instance_id,
-                            memory: page.get_memory_configuration(cx)
-                        });
-                        cx.notify();
-                    }
-                })))
+                .child("Version")
+                .child(h_flex()
+                    .gap_2()
+                    .child(SharedString::new(format!("{} {}", current_version.minecraft_version, current_version.loader.name())))
+                    .child(Button::new("change_version").small().label("Change version").on_click(cx.listener(move |page, _, window, cx| {
+                        crate::modals::change_version::open(page.instance_id, current_version, &page.metadata, page.backend_handle.clone(), window, cx);
+                    }))))
+            )
+            .child(div().text_sm().text_color(theme.muted_foreground).child("Default uses the launcher defaults from the Game settings, Off doesn't apply the setting even when the launcher default does"))
+            .child(v_flex()
+                .gap_1()
+                .child(self.render_setting_mode("memory", "Memory", self.memory_mode, |page, mode, cx| {
+                    page.memory_mode = mode;
+                    page.backend_handle.send(MessageToBackend::SetInstanceMemory {
+                        id: page.instance_id,
+                        memory: page.get_memory_configuration(cx)
+                    });
+                }, cx))
                 .child(h_flex()
                     .gap_1()
-                    .child(NumberInput::new(&self.memory_min_input_state).max_w_64().small().suffix("MiB").disabled(!memory_override_enabled))
+                    .child(NumberInput::new(&self.memory_min_input_state).max_w_64().small().suffix("MiB").disabled(!memory_override))
                     .child("Min"))
                 .child(h_flex()
                     .gap_1()
-                    .child(NumberInput::new(&self.memory_max_input_state).max_w_64().small().suffix("MiB").disabled(!memory_override_enabled))
+                    .child(NumberInput::new(&self.memory_max_input_state).max_w_64().small().suffix("MiB").disabled(!memory_override))
                     .child("Max"))
+                .children(self.render_memory_recommendation(cx))
                 )
             .child(v_flex()
                 .gap_1()
-                .child(Checkbox::new("jvm_flags").label("Add JVM Flags").checked(jvm_flags_enabled).on_click(cx.listener(|page, value, _, cx| {
-                    if page.jvm_flags_enabled != *value {
-                        page.jvm_flags_enabled = *value;
-                        page.backend_handle.send(MessageToBackend::SetInstanceJvmFlags {
-                            id: page.instance_id,
-                            jvm_flags: page.get_jvm_flags_configuration(cx)
-                        });
-                        cx.notify();
-                    }
-                })))
-                .child(div().max_w_64().child(Input::new(&self.jvm_flags_input_state).disabled(!jvm_flags_enabled)))
+                .child(self.render_setting_mode("jvm_flags", "JVM Flags", self.jvm_flags_mode, |page, mode, cx| {
+                    page.jvm_flags_mode = mode;
+                    page.backend_handle.send(MessageToBackend::SetInstanceJvmFlags {
+                        id: page.instance_id,
+                        jvm_flags: page.get_jvm_flags_configuration(cx)
+                    });
+                }, cx))
+                .child(div().max_w_64().child(Input::new(&self.jvm_flags_input_state).disabled(!jvm_flags_override)))
+                .child(self.render_jvm_flag_presets(cx))
             )
             .child(v_flex()
                 .gap_1()
-                .child(Checkbox::new("jvm_binary").label("Override JVM Binary").checked(jvm_binary_enabled).on_click(cx.listener(|page, value, _, cx| {
-                    if page.jvm_binary_enabled != *value {
-                        page.jvm_binary_enabled = *value;
-                        page.backend_handle.send(MessageToBackend::SetInstanceJvmBinary {
-                            id: page.instance_id,
-                            jvm_binary: page.get_jvm_binary_configuration()
-                        });
-                        cx.notify();
-                    }
-                })))
-                .child(div().max_w_64().child(Button::new("select_jvm_binary").success().label(jvm_binary_label).disabled(!jvm_binary_enabled).on_click(cx.listener(|this, _, window, cx| {
+                .child(self.render_setting_mode("jvm_binary", "JVM Binary", self.jvm_binary_mode, |page, mode, _| {
+                    page.jvm_binary_mode = mode;
+                    page.backend_handle.send(MessageToBackend::SetInstanceJvmBinary {
+                        id: page.instance_id,
+                        jvm_binary: page.get_jvm_binary_configuration()
+                    });
+                }, cx))
+                .child(div().max_w_64().child(Button::new("select_jvm_binary").success().label(jvm_binary_label).disabled(!jvm_binary_override).on_click(cx.listener(|this, _, window, cx| {
                     let receiver = cx.prompt_for_paths(PathPromptOptions {
                         files: true,
                         directories: false,
@@ -333,6 +599,37 @@ impl Render for InstanceSettingsSubpage {
                     this._select_file_task = add_from_file_task;
                 }))))
             )
+            .child(v_flex()
+                .gap_1()
+                .child(self.render_setting_mode("resolution", "Resolution", self.resolution_mode, |page, mode, cx| {
+                    page.resolution_mode = mode;
+                    page.backend_handle.send(MessageToBackend::SetInstanceResolution {
+                        id: page.instance_id,
+                        resolution: page.get_resolution_configuration(cx)
+                    });
+                }, cx))
+                .child(h_flex()
+                    .gap_1()
+                    .child(Input::new(&self.resolution_width_input_state).max_w_32().small().disabled(!resolution_override))
+                    .child("x")
+                    .child(Input::new(&self.resolution_height_input_state).max_w_32().small().disabled(!resolution_override)))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(self.render_setting_mode("environment_variables", "Environment Variables", self.environment_variables_mode, |page, mode, cx| {
+                    page.environment_variables_mode = mode;
+                    page.backend_handle.send(MessageToBackend::SetInstanceEnvironmentVariables {
+                        id: page.instance_id,
+                        environment_variables: page.get_environment_variables_configuration(cx)
+                    });
+                }, cx))
+                .child(div().max_w_64().child(Input::new(&self.environment_variables_input_state).disabled(!environment_variables_override)))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child("Sync group")
+                .child(h_flex().gap_1().flex_wrap().children(sync_group_buttons))
+            )
             .child(Button::new("delete").max_w_64().label("Delete this instance").danger().on_click({
                 let instance = self.instance.clone();
                 let backend_handle = self.backend_handle.clone();
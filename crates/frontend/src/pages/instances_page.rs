@@ -3,7 +3,7 @@ use std::sync::{
     atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
-use bridge::{handle::BackendHandle, message::MessageToBackend};
+use bridge::{handle::BackendHandle, message::MessageToBackend, modal_action::ModalAction};
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme as _, IconName, IndexPath, Selectable, WindowExt,
@@ -27,6 +27,8 @@ use crate::{
 
 pub struct InstancesPage {
     instance_table: Entity<TableState<InstanceList>>,
+    search_state: Entity<InputState>,
+    _search_subscription: Subscription,
 
     metadata: Entity<FrontendMetadata>,
     instances: Entity<InstanceEntries>,
@@ -38,8 +40,21 @@ impl InstancesPage {
     pub fn new(data: &DataEntities, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let instance_table = InstanceList::create_table(data, window, cx);
 
+        let search_state = cx.new(|cx| InputState::new(window, cx).placeholder("Search instances, filter with tag:, group:, loader: or version:"));
+        let _search_subscription = cx.subscribe(&search_state, |this, state, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                let query = state.read(cx).value();
+                this.instance_table.update(cx, |table, cx| {
+                    table.delegate_mut().set_search_query(&query, cx);
+                    cx.notify();
+                });
+            }
+        });
+
         Self {
             instance_table,
+            search_state,
+            _search_subscription,
             metadata: data.metadata.clone(),
             instances: data.instances.clone(),
             backend_handle: data.backend_handle.clone(),
@@ -57,7 +72,16 @@ impl Render for InstancesPage {
                 this.show_create_instance_modal(window, cx);
             }));
 
-        ui::page(cx, h_flex().gap_8().child("Instances").child(create_instance))
+        let import_modpack = Button::new("import_curseforge_modpack")
+            .info()
+            .icon(IconName::FolderOpen)
+            .label("Import CurseForge Modpack")
+            .on_click(cx.listener(|this, _, window, cx| {
+                this.import_curseforge_modpack(window, cx);
+            }));
+
+        ui::page(cx, h_flex().gap_8().child("Instances").child(h_flex().gap_2().child(create_instance).child(import_modpack)))
+            .child(div().p_4().pb_2().child(Input::new(&self.search_state)))
             .child(Table::new(&self.instance_table).bordered(false))
     }
 }
@@ -108,6 +132,33 @@ impl SelectDelegate for VersionList {
 }
 
 impl InstancesPage {
+    fn import_curseforge_modpack(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Select CurseForge modpack zip".into())
+        });
+
+        let backend_handle = self.backend_handle.clone();
+        window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            _ = cx.update(move |window, cx| {
+                let modal_action = ModalAction::default();
+                backend_handle.send(MessageToBackend::ImportCurseForgeModpack {
+                    path: path.into(),
+                    modal_action: modal_action.clone(),
+                });
+                crate::modals::generic::show_modal(window, cx, "Importing modpack".into(), "Error importing modpack".into(), modal_action);
+            });
+        }).detach();
+    }
+
     pub fn show_create_instance_modal(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let selected_loader = Arc::new(AtomicUsize::new(0));
         let loaded_versions = Arc::new(AtomicBool::new(false));
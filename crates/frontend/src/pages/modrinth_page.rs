@@ -627,7 +627,7 @@ impl Render for ModrinthSearchPage {
     }
 }
 
-fn format_downloads(downloads: usize) -> String {
+pub(crate) fn format_downloads(downloads: usize) -> String {
     if downloads >= 1_000_000_000 {
         format!("{}B Downloads", (downloads / 10_000_000) as f64 / 100.0)
     } else if downloads >= 1_000_000 {
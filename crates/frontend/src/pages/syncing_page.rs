@@ -1,14 +1,18 @@
+use std::sync::Arc;
+
 use bridge::{handle::BackendHandle, message::{MessageToBackend, SyncState, SyncTarget}};
 use enumset::EnumSet;
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, scroll::ScrollableElement, spinner::Spinner, tooltip::Tooltip, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Sizable
+    button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputState}, scroll::ScrollableElement, spinner::Spinner, tooltip::Tooltip, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Selectable, Sizable
 };
 
 use crate::{entity::DataEntities, ui};
 
 pub struct SyncingPage {
     backend_handle: BackendHandle,
+    group: Option<Arc<str>>,
+    new_group_input: Entity<InputState>,
     sync_state: SyncState,
     pending: EnumSet<SyncTarget>,
     loading: EnumSet<SyncTarget>,
@@ -16,9 +20,13 @@ pub struct SyncingPage {
 }
 
 impl SyncingPage {
-    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub fn new(data: &DataEntities, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let new_group_input = cx.new(|cx| InputState::new(window, cx).placeholder("New group name"));
+
         let mut page = Self {
             backend_handle: data.backend_handle.clone(),
+            group: None,
+            new_group_input,
             sync_state: SyncState::default(),
             pending: EnumSet::all(),
             loading: EnumSet::all(),
@@ -37,6 +45,12 @@ impl SyncingPage {
         self._get_sync_state_task = cx.spawn(async move |page, cx| {
             let result: SyncState = recv.await.unwrap_or_default();
             let _ = page.update(cx, move |page, cx| {
+                // The group was deleted or couldn't be created
+                if let Some(group) = &page.group && !result.groups.contains(group) {
+                    page.select_group(None, cx);
+                    return;
+                }
+
                 page.loading.remove_all(page.pending);
                 page.pending = EnumSet::empty();
                 page.sync_state = result;
@@ -50,10 +64,62 @@ impl SyncingPage {
         });
 
         self.backend_handle.send(MessageToBackend::GetSyncState {
+            group: self.group.clone(),
             channel: send,
         });
     }
 
+    fn select_group(&mut self, group: Option<Arc<str>>, cx: &mut Context<Self>) {
+        self.group = group;
+        self.loading = EnumSet::all();
+        self.pending = EnumSet::all();
+        self.update_sync_state(cx);
+        cx.notify();
+    }
+
+    fn render_groups(&mut self, cx: &mut Context<Self>) -> Div {
+        let default_button = Button::new("group-default").label("Default").selected(self.group.is_none()).on_click(cx.listener(|page, _, _, cx| {
+            page.select_group(None, cx);
+        }));
+
+        let group_buttons = self.sync_state.groups.iter().enumerate().map(|(index, group)| {
+            let group = group.clone();
+            Button::new(("group", index))
+                .label(SharedString::new(group.clone()))
+                .selected(self.group.as_ref() == Some(&group))
+                .on_click(cx.listener(move |page, _, _, cx| {
+                    page.select_group(Some(group.clone()), cx);
+                }))
+        }).collect::<Vec<_>>();
+
+        h_flex()
+            .gap_2()
+            .flex_wrap()
+            .child(default_button)
+            .children(group_buttons)
+            .child(div().w_48().child(Input::new(&self.new_group_input)))
+            .child(Button::new("create-group").success().icon(IconName::Plus).label("Create group").on_click(cx.listener(|page, _, window, cx| {
+                let name = page.new_group_input.read(cx).value();
+                let name = name.trim_ascii();
+                if name.is_empty() {
+                    return;
+                }
+
+                let name: Arc<str> = name.into();
+                page.backend_handle.send(MessageToBackend::CreateSyncGroup { name: name.clone() });
+                page.new_group_input.update(cx, |input, cx| input.set_value("", window, cx));
+                page.select_group(Some(name), cx);
+            })))
+            .when_some(self.group.clone(), |this, group| {
+                this.child(Button::new("delete-group").danger().label("Delete group")
+                    .tooltip("Instances in this group stop syncing, the synced folder is kept")
+                    .on_click(cx.listener(move |page, _, _, cx| {
+                        page.backend_handle.send(MessageToBackend::DeleteSyncGroup { name: group.clone() });
+                        page.select_group(None, cx);
+                    })))
+            })
+    }
+
     pub fn create_entry(&mut self, id: &'static str, label: &'static str, target: SyncTarget, warning: Hsla, info: Hsla, cx: &mut Context<Self>) -> Div {
         let synced_count = self.sync_state.synced[target];
         let cannot_sync_count = self.sync_state.cannot_sync[target];
@@ -70,6 +136,7 @@ impl SyncingPage {
             }))
             .on_click(cx.listener(move |page, value, _, cx| {
             backend_handle.send(MessageToBackend::SetSyncing {
+                group: page.group.clone(),
                 target,
                 value: *value,
             });
@@ -119,6 +186,9 @@ impl Render for SyncingPage {
         let info = cx.theme().blue;
         let content = v_flex().size_full().p_3().gap_3()
             .child("These options allow for syncing various files/folders across instances")
+            .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                .child("Each group has its own synced folder. Instances are assigned to a group in their settings"))
+            .child(self.render_groups(cx))
             .when_some(sync_folder, |this, sync_folder| {
                 this.child(Button::new("open").info().icon(IconName::FolderOpen).label("Open synced folders directory").on_click(move |_, window, cx| {
                     crate::open_folder(&sync_folder, window, cx);
@@ -34,6 +34,8 @@ impl Processor {
                 name,
                 dot_minecraft_folder,
                 configuration,
+                organization,
+                icon,
                 worlds_state,
                 servers_state,
                 mods_state,
@@ -44,6 +46,8 @@ impl Processor {
                     name.as_str().into(),
                     dot_minecraft_folder,
                     configuration,
+                    organization,
+                    icon,
                     worlds_state,
                     servers_state,
                     mods_state,
@@ -58,6 +62,8 @@ impl Processor {
                 name,
                 dot_minecraft_folder,
                 configuration,
+                organization,
+                icon,
                 status,
             } => {
                 InstanceEntries::modify(
@@ -66,6 +72,8 @@ impl Processor {
                     name.as_str().into(),
                     dot_minecraft_folder,
                     configuration,
+                    organization,
+                    icon,
                     status,
                     cx,
                 );
@@ -79,6 +87,9 @@ impl Processor {
             MessageToFrontend::InstanceModsUpdated { id, mods } => {
                 InstanceEntries::set_mods(&self.data.instances, id, mods, cx);
             },
+            MessageToFrontend::InstancePacksUpdated { id, folder, state, packs } => {
+                InstanceEntries::set_packs(&self.data.instances, id, folder, state, packs, cx);
+            },
             MessageToFrontend::AddNotification { notification_type, message } => {
                 self.main_window_handle.update(cx, |_, window, cx| {
                     let notification_type = match notification_type {
@@ -118,7 +129,7 @@ impl Processor {
                 _ = cx.open_window(options, |window, cx| {
                     let game_output = cx.new(|_| GameOutput::default());
                     let game_output_root = cx
-                        .new(|cx| GameOutputRoot::new(keep_alive, game_output.clone(), window, cx));
+                        .new(|cx| GameOutputRoot::new(keep_alive, game_output.clone(), self.data.backend_handle.clone(), window, cx));
                     window.activate_window();
                     let window_handle = window.window_handle().downcast::<Root>().unwrap();
                     self.game_output_windows.insert(id, (window_handle, game_output.clone()));
@@ -129,12 +140,14 @@ impl Processor {
                 id,
                 time,
                 level,
+                logger,
+                thread,
                 text,
             } => {
                 if let Some((window, game_output)) = self.game_output_windows.get(&id) {
                     _ = window.update(cx, |_, window, cx| {
                         game_output.update(cx, |game_output, _| {
-                            game_output.add(time, level, text);
+                            game_output.add(time, level, logger, thread, text);
                         });
                         window.refresh();
                     });
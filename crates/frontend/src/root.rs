@@ -3,7 +3,7 @@ use std::{path::Path, sync::{Arc, RwLock}};
 use bridge::{
     handle::BackendHandle,
     install::ContentInstall,
-    instance::{InstanceID, InstanceModID},
+    instance::{InstanceID, InstanceModID, PackFolder},
     message::{MessageToBackend, QuickPlayLaunch},
     modal_action::ModalAction,
 };
@@ -169,6 +169,22 @@ pub fn start_update_check(
     modals::generic::show_modal(window, cx, title, "Error checking for updates".into(), modal_action);
 }
 
+pub fn install_missing_dependencies(
+    instance: InstanceID,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let modal_action = ModalAction::default();
+
+    backend_handle.send(MessageToBackend::InstallMissingDependencies {
+        instance,
+        modal_action: modal_action.clone(),
+    });
+
+    modals::generic::show_notification(window, cx, "Error installing dependencies".into(), modal_action);
+}
+
 pub fn update_single_mod(
     instance: InstanceID,
     mod_id: InstanceModID,
@@ -187,6 +203,45 @@ pub fn update_single_mod(
     modals::generic::show_notification(window, cx, "Error downloading update".into(), modal_action);
 }
 
+pub fn start_pack_update_check(
+    instance: InstanceID,
+    folder: PackFolder,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let modal_action = ModalAction::default();
+
+    backend_handle.send(MessageToBackend::PackUpdateCheck {
+        instance,
+        folder,
+        modal_action: modal_action.clone(),
+    });
+
+    let title: SharedString = "Checking for updates".into();
+    modals::generic::show_modal(window, cx, title, "Error checking for updates".into(), modal_action);
+}
+
+pub fn update_single_pack(
+    instance: InstanceID,
+    folder: PackFolder,
+    path: Arc<Path>,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let modal_action = ModalAction::default();
+
+    backend_handle.send(MessageToBackend::UpdatePack {
+        instance,
+        folder,
+        path,
+        modal_action: modal_action.clone(),
+    });
+
+    modals::generic::show_notification(window, cx, "Error downloading update".into(), modal_action);
+}
+
 pub fn upload_log_file(
     path: Arc<Path>,
     backend_handle: &BackendHandle,
@@ -204,6 +259,23 @@ pub fn upload_log_file(
     modals::generic::show_modal(window, cx, title, "Error uploading log file".into(), modal_action);
 }
 
+pub fn upload_log_bundle(
+    instance: InstanceID,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let modal_action = ModalAction::default();
+
+    backend_handle.send(MessageToBackend::UploadLogBundle {
+        instance,
+        modal_action: modal_action.clone(),
+    });
+
+    let title: SharedString = "Uploading log bundle".into();
+    modals::generic::show_modal(window, cx, title, "Error uploading log bundle".into(), modal_action);
+}
+
 pub fn switch_page(
     page: PageType,
     breadcrumb: Option<Box<dyn Fn() -> Breadcrumb>>,
@@ -5,6 +5,7 @@ use std::{fmt::Debug, ptr::NonNull, result};
 pub mod decode;
 pub mod encode;
 mod pretty;
+pub mod region;
 pub mod stringified;
 
 mod reference;
@@ -12,6 +13,27 @@ mod reference;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TagType(pub(crate) u8);
 
+impl TagType {
+    pub fn name(self) -> &'static str {
+        match self.0 {
+            0 => "End",
+            1 => "Byte",
+            2 => "Short",
+            3 => "Int",
+            4 => "Long",
+            5 => "Float",
+            6 => "Double",
+            7 => "Byte Array",
+            8 => "String",
+            9 => "List",
+            10 => "Compound",
+            11 => "Int Array",
+            12 => "Long Array",
+            _ => "Unknown",
+        }
+    }
+}
+
 pub const TAG_END_ID: TagType = TagType(0);
 pub const TAG_BYTE_ID: TagType = TagType(1);
 pub const TAG_SHORT_ID: TagType = TagType(2);
@@ -193,7 +215,7 @@ impl NBT {
     }
 
     fn remove_node(&mut self, idx: usize) {
-        if idx == 0 {
+        if idx == self.root_index {
             panic!("Cannot remove root node");
         }
         match self.nodes.remove(idx) {
@@ -614,6 +614,10 @@ impl<'a> ListRef<'a> {
         }
     }
 
+    pub fn children_type(&self) -> TagType {
+        self.children_type
+    }
+
     pub fn len(&self) -> usize {
         self.get_self_node().1.len()
     }
@@ -766,6 +770,33 @@ impl<'a> ListRefMut<'a> {
             node_idx: idx,
         }
     }
+
+    pub fn get_compound_mut(&mut self, index: usize) -> Option<CompoundRefMut<'_>> {
+        let (_, children) = self.get_self_node();
+        let idx = *children.get(index)?;
+        match &self.nbt.nodes[idx] {
+            NBTNode::Compound(_) => Some(CompoundRefMut {
+                nbt: self.nbt,
+                node_idx: idx,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (_, children) = self.get_self_node_mut();
+        if index >= children.len() {
+            return false;
+        }
+        let idx = children.remove(index);
+        self.nbt.remove_node(idx);
+        true
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (_, children) = self.get_self_node_mut();
+        children.swap(a, b);
+    }
 }
 
 pub struct ListIterator<'a> {
@@ -0,0 +1,526 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use crate::NBT;
+
+pub const REGION_WIDTH: usize = 32;
+pub const REGION_CHUNKS: usize = REGION_WIDTH * REGION_WIDTH;
+pub const SECTOR_SIZE: usize = 4096;
+
+const HEADER_SIZE: usize = SECTOR_SIZE * 2;
+const MAX_INLINE_SECTORS: usize = 255;
+const EXTERNAL_FLAG: u8 = 0x80;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChunkCompression {
+    Gzip,
+    Zlib,
+    None,
+    Lz4,
+}
+
+impl ChunkCompression {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::Gzip),
+            2 => Some(Self::Zlib),
+            3 => Some(Self::None),
+            4 => Some(Self::Lz4),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Gzip => 1,
+            Self::Zlib => 2,
+            Self::None => 3,
+            Self::Lz4 => 4,
+        }
+    }
+}
+
+/// A single chunk inside a region file, kept in its compressed form so that
+/// chunks which aren't modified are written back byte-for-byte
+#[derive(Debug, Clone)]
+pub struct RegionChunk {
+    pub timestamp: u32,
+    compression: ChunkCompression,
+    payload: Vec<u8>,
+}
+
+impl RegionChunk {
+    /// LZ4 chunks are re-encoded using zlib, which every version that reads LZ4 also supports
+    pub fn from_nbt(nbt: &NBT, compression: ChunkCompression, timestamp: u32) -> anyhow::Result<Self> {
+        let encoded = crate::encode::write_named(nbt);
+        let (compression, payload) = match compression {
+            ChunkCompression::None => (ChunkCompression::None, encoded),
+            ChunkCompression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&encoded)?;
+                (ChunkCompression::Gzip, encoder.finish()?)
+            },
+            ChunkCompression::Zlib | ChunkCompression::Lz4 => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&encoded)?;
+                (ChunkCompression::Zlib, encoder.finish()?)
+            },
+        };
+        Ok(Self { timestamp, compression, payload })
+    }
+
+    pub fn compression(&self) -> ChunkCompression {
+        self.compression
+    }
+
+    /// Size of the compressed chunk data
+    pub fn compressed_len(&self) -> usize {
+        self.payload.len()
+    }
+
+    pub fn decompress(&self) -> anyhow::Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        match self.compression {
+            ChunkCompression::None => decompressed.extend_from_slice(&self.payload),
+            ChunkCompression::Gzip => {
+                flate2::bufread::GzDecoder::new(self.payload.as_slice()).read_to_end(&mut decompressed)?;
+            },
+            ChunkCompression::Zlib => {
+                flate2::bufread::ZlibDecoder::new(self.payload.as_slice()).read_to_end(&mut decompressed)?;
+            },
+            ChunkCompression::Lz4 => decompress_lz4_stream(&self.payload, &mut decompressed)?,
+        }
+        Ok(decompressed)
+    }
+
+    pub fn read_nbt(&self) -> anyhow::Result<NBT> {
+        let decompressed = self.decompress()?;
+        let mut data = decompressed.as_slice();
+        crate::decode::read_named(&mut data)
+    }
+
+    pub fn set_nbt(&mut self, nbt: &NBT) -> anyhow::Result<()> {
+        *self = Self::from_nbt(nbt, self.compression, self.timestamp)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub region_x: i32,
+    pub region_z: i32,
+    chunks: Vec<Option<RegionChunk>>,
+    /// Chunks that were stored in external `.mcc` files when the region was read
+    external: Vec<usize>,
+}
+
+impl Region {
+    pub fn new(region_x: i32, region_z: i32) -> Self {
+        Self {
+            region_x,
+            region_z,
+            chunks: vec![None; REGION_CHUNKS],
+            external: Vec::new(),
+        }
+    }
+
+    /// Parses the region coordinates from a file name like `r.-1.2.mca`
+    pub fn coordinates_from_path(path: &Path) -> Option<(i32, i32)> {
+        let name = path.file_name()?.to_str()?;
+        let name = name.strip_prefix("r.")?.strip_suffix(".mca")?;
+        let (x, z) = name.split_once('.')?;
+        Some((x.parse().ok()?, z.parse().ok()?))
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let (region_x, region_z) = Self::coordinates_from_path(path).context("Invalid region file name")?;
+        let bytes = std::fs::read(path)?;
+        let directory = path.parent().unwrap_or(Path::new("."));
+
+        let mut region = Self::new(region_x, region_z);
+
+        // Minecraft treats a truncated or empty region file as containing no chunks
+        if bytes.len() < HEADER_SIZE {
+            return Ok(region);
+        }
+
+        for index in 0..REGION_CHUNKS {
+            let location = BigEndian::read_u32(&bytes[index * 4..]);
+            if location == 0 {
+                continue;
+            }
+            let timestamp = BigEndian::read_u32(&bytes[SECTOR_SIZE + index * 4..]);
+
+            let offset = (location >> 8) as usize * SECTOR_SIZE;
+            let sectors = (location & 0xFF) as usize;
+            if offset < HEADER_SIZE || offset + 5 > bytes.len() {
+                bail!("region: chunk {index} has invalid offset {offset}");
+            }
+
+            let length = BigEndian::read_u32(&bytes[offset..]) as usize;
+            if length == 0 || length > sectors * SECTOR_SIZE || offset + 4 + length > bytes.len() {
+                bail!("region: chunk {index} has invalid length {length}");
+            }
+
+            let compression_id = bytes[offset + 4];
+            let compression = ChunkCompression::from_id(compression_id & !EXTERNAL_FLAG)
+                .with_context(|| format!("region: chunk {index} has unknown compression {compression_id}"))?;
+
+            let payload = if compression_id & EXTERNAL_FLAG != 0 {
+                region.external.push(index);
+                let external_path = directory.join(region.external_file_name(index));
+                std::fs::read(&external_path).with_context(|| format!("region: unable to read {}", external_path.display()))?
+            } else {
+                bytes[offset + 5..offset + 4 + length].to_vec()
+            };
+
+            region.chunks[index] = Some(RegionChunk { timestamp, compression, payload });
+        }
+
+        Ok(region)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let directory = path.parent().unwrap_or(Path::new("."));
+
+        let mut bytes = vec![0_u8; HEADER_SIZE];
+        let mut external = Vec::new();
+
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let Some(chunk) = chunk else {
+                continue;
+            };
+
+            let sector = bytes.len() / SECTOR_SIZE;
+            let mut sectors = (chunk.payload.len() + 5).div_ceil(SECTOR_SIZE);
+
+            if sectors > MAX_INLINE_SECTORS {
+                write_replace(&directory.join(self.external_file_name(index)), &chunk.payload)?;
+                external.push(index);
+
+                bytes.extend_from_slice(&1_u32.to_be_bytes());
+                bytes.push(chunk.compression.id() | EXTERNAL_FLAG);
+                sectors = 1;
+            } else {
+                bytes.extend_from_slice(&(chunk.payload.len() as u32 + 1).to_be_bytes());
+                bytes.push(chunk.compression.id());
+                bytes.extend_from_slice(&chunk.payload);
+            }
+            bytes.resize((sector + sectors) * SECTOR_SIZE, 0);
+
+            BigEndian::write_u32(&mut bytes[index * 4..], ((sector as u32) << 8) | sectors as u32);
+            BigEndian::write_u32(&mut bytes[SECTOR_SIZE + index * 4..], chunk.timestamp);
+        }
+
+        write_replace(path, &bytes)?;
+
+        for index in &self.external {
+            if !external.contains(index) {
+                _ = std::fs::remove_file(directory.join(self.external_file_name(*index)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn index(x: usize, z: usize) -> usize {
+        (x % REGION_WIDTH) + (z % REGION_WIDTH) * REGION_WIDTH
+    }
+
+    fn external_file_name(&self, index: usize) -> String {
+        let chunk_x = self.region_x * REGION_WIDTH as i32 + (index % REGION_WIDTH) as i32;
+        let chunk_z = self.region_z * REGION_WIDTH as i32 + (index / REGION_WIDTH) as i32;
+        format!("c.{chunk_x}.{chunk_z}.mcc")
+    }
+
+    /// `x` and `z` are local to the region, in the range 0..32
+    pub fn get(&self, x: usize, z: usize) -> Option<&RegionChunk> {
+        self.chunks[Self::index(x, z)].as_ref()
+    }
+
+    pub fn get_mut(&mut self, x: usize, z: usize) -> Option<&mut RegionChunk> {
+        self.chunks[Self::index(x, z)].as_mut()
+    }
+
+    pub fn insert(&mut self, x: usize, z: usize, chunk: RegionChunk) -> Option<RegionChunk> {
+        self.chunks[Self::index(x, z)].replace(chunk)
+    }
+
+    pub fn remove(&mut self, x: usize, z: usize) -> Option<RegionChunk> {
+        self.chunks[Self::index(x, z)].take()
+    }
+
+    /// Iterates over the chunks present in the region, yielding their absolute chunk coordinates
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, &RegionChunk)> {
+        self.chunks.iter().enumerate().filter_map(|(index, chunk)| {
+            let chunk = chunk.as_ref()?;
+            let chunk_x = self.region_x * REGION_WIDTH as i32 + (index % REGION_WIDTH) as i32;
+            let chunk_z = self.region_z * REGION_WIDTH as i32 + (index / REGION_WIDTH) as i32;
+            Some((chunk_x, chunk_z, chunk))
+        })
+    }
+
+    /// Keeps only the chunks for which `keep` returns true, given absolute chunk coordinates
+    pub fn retain(&mut self, mut keep: impl FnMut(i32, i32, &RegionChunk) -> bool) {
+        let region_x = self.region_x;
+        let region_z = self.region_z;
+        for (index, slot) in self.chunks.iter_mut().enumerate() {
+            if let Some(chunk) = slot {
+                let chunk_x = region_x * REGION_WIDTH as i32 + (index % REGION_WIDTH) as i32;
+                let chunk_z = region_z * REGION_WIDTH as i32 + (index / REGION_WIDTH) as i32;
+                if !keep(chunk_x, chunk_z, chunk) {
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    /// Size of the `.mca` file that `write` would produce, not counting external chunk files
+    pub fn encoded_size(&self) -> u64 {
+        let sectors: usize = self.chunks.iter().flatten().map(|chunk| {
+            let sectors = (chunk.payload.len() + 5).div_ceil(SECTOR_SIZE);
+            // External chunks only keep a single stub sector in the region file
+            if sectors > MAX_INLINE_SECTORS { 1 } else { sectors }
+        }).sum();
+        (HEADER_SIZE + sectors * SECTOR_SIZE) as u64
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.iter().filter(|chunk| chunk.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().all(Option::is_none)
+    }
+}
+
+fn write_replace(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut temp = PathBuf::from(path);
+    temp.add_extension("tmp");
+    std::fs::write(&temp, bytes)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+const LZ4_BLOCK_MAGIC: &[u8] = b"LZ4Block";
+const LZ4_BLOCK_HEADER_SIZE: usize = LZ4_BLOCK_MAGIC.len() + 13;
+const LZ4_METHOD_RAW: u8 = 0x10;
+const LZ4_METHOD_LZ4: u8 = 0x20;
+
+/// Reads the framing used by lz4-java's `LZ4BlockOutputStream`, which is what Minecraft writes
+fn decompress_lz4_stream(mut data: &[u8], out: &mut Vec<u8>) -> anyhow::Result<()> {
+    while !data.is_empty() {
+        if data.len() < LZ4_BLOCK_HEADER_SIZE || !data.starts_with(LZ4_BLOCK_MAGIC) {
+            bail!("lz4: invalid block header");
+        }
+        let token = data[LZ4_BLOCK_MAGIC.len()];
+        let compressed_len = LittleEndian::read_u32(&data[LZ4_BLOCK_MAGIC.len() + 1..]) as usize;
+        let original_len = LittleEndian::read_u32(&data[LZ4_BLOCK_MAGIC.len() + 5..]) as usize;
+        data = &data[LZ4_BLOCK_HEADER_SIZE..];
+
+        if original_len == 0 {
+            break;
+        }
+        if compressed_len > data.len() {
+            bail!("lz4: block is truncated");
+        }
+        let (block, rest) = data.split_at(compressed_len);
+        data = rest;
+
+        let start = out.len();
+        match token & 0xF0 {
+            LZ4_METHOD_RAW => out.extend_from_slice(block),
+            LZ4_METHOD_LZ4 => decompress_lz4_block(block, out)?,
+            method => bail!("lz4: unknown compression method {method}"),
+        }
+        if out.len() - start != original_len {
+            bail!("lz4: block decompressed to the wrong size");
+        }
+    }
+    Ok(())
+}
+
+fn decompress_lz4_block(block: &[u8], out: &mut Vec<u8>) -> anyhow::Result<()> {
+    let start = out.len();
+    let mut position = 0;
+
+    let read_length = |position: &mut usize, mut length: usize| -> anyhow::Result<usize> {
+        if length == 15 {
+            loop {
+                let byte = *block.get(*position).context("lz4: unexpected end of block")?;
+                *position += 1;
+                length += byte as usize;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Ok(length)
+    };
+
+    while position < block.len() {
+        let token = block[position];
+        position += 1;
+
+        let literals = read_length(&mut position, (token >> 4) as usize)?;
+        let literal_bytes = block.get(position..position + literals).context("lz4: unexpected end of block")?;
+        out.extend_from_slice(literal_bytes);
+        position += literals;
+
+        // The last sequence only contains literals
+        if position >= block.len() {
+            break;
+        }
+
+        let offset = block.get(position..position + 2).context("lz4: unexpected end of block")?;
+        let offset = LittleEndian::read_u16(offset) as usize;
+        position += 2;
+        if offset == 0 || offset > out.len() - start {
+            bail!("lz4: invalid match offset {offset}");
+        }
+
+        let match_length = read_length(&mut position, (token & 0x0F) as usize)? + 4;
+        let match_start = out.len() - offset;
+        // Matches may overlap the bytes being written, so copy one at a time
+        for index in 0..match_length {
+            out.push(out[match_start + index]);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{ChunkCompression, Region, RegionChunk, MAX_INLINE_SECTORS, SECTOR_SIZE};
+    use crate::NBT;
+
+    /// Output of lz4-java's `LZ4BlockOutputStream` with the default 64KiB block size, as written by Minecraft
+    const LZ4_JAVA_STREAM: &str = "4c5a34426c6f636b26370000002c010000c2c7dcc2ff016d696e6563726166743a73746f6e652010001747646972742f000b0f00b767726173735f626c6f636b25000f6400a6506c6f636b204c5a34426c6f636b16000000000000000000000000";
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nbt-region-test-{name}-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn chunk_nbt(x: i32, z: i32) -> NBT {
+        let mut nbt = NBT::new();
+        let mut compound = nbt.as_compound_mut().unwrap();
+        compound.insert_int("xPos", x);
+        compound.insert_int("zPos", z);
+        compound.insert_string("Status", "minecraft:full".into());
+        compound.insert_long_array("Heightmap", vec![i64::from(x) * 31 + i64::from(z); 37]);
+        nbt
+    }
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = test_dir("round-trip");
+        let path = dir.join("r.-1.2.mca");
+
+        let mut region = Region::new(-1, 2);
+        let compressions = [ChunkCompression::Zlib, ChunkCompression::Gzip, ChunkCompression::None];
+        for (index, compression) in compressions.into_iter().enumerate() {
+            let chunk = RegionChunk::from_nbt(&chunk_nbt(index as i32, 7), compression, 1000 + index as u32).unwrap();
+            region.insert(index, 7, chunk);
+        }
+        region.write(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), region.encoded_size());
+
+        let read = Region::read(&path).unwrap();
+        assert_eq!((read.region_x, read.region_z), (-1, 2));
+        assert_eq!(read.len(), 3);
+        for (index, compression) in compressions.into_iter().enumerate() {
+            let chunk = read.get(index, 7).unwrap();
+            assert_eq!(chunk.compression(), compression);
+            assert_eq!(chunk.timestamp, 1000 + index as u32);
+            assert_eq!(chunk.read_nbt().unwrap(), chunk_nbt(index as i32, 7));
+        }
+
+        let coordinates: Vec<_> = read.iter().map(|(x, z, _)| (x, z)).collect();
+        assert_eq!(coordinates, [(-32, 71), (-31, 71), (-30, 71)]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_external_chunk() {
+        let dir = test_dir("external");
+        let path = dir.join("r.0.0.mca");
+
+        let mut large = NBT::new();
+        large.as_compound_mut().unwrap().insert_byte_array("Data", vec![1; MAX_INLINE_SECTORS * SECTOR_SIZE]);
+
+        let mut region = Region::new(0, 0);
+        region.insert(3, 4, RegionChunk::from_nbt(&large, ChunkCompression::None, 5).unwrap());
+        region.insert(0, 0, RegionChunk::from_nbt(&chunk_nbt(0, 0), ChunkCompression::Zlib, 6).unwrap());
+        region.write(&path).unwrap();
+
+        let external_path = dir.join("c.3.4.mcc");
+        assert!(external_path.exists());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), region.encoded_size());
+
+        let mut read = Region::read(&path).unwrap();
+        assert_eq!(read.get(3, 4).unwrap().read_nbt().unwrap(), large);
+        assert_eq!(read.get(0, 0).unwrap().read_nbt().unwrap(), chunk_nbt(0, 0));
+
+        // Removing the chunk also removes its external file
+        read.remove(3, 4);
+        read.write(&path).unwrap();
+        assert!(!external_path.exists());
+        assert_eq!(Region::read(&path).unwrap().len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_empty_region() {
+        let dir = test_dir("empty");
+
+        let path = dir.join("r.5.-5.mca");
+        Region::new(5, -5).write(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), (SECTOR_SIZE * 2) as u64);
+        assert!(Region::read(&path).unwrap().is_empty());
+
+        // Minecraft leaves zero length region files behind when it crashes
+        let path = dir.join("r.6.-5.mca");
+        std::fs::write(&path, []).unwrap();
+        assert!(Region::read(&path).unwrap().is_empty());
+
+        assert!(Region::read(&dir.join("region.mca")).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_lz4() {
+        let chunk = RegionChunk {
+            timestamp: 0,
+            compression: ChunkCompression::Lz4,
+            payload: decode_hex(LZ4_JAVA_STREAM),
+        };
+        let expected = b"minecraft:stone minecraft:stone minecraft:stone minecraft:dirt minecraft:dirt minecraft:grass_block ".repeat(3);
+        assert_eq!(chunk.decompress().unwrap(), expected);
+
+        let truncated = RegionChunk { payload: chunk.payload[..40].to_vec(), ..chunk.clone() };
+        assert!(truncated.decompress().is_err());
+
+        // Re-encoding falls back to zlib
+        let mut chunk = chunk;
+        chunk.set_nbt(&chunk_nbt(1, 2)).unwrap();
+        assert_eq!(chunk.compression(), ChunkCompression::Zlib);
+        assert_eq!(chunk.read_nbt().unwrap(), chunk_nbt(1, 2));
+    }
+}
@@ -11,6 +11,29 @@ pub fn to_snbt<T: Write>(writer: &mut T, nbt: &NBT) -> std::fmt::Result {
     write_node(writer, &nbt.nodes, &nbt.nodes[nbt.root_index])
 }
 
+pub fn ref_to_snbt_string(value: NBTRef<'_>) -> String {
+    let mut snbt = String::new();
+    ref_to_snbt(&mut snbt, value).expect("string writing is infallible");
+    snbt
+}
+
+pub fn ref_to_snbt<T: Write>(writer: &mut T, value: NBTRef<'_>) -> std::fmt::Result {
+    match value {
+        NBTRef::Byte(value) => write_byte(writer, *value),
+        NBTRef::Short(value) => write_short(writer, *value),
+        NBTRef::Int(value) => write_int(writer, *value),
+        NBTRef::Long(value) => write_long(writer, *value),
+        NBTRef::Float(value) => write_float(writer, *value),
+        NBTRef::Double(value) => write_double(writer, *value),
+        NBTRef::ByteArray(values) => write_byte_array(writer, values),
+        NBTRef::String(value) => write_string(writer, value),
+        NBTRef::List(list) => write_node(writer, &list.nbt.nodes, &list.nbt.nodes[list.node_idx]),
+        NBTRef::Compound(compound) => write_node(writer, &compound.nbt.nodes, &compound.nbt.nodes[compound.node_idx]),
+        NBTRef::IntArray(values) => write_int_array(writer, values),
+        NBTRef::LongArray(values) => write_long_array(writer, values),
+    }
+}
+
 fn write_node<T: Write>(writer: &mut T, nodes: &Slab<NBTNode>, node: &NBTNode) -> std::fmt::Result {
     match node {
         NBTNode::Byte(value) => write_byte(writer, *value),
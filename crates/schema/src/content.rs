@@ -5,4 +5,5 @@ use serde::{Deserialize, Serialize};
 pub enum ContentSource {
     Manual,
     Modrinth,
+    CurseForge,
 }
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::loader::Loader;
+
+pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com";
+pub const CURSEFORGE_MINECRAFT_GAME_ID: u32 = 432;
+
+pub const CURSEFORGE_CLASS_MODS: u32 = 6;
+pub const CURSEFORGE_CLASS_RESOURCEPACKS: u32 = 12;
+pub const CURSEFORGE_CLASS_SHADERS: u32 = 6552;
+pub const CURSEFORGE_CLASS_MODPACKS: u32 = 4471;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CurseForgeConfiguration {
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<Arc<str>>,
+    /// Overrides the api base url, e.g. to point at a local mock server
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<Arc<str>>,
+}
+
+impl CurseForgeConfiguration {
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().map(|url| url.trim_end_matches('/')).unwrap_or(CURSEFORGE_API_URL)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurseForgeModLoaderType {
+    Any,
+    Forge,
+    Fabric,
+    Quilt,
+    NeoForge,
+}
+
+impl CurseForgeModLoaderType {
+    pub fn from_loader(loader: Loader) -> Self {
+        match loader {
+            Loader::Fabric => Self::Fabric,
+            Loader::Forge => Self::Forge,
+            Loader::NeoForge => Self::NeoForge,
+            Loader::Vanilla | Loader::Unknown => Self::Any,
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            Self::Any => 0,
+            Self::Forge => 1,
+            Self::Fabric => 4,
+            Self::Quilt => 5,
+            Self::NeoForge => 6,
+        }
+    }
+
+    /// The name CurseForge lists in a file's `gameVersions`
+    pub fn game_version_name(self) -> Option<&'static str> {
+        match self {
+            Self::Any => None,
+            Self::Forge => Some("Forge"),
+            Self::Fabric => Some("Fabric"),
+            Self::Quilt => Some("Quilt"),
+            Self::NeoForge => Some("NeoForge"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct CurseForgeSearchRequest {
+    pub query: Option<Arc<str>>,
+    pub class_id: u32,
+    pub game_version: Option<Arc<str>>,
+    pub loader: Loader,
+    pub index: usize,
+    pub page_size: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeResponse<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeSearchResult {
+    pub data: Arc<[CurseForgeMod]>,
+    pub pagination: CurseForgePagination,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgePagination {
+    pub index: usize,
+    pub page_size: usize,
+    pub result_count: usize,
+    pub total_count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    pub id: u32,
+    pub name: Arc<str>,
+    pub slug: Option<Arc<str>>,
+    pub summary: Option<Arc<str>>,
+    pub class_id: Option<u32>,
+    #[serde(default)]
+    pub download_count: f64,
+    pub logo: Option<CurseForgeLogo>,
+    #[serde(default)]
+    pub authors: Vec<CurseForgeAuthor>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeLogo {
+    pub thumbnail_url: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeAuthor {
+    pub name: Arc<str>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+    pub id: u32,
+    pub mod_id: u32,
+    pub display_name: Arc<str>,
+    pub file_name: Arc<str>,
+    pub release_type: u8,
+    #[serde(default)]
+    pub hashes: Vec<CurseForgeFileHash>,
+    pub file_length: usize,
+    pub download_url: Option<Arc<str>>,
+    #[serde(default)]
+    pub game_versions: Arc<[Arc<str>]>,
+    #[serde(default)]
+    pub dependencies: Vec<CurseForgeFileDependency>,
+    #[serde(default)]
+    pub file_fingerprint: u32,
+}
+
+impl CurseForgeFile {
+    pub const RELEASE: u8 = 1;
+    pub const BETA: u8 = 2;
+    pub const ALPHA: u8 = 3;
+
+    pub fn sha1(&self) -> Option<&Arc<str>> {
+        self.hashes.iter().find(|hash| hash.algo == CurseForgeFileHash::SHA1).map(|hash| &hash.value)
+    }
+
+    pub fn supports(&self, minecraft_version: &str, loader: CurseForgeModLoaderType) -> bool {
+        if !self.game_versions.iter().any(|version| &**version == minecraft_version) {
+            return false;
+        }
+        let Some(loader_name) = loader.game_version_name() else {
+            return true;
+        };
+
+        // Files that don't list any loader (e.g. resourcepacks) work everywhere
+        let lists_loader = self.game_versions.iter().any(|version| matches!(&**version, "Forge" | "Fabric" | "Quilt" | "NeoForge"));
+        !lists_loader || self.game_versions.iter().any(|version| version.eq_ignore_ascii_case(loader_name))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeFileHash {
+    pub value: Arc<str>,
+    pub algo: u8,
+}
+
+impl CurseForgeFileHash {
+    pub const SHA1: u8 = 1;
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFileDependency {
+    pub mod_id: u32,
+    pub relation_type: u8,
+}
+
+impl CurseForgeFileDependency {
+    pub const REQUIRED: u8 = 3;
+    pub const INCOMPATIBLE: u8 = 5;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFingerprintsResult {
+    #[serde(default)]
+    pub exact_matches: Vec<CurseForgeFingerprintMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeFingerprintMatch {
+    pub id: u32,
+    pub file: CurseForgeFile,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifest {
+    pub minecraft: CurseForgeManifestMinecraft,
+    pub name: Arc<str>,
+    pub version: Option<Arc<str>>,
+    pub author: Option<Arc<str>>,
+    #[serde(default)]
+    pub files: Vec<CurseForgeManifestFile>,
+    pub overrides: Option<Arc<str>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifestMinecraft {
+    pub version: Arc<str>,
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseForgeManifestModLoader>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CurseForgeManifestModLoader {
+    pub id: Arc<str>,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl CurseForgeManifest {
+    pub fn loader(&self) -> Loader {
+        let Some(mod_loader) = self.minecraft.mod_loaders.iter().find(|loader| loader.primary).or(self.minecraft.mod_loaders.first()) else {
+            return Loader::Vanilla;
+        };
+        // Ids look like "forge-47.2.0" or "fabric-0.15.11"
+        let name = mod_loader.id.split('-').next().unwrap_or_default();
+        Loader::from_name(name)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct CurseForgeManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u32,
+    #[serde(rename = "fileID")]
+    pub file_id: u32,
+    #[serde(default = "default_true")]
+    pub required: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
@@ -11,17 +11,38 @@ pub struct FabricModJson {
     // pub description: Option<Arc<str>>,
     pub authors: Option<Vec<Person>>,
     pub icon: Option<Icon>,
-    // #[serde(alias = "requires")]
-    // pub depends: Option<HashMap<Arc<str>, Dependency>>,
-    // pub breaks: Option<HashMap<Arc<str>, Dependency>>,
+    #[serde(default, alias = "requires", deserialize_with = "crate::try_deserialize")]
+    pub depends: HashMap<Arc<str>, Dependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub recommends: HashMap<Arc<str>, Dependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub breaks: HashMap<Arc<str>, Dependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub provides: Vec<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub jars: Vec<NestedJar>,
 }
 
-// #[derive(Deserialize, Debug)]
-// #[serde(untagged)]
-// enum Dependency {
-//     Single(Arc<str>),
-//     Multiple(Vec<Arc<str>>)
-// }
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Dependency {
+    Single(Arc<str>),
+    Multiple(Vec<Arc<str>>)
+}
+
+impl Dependency {
+    pub fn into_vec(self) -> Vec<Arc<str>> {
+        match self {
+            Dependency::Single(version) => vec![version],
+            Dependency::Multiple(versions) => versions,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NestedJar {
+    pub file: Arc<str>,
+}
 
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
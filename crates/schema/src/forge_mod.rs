@@ -1,10 +1,24 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub struct ModsToml {
-    pub mods: Vec<ModsTomlMod>
+    pub mods: Vec<ModsTomlMod>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub dependencies: HashMap<Arc<str>, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsTomlDependency {
+    pub mod_id: Arc<str>,
+    /// Used by Forge, NeoForge uses `type` instead
+    pub mandatory: Option<bool>,
+    #[serde(rename = "type")]
+    pub dependency_type: Option<Arc<str>>,
+    pub version_range: Option<Arc<str>>,
+    pub side: Option<Arc<str>>,
 }
 
 #[derive(Deserialize, Debug)]
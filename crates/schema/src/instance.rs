@@ -1,25 +1,194 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use ustr::Ustr;
 
-use crate::loader::Loader;
+use crate::{loader::Loader, modrinth::ModrinthVersionType};
 
+/// The memory, jvm and game settings either inherit the launcher-wide default from [`GameConfiguration`],
+/// override it or turn the setting off, see [`InstanceSettingMode`]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InstanceConfiguration {
     pub minecraft_version: Ustr,
     pub loader: Loader,
-    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_memory_configuration")]
-    pub memory: Option<InstanceMemoryConfiguration>,
-    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_jvm_flags_configuration")]
-    pub jvm_flags: Option<InstanceJvmFlagsConfiguration>,
-    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_jvm_binary_configuration")]
-    pub jvm_binary: Option<InstanceJvmBinaryConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_setting")]
+    pub memory: Option<InstanceSetting<InstanceMemoryConfiguration>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_setting")]
+    pub jvm_flags: Option<InstanceSetting<InstanceJvmFlagsConfiguration>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_setting")]
+    pub jvm_binary: Option<InstanceSetting<InstanceJvmBinaryConfiguration>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_setting")]
+    pub resolution: Option<InstanceSetting<InstanceResolutionConfiguration>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_setting")]
+    pub environment_variables: Option<InstanceSetting<InstanceEnvironmentVariablesConfiguration>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "InstanceSyncGroup::is_default")]
+    pub sync_group: InstanceSyncGroup,
+    /// Files from modpacks, relative to the .minecraft folder, that were modified by the user and are no longer overwritten
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "<[_]>::is_empty")]
+    pub preserved_modpack_files: Arc<[Arc<str>]>,
+    /// Keyed by the mod id, so preferences carry over when the mod is updated
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "BTreeMap::is_empty")]
+    pub mod_update_preferences: BTreeMap<Arc<str>, ModUpdatePreference>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
-pub struct InstanceMemoryConfiguration {
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModUpdatePreference {
+    /// Pinned mods are never updated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Sha1 of update files the user chose to skip
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub skipped_updates: Arc<[Arc<str>]>,
+    /// The least stable Modrinth version type that is offered as an update, any type if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<ModrinthVersionType>,
+}
+
+impl ModUpdatePreference {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn is_skipped(&self, sha1: &str) -> bool {
+        self.skipped_updates.iter().any(|skipped| skipped.eq_ignore_ascii_case(sha1))
+    }
+
+    pub fn accepts(&self, version_type: Option<ModrinthVersionType>) -> bool {
+        match (self.channel, version_type) {
+            (None, _) => true,
+            (Some(channel), Some(version_type)) => version_type.stability() <= channel.stability(),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Which sync group the instance shares files and folders with
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSyncGroup {
+    /// The launcher-wide group, stored directly in the synced folder
+    #[default]
+    Default,
+    Disabled,
+    Named(Arc<str>),
+}
+
+impl InstanceSyncGroup {
+    pub fn is_default(&self) -> bool {
+        *self == InstanceSyncGroup::Default
+    }
+
+    /// Checks membership of a group, `None` being the default group
+    pub fn is_member_of(&self, group: Option<&str>) -> bool {
+        match (self, group) {
+            (InstanceSyncGroup::Default, None) => true,
+            (InstanceSyncGroup::Named(name), Some(group)) => &**name == group,
+            _ => false,
+        }
+    }
+}
+
+impl InstanceConfiguration {
+    /// Combines the instance's overrides with the launcher-wide defaults
+    pub fn resolve_game_configuration(&self, defaults: &GameConfiguration) -> GameConfiguration {
+        fn resolve<T: Clone>(setting: &Option<InstanceSetting<T>>, default: &GameSetting<T>) -> GameSetting<T> {
+            match setting {
+                Some(InstanceSetting { mode: InstanceSettingMode::Override, value }) => GameSetting { enabled: true, value: value.clone() },
+                Some(InstanceSetting { mode: InstanceSettingMode::Off, .. }) => GameSetting { enabled: false, value: default.value.clone() },
+                _ => default.clone(),
+            }
+        }
+
+        GameConfiguration {
+            memory: resolve(&self.memory, &defaults.memory),
+            jvm_flags: resolve(&self.jvm_flags, &defaults.jvm_flags),
+            jvm_binary: resolve(&self.jvm_binary, &defaults.jvm_binary),
+            resolution: resolve(&self.resolution, &defaults.resolution),
+            environment_variables: resolve(&self.environment_variables, &defaults.environment_variables),
+        }
+    }
+}
+
+/// How an instance setting relates to the launcher-wide default
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSettingMode {
+    /// Use the launcher-wide default
+    #[default]
+    Inherit,
+    /// Use the instance's own value
+    Override,
+    /// Don't apply the setting, even if the launcher-wide default does
+    Off,
+}
+
+impl InstanceSettingMode {
+    pub const ALL: [Self; 3] = [Self::Inherit, Self::Override, Self::Off];
+
+    pub fn is_override(self) -> bool {
+        self == Self::Override
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Inherit => "Default",
+            Self::Override => "Override",
+            Self::Off => "Off",
+        }
+    }
+}
+
+/// Instances used to store an `enabled` flag instead of the mode, which meant the setting was overridden
+fn deserialize_setting_mode<'de, D>(deserializer: D) -> Result<InstanceSettingMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(true) => InstanceSettingMode::Override,
+        serde_json::Value::Bool(false) => InstanceSettingMode::Inherit,
+        value => InstanceSettingMode::deserialize(value).unwrap_or_default(),
+    })
+}
+
+/// A memory, jvm or game setting of an instance
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceSetting<T> {
+    #[serde(default, alias = "enabled", deserialize_with = "deserialize_setting_mode")]
+    pub mode: InstanceSettingMode,
+    #[serde(flatten)]
+    pub value: T,
+}
+
+fn is_default_setting<T: Default + PartialEq>(setting: &Option<InstanceSetting<T>>) -> bool {
+    setting.as_ref().is_none_or(|setting| *setting == InstanceSetting::default())
+}
+
+/// A launcher-wide default for instances that don't override it, only applied if `enabled`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameSetting<T> {
+    #[serde(default)]
     pub enabled: bool,
+    #[serde(flatten)]
+    pub value: T,
+}
+
+/// Launcher-wide defaults for instances that don't override them
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameConfiguration {
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub memory: GameSetting<InstanceMemoryConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub jvm_flags: GameSetting<InstanceJvmFlagsConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub jvm_binary: GameSetting<InstanceJvmBinaryConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub resolution: GameSetting<InstanceResolutionConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub environment_variables: GameSetting<InstanceEnvironmentVariablesConfiguration>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InstanceMemoryConfiguration {
     pub min: u32,
     pub max: u32,
 }
@@ -32,47 +201,234 @@ impl InstanceMemoryConfiguration {
 impl Default for InstanceMemoryConfiguration {
     fn default() -> Self {
         Self {
-            enabled: false,
             min: Self::DEFAULT_MIN,
             max: Self::DEFAULT_MAX
         }
     }
 }
 
-fn is_default_memory_configuration(config: &Option<InstanceMemoryConfiguration>) -> bool {
-    if let Some(config) = config {
-        !config.enabled &&
-            config.min == InstanceMemoryConfiguration::DEFAULT_MIN &&
-            config.max == InstanceMemoryConfiguration::DEFAULT_MAX
-    } else {
-        true
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceJvmFlagsConfiguration {
+    pub flags: Arc<str>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceJvmBinaryConfiguration {
+    pub path: Option<Arc<Path>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InstanceResolutionConfiguration {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl InstanceResolutionConfiguration {
+    pub const DEFAULT_WIDTH: u32 = 854;
+    pub const DEFAULT_HEIGHT: u32 = 480;
+}
+
+impl Default for InstanceResolutionConfiguration {
+    fn default() -> Self {
+        Self {
+            width: Self::DEFAULT_WIDTH,
+            height: Self::DEFAULT_HEIGHT,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub struct InstanceJvmFlagsConfiguration {
-    pub enabled: bool,
-    pub flags: Arc<str>,
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceEnvironmentVariablesConfiguration {
+    /// One `KEY=VALUE` pair per line
+    pub variables: Arc<str>,
 }
 
-fn is_default_jvm_flags_configuration(config: &Option<InstanceJvmFlagsConfiguration>) -> bool {
-    if let Some(config) = config {
-        !config.enabled && config.flags.trim_ascii().is_empty()
-    } else {
-        true
+impl InstanceEnvironmentVariablesConfiguration {
+    pub fn parse(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables.lines().filter_map(|line| {
+            let (key, value) = line.trim_ascii_start().split_once('=')?;
+            let key = key.trim_ascii();
+            if key.is_empty() || key.starts_with('#') {
+                None
+            } else {
+                Some((key, value.trim_ascii()))
+            }
+        })
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub struct InstanceJvmBinaryConfiguration {
-    pub enabled: bool,
-    pub path: Option<Arc<Path>>,
+/// How the instance is organized and presented on the instances page, stored separately from the
+/// configuration since it changes whenever the instance is launched
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceOrganization {
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "InstanceIcon::is_default")]
+    pub icon: InstanceIcon,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub group: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "<[_]>::is_empty")]
+    pub tags: Arc<[Arc<str>]>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "std::ops::Not::not")]
+    pub favourite: bool,
+    /// Unix timestamp in milliseconds
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<i64>,
+}
+
+impl InstanceOrganization {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceIcon {
+    #[default]
+    Default,
+    /// Name of an icon from the launcher's built-in set
+    Builtin(Arc<str>),
+    /// The `icon.png` stored in the instance folder
+    Custom,
+}
+
+impl InstanceIcon {
+    pub fn is_default(&self) -> bool {
+        *self == InstanceIcon::Default
+    }
 }
 
-fn is_default_jvm_binary_configuration(config: &Option<InstanceJvmBinaryConfiguration>) -> bool {
-    if let Some(config) = config {
-        !config.enabled && config.path.is_none()
-    } else {
-        true
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configuration(json: &str) -> InstanceConfiguration {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn defaults() -> GameConfiguration {
+        GameConfiguration {
+            memory: GameSetting {
+                enabled: true,
+                value: InstanceMemoryConfiguration { min: 1024, max: 2048 },
+            },
+            jvm_flags: GameSetting {
+                enabled: true,
+                value: InstanceJvmFlagsConfiguration { flags: "-XX:+UseG1GC".into() },
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_inherit() {
+        let instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric"}"#);
+        assert_eq!(instance.resolve_game_configuration(&defaults()), defaults());
+
+        let instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric", "memory": {"mode": "inherit", "min": 1, "max": 2}}"#);
+        assert_eq!(instance.resolve_game_configuration(&defaults()).memory, defaults().memory);
+    }
+
+    #[test]
+    fn test_resolve_override() {
+        let instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric", "memory": {"mode": "override", "min": 512, "max": 8192}, "resolution": {"mode": "override", "width": 1920, "height": 1080}}"#);
+        let resolved = instance.resolve_game_configuration(&defaults());
+        assert!(resolved.memory.enabled);
+        assert_eq!(resolved.memory.value, InstanceMemoryConfiguration { min: 512, max: 8192 });
+        // Overriding a setting that is off by default turns it on
+        assert!(resolved.resolution.enabled);
+        assert_eq!(resolved.resolution.value, InstanceResolutionConfiguration { width: 1920, height: 1080 });
+        assert_eq!(resolved.jvm_flags, defaults().jvm_flags);
+    }
+
+    #[test]
+    fn test_resolve_off() {
+        let instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric", "jvm_flags": {"mode": "off", "flags": "-Xss4M"}}"#);
+        let resolved = instance.resolve_game_configuration(&defaults());
+        assert!(!resolved.jvm_flags.enabled);
+        assert_eq!(resolved.memory, defaults().memory);
+    }
+
+    #[test]
+    fn test_legacy_enabled_flag() {
+        let instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric", "memory": {"enabled": true, "min": 512, "max": 8192}, "jvm_flags": {"enabled": false, "flags": "-Xss4M"}}"#);
+        assert_eq!(instance.memory.as_ref().unwrap().mode, InstanceSettingMode::Override);
+        assert_eq!(instance.memory.as_ref().unwrap().value, InstanceMemoryConfiguration { min: 512, max: 8192 });
+        assert_eq!(instance.jvm_flags.as_ref().unwrap().mode, InstanceSettingMode::Inherit);
+        assert_eq!(&*instance.jvm_flags.as_ref().unwrap().value.flags, "-Xss4M");
+
+        // Migrated settings are written back with the mode
+        let serialized = serde_json::to_value(&instance).unwrap();
+        assert_eq!(serialized["memory"], serde_json::json!({"mode": "override", "min": 512, "max": 8192}));
+        assert!(serialized["memory"].get("enabled").is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut instance = configuration(r#"{"minecraft_version": "1.21.1", "loader": "fabric"}"#);
+        instance.resolution = Some(InstanceSetting {
+            mode: InstanceSettingMode::Off,
+            value: InstanceResolutionConfiguration::default(),
+        });
+        instance.memory = Some(InstanceSetting::default());
+
+        let serialized = serde_json::to_string(&instance).unwrap();
+        let deserialized: InstanceConfiguration = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.resolution, instance.resolution);
+        // Inherited settings with default values aren't written at all
+        assert_eq!(deserialized.memory, None);
+        assert!(!serialized.contains("memory"));
+    }
+
+    #[test]
+    fn test_game_configuration_has_no_mode() {
+        let serialized = serde_json::to_value(defaults()).unwrap();
+        assert_eq!(serialized["memory"], serde_json::json!({"enabled": true, "min": 1024, "max": 2048}));
+    }
+
+    #[test]
+    fn test_update_channel() {
+        let any = ModUpdatePreference::default();
+        assert!(any.accepts(Some(ModrinthVersionType::Alpha)));
+        assert!(any.accepts(None));
+
+        let beta = ModUpdatePreference { channel: Some(ModrinthVersionType::Beta), ..Default::default() };
+        assert!(beta.accepts(Some(ModrinthVersionType::Release)));
+        assert!(beta.accepts(Some(ModrinthVersionType::Beta)));
+        assert!(!beta.accepts(Some(ModrinthVersionType::Alpha)));
+        assert!(!beta.accepts(Some(ModrinthVersionType::Other)));
+        // Versions without a type can't be known to be stable enough
+        assert!(!beta.accepts(None));
+
+        let release = ModUpdatePreference { channel: Some(ModrinthVersionType::Release), ..Default::default() };
+        assert!(release.accepts(Some(ModrinthVersionType::Release)));
+        assert!(!release.accepts(Some(ModrinthVersionType::Beta)));
+    }
+
+    #[test]
+    fn test_skipped_updates() {
+        let preference = ModUpdatePreference {
+            skipped_updates: ["da39a3ee5e6b4b0d3255bfef95601890afd80709".into()].into(),
+            ..Default::default()
+        };
+        assert!(preference.is_skipped("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+        assert!(preference.is_skipped("DA39A3EE5E6B4B0D3255BFEF95601890AFD80709"));
+        assert!(!preference.is_skipped("0000000000000000000000000000000000000000"));
+        assert!(!ModUpdatePreference::default().is_skipped("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+        assert!(!preference.is_default());
+        assert!(ModUpdatePreference::default().is_default());
+    }
+
+    #[test]
+    fn test_parse_environment_variables() {
+        let configuration = InstanceEnvironmentVariablesConfiguration {
+            variables: "# comment\n\nFOO=bar\n  SPACED  =  value  \nJAVA_TOOL_OPTIONS=-Dkey=value\n=missing key\n   \nNO_VALUE\nEMPTY=".into(),
+        };
+        let variables: Vec<_> = configuration.parse().collect();
+        assert_eq!(variables, [
+            ("FOO", "bar"),
+            ("SPACED", "value"),
+            ("JAVA_TOOL_OPTIONS", "-Dkey=value"),
+            ("EMPTY", ""),
+        ]);
     }
 }
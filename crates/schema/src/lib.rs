@@ -2,6 +2,7 @@ use serde::Deserialize;
 
 pub mod assets_index;
 pub mod content;
+pub mod curseforge;
 pub mod fabric_launch;
 pub mod fabric_loader_manifest;
 pub mod fabric_mod;
@@ -15,6 +16,9 @@ pub mod maven;
 pub mod modification;
 pub mod modrinth;
 pub mod mrpack;
+pub mod network;
+pub mod paste;
+pub mod redaction;
 pub mod version;
 pub mod version_manifest;
 
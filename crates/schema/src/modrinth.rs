@@ -113,6 +113,7 @@ pub struct ModrinthProjectVersion {
     pub dependencies: Option<Vec<ModrinthDependency>>,
     pub version_type: Option<ModrinthVersionType>,
     pub status: Option<ModrinthVersionStatus>,
+    pub changelog: Option<Arc<str>>,
     pub files: Arc<[ModrinthFile]>,
 }
 
@@ -146,6 +147,8 @@ pub enum ModrinthLoader {
     Iris,
     Optifine,
     Canvas,
+    // Datapacks
+    Datapack,
     // Other
     #[serde(other)]
     Unknown,
@@ -158,7 +161,8 @@ impl ModrinthLoader {
             ModrinthLoader::Minecraft => Some("resourcepacks"),
             ModrinthLoader::Iris | ModrinthLoader::Optifine => Some("shaderpacks"),
             ModrinthLoader::Canvas => Some("resourcepacks"),
-            ModrinthLoader::Unknown => None,
+            // Datapacks are installed per-world
+            ModrinthLoader::Datapack | ModrinthLoader::Unknown => None,
         }
     }
 
@@ -171,6 +175,7 @@ impl ModrinthLoader {
             Self::Iris => "Iris",
             Self::Optifine => "Optifine",
             Self::Canvas => "Canvas",
+            Self::Datapack => "Datapack",
             Self::Unknown => "Unknown",
         }
     }
@@ -184,6 +189,7 @@ impl ModrinthLoader {
             Self::Iris => "iris",
             Self::Optifine => "optifine",
             Self::Canvas => "canvas",
+            Self::Datapack => "datapack",
             Self::Unknown => "unknown",
         }
     }
@@ -197,12 +203,13 @@ impl ModrinthLoader {
             "Iris" | "iris" => Self::Iris,
             "Optifine" | "optifine" => Self::Optifine,
             "Canvas" | "canvas" => Self::Canvas,
+            "Datapack" | "datapack" => Self::Datapack,
             _ => Self::Unknown,
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ModrinthVersionType {
     Release,
@@ -212,6 +219,27 @@ pub enum ModrinthVersionType {
     Other,
 }
 
+impl ModrinthVersionType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ModrinthVersionType::Release => "Release",
+            ModrinthVersionType::Beta => "Beta",
+            ModrinthVersionType::Alpha => "Alpha",
+            ModrinthVersionType::Other => "Other",
+        }
+    }
+
+    /// Lower is more stable
+    pub(crate) fn stability(self) -> u8 {
+        match self {
+            ModrinthVersionType::Release => 0,
+            ModrinthVersionType::Beta => 1,
+            ModrinthVersionType::Alpha => 2,
+            ModrinthVersionType::Other => 3,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ModrinthVersionStatus {
@@ -240,3 +268,39 @@ pub struct ModrinthHashes {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ModrinthVersionFileUpdateResult(pub ModrinthProjectVersion);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthCollection {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    pub description: Option<Arc<str>>,
+    pub projects: Arc<[Arc<str>]>,
+}
+
+impl ModrinthCollection {
+    /// Accepts either a bare collection id or a `modrinth.com/collection/<id>` url
+    pub fn parse_id(input: &str) -> Option<Arc<str>> {
+        let input = input.trim();
+        let id = if let Some(index) = input.find("/collection/") {
+            input[index + "/collection/".len()..].split(['/', '?', '#']).next()?
+        } else {
+            input
+        };
+
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(id.into())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthProject {
+    pub id: Arc<str>,
+    pub title: Option<Arc<str>>,
+    pub project_type: ModrinthProjectType,
+    pub icon_url: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthProjectsResult(pub Arc<[ModrinthProject]>);
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkConfiguration {
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<DownloadMirror>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub downloads: DownloadConfiguration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DownloadConfiguration {
+    pub max_concurrent: usize,
+    pub max_per_host: usize,
+    pub max_retries: u32,
+    pub bandwidth_limit_kib: Option<u32>,
+}
+
+impl DownloadConfiguration {
+    pub const DEFAULT_MAX_CONCURRENT: usize = 16;
+    pub const DEFAULT_MAX_PER_HOST: usize = 8;
+    pub const DEFAULT_MAX_RETRIES: u32 = 3;
+}
+
+impl Default for DownloadConfiguration {
+    fn default() -> Self {
+        Self {
+            max_concurrent: Self::DEFAULT_MAX_CONCURRENT,
+            max_per_host: Self::DEFAULT_MAX_PER_HOST,
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            bandwidth_limit_kib: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DownloadMirror {
+    pub enabled: bool,
+    pub from: Arc<str>,
+    pub to: Arc<str>,
+}
+
+impl DownloadMirror {
+    /// Rewrites `url` if it starts with `from`, only matching on path boundaries
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        let from = self.from.trim_end_matches('/');
+        let rest = url.strip_prefix(from)?;
+        if !rest.is_empty() && !rest.starts_with(['/', '?']) {
+            return None;
+        }
+        Some(format!("{}{}", self.to.trim_end_matches('/'), rest))
+    }
+}
+
+const BMCLAPI: &str = "https://bmclapi2.bangbang93.com";
+
+pub const BMCLAPI_MIRRORS: &[(&str, &str)] = &[
+    ("https://piston-meta.mojang.com", BMCLAPI),
+    ("https://piston-data.mojang.com", BMCLAPI),
+    ("https://launchermeta.mojang.com", BMCLAPI),
+    ("https://launcher.mojang.com", BMCLAPI),
+    ("https://resources.download.minecraft.net", "https://bmclapi2.bangbang93.com/assets"),
+    ("https://libraries.minecraft.net", "https://bmclapi2.bangbang93.com/maven"),
+    ("https://maven.minecraftforge.net", "https://bmclapi2.bangbang93.com/maven"),
+    ("https://maven.neoforged.net/releases", "https://bmclapi2.bangbang93.com/maven"),
+    ("https://maven.fabricmc.net", "https://bmclapi2.bangbang93.com/maven"),
+    ("https://meta.fabricmc.net", "https://bmclapi2.bangbang93.com/fabric-meta"),
+];
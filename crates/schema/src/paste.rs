@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+pub const MCLOGS_API_URL: &str = "https://api.mclo.gs";
+
+/// mclo.gs rejects logs larger than ~10.5mb
+pub const MCLOGS_MAX_SIZE: usize = 11000000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PasteService {
+    #[default]
+    McLogs,
+    /// Any server implementing the hastebin api, e.g. a self-hosted haste-server
+    Hastebin {
+        url: Arc<str>,
+    },
+    /// Posts the raw text to `upload_url`. The returned key is substituted for `{key}` in `view_url`,
+    /// if the server returns a full url it is used directly
+    Custom {
+        upload_url: Arc<str>,
+        view_url: Arc<str>,
+    },
+}
+
+impl PasteService {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PasteService::McLogs => "mclo.gs",
+            PasteService::Hastebin { .. } => "Hastebin",
+            PasteService::Custom { .. } => "Custom",
+        }
+    }
+
+    pub fn max_size(&self) -> Option<usize> {
+        match self {
+            PasteService::McLogs => Some(MCLOGS_MAX_SIZE),
+            PasteService::Hastebin { .. } | PasteService::Custom { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasteConfiguration {
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub service: PasteService,
+}
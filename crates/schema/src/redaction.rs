@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionConfiguration {
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RedactionRule>,
+}
+
+/// A regex applied to logs after the built-in token and username redactions.
+/// The replacement can reference capture groups, e.g. `$1`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RedactionRule {
+    pub enabled: bool,
+    pub pattern: Arc<str>,
+    pub replacement: Arc<str>,
+}